@@ -0,0 +1,309 @@
+//! Classification of queries by what they access.
+//!
+//! [`Query::access_mode`] tells whether a query only reads, for instance to
+//! route it to a replica, and [`Query::access_summary`] additionally lists the
+//! labels, relationship types and property keys the query reads and writes.
+//! [`Statement`] has the same methods, which classify schema commands as
+//! [`AccessMode::Schema`].
+//! Procedures are classified by their [`ProcedureMode`] in a
+//! [`ProcedureCatalog`]; a call of a procedure missing from the catalog makes
+//! the mode [`AccessMode::UnknownProcedure`].
+//!
+//! The summary only contains names that are spelled out in the query. Dynamic
+//! labels and property keys, `SET n = map` with a non-literal map and the
+//! effects of procedures are not resolved.
+
+use std::collections::BTreeSet;
+
+use crate::analysis::procedures::{ProcedureCatalog, ProcedureMode, ProcedureRegistry};
+use crate::ast::cypher::visit::{
+    walk_atom, walk_node_pattern, walk_property_or_labels_expression, walk_reading_clause, walk_relationship_pattern,
+    walk_standalone_call, walk_updating_clause, Visitor,
+};
+use crate::ast::cypher::{
+    Atom, Expression, LabelExpression, LabelOrType, Literal, MapProjectionItem, MergeAction, NodePattern, ProcedureInvocation,
+    Properties, PropertyExpression, PropertyKey, PropertyOrLabelsExpression, QualifiedName, Query, ReadingClause,
+    RelationshipPattern, RemoveItem, SchemaCommand, SchemaTarget, Set, SetItem, StandaloneCall, Statement, UpdatingClause,
+};
+
+/// What a query needs to be allowed to do, from least to most demanding.
+///
+/// The modes are ordered so that the mode of a query is the maximum of the
+/// modes of its clauses.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessMode {
+    #[default]
+    Read,
+    Write,
+    Schema,
+    Admin,
+    /// The query calls a procedure whose mode is unknown.
+    UnknownProcedure,
+}
+
+impl From<ProcedureMode> for AccessMode {
+    fn from(mode: ProcedureMode) -> Self {
+        match mode {
+            ProcedureMode::Read => AccessMode::Read,
+            ProcedureMode::Write => AccessMode::Write,
+            ProcedureMode::Schema => AccessMode::Schema,
+            ProcedureMode::Dbms => AccessMode::Admin,
+        }
+    }
+}
+
+/// The access mode of a query and the schema tokens it reads and writes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccessSummary {
+    pub mode: AccessMode,
+    pub labels_read: BTreeSet<String>,
+    pub labels_written: BTreeSet<String>,
+    pub relationship_types_read: BTreeSet<String>,
+    pub relationship_types_written: BTreeSet<String>,
+    pub property_keys_read: BTreeSet<String>,
+    pub property_keys_written: BTreeSet<String>,
+}
+
+impl Query {
+    /// Returns the access mode of the query, classifying procedure calls with
+    /// [`ProcedureRegistry::builtin`].
+    pub fn access_mode(&self) -> AccessMode {
+        self.access_summary().mode
+    }
+
+    /// Returns the access summary of the query, classifying procedure calls
+    /// with [`ProcedureRegistry::builtin`].
+    pub fn access_summary(&self) -> AccessSummary {
+        summarize_access(self, &ProcedureRegistry::builtin())
+    }
+}
+
+impl Statement {
+    /// Returns the access mode of the statement, classifying procedure calls
+    /// with [`ProcedureRegistry::builtin`].
+    pub fn access_mode(&self) -> AccessMode {
+        self.access_summary().mode
+    }
+
+    /// Returns the access summary of the statement, classifying procedure
+    /// calls with [`ProcedureRegistry::builtin`].
+    pub fn access_summary(&self) -> AccessSummary {
+        let catalog = ProcedureRegistry::builtin();
+        let mut collector = AccessCollector { catalog: &catalog, summary: AccessSummary::default(), reading: true, writing: false };
+        collector.visit_statement(self);
+        collector.summary
+    }
+}
+
+/// Computes the access summary of the query, classifying procedure calls with
+/// the catalog.
+pub fn summarize_access(query: &Query, catalog: &dyn ProcedureCatalog) -> AccessSummary {
+    let mut collector = AccessCollector { catalog, summary: AccessSummary::default(), reading: true, writing: false };
+    collector.visit_query(query);
+    collector.summary
+}
+
+struct AccessCollector<'c> {
+    catalog: &'c dyn ProcedureCatalog,
+    summary: AccessSummary,
+    /// Whether the patterns being visited are matched, as in `MATCH` and `MERGE`.
+    reading: bool,
+    /// Whether the patterns being visited are created, as in `CREATE` and `MERGE`.
+    writing: bool,
+}
+
+impl AccessCollector<'_> {
+    fn require(&mut self, mode: AccessMode) {
+        self.summary.mode = self.summary.mode.max(mode);
+    }
+
+    fn call(&mut self, name: &QualifiedName) {
+        let mode = self.catalog.lookup(name).map_or(AccessMode::UnknownProcedure, |signature| signature.mode.into());
+        self.require(mode);
+    }
+
+    /// Visits patterns in the given context and restores the previous one.
+    fn patterns(&mut self, reading: bool, writing: bool, visit: impl FnOnce(&mut Self)) {
+        let outer = (self.reading, self.writing);
+        (self.reading, self.writing) = (reading, writing);
+        visit(self);
+        (self.reading, self.writing) = outer;
+    }
+
+    fn pattern_tokens(&mut self, labels: Vec<String>, properties: Option<&Properties>, relationship: bool) {
+        let keys = match properties {
+            Some(Properties::MapLiteral(map)) => map.keys().cloned().collect(),
+            _ => Vec::new(),
+        };
+        let summary = &mut self.summary;
+        let (labels_read, labels_written) = if relationship {
+            (&mut summary.relationship_types_read, &mut summary.relationship_types_written)
+        }
+        else {
+            (&mut summary.labels_read, &mut summary.labels_written)
+        };
+        if self.reading {
+            labels_read.extend(labels.iter().cloned());
+            summary.property_keys_read.extend(keys.iter().cloned());
+        }
+        if self.writing {
+            labels_written.extend(labels);
+            summary.property_keys_written.extend(keys);
+        }
+    }
+
+    fn set(&mut self, set: &Set) {
+        for item in &set.items {
+            match item {
+                SetItem::AssignPropertyExpression { property_expression, .. } => self.write_property(property_expression),
+                SetItem::AssignVariable { expression, .. } | SetItem::IncrementVariable { expression, .. } => {
+                    self.summary.property_keys_written.extend(map_literal_keys(expression));
+                }
+                SetItem::VariableNodeLabels { node_labels, .. } => {
+                    self.summary.labels_written.extend(static_labels(node_labels));
+                }
+            }
+        }
+    }
+
+    fn write_property(&mut self, expression: &PropertyExpression) {
+        if let Some(PropertyKey::Static(key)) = expression.property_path.last() {
+            self.summary.property_keys_written.insert(key.clone());
+        }
+        let path = &expression.property_path[..expression.property_path.len().saturating_sub(1)];
+        for key in path {
+            if let PropertyKey::Static(key) = key {
+                self.summary.property_keys_read.insert(key.clone());
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for AccessCollector<'_> {
+    fn visit_standalone_call(&mut self, call: &'ast StandaloneCall) {
+        match &call.invocation {
+            ProcedureInvocation::Explicit(invocation) => self.call(&invocation.name),
+            ProcedureInvocation::Implicit(invocation) => self.call(&invocation.name),
+        }
+        walk_standalone_call(self, call);
+    }
+
+    fn visit_schema_command(&mut self, command: &'ast SchemaCommand) {
+        // Indexes and constraints read the tokens they cover but do not write data.
+        self.require(AccessMode::Schema);
+        let (target, properties) = match command {
+            SchemaCommand::CreateIndex(index) => (&index.target, &index.properties),
+            SchemaCommand::CreateConstraint(constraint) => (&constraint.target, &constraint.properties),
+            SchemaCommand::DropIndex(_) | SchemaCommand::DropConstraint(_) => return,
+        };
+        match target {
+            SchemaTarget::Node { labels, .. } => self.summary.labels_read.extend(labels.iter().cloned()),
+            SchemaTarget::Relationship { relationship_types, .. } => {
+                self.summary.relationship_types_read.extend(relationship_types.iter().cloned())
+            }
+        }
+        self.summary.property_keys_read.extend(properties.iter().map(|property| property.property_key.clone()));
+    }
+
+    fn visit_reading_clause(&mut self, clause: &'ast ReadingClause) {
+        if let ReadingClause::InQueryCall(call) = clause {
+            self.call(&call.invocation.name);
+        }
+        walk_reading_clause(self, clause);
+    }
+
+    fn visit_updating_clause(&mut self, clause: &'ast UpdatingClause) {
+        if matches!(clause, UpdatingClause::Filter(_) | UpdatingClause::OrderSkipLimit(_)) {
+            return walk_updating_clause(self, clause);
+        }
+        self.require(AccessMode::Write);
+        match clause {
+            UpdatingClause::Create(create) => self.patterns(false, true, |collector| collector.visit_pattern(&create.pattern)),
+            UpdatingClause::Merge(merge) => {
+                self.patterns(true, true, |collector| collector.visit_pattern_part(&merge.pattern_part));
+                for action in &merge.actions {
+                    let (MergeAction::Match(set) | MergeAction::Create(set)) = action;
+                    self.set(set);
+                }
+            }
+            UpdatingClause::Set(set) => self.set(set),
+            UpdatingClause::Remove(remove) => {
+                for item in &remove.items {
+                    match item {
+                        RemoveItem::VariableNodeLabels { node_labels, .. } => {
+                            self.summary.labels_written.extend(static_labels(node_labels))
+                        }
+                        RemoveItem::PropertyExpression(expression) => self.write_property(expression),
+                    }
+                }
+            }
+            // The clauses of a FOREACH are classified when they are walked.
+            UpdatingClause::Delete(_) | UpdatingClause::Foreach(_) | UpdatingClause::Filter(_) | UpdatingClause::OrderSkipLimit(_) => (),
+        }
+        // Walking a MERGE visits its pattern once more as a read, which it is as well.
+        if !matches!(clause, UpdatingClause::Create(_)) {
+            walk_updating_clause(self, clause);
+        }
+    }
+
+    fn visit_node_pattern(&mut self, node: &'ast NodePattern) {
+        let labels = node.label_expression.as_ref().map(label_names).unwrap_or_default();
+        self.pattern_tokens(labels, node.properties.as_ref(), false);
+        // Property values are expressions that are read.
+        self.patterns(true, false, |collector| walk_node_pattern(collector, node));
+    }
+
+    fn visit_relationship_pattern(&mut self, relationship: &'ast RelationshipPattern) {
+        let (RelationshipPattern::LeftAndRight(detail)
+            | RelationshipPattern::Left(detail)
+            | RelationshipPattern::Right(detail)
+            | RelationshipPattern::Undirected(detail)) = relationship;
+        if let Some(detail) = detail {
+            let types = detail.label_expression.as_ref().map(label_names).unwrap_or_default();
+            self.pattern_tokens(types, detail.properties.as_ref(), true);
+        }
+        self.patterns(true, false, |collector| walk_relationship_pattern(collector, relationship));
+    }
+
+    fn visit_property_or_labels_expression(&mut self, expression: &'ast PropertyOrLabelsExpression) {
+        self.summary.property_keys_read.extend(expression.property_lookup.iter().cloned());
+        if let Some(label_expression) = &expression.label_expression {
+            self.summary.labels_read.extend(label_names(label_expression));
+        }
+        walk_property_or_labels_expression(self, expression);
+    }
+
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        if let Atom::MapProjection(projection) = atom {
+            for item in &projection.items {
+                if let MapProjectionItem::Property(key) = item {
+                    self.summary.property_keys_read.insert(key.clone());
+                }
+            }
+        }
+        walk_atom(self, atom);
+    }
+}
+
+fn label_names(label_expression: &LabelExpression) -> Vec<String> {
+    match label_expression {
+        LabelExpression::Label(label) => static_labels(std::slice::from_ref(label)).collect(),
+        LabelExpression::AnyOf(_) | LabelExpression::Wildcard => Vec::new(),
+        LabelExpression::Not(inner) => label_names(inner),
+        LabelExpression::And(inner) | LabelExpression::Or(inner) => inner.iter().flat_map(label_names).collect(),
+    }
+}
+
+fn static_labels(labels: &[LabelOrType]) -> impl Iterator<Item = String> + '_ {
+    labels.iter().filter_map(|label| match label {
+        LabelOrType::Static(name) => Some(name.clone()),
+        LabelOrType::Dynamic(_) => None,
+    })
+}
+
+fn map_literal_keys(expression: &Expression) -> Vec<String> {
+    match expression.as_atom() {
+        Some(Atom::Literal(Literal::MapLiteral(map))) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,284 @@
+//! Validation of aggregating expressions.
+//!
+//! A `WITH` or `RETURN` projection aggregates as soon as one of its items
+//! contains an aggregate function. Its grouping keys are then the items
+//! without aggregates, and every variable used next to an aggregate must be
+//! covered by a grouping key. [`check_aggregation`] reports violations of this
+//! rule, nested aggregates and aggregates in places that do not aggregate,
+//! such as `WHERE`.
+
+use crate::analysis::functions::FunctionRegistry;
+use crate::analysis::semantic::{SemanticError, SemanticErrorKind};
+use crate::ast::cypher::visit::{walk_atom, walk_expression, walk_function_invocation, walk_property_or_labels_expression, Visitor};
+use crate::ast::cypher::{Atom, Expression, FilterExpression, ProjectionBody, ProjectionItem, PropertyOrLabelsExpression, Query, Span, Variable};
+
+/// Checks the use of the built-in aggregate functions in all projections of the query.
+pub fn check_aggregation(query: &Query) -> Vec<SemanticError> {
+    check_aggregation_with(query, &FunctionRegistry::builtin())
+}
+
+/// Checks the use of the aggregate functions of the registry in all projections of the query.
+pub fn check_aggregation_with(query: &Query, registry: &FunctionRegistry) -> Vec<SemanticError> {
+    let mut checker = AggregationChecker { registry, errors: Vec::new() };
+    checker.visit_query(query);
+    checker.errors
+}
+
+/// Returns whether the projection aggregates, i.e. whether one of its items
+/// contains an aggregate function of the registry.
+pub(crate) fn is_aggregating(body: &ProjectionBody, registry: &FunctionRegistry) -> bool {
+    let mut errors = Vec::new();
+    body.items.items.iter().any(|item| {
+        let (ProjectionItem::Expression(expression) | ProjectionItem::AsVariable { expression, .. }) = item;
+        let mut finder = AggregateFinder::new(registry, &mut errors);
+        finder.visit_expression(expression);
+        !finder.aggregates.is_empty()
+    })
+}
+
+struct AggregationChecker<'r> {
+    registry: &'r FunctionRegistry,
+    errors: Vec<SemanticError>,
+}
+
+impl AggregationChecker<'_> {
+    /// Returns the spans of the outermost aggregates in the expression.
+    fn find_aggregates(&mut self, expression: &Expression) -> Vec<Span> {
+        let mut finder = AggregateFinder::new(self.registry, &mut self.errors);
+        finder.visit_expression(expression);
+        finder.aggregates
+    }
+
+    fn forbid_aggregates(&mut self, aggregates: Vec<Span>, context: &str) {
+        for span in aggregates {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::InvalidAggregation,
+                span,
+                message: format!("aggregate functions cannot be used {}", context),
+            });
+        }
+    }
+
+    fn check_projection(&mut self, body: &ProjectionBody) {
+        let mut keys = Vec::new();
+        let mut aggregating_items = Vec::new();
+        let mut aliases = Vec::new();
+
+        for item in &body.items.items {
+            let (ProjectionItem::Expression(expression) | ProjectionItem::AsVariable { expression, .. }) = item;
+            if let ProjectionItem::AsVariable { variable, .. } = item {
+                aliases.push(variable);
+            }
+
+            if self.find_aggregates(expression).is_empty() {
+                keys.push(expression);
+            }
+            else {
+                aggregating_items.push(expression);
+            }
+        }
+
+        let aggregating = !aggregating_items.is_empty();
+        for expression in aggregating_items {
+            self.check_grouping(expression, &keys, &[]);
+        }
+
+        for item in body.order.iter().flat_map(|order| &order.items) {
+            let aggregates = self.find_aggregates(&item.expression);
+            if aggregating {
+                self.check_grouping(&item.expression, &keys, &aliases);
+            }
+            else {
+                self.forbid_aggregates(aggregates, "in ORDER BY unless the projection aggregates");
+            }
+        }
+
+        for expression in body.skip.iter().map(|skip| &skip.expression).chain(body.limit.iter().map(|limit| &limit.expression)) {
+            let aggregates = self.find_aggregates(expression);
+            self.forbid_aggregates(aggregates, "in SKIP or LIMIT");
+        }
+    }
+
+    fn check_grouping(&mut self, expression: &Expression, keys: &[&Expression], aliases: &[&Variable]) {
+        let mut checker = GroupingChecker { registry: self.registry, keys, aliases, locals: Vec::new(), errors: &mut self.errors };
+        checker.visit_expression(expression);
+    }
+}
+
+impl<'ast> Visitor<'ast> for AggregationChecker<'_> {
+    fn visit_projection_body(&mut self, body: &'ast ProjectionBody) {
+        self.check_projection(body);
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        let aggregates = self.find_aggregates(expression);
+        self.forbid_aggregates(aggregates, "outside of WITH and RETURN projections");
+    }
+
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        let mut finder = AggregateFinder::new(self.registry, &mut self.errors);
+        finder.visit_atom(atom);
+        let aggregates = finder.aggregates;
+        self.forbid_aggregates(aggregates, "outside of WITH and RETURN projections");
+    }
+}
+
+/// Collects the outermost aggregates of an expression and reports aggregates
+/// that are nested or evaluated per list element.
+struct AggregateFinder<'e> {
+    registry: &'e FunctionRegistry,
+    errors: &'e mut Vec<SemanticError>,
+    aggregates: Vec<Span>,
+    inside_aggregate: bool,
+    inside_list_body: bool,
+}
+
+impl<'e> AggregateFinder<'e> {
+    fn new(registry: &'e FunctionRegistry, errors: &'e mut Vec<SemanticError>) -> Self {
+        AggregateFinder { registry, errors, aggregates: Vec::new(), inside_aggregate: false, inside_list_body: false }
+    }
+
+    fn found(&mut self, span: Span, name: &str) {
+        if self.inside_aggregate {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::NestedAggregation,
+                span,
+                message: format!("aggregate `{}` cannot be nested in another aggregate", name),
+            });
+        }
+        else if self.inside_list_body {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::InvalidAggregation,
+                span,
+                message: format!("aggregate `{}` cannot be evaluated per list element", name),
+            });
+        }
+        else {
+            self.aggregates.push(span);
+        }
+    }
+
+    fn list_body(&mut self, visit: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.inside_list_body, true);
+        visit(self);
+        self.inside_list_body = outer;
+    }
+
+    fn filter_expression(&mut self, filter: &FilterExpression, expression: Option<&Expression>) {
+        self.visit_expression(&filter.id_in_coll.expression);
+        self.list_body(|finder| {
+            if let Some(where_) = &filter.where_ {
+                finder.visit_expression(where_);
+            }
+            if let Some(expression) = expression {
+                finder.visit_expression(expression);
+            }
+        });
+    }
+}
+
+impl<'ast> Visitor<'ast> for AggregateFinder<'_> {
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        match atom {
+            Atom::CountStar(count) => self.found(count.span, "count(*)"),
+            Atom::FunctionInvocation(function) if self.registry.is_aggregate(function) => {
+                self.found(function.span, &function.function_name.to_string());
+                let outer = std::mem::replace(&mut self.inside_aggregate, true);
+                walk_function_invocation(self, function);
+                self.inside_aggregate = outer;
+            }
+            Atom::ListComprehension(comprehension) => {
+                self.filter_expression(&comprehension.filter_expression, comprehension.expression.as_ref())
+            }
+            Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => {
+                self.filter_expression(filter, None)
+            }
+            Atom::Reduce(reduce) => {
+                self.visit_expression(&reduce.initial_value);
+                self.visit_expression(&reduce.id_in_coll.expression);
+                self.list_body(|finder| finder.visit_expression(&reduce.expression));
+            }
+            Atom::PatternComprehension(_) => self.list_body(|finder| walk_atom(finder, atom)),
+            Atom::ExistentialSubquery(_) => {
+                // Subqueries aggregate on their own and are checked as a whole.
+                let mut checker = AggregationChecker { registry: self.registry, errors: Vec::new() };
+                walk_atom(&mut checker, atom);
+                self.errors.append(&mut checker.errors);
+            }
+            _ => walk_atom(self, atom),
+        }
+    }
+}
+
+/// Reports variables next to aggregates that are not covered by a grouping key.
+struct GroupingChecker<'a> {
+    registry: &'a FunctionRegistry,
+    keys: &'a [&'a Expression],
+    aliases: &'a [&'a Variable],
+    locals: Vec<&'a str>,
+    errors: &'a mut Vec<SemanticError>,
+}
+
+impl GroupingChecker<'_> {
+    fn is_key(&self, variable: &Variable) -> bool {
+        self.locals.contains(&variable.as_str())
+            || self.aliases.iter().any(|alias| alias.name == variable.name)
+            || self.keys.iter().any(|key| matches!(key.as_atom(), Some(Atom::Variable(key)) if key == variable))
+    }
+
+    fn check_variable(&mut self, variable: &Variable) {
+        if !self.is_key(variable) {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::AmbiguousAggregationExpression,
+                span: variable.span,
+                message: format!("variable `{}` is used next to an aggregate but is not a grouping key", variable),
+            });
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for GroupingChecker<'a> {
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        if !self.keys.contains(&expression) {
+            walk_expression(self, expression);
+        }
+    }
+
+    fn visit_property_or_labels_expression(&mut self, expression: &'a PropertyOrLabelsExpression) {
+        let is_key = |key: &&Expression| key.as_property_or_labels_expression() == Some(expression);
+        if !self.keys.iter().any(is_key) {
+            walk_property_or_labels_expression(self, expression);
+        }
+    }
+
+    fn visit_atom(&mut self, atom: &'a Atom) {
+        match atom {
+            Atom::CountStar(_) => (),
+            Atom::FunctionInvocation(function) if self.registry.is_aggregate(function) => (),
+            Atom::Variable(variable) => self.check_variable(variable),
+            Atom::MapProjection(projection) => {
+                self.check_variable(&projection.variable);
+                walk_atom(self, atom);
+            }
+            Atom::ListComprehension(comprehension) => {
+                self.locals.push(comprehension.filter_expression.id_in_coll.variable.as_str());
+                walk_atom(self, atom);
+                self.locals.pop();
+            }
+            Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => {
+                self.locals.push(filter.id_in_coll.variable.as_str());
+                walk_atom(self, atom);
+                self.locals.pop();
+            }
+            Atom::Reduce(reduce) => {
+                self.locals.push(reduce.accumulator.as_str());
+                self.locals.push(reduce.id_in_coll.variable.as_str());
+                walk_atom(self, atom);
+                self.locals.truncate(self.locals.len() - 2);
+            }
+            // Subqueries are checked by the aggregation checker on their own.
+            Atom::ExistentialSubquery(_) => (),
+            _ => walk_atom(self, atom),
+        }
+    }
+}
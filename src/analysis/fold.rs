@@ -0,0 +1,880 @@
+//! Constant folding of expressions.
+//!
+//! [`fold_expression`] evaluates the parts of an expression that do not
+//! depend on the data, following the three-valued logic of Cypher: `null`
+//! propagates through arithmetic and comparisons, `false AND null` is
+//! `false` and `true OR null` is `true`. Boolean identities such as
+//! `x AND true`, double negations, `CASE` alternatives with literal tests and
+//! `IN` on literal lists are simplified as well.
+//!
+//! Operations that would fail at runtime, such as an integer division by zero
+//! or an overflow, and operations whose result cannot be written as a
+//! literal, such as `1.0 / 0`, are left in place so that the error or value
+//! is still produced when the query runs.
+
+use std::cmp::Ordering;
+
+use crate::ast::cypher::visit_mut::{walk_atom, VisitorMut};
+use crate::ast::cypher::{
+    AddOrSubtractExpression, AndExpression, Atom, CaseExpression, ComparisonExpression, ComparisonOperator, Expression,
+    ListOperatorExpression, Literal, MultiplyDivideModuloExpression, MultiplyDivideModuloOperator, NotExpression,
+    NullOperatorExpression, NumberLiteral, OrExpression, PlusMinusOperator, PowerOfExpression, PropertyOrLabelsExpression,
+    Query, Span, StringListNullOperatorExpression, StringListNullOperatorExpressionInner, StringOperator,
+    UnaryAddOrSubtractExpression, XorExpression,
+};
+
+/// Returns the query with all of its expressions folded.
+pub fn fold_constants(query: &Query) -> Query {
+    let mut query = query.clone();
+    ExpressionFolder.visit_query(&mut query);
+    query
+}
+
+/// Returns the expression with its constant parts evaluated.
+pub fn fold_expression(expression: &Expression) -> Expression {
+    or(expression).into_node()
+}
+
+/// Replaces every expression it visits by its folded form.
+struct ExpressionFolder;
+
+impl VisitorMut for ExpressionFolder {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        *expression = fold_expression(expression);
+    }
+}
+
+/// A value known without running the query.
+#[derive(Debug, Clone, PartialEq)]
+enum Constant {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Constant>),
+}
+
+impl Constant {
+    fn from_literal(literal: &Literal) -> Option<Constant> {
+        match literal {
+            Literal::Null => Some(Constant::Null),
+            Literal::Boolean(value) => Some(Constant::Boolean(*value)),
+            Literal::Number(NumberLiteral::Integer(value)) => Some(Constant::Integer(*value)),
+            Literal::Number(NumberLiteral::Double(value)) => Some(Constant::Float(*value)),
+            Literal::String(value) => Some(Constant::String(value.clone())),
+            Literal::MapLiteral(_) | Literal::ListLiteral(_) => None,
+        }
+    }
+
+    fn from_truth(value: Option<bool>) -> Constant {
+        value.map_or(Constant::Null, Constant::Boolean)
+    }
+
+    /// Returns the truth value of a boolean or `null`, and `None` for other
+    /// types, which are a type error in a boolean context.
+    fn truth(&self) -> Option<Option<bool>> {
+        match self {
+            Constant::Null => Some(None),
+            Constant::Boolean(value) => Some(Some(*value)),
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Constant::Integer(value) => Some(*value as f64),
+            Constant::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The result of `self = other`, where `None` stands for `null`.
+    fn equals(&self, other: &Constant) -> Option<bool> {
+        match (self, other) {
+            (Constant::Null, _) | (_, Constant::Null) => None,
+            (Constant::Integer(a), Constant::Integer(b)) => Some(a == b),
+            (Constant::Integer(_) | Constant::Float(_), Constant::Integer(_) | Constant::Float(_)) => {
+                Some(self.as_float() == other.as_float())
+            }
+            (Constant::List(a), Constant::List(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                let mut result = Some(true);
+                for (a, b) in a.iter().zip(b) {
+                    match a.equals(b) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => (),
+                    }
+                }
+                result
+            }
+            _ => Some(self == other),
+        }
+    }
+
+    /// Orders two values of the same kind, or returns `None` for `null` and
+    /// values that are not comparable.
+    fn compare(&self, other: &Constant) -> Option<Ordering> {
+        match (self, other) {
+            (Constant::Integer(a), Constant::Integer(b)) => Some(a.cmp(b)),
+            (Constant::Integer(_) | Constant::Float(_), Constant::Integer(_) | Constant::Float(_)) => {
+                self.as_float()?.partial_cmp(&other.as_float()?)
+            }
+            (Constant::String(a), Constant::String(b)) => Some(a.cmp(b)),
+            (Constant::Boolean(a), Constant::Boolean(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    /// Whether the value is comparable with `<` and friends; the result for
+    /// other values such as lists is left to the runtime.
+    fn is_orderable(&self) -> bool {
+        matches!(self, Constant::Null | Constant::Boolean(_) | Constant::Integer(_) | Constant::Float(_) | Constant::String(_))
+    }
+
+    fn to_atom(&self) -> Atom {
+        match self {
+            Constant::Null => Atom::Literal(Literal::Null),
+            Constant::Boolean(value) => Atom::Literal(Literal::Boolean(*value)),
+            Constant::Integer(value) if *value >= 0 => Atom::Literal(Literal::Number(NumberLiteral::Integer(*value))),
+            Constant::Float(value) if value.is_sign_positive() => Atom::Literal(Literal::Number(NumberLiteral::Double(*value))),
+            Constant::Integer(_) | Constant::Float(_) => Atom::ParenthesizedExpression(self.to_expression()),
+            Constant::String(value) => Atom::Literal(Literal::String(value.clone())),
+            Constant::List(values) => Atom::Literal(Literal::ListLiteral(values.iter().map(Constant::to_expression).collect())),
+        }
+    }
+
+    /// Negative numbers are written with a unary minus, as the parser does.
+    fn to_unary(&self) -> UnaryAddOrSubtractExpression {
+        let (negate, atom) = match self {
+            Constant::Integer(value) if *value < 0 && *value != i64::MIN => {
+                (true, Atom::Literal(Literal::Number(NumberLiteral::Integer(-value))))
+            }
+            Constant::Float(value) if value.is_sign_negative() => {
+                (true, Atom::Literal(Literal::Number(NumberLiteral::Double(-value))))
+            }
+            Constant::Integer(value) if *value < 0 => (false, Atom::Literal(Literal::Number(NumberLiteral::Integer(*value)))),
+            _ => (false, self.to_atom()),
+        };
+        UnaryAddOrSubtractExpression { negate, expression: Node::from_atom(atom) }
+    }
+
+    fn to_expression(&self) -> Expression {
+        Lift::lift(self.to_unary())
+    }
+}
+
+/// The result of folding a node: either a constant or the folded node.
+enum Folded<T> {
+    Constant(Constant),
+    Node(T),
+}
+
+impl<T: Node> Folded<T> {
+    fn into_node(self) -> T {
+        match self {
+            Folded::Constant(value) => T::from_constant(&value),
+            Folded::Node(node) => node,
+        }
+    }
+
+    fn constant(&self) -> Option<&Constant> {
+        match self {
+            Folded::Constant(value) => Some(value),
+            Folded::Node(_) => None,
+        }
+    }
+}
+
+/// An expression node that can be built from a constant or an atom.
+trait Node: Sized {
+    fn from_atom(atom: Atom) -> Self;
+
+    fn from_constant(value: &Constant) -> Self;
+}
+
+impl Node for Atom {
+    fn from_atom(atom: Atom) -> Self {
+        atom
+    }
+
+    fn from_constant(value: &Constant) -> Self {
+        value.to_atom()
+    }
+}
+
+impl Node for PropertyOrLabelsExpression {
+    fn from_atom(atom: Atom) -> Self {
+        PropertyOrLabelsExpression { atom, property_lookup: Vec::new(), label_expression: None, span: Span::default() }
+    }
+
+    fn from_constant(value: &Constant) -> Self {
+        Self::from_atom(value.to_atom())
+    }
+}
+
+impl Node for StringListNullOperatorExpression {
+    fn from_atom(atom: Atom) -> Self {
+        StringListNullOperatorExpression { expression: Node::from_atom(atom), operations: Vec::new() }
+    }
+
+    fn from_constant(value: &Constant) -> Self {
+        Self::from_atom(value.to_atom())
+    }
+}
+
+impl Node for UnaryAddOrSubtractExpression {
+    fn from_atom(atom: Atom) -> Self {
+        UnaryAddOrSubtractExpression { negate: false, expression: Node::from_atom(atom) }
+    }
+
+    fn from_constant(value: &Constant) -> Self {
+        value.to_unary()
+    }
+}
+
+/// Wraps a unary expression in the higher precedence levels.
+trait Lift: Sized {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self;
+}
+
+macro_rules! lift_node {
+    ($node:ty) => {
+        impl Node for $node {
+            fn from_atom(atom: Atom) -> Self {
+                Lift::lift(UnaryAddOrSubtractExpression::from_atom(atom))
+            }
+
+            fn from_constant(value: &Constant) -> Self {
+                Lift::lift(value.to_unary())
+            }
+        }
+    };
+}
+
+impl Lift for PowerOfExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        PowerOfExpression(vec![unary])
+    }
+}
+
+impl Lift for MultiplyDivideModuloExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        MultiplyDivideModuloExpression { expression: Lift::lift(unary), operations: Vec::new() }
+    }
+}
+
+impl Lift for AddOrSubtractExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        AddOrSubtractExpression { expression: Lift::lift(unary), operations: Vec::new() }
+    }
+}
+
+impl Lift for ComparisonExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        ComparisonExpression { expression: Lift::lift(unary), comparisons: Vec::new() }
+    }
+}
+
+impl Lift for NotExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        NotExpression { not: false, expression: Lift::lift(unary) }
+    }
+}
+
+impl Lift for AndExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        AndExpression(vec![Lift::lift(unary)])
+    }
+}
+
+impl Lift for XorExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        XorExpression(vec![Lift::lift(unary)])
+    }
+}
+
+impl Lift for OrExpression {
+    fn lift(unary: UnaryAddOrSubtractExpression) -> Self {
+        OrExpression(vec![Lift::lift(unary)])
+    }
+}
+
+lift_node!(PowerOfExpression);
+lift_node!(MultiplyDivideModuloExpression);
+lift_node!(AddOrSubtractExpression);
+lift_node!(ComparisonExpression);
+lift_node!(NotExpression);
+lift_node!(AndExpression);
+lift_node!(XorExpression);
+lift_node!(OrExpression);
+
+/// Returns the atom for an expression, without parentheses if it is one already.
+fn parenthesize(expression: Expression) -> Atom {
+    match expression.as_atom() {
+        Some(atom) => atom.clone(),
+        None => Atom::ParenthesizedExpression(expression),
+    }
+}
+
+/// Returns the single `NOT` expression of a parenthesized expression such as `(NOT x)`.
+fn as_single_not(atom: &Atom) -> Option<&NotExpression> {
+    let Atom::ParenthesizedExpression(expression) = atom else { return None };
+    let [xor] = expression.0.as_slice() else { return None };
+    let [and] = xor.0.as_slice() else { return None };
+    let [not] = and.0.as_slice() else { return None };
+    Some(not)
+}
+
+fn or(expression: &OrExpression) -> Folded<OrExpression> {
+    if let [operand] = expression.0.as_slice() {
+        return match xor(operand) {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(node) => Folded::Node(OrExpression(vec![node])),
+        };
+    }
+    let mut operands = Vec::new();
+    let mut null = false;
+    let mut constants = Vec::new();
+    for operand in &expression.0 {
+        match xor(operand) {
+            Folded::Constant(value) => match value.truth() {
+                Some(Some(true)) => return Folded::Constant(Constant::Boolean(true)),
+                Some(Some(false)) => (),
+                Some(None) => null = true,
+                None => constants.push(value),
+            },
+            Folded::Node(node) => operands.push(node),
+        }
+    }
+    // Other constants are a type error, which is left to the runtime.
+    operands.extend(constants.iter().map(XorExpression::from_constant));
+    match (operands.is_empty(), null) {
+        (true, true) => Folded::Constant(Constant::Null),
+        (true, false) => Folded::Constant(Constant::Boolean(false)),
+        (false, null) => {
+            if null {
+                operands.push(XorExpression::from_constant(&Constant::Null));
+            }
+            Folded::Node(OrExpression(operands))
+        }
+    }
+}
+
+fn xor(expression: &XorExpression) -> Folded<XorExpression> {
+    if let [operand] = expression.0.as_slice() {
+        return match and(operand) {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(node) => Folded::Node(XorExpression(vec![node])),
+        };
+    }
+    let mut operands = Vec::new();
+    let mut parity = false;
+    let mut null = false;
+    for operand in &expression.0 {
+        match and(operand) {
+            Folded::Constant(value) => match value.truth() {
+                Some(Some(value)) => parity ^= value,
+                Some(None) => null = true,
+                None => operands.push(AndExpression::from_constant(&value)),
+            },
+            Folded::Node(node) => operands.push(node),
+        }
+    }
+    if null {
+        return Folded::Constant(Constant::Null);
+    }
+    if operands.is_empty() {
+        return Folded::Constant(Constant::Boolean(parity));
+    }
+    if parity {
+        operands.push(AndExpression::from_constant(&Constant::Boolean(true)));
+    }
+    Folded::Node(XorExpression(operands))
+}
+
+fn and(expression: &AndExpression) -> Folded<AndExpression> {
+    if let [operand] = expression.0.as_slice() {
+        return match not(operand) {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(node) => Folded::Node(AndExpression(vec![node])),
+        };
+    }
+    let mut operands = Vec::new();
+    let mut null = false;
+    let mut constants = Vec::new();
+    for operand in &expression.0 {
+        match not(operand) {
+            Folded::Constant(value) => match value.truth() {
+                Some(Some(false)) => return Folded::Constant(Constant::Boolean(false)),
+                Some(Some(true)) => (),
+                Some(None) => null = true,
+                None => constants.push(value),
+            },
+            Folded::Node(node) => operands.push(node),
+        }
+    }
+    operands.extend(constants.iter().map(NotExpression::from_constant));
+    match (operands.is_empty(), null) {
+        (true, true) => Folded::Constant(Constant::Null),
+        (true, false) => Folded::Constant(Constant::Boolean(true)),
+        (false, null) => {
+            if null {
+                operands.push(NotExpression::from_constant(&Constant::Null));
+            }
+            Folded::Node(AndExpression(operands))
+        }
+    }
+}
+
+fn not(expression: &NotExpression) -> Folded<NotExpression> {
+    let folded = comparison(&expression.expression);
+    if !expression.not {
+        return match folded {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(node) => Folded::Node(NotExpression { not: false, expression: node }),
+        };
+    }
+
+    match folded {
+        Folded::Constant(value) => match value.truth() {
+            Some(truth) => Folded::Constant(Constant::from_truth(truth.map(|value| !value))),
+            None => Folded::Node(NotExpression { not: true, expression: ComparisonExpression::from_constant(&value) }),
+        },
+        Folded::Node(node) => {
+            // `NOT (NOT x)` is `x`.
+            let inner = node.comparisons.is_empty().then(|| single_atom(&node.expression)).flatten().and_then(as_single_not);
+            match inner {
+                Some(inner) => Folded::Node(NotExpression { not: !inner.not, expression: inner.expression.clone() }),
+                None => Folded::Node(NotExpression { not: true, expression: node }),
+            }
+        }
+    }
+}
+
+/// Returns the atom of an arithmetic expression that consists of nothing else.
+fn single_atom(expression: &AddOrSubtractExpression) -> Option<&Atom> {
+    if !expression.operations.is_empty() || !expression.expression.operations.is_empty() {
+        return None;
+    }
+    let [unary] = expression.expression.expression.0.as_slice() else { return None };
+    let inner = &unary.expression;
+    if unary.negate || !inner.operations.is_empty() || !inner.expression.property_lookup.is_empty() || inner.expression.label_expression.is_some() {
+        return None;
+    }
+    Some(&inner.expression.atom)
+}
+
+fn comparison(expression: &ComparisonExpression) -> Folded<ComparisonExpression> {
+    let first = add_or_subtract(&expression.expression);
+    let operands: Vec<_> = expression.comparisons.iter().map(|(operator, operand)| (operator, add_or_subtract(operand))).collect();
+
+    if operands.is_empty() {
+        return match first {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(node) => Folded::Node(ComparisonExpression { expression: node, comparisons: Vec::new() }),
+        };
+    }
+
+    // `a < b < c` is `a < b AND b < c`.
+    let mut constants = vec![first.constant()];
+    constants.extend(operands.iter().map(|(_, operand)| operand.constant()));
+    if let Some(constants) = constants.into_iter().collect::<Option<Vec<_>>>() {
+        let mut result = Some(true);
+        let mut foldable = true;
+        for (index, (operator, _)) in operands.iter().enumerate() {
+            match compare(operator, constants[index], constants[index + 1]) {
+                Some(Some(false)) => {
+                    result = Some(false);
+                    break;
+                }
+                Some(Some(true)) => (),
+                Some(None) => result = None,
+                None => foldable = false,
+            }
+        }
+        if foldable || result == Some(false) {
+            return Folded::Constant(Constant::from_truth(result));
+        }
+    }
+
+    Folded::Node(ComparisonExpression {
+        expression: first.into_node(),
+        comparisons: operands.into_iter().map(|(operator, operand)| (operator.clone(), operand.into_node())).collect(),
+    })
+}
+
+/// Returns the three-valued result of the comparison, or `None` if it is not folded.
+fn compare(operator: &ComparisonOperator, a: &Constant, b: &Constant) -> Option<Option<bool>> {
+    match operator {
+        ComparisonOperator::Equal => Some(a.equals(b)),
+        ComparisonOperator::NotEqual => Some(a.equals(b).map(|equal| !equal)),
+        _ if !a.is_orderable() || !b.is_orderable() => None,
+        _ => {
+            let Some(ordering) = a.compare(b) else { return Some(None) };
+            Some(Some(match operator {
+                ComparisonOperator::LessThan => ordering == Ordering::Less,
+                ComparisonOperator::GreaterThan => ordering == Ordering::Greater,
+                ComparisonOperator::LessEqual => ordering != Ordering::Greater,
+                ComparisonOperator::GreaterEqual => ordering != Ordering::Less,
+                ComparisonOperator::Equal | ComparisonOperator::NotEqual => unreachable!(),
+            }))
+        }
+    }
+}
+
+fn add_or_subtract(expression: &AddOrSubtractExpression) -> Folded<AddOrSubtractExpression> {
+    let mut accumulator = multiply_divide_modulo(&expression.expression);
+    let mut operations = Vec::new();
+    for (operator, operand) in &expression.operations {
+        let operand = multiply_divide_modulo(operand);
+        if operations.is_empty() {
+            if let (Some(a), Some(b)) = (accumulator.constant(), operand.constant()) {
+                if let Some(value) = add(operator, a, b) {
+                    accumulator = Folded::Constant(value);
+                    continue;
+                }
+            }
+        }
+        operations.push((operator.clone(), operand.into_node()));
+    }
+    match (accumulator, operations.is_empty()) {
+        (Folded::Constant(value), true) => Folded::Constant(value),
+        (accumulator, _) => Folded::Node(AddOrSubtractExpression { expression: accumulator.into_node(), operations }),
+    }
+}
+
+fn add(operator: &PlusMinusOperator, a: &Constant, b: &Constant) -> Option<Constant> {
+    use Constant::*;
+
+    match (operator, a, b) {
+        (_, Null, _) | (_, _, Null) => Some(Null),
+        (PlusMinusOperator::Plus, Integer(a), Integer(b)) => a.checked_add(*b).map(Integer),
+        (PlusMinusOperator::Minus, Integer(a), Integer(b)) => a.checked_sub(*b).map(Integer),
+        (PlusMinusOperator::Plus, Integer(_) | Float(_), Integer(_) | Float(_)) => finite(a.as_float()? + b.as_float()?),
+        (PlusMinusOperator::Minus, Integer(_) | Float(_), Integer(_) | Float(_)) => finite(a.as_float()? - b.as_float()?),
+        (PlusMinusOperator::Plus | PlusMinusOperator::Concatenate, String(a), String(b)) => Some(String(format!("{}{}", a, b))),
+        (PlusMinusOperator::Plus | PlusMinusOperator::Concatenate, List(a), List(b)) => {
+            Some(List(a.iter().chain(b).cloned().collect()))
+        }
+        (PlusMinusOperator::Plus, List(a), b) => Some(List(a.iter().chain(std::iter::once(b)).cloned().collect())),
+        (PlusMinusOperator::Plus, a, List(b)) => Some(List(std::iter::once(a).chain(b).cloned().collect())),
+        (PlusMinusOperator::Plus, String(a), Integer(b)) => Some(String(format!("{}{}", a, b))),
+        (PlusMinusOperator::Plus, Integer(a), String(b)) => Some(String(format!("{}{}", a, b))),
+        _ => None,
+    }
+}
+
+fn finite(value: f64) -> Option<Constant> {
+    value.is_finite().then_some(Constant::Float(value))
+}
+
+fn multiply_divide_modulo(expression: &MultiplyDivideModuloExpression) -> Folded<MultiplyDivideModuloExpression> {
+    let mut accumulator = power_of(&expression.expression);
+    let mut operations = Vec::new();
+    for (operator, operand) in &expression.operations {
+        let operand = power_of(operand);
+        if operations.is_empty() {
+            if let (Some(a), Some(b)) = (accumulator.constant(), operand.constant()) {
+                if let Some(value) = multiply(operator, a, b) {
+                    accumulator = Folded::Constant(value);
+                    continue;
+                }
+            }
+        }
+        operations.push((operator.clone(), operand.into_node()));
+    }
+    match (accumulator, operations.is_empty()) {
+        (Folded::Constant(value), true) => Folded::Constant(value),
+        (accumulator, _) => Folded::Node(MultiplyDivideModuloExpression { expression: accumulator.into_node(), operations }),
+    }
+}
+
+fn multiply(operator: &MultiplyDivideModuloOperator, a: &Constant, b: &Constant) -> Option<Constant> {
+    use Constant::*;
+
+    match (operator, a, b) {
+        (_, Null, _) | (_, _, Null) => Some(Null),
+        (MultiplyDivideModuloOperator::Multiply, Integer(a), Integer(b)) => a.checked_mul(*b).map(Integer),
+        (MultiplyDivideModuloOperator::Divide, Integer(a), Integer(b)) => a.checked_div(*b).map(Integer),
+        (MultiplyDivideModuloOperator::Modulo, Integer(a), Integer(b)) => a.checked_rem(*b).map(Integer),
+        (operator, Integer(_) | Float(_), Integer(_) | Float(_)) => {
+            let (a, b) = (a.as_float()?, b.as_float()?);
+            finite(match operator {
+                MultiplyDivideModuloOperator::Multiply => a * b,
+                MultiplyDivideModuloOperator::Divide => a / b,
+                MultiplyDivideModuloOperator::Modulo => a % b,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn power_of(expression: &PowerOfExpression) -> Folded<PowerOfExpression> {
+    let mut operands = expression.0.iter().map(unary);
+    let mut accumulator = operands.next().expect("a power expression has an operand");
+    let mut rest = Vec::new();
+    // `^` is left associative, so only a constant prefix can be folded.
+    for operand in operands {
+        if rest.is_empty() {
+            if let (Some(a), Some(b)) = (accumulator.constant(), operand.constant()) {
+                let value = match (a, b) {
+                    (Constant::Null, _) | (_, Constant::Null) => Some(Constant::Null),
+                    _ => a.as_float().zip(b.as_float()).and_then(|(a, b)| finite(a.powf(b))),
+                };
+                if let Some(value) = value {
+                    accumulator = Folded::Constant(value);
+                    continue;
+                }
+            }
+        }
+        rest.push(operand.into_node());
+    }
+    match (accumulator, rest.is_empty()) {
+        (Folded::Constant(value), true) => Folded::Constant(value),
+        (accumulator, _) => Folded::Node(PowerOfExpression(std::iter::once(accumulator.into_node()).chain(rest).collect())),
+    }
+}
+
+fn unary(expression: &UnaryAddOrSubtractExpression) -> Folded<UnaryAddOrSubtractExpression> {
+    let folded = string_list_null(&expression.expression);
+    if let Folded::Constant(value) = &folded {
+        let negated = match value {
+            _ if !expression.negate => Some(value.clone()),
+            Constant::Null => Some(Constant::Null),
+            Constant::Integer(value) => value.checked_neg().map(Constant::Integer),
+            Constant::Float(value) => Some(Constant::Float(-value)),
+            _ => None,
+        };
+        if let Some(value) = negated {
+            return Folded::Constant(value);
+        }
+    }
+    Folded::Node(UnaryAddOrSubtractExpression { negate: expression.negate, expression: folded.into_node() })
+}
+
+fn string_list_null(expression: &StringListNullOperatorExpression) -> Folded<StringListNullOperatorExpression> {
+    let mut accumulator = property_or_labels(&expression.expression);
+    let mut operations = Vec::new();
+    for operation in &expression.operations {
+        let operation = fold_operation(operation);
+        if operations.is_empty() {
+            if let Some(value) = accumulator.constant().and_then(|value| apply(value, &operation)) {
+                accumulator = Folded::Constant(value);
+                continue;
+            }
+        }
+        operations.push(operation);
+    }
+    match (accumulator, operations.is_empty()) {
+        (Folded::Constant(value), true) => Folded::Constant(value),
+        (accumulator, _) => Folded::Node(StringListNullOperatorExpression { expression: accumulator.into_node(), operations }),
+    }
+}
+
+fn fold_operation(operation: &StringListNullOperatorExpressionInner) -> StringListNullOperatorExpressionInner {
+    let mut operation = operation.clone();
+    match &mut operation {
+        StringListNullOperatorExpressionInner::StringOperator(operation) => {
+            operation.expression = property_or_labels(&operation.expression).into_node();
+        }
+        StringListNullOperatorExpressionInner::ListOperator(operation) => match operation {
+            ListOperatorExpression::In(expression) => **expression = property_or_labels(expression).into_node(),
+            ListOperatorExpression::Index(expression) => *expression = fold_expression(expression),
+            ListOperatorExpression::Range(from, to) => {
+                for expression in from.iter_mut().chain(to) {
+                    *expression = fold_expression(expression);
+                }
+            }
+        },
+        StringListNullOperatorExpressionInner::NullOperator(_)
+        | StringListNullOperatorExpressionInner::NormalizedPredicate(_)
+        | StringListNullOperatorExpressionInner::TypePredicate(_) => (),
+    }
+    operation
+}
+
+/// Returns the constant of an operand that consists of nothing but a literal.
+fn operand_constant(expression: &PropertyOrLabelsExpression) -> Option<Constant> {
+    if !expression.property_lookup.is_empty() || expression.label_expression.is_some() {
+        return None;
+    }
+    atom_constant(&expression.atom)
+}
+
+fn expression_constant(expression: &Expression) -> Option<Constant> {
+    match or(expression) {
+        Folded::Constant(value) => Some(value),
+        Folded::Node(_) => None,
+    }
+}
+
+fn atom_constant(atom: &Atom) -> Option<Constant> {
+    match atom {
+        Atom::Literal(Literal::ListLiteral(list)) => list.iter().map(expression_constant).collect::<Option<_>>().map(Constant::List),
+        Atom::Literal(literal) => Constant::from_literal(literal),
+        Atom::ParenthesizedExpression(expression) => expression_constant(expression),
+        _ => None,
+    }
+}
+
+/// Applies a postfix operation to a constant, or returns `None` if it is not folded.
+fn apply(value: &Constant, operation: &StringListNullOperatorExpressionInner) -> Option<Constant> {
+    match operation {
+        StringListNullOperatorExpressionInner::NullOperator(NullOperatorExpression { not }) => {
+            Some(Constant::Boolean((*value == Constant::Null) != *not))
+        }
+        StringListNullOperatorExpressionInner::StringOperator(operation) => {
+            let operand = operand_constant(&operation.expression)?;
+            match (value, &operand) {
+                (Constant::Null, _) | (_, Constant::Null) => Some(Constant::Null),
+                (Constant::String(text), Constant::String(pattern)) => match operation.operator {
+                    StringOperator::StartsWith => Some(Constant::Boolean(text.starts_with(pattern.as_str()))),
+                    StringOperator::EndsWith => Some(Constant::Boolean(text.ends_with(pattern.as_str()))),
+                    StringOperator::Contains => Some(Constant::Boolean(text.contains(pattern.as_str()))),
+                    StringOperator::RegularExpression => None,
+                },
+                _ => None,
+            }
+        }
+        StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::In(list)) => {
+            match operand_constant(list)? {
+                Constant::Null => Some(Constant::Null),
+                Constant::List(list) => {
+                    // `x IN list` is true if an element equals x and null if
+                    // no element does but some comparison was null.
+                    let mut result = Some(false);
+                    for element in &list {
+                        match value.equals(element) {
+                            Some(true) => return Some(Constant::Boolean(true)),
+                            None => result = None,
+                            Some(false) => (),
+                        }
+                    }
+                    Some(Constant::from_truth(result))
+                }
+                _ => None,
+            }
+        }
+        StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Index(index)) => {
+            match (value, expression_constant(index)?) {
+                (Constant::Null, _) | (_, Constant::Null) => Some(Constant::Null),
+                (Constant::List(list), Constant::Integer(index)) => {
+                    let index = if index < 0 { index.checked_add(list.len() as i64)? } else { index };
+                    Some(usize::try_from(index).ok().and_then(|index| list.get(index)).cloned().unwrap_or(Constant::Null))
+                }
+                _ => None,
+            }
+        }
+        StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Range(from, to)) => {
+            let bound = |bound: &Option<Expression>| bound.as_ref().map(expression_constant);
+            let (from, to) = (bound(from), bound(to));
+            if value == &Constant::Null || from == Some(Some(Constant::Null)) || to == Some(Some(Constant::Null)) {
+                return Some(Constant::Null);
+            }
+            let Constant::List(list) = value else { return None };
+            let length = list.len() as i64;
+            let resolve = |bound: Option<Option<Constant>>, default: i64| match bound {
+                None => Some(default),
+                Some(Some(Constant::Integer(bound))) => Some(if bound < 0 { (length + bound).max(0) } else { bound.min(length) }),
+                Some(_) => None,
+            };
+            let (from, to) = (resolve(from, 0)?, resolve(to, length)?);
+            let slice = if from < to { list[from as usize..to as usize].to_vec() } else { Vec::new() };
+            Some(Constant::List(slice))
+        }
+        StringListNullOperatorExpressionInner::NormalizedPredicate(_)
+        | StringListNullOperatorExpressionInner::TypePredicate(_) => None,
+    }
+}
+
+fn property_or_labels(expression: &PropertyOrLabelsExpression) -> Folded<PropertyOrLabelsExpression> {
+    let atom = atom(&expression.atom);
+    let plain = expression.property_lookup.is_empty() && expression.label_expression.is_none();
+    match atom {
+        Folded::Constant(value) if plain => Folded::Constant(value),
+        // `(n.name)` is `n.name`.
+        Folded::Node(Atom::ParenthesizedExpression(inner)) if plain && inner.as_property_or_labels_expression().is_some() => {
+            Folded::Node(inner.as_property_or_labels_expression().unwrap().clone())
+        }
+        // Property lookups and label predicates on `null` are `null`.
+        Folded::Constant(Constant::Null) => Folded::Constant(Constant::Null),
+        atom => Folded::Node(PropertyOrLabelsExpression {
+            atom: atom.into_node(),
+            property_lookup: expression.property_lookup.clone(),
+            label_expression: expression.label_expression.clone(),
+            span: expression.span,
+        }),
+    }
+}
+
+fn atom(atom: &Atom) -> Folded<Atom> {
+    match atom {
+        Atom::Literal(Literal::ListLiteral(list)) => {
+            let elements: Vec<Folded<OrExpression>> = list.iter().map(or).collect();
+            match elements.iter().map(|element| element.constant().cloned()).collect::<Option<Vec<_>>>() {
+                Some(values) => Folded::Constant(Constant::List(values)),
+                None => Folded::Node(Atom::Literal(Literal::ListLiteral(elements.into_iter().map(Folded::into_node).collect()))),
+            }
+        }
+        Atom::Literal(literal) if Constant::from_literal(literal).is_some() => {
+            Folded::Constant(Constant::from_literal(literal).unwrap())
+        }
+        Atom::ParenthesizedExpression(expression) => match or(expression) {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(expression) => Folded::Node(parenthesize(expression)),
+        },
+        Atom::CaseExpression(case) => case_expression(case),
+        _ => {
+            let mut atom = atom.clone();
+            walk_atom(&mut ExpressionFolder, &mut atom);
+            Folded::Node(atom)
+        }
+    }
+}
+
+fn case_expression(case: &CaseExpression) -> Folded<Atom> {
+    let subject = case.expression.as_ref().map(or);
+    let mut alternatives = Vec::new();
+    let mut chosen = None;
+
+    for (when, then) in &case.alternatives {
+        let when = or(when);
+        if alternatives.is_empty() && chosen.is_none() {
+            // A simple CASE compares the subject with `=`, a generic CASE tests the condition.
+            let test = match (&subject, when.constant()) {
+                (Some(subject), Some(value)) => subject.constant().map(|subject| subject.equals(value)),
+                (None, Some(value)) => value.truth(),
+                (_, None) => None,
+            };
+            match test {
+                Some(Some(true)) => chosen = Some(then),
+                Some(_) => continue,
+                None => (),
+            }
+        }
+        if chosen.is_none() {
+            alternatives.push((when.into_node(), fold_expression(then)));
+        }
+    }
+
+    let result = match (chosen, alternatives.is_empty()) {
+        (Some(then), _) => Some(then.clone()),
+        (None, true) => Some(case.else_.clone().unwrap_or_else(|| Expression::from_constant(&Constant::Null))),
+        (None, false) => None,
+    };
+    match result {
+        Some(result) => match or(&result) {
+            Folded::Constant(value) => Folded::Constant(value),
+            Folded::Node(expression) => Folded::Node(parenthesize(expression)),
+        },
+        None => Folded::Node(Atom::CaseExpression(CaseExpression {
+            expression: subject.map(Folded::into_node),
+            alternatives,
+            else_: case.else_.as_ref().map(fold_expression),
+        })),
+    }
+}
@@ -0,0 +1,422 @@
+//! A catalog of the functions that may be called from Cypher expressions.
+//!
+//! [`FunctionRegistry::builtin`] describes the openCypher built-in functions.
+//! User-defined functions can be added with [`FunctionRegistry::register`],
+//! after which they are known to all passes that take a registry.
+//! [`check_functions`] reports calls of unknown functions and calls with the
+//! wrong number of arguments; argument types are checked by
+//! [`crate::analysis::types`].
+
+use std::collections::HashMap;
+
+use crate::analysis::semantic::{SemanticError, SemanticErrorKind};
+use crate::analysis::types::{element_type, join, list, number};
+use crate::ast::cypher::visit::{walk_function_invocation, Visitor};
+use crate::ast::cypher::{CypherType, FunctionInvocation, QualifiedName, Query};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionCategory {
+    Aggregate,
+    Predicate,
+    Scalar,
+    List,
+    Math,
+    Trigonometric,
+    String,
+    Temporal,
+    Spatial,
+    UserDefined,
+}
+
+/// Whether a function returns the same result for the same arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Determinism {
+    Deterministic,
+    NonDeterministic,
+    /// Deterministic unless called without arguments, e.g. `date()` returns
+    /// the current date whereas `date('2020-01-01')` is a constant.
+    NonDeterministicWithoutArguments,
+}
+
+/// How the return type of a function is determined.
+#[derive(Debug, Clone)]
+pub enum ReturnType {
+    Fixed(CypherType),
+    /// Computed from the types of the arguments, e.g. `head()` returns the
+    /// element type of its list argument.
+    Computed(fn(&[CypherType]) -> CypherType),
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub type_: CypherType,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    pub name: QualifiedName,
+    pub category: FunctionCategory,
+    pub parameters: Vec<Parameter>,
+    /// Whether the last parameter may be repeated any number of times.
+    pub variadic: bool,
+    pub returns: ReturnType,
+    pub determinism: Determinism,
+    pub aggregate: bool,
+}
+
+impl FunctionSignature {
+    /// Creates a deterministic, non-aggregating signature without parameters.
+    /// `name` may contain a namespace, e.g. `date.truncate`.
+    pub fn new(name: &str, category: FunctionCategory, returns: ReturnType) -> Self {
+        let mut parts: Vec<String> = name.split('.').map(str::to_string).collect();
+        let name = parts.pop().unwrap_or_default();
+        FunctionSignature {
+            name: QualifiedName { namespace: parts, name },
+            category,
+            parameters: Vec::new(),
+            variadic: false,
+            returns,
+            determinism: Determinism::Deterministic,
+            aggregate: category == FunctionCategory::Aggregate,
+        }
+    }
+
+    pub fn parameter(mut self, name: &str, type_: CypherType) -> Self {
+        self.parameters.push(Parameter { name: name.to_string(), type_, optional: false });
+        self
+    }
+
+    pub fn optional_parameter(mut self, name: &str, type_: CypherType) -> Self {
+        self.parameters.push(Parameter { name: name.to_string(), type_, optional: true });
+        self
+    }
+
+    pub fn variadic(mut self) -> Self {
+        self.variadic = true;
+        self
+    }
+
+    pub fn non_deterministic(mut self) -> Self {
+        self.determinism = Determinism::NonDeterministic;
+        self
+    }
+
+    pub fn non_deterministic_without_arguments(mut self) -> Self {
+        self.determinism = Determinism::NonDeterministicWithoutArguments;
+        self
+    }
+
+    pub fn aggregate(mut self) -> Self {
+        self.aggregate = true;
+        self
+    }
+
+    /// Returns whether a call with `arguments` arguments returns the same
+    /// result every time it is evaluated with the same argument values.
+    pub fn is_deterministic(&self, arguments: usize) -> bool {
+        match self.determinism {
+            Determinism::Deterministic => true,
+            Determinism::NonDeterministic => false,
+            Determinism::NonDeterministicWithoutArguments => arguments > 0,
+        }
+    }
+
+    pub fn min_arguments(&self) -> usize {
+        self.parameters.iter().filter(|parameter| !parameter.optional).count()
+    }
+
+    /// Returns the maximal number of arguments, or `None` for variadic functions.
+    pub fn max_arguments(&self) -> Option<usize> {
+        if self.variadic { None } else { Some(self.parameters.len()) }
+    }
+
+    /// Returns the parameter the argument at `index` is passed to.
+    pub fn parameter_at(&self, index: usize) -> Option<&Parameter> {
+        match self.parameters.get(index) {
+            Some(parameter) => Some(parameter),
+            None if self.variadic => self.parameters.last(),
+            None => None,
+        }
+    }
+
+    pub fn return_type(&self, arguments: &[CypherType]) -> CypherType {
+        match &self.returns {
+            ReturnType::Fixed(type_) => type_.clone(),
+            ReturnType::Computed(compute) => compute(arguments),
+        }
+    }
+}
+
+/// Functions by their case-insensitive qualified name.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, FunctionSignature>,
+}
+
+fn key(name: &QualifiedName) -> String {
+    name.parts().collect::<Vec<_>>().join(".").to_lowercase()
+}
+
+impl FunctionRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        FunctionRegistry::default()
+    }
+
+    /// Creates a registry containing the openCypher built-in functions.
+    pub fn builtin() -> Self {
+        let mut registry = FunctionRegistry::new();
+        for signature in builtin_functions() {
+            registry.register(signature);
+        }
+        registry
+    }
+
+    /// Adds a function, replacing and returning a function of the same name.
+    pub fn register(&mut self, signature: FunctionSignature) -> Option<FunctionSignature> {
+        self.functions.insert(key(&signature.name), signature)
+    }
+
+    pub fn lookup(&self, name: &QualifiedName) -> Option<&FunctionSignature> {
+        self.functions.get(&key(name))
+    }
+
+    pub fn is_aggregate(&self, function: &FunctionInvocation) -> bool {
+        self.lookup(&function.function_name).is_some_and(|signature| signature.aggregate)
+    }
+
+    /// Returns whether the call is known to be deterministic; calls of
+    /// unknown functions are not.
+    pub fn is_deterministic(&self, function: &FunctionInvocation) -> bool {
+        self.lookup(&function.function_name).is_some_and(|signature| signature.is_deterministic(function.arguments.len()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FunctionSignature> {
+        self.functions.values()
+    }
+}
+
+/// Reports calls of unknown functions and calls with a wrong number of arguments.
+pub fn check_functions(query: &Query, registry: &FunctionRegistry) -> Vec<SemanticError> {
+    let mut checker = FunctionChecker { registry, errors: Vec::new() };
+    checker.visit_query(query);
+    checker.errors
+}
+
+struct FunctionChecker<'r> {
+    registry: &'r FunctionRegistry,
+    errors: Vec<SemanticError>,
+}
+
+impl<'ast> Visitor<'ast> for FunctionChecker<'_> {
+    fn visit_function_invocation(&mut self, function: &'ast FunctionInvocation) {
+        match self.registry.lookup(&function.function_name) {
+            None => self.errors.push(SemanticError {
+                kind: SemanticErrorKind::UnknownFunction,
+                span: function.span,
+                message: format!("unknown function `{}`", function.function_name),
+            }),
+            Some(signature) => {
+                let count = function.arguments.len();
+                let too_many = signature.max_arguments().is_some_and(|max| count > max);
+                if count < signature.min_arguments() || too_many {
+                    let expected = match signature.max_arguments() {
+                        Some(max) if max == signature.min_arguments() => max.to_string(),
+                        Some(max) => format!("{} to {}", signature.min_arguments(), max),
+                        None => format!("at least {}", signature.min_arguments()),
+                    };
+                    self.errors.push(SemanticError {
+                        kind: SemanticErrorKind::InvalidNumberOfArguments,
+                        span: function.span,
+                        message: format!("function `{}` expects {} arguments but got {}", function.function_name, expected, count),
+                    });
+                }
+                if function.distinct && !signature.aggregate {
+                    self.errors.push(SemanticError {
+                        kind: SemanticErrorKind::InvalidArgumentPassingMode,
+                        span: function.span,
+                        message: format!("DISTINCT cannot be used with the non-aggregate function `{}`", function.function_name),
+                    });
+                }
+            }
+        }
+
+        walk_function_invocation(self, function);
+    }
+}
+
+fn first_argument(arguments: &[CypherType]) -> CypherType {
+    arguments.first().cloned().unwrap_or(CypherType::Any)
+}
+
+fn element_of_first_argument(arguments: &[CypherType]) -> CypherType {
+    element_type(&first_argument(arguments))
+}
+
+fn list_of_first_argument(arguments: &[CypherType]) -> CypherType {
+    list(first_argument(arguments))
+}
+
+fn join_of_arguments(arguments: &[CypherType]) -> CypherType {
+    arguments.iter().fold(CypherType::Nothing, |type_, argument| join(&type_, argument))
+}
+
+fn average(arguments: &[CypherType]) -> CypherType {
+    match first_argument(arguments) {
+        CypherType::Duration => CypherType::Duration,
+        _ => CypherType::Float,
+    }
+}
+
+fn builtin_functions() -> Vec<FunctionSignature> {
+    use CypherType::*;
+    use FunctionCategory as C;
+    use ReturnType::{Computed, Fixed};
+
+    let f = FunctionSignature::new;
+    let union = CypherType::Union;
+    let number_or_duration = || union(vec![Integer, Float, Duration]);
+    let entity = || union(vec![Node, Relationship]);
+    let entity_or_map = || union(vec![Node, Relationship, Map]);
+    let string_or_list = || union(vec![String, list(Any)]);
+    let temporal = || union(vec![Date, LocalTime, ZonedTime, LocalDateTime, ZonedDateTime]);
+
+    let mut functions = vec![
+        f("avg", C::Aggregate, Computed(average)).parameter("input", number_or_duration()),
+        f("collect", C::Aggregate, Computed(list_of_first_argument)).parameter("input", Any),
+        f("count", C::Aggregate, Fixed(Integer)).parameter("input", Any),
+        f("max", C::Aggregate, Computed(first_argument)).parameter("input", Any),
+        f("min", C::Aggregate, Computed(first_argument)).parameter("input", Any),
+        f("percentileCont", C::Aggregate, Fixed(Float)).parameter("input", number()).parameter("percentile", number()),
+        f("percentileDisc", C::Aggregate, Computed(first_argument)).parameter("input", number()).parameter("percentile", number()),
+        f("stDev", C::Aggregate, Fixed(Float)).parameter("input", number()),
+        f("stDevP", C::Aggregate, Fixed(Float)).parameter("input", number()),
+        f("sum", C::Aggregate, Computed(first_argument)).parameter("input", number_or_duration()),
+        f("exists", C::Predicate, Fixed(Boolean)).parameter("input", Any),
+        f("isEmpty", C::Predicate, Fixed(Boolean)).parameter("input", union(vec![String, list(Any), Map])),
+        f("char_length", C::Scalar, Fixed(Integer)).parameter("input", String),
+        f("character_length", C::Scalar, Fixed(Integer)).parameter("input", String),
+        f("coalesce", C::Scalar, Computed(join_of_arguments)).parameter("input", Any).variadic(),
+        f("elementId", C::Scalar, Fixed(String)).parameter("input", entity()),
+        f("endNode", C::Scalar, Fixed(Node)).parameter("input", Relationship),
+        f("head", C::Scalar, Computed(element_of_first_argument)).parameter("list", list(Any)),
+        f("id", C::Scalar, Fixed(Integer)).parameter("input", entity()),
+        f("last", C::Scalar, Computed(element_of_first_argument)).parameter("list", list(Any)),
+        f("length", C::Scalar, Fixed(Integer)).parameter("input", Path),
+        f("nullIf", C::Scalar, Computed(first_argument)).parameter("v1", Any).parameter("v2", Any),
+        f("properties", C::Scalar, Fixed(Map)).parameter("input", entity_or_map()),
+        f("randomUUID", C::Scalar, Fixed(String)).non_deterministic(),
+        f("size", C::Scalar, Fixed(Integer)).parameter("input", string_or_list()),
+        f("startNode", C::Scalar, Fixed(Node)).parameter("input", Relationship),
+        f("timestamp", C::Scalar, Fixed(Integer)).non_deterministic(),
+        f("toBoolean", C::Scalar, Fixed(Boolean)).parameter("input", union(vec![String, Boolean, Integer])),
+        f("toBooleanOrNull", C::Scalar, Fixed(Boolean)).parameter("input", Any),
+        f("toFloat", C::Scalar, Fixed(Float)).parameter("input", union(vec![String, Integer, Float])),
+        f("toFloatOrNull", C::Scalar, Fixed(Float)).parameter("input", Any),
+        f("toInteger", C::Scalar, Fixed(Integer)).parameter("input", union(vec![String, Integer, Float, Boolean])),
+        f("toIntegerOrNull", C::Scalar, Fixed(Integer)).parameter("input", Any),
+        f("type", C::Scalar, Fixed(String)).parameter("input", Relationship),
+        f("valueType", C::Scalar, Fixed(String)).parameter("input", Any),
+        f("keys", C::List, Fixed(list(String))).parameter("input", entity_or_map()),
+        f("labels", C::List, Fixed(list(String))).parameter("input", Node),
+        f("nodes", C::List, Fixed(list(Node))).parameter("input", Path),
+        f("range", C::List, Fixed(list(Integer)))
+            .parameter("start", Integer)
+            .parameter("end", Integer)
+            .optional_parameter("step", Integer),
+        f("relationships", C::List, Fixed(list(Relationship))).parameter("input", Path),
+        f("reverse", C::List, Computed(first_argument)).parameter("input", string_or_list()),
+        f("tail", C::List, Computed(first_argument)).parameter("input", list(Any)),
+        f("toBooleanList", C::List, Fixed(list(Boolean))).parameter("input", list(Any)),
+        f("toFloatList", C::List, Fixed(list(Float))).parameter("input", list(Any)),
+        f("toIntegerList", C::List, Fixed(list(Integer))).parameter("input", list(Any)),
+        f("toStringList", C::List, Fixed(list(String))).parameter("input", list(Any)),
+        f("abs", C::Math, Computed(first_argument)).parameter("input", number()),
+        f("ceil", C::Math, Fixed(Float)).parameter("input", number()),
+        f("e", C::Math, Fixed(Float)),
+        f("exp", C::Math, Fixed(Float)).parameter("input", number()),
+        f("floor", C::Math, Fixed(Float)).parameter("input", number()),
+        f("isNaN", C::Math, Fixed(Boolean)).parameter("input", number()),
+        f("log", C::Math, Fixed(Float)).parameter("input", number()),
+        f("log10", C::Math, Fixed(Float)).parameter("input", number()),
+        f("pi", C::Math, Fixed(Float)),
+        f("rand", C::Math, Fixed(Float)).non_deterministic(),
+        f("round", C::Math, Fixed(Float))
+            .parameter("input", number())
+            .optional_parameter("precision", number())
+            .optional_parameter("mode", String),
+        f("sign", C::Math, Fixed(Integer)).parameter("input", number()),
+        f("sqrt", C::Math, Fixed(Float)).parameter("input", number()),
+        f("atan2", C::Trigonometric, Fixed(Float)).parameter("y", number()).parameter("x", number()),
+        f("btrim", C::String, Fixed(String)).parameter("original", String).optional_parameter("trimCharacterString", String),
+        f("left", C::String, Fixed(String)).parameter("original", String).parameter("length", Integer),
+        f("ltrim", C::String, Fixed(String)).parameter("original", String).optional_parameter("trimCharacterString", String),
+        f("replace", C::String, Fixed(String)).parameter("original", String).parameter("search", String).parameter("replace", String),
+        f("right", C::String, Fixed(String)).parameter("original", String).parameter("length", Integer),
+        f("rtrim", C::String, Fixed(String)).parameter("original", String).optional_parameter("trimCharacterString", String),
+        f("split", C::String, Fixed(list(String))).parameter("original", String).parameter("splitDelimiter", string_or_list()),
+        f("substring", C::String, Fixed(String))
+            .parameter("original", String)
+            .parameter("start", Integer)
+            .optional_parameter("length", Integer),
+        f("toString", C::String, Fixed(String)).parameter("input", Any),
+        f("toStringOrNull", C::String, Fixed(String)).parameter("input", Any),
+        f("trim", C::String, Fixed(String)).parameter("original", String),
+        f("datetime.fromEpoch", C::Temporal, Fixed(ZonedDateTime)).parameter("seconds", number()).parameter("nanoseconds", number()),
+        f("datetime.fromEpochMillis", C::Temporal, Fixed(ZonedDateTime)).parameter("milliseconds", number()),
+        f("point", C::Spatial, Fixed(Point)).parameter("input", Map),
+        f("point.distance", C::Spatial, Fixed(Float)).parameter("from", Point).parameter("to", Point),
+        f("point.withinBBox", C::Spatial, Fixed(Boolean))
+            .parameter("point", Point)
+            .parameter("lowerLeft", Point)
+            .parameter("upperRight", Point),
+        f("distance", C::Spatial, Fixed(Float)).parameter("from", Point).parameter("to", Point),
+    ];
+
+    for name in ["lower", "toLower", "upper", "toUpper"] {
+        functions.push(f(name, C::String, Fixed(String)).parameter("original", String));
+    }
+    for name in ["acos", "asin", "atan", "cos", "cot", "degrees", "haversin", "radians", "sin", "tan"] {
+        functions.push(f(name, C::Trigonometric, Fixed(Float)).parameter("input", number()));
+    }
+
+    let temporal_types = [
+        ("date", Date),
+        ("localtime", LocalTime),
+        ("time", ZonedTime),
+        ("localdatetime", LocalDateTime),
+        ("datetime", ZonedDateTime),
+    ];
+    for (name, type_) in temporal_types {
+        // Without arguments the constructors return the current time.
+        functions.push(
+            f(name, C::Temporal, Fixed(type_.clone())).optional_parameter("input", Any).non_deterministic_without_arguments(),
+        );
+        for clock in ["realtime", "statement", "transaction"] {
+            functions.push(
+                f(&format!("{}.{}", name, clock), C::Temporal, Fixed(type_.clone()))
+                    .optional_parameter("timezone", String)
+                    .non_deterministic(),
+            );
+        }
+        functions.push(
+            f(&format!("{}.truncate", name), C::Temporal, Fixed(type_))
+                .parameter("unit", String)
+                .optional_parameter("input", temporal())
+                .optional_parameter("fields", Map),
+        );
+    }
+
+    functions.push(f("duration", C::Temporal, Fixed(Duration)).parameter("input", union(vec![String, Map])));
+    for name in ["between", "inMonths", "inDays", "inSeconds"] {
+        functions.push(
+            f(&format!("duration.{}", name), C::Temporal, Fixed(Duration))
+                .parameter("from", temporal())
+                .parameter("to", temporal()),
+        );
+    }
+
+    functions
+}
@@ -0,0 +1,816 @@
+//! Recognition of temporal and spatial literals.
+//!
+//! Cypher has no literal syntax for dates, times, durations or points; they
+//! are written as calls to constructor functions such as `date('2020-01-01')`
+//! or `point({x: 1, y: 2})`. When such a call only has literal arguments its
+//! value is known statically, so [`recognize_literals`] validates the ISO-8601
+//! strings and map fields and replaces the call with an
+//! [`Atom::TemporalLiteral`] or [`Atom::PointLiteral`].
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::cypher::visit_mut::{walk_atom, VisitorMut};
+use crate::ast::cypher::{
+    Atom, CoordinateReferenceSystem, Date, DateTime, Duration, Expression, FunctionInvocation, Literal, LocalDateTime,
+    LocalTime, NumberLiteral, PointLiteral, Query, Span, TemporalLiteral, Time, TimeZone,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiteralError {
+    pub span: Span,
+    pub function: String,
+    pub message: String,
+}
+
+impl fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid argument to {}() at {}..{}: {}", self.function, self.span.start, self.span.end, self.message)
+    }
+}
+
+impl std::error::Error for LiteralError {}
+
+/// Replaces all temporal and point constructor calls with literal arguments
+/// in `query` by typed literals and returns the errors found in the calls
+/// that could not be converted. Invalid calls are left untouched.
+pub fn recognize_literals(query: &mut Query) -> Vec<LiteralError> {
+    let mut recognizer = LiteralRecognizer { errors: Vec::new() };
+    recognizer.visit_query(query);
+    recognizer.errors
+}
+
+/// Evaluates a single constructor call. Returns `None` if the call is not a
+/// temporal or point constructor or if its arguments are not all literals.
+pub fn recognize_function(function: &FunctionInvocation) -> Option<Result<Atom, LiteralError>> {
+    if !function.function_name.namespace.is_empty() {
+        return None;
+    }
+    let name = function.function_name.name.to_lowercase();
+    if !matches!(name.as_str(), "date" | "localtime" | "time" | "localdatetime" | "datetime" | "duration" | "point")
+        || function.distinct
+    {
+        return None;
+    }
+
+    let [argument] = function.arguments.as_slice() else { return None };
+    let argument = constant(argument)?;
+    let error = |message: String| LiteralError { span: function.span, function: name.clone(), message };
+
+    let result = match (name.as_str(), argument) {
+        ("point", Constant::Map(map)) => point(&map).map(Atom::PointLiteral),
+        ("point", _) => Err("expected a map".to_string()),
+        ("duration", Constant::String(text)) => parse_duration(&text).map(duration_atom),
+        ("duration", Constant::Map(map)) => duration_from_map(&map).map(duration_atom),
+        (_, Constant::String(text)) => temporal_from_string(&name, &text),
+        (_, Constant::Map(map)) => match temporal_from_map(&name, &map) {
+            Ok(Some(temporal)) => Ok(temporal),
+            Ok(None) => return None,
+            Err(message) => Err(message),
+        },
+        _ => Err("expected a string or a map".to_string()),
+    };
+
+    Some(result.map_err(error))
+}
+
+fn duration_atom(duration: Duration) -> Atom {
+    Atom::TemporalLiteral(TemporalLiteral::Duration(duration))
+}
+
+struct LiteralRecognizer {
+    errors: Vec<LiteralError>,
+}
+
+impl VisitorMut for LiteralRecognizer {
+    fn visit_atom(&mut self, atom: &mut Atom) {
+        walk_atom(self, atom);
+
+        if let Atom::FunctionInvocation(function) = atom {
+            match recognize_function(function) {
+                Some(Ok(literal)) => *atom = literal,
+                Some(Err(error)) => self.errors.push(error),
+                None => (),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Constant {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Map(HashMap<String, Constant>),
+}
+
+impl Constant {
+    fn as_integer(&self, key: &str) -> Result<i64, String> {
+        match self {
+            Constant::Integer(value) => Ok(*value),
+            _ => Err(format!("field `{}` must be an integer", key)),
+        }
+    }
+
+    /// Returns the number scaled by 10^9, which keeps nine fractional digits exact.
+    fn as_scaled(&self, key: &str) -> Result<i128, String> {
+        match self {
+            Constant::Integer(value) => Ok(*value as i128 * NANOS_PER_SECOND),
+            Constant::Float(value) if value.is_finite() => {
+                let scaled = (value * 1e9).round();
+                if scaled.abs() < i128::MAX as f64 { Ok(scaled as i128) } else { Err(format!("field `{}` is out of range", key)) }
+            }
+            _ => Err(format!("field `{}` must be a number", key)),
+        }
+    }
+
+    fn as_float(&self, key: &str) -> Result<f64, String> {
+        match self {
+            Constant::Integer(value) => Ok(*value as f64),
+            Constant::Float(value) => Ok(*value),
+            _ => Err(format!("field `{}` must be a number", key)),
+        }
+    }
+}
+
+/// Returns the value of a literal string, number or map of such literals.
+fn constant(expression: &Expression) -> Option<Constant> {
+    let (negate, atom) = expression.as_signed_atom()?;
+    match atom {
+        Atom::Literal(Literal::Number(NumberLiteral::Integer(value))) => {
+            Some(Constant::Integer(if negate { value.checked_neg()? } else { *value }))
+        }
+        Atom::Literal(Literal::Number(NumberLiteral::Double(value))) => {
+            Some(Constant::Float(if negate { -value } else { *value }))
+        }
+        Atom::Literal(Literal::String(value)) if !negate => Some(Constant::String(value.clone())),
+        Atom::Literal(Literal::MapLiteral(map)) if !negate => map
+            .iter()
+            .map(|(key, value)| match constant(value)? {
+                Constant::Map(_) => None,
+                value => Some((key.clone(), value)),
+            })
+            .collect::<Option<HashMap<_, _>>>()
+            .map(Constant::Map),
+        Atom::ParenthesizedExpression(expression) if !negate => constant(expression),
+        _ => None,
+    }
+}
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const SECONDS_PER_DAY: i128 = 86_400;
+/// The average length of a month in seconds, used to spill fractional months.
+const SECONDS_PER_MONTH: i128 = 2_629_746;
+const MIN_YEAR: i64 = -999_999_999;
+const MAX_YEAR: i64 = 999_999_999;
+const MAX_OFFSET_SECONDS: i32 = 18 * 3600;
+
+fn temporal_from_string(name: &str, text: &str) -> Result<Atom, String> {
+    let temporal = match name {
+        "date" => TemporalLiteral::Date(parse_date(text)?),
+        "localtime" => TemporalLiteral::LocalTime(parse_local_time(text)?),
+        "time" => {
+            let (time, timezone) = split_time_zone(text);
+            TemporalLiteral::Time(Time { time: parse_local_time(time)?, timezone: parse_time_zone(timezone)? })
+        }
+        "localdatetime" => {
+            let (date, time) = split_date_time(text);
+            let time = time.map(parse_local_time).transpose()?.unwrap_or_default();
+            TemporalLiteral::LocalDateTime(LocalDateTime { date: parse_date(date)?, time })
+        }
+        "datetime" => {
+            let (date, time) = split_date_time(text);
+            let (time, timezone) = match time {
+                Some(time) => {
+                    let (time, timezone) = split_time_zone(time);
+                    (parse_local_time(time)?, parse_time_zone(timezone)?)
+                }
+                None => (LocalTime::default(), None),
+            };
+            TemporalLiteral::DateTime(DateTime { date: parse_date(date)?, time, timezone })
+        }
+        _ => unreachable!("Unexpected temporal constructor: {}", name),
+    };
+
+    Ok(Atom::TemporalLiteral(temporal))
+}
+
+fn temporal_from_map(name: &str, map: &HashMap<String, Constant>) -> Result<Option<Atom>, String> {
+    const DATE_FIELDS: &[&str] = &["year", "month", "day", "week", "dayOfWeek", "quarter", "dayOfQuarter", "ordinalDay"];
+    const TIME_FIELDS: &[&str] = &["hour", "minute", "second", "millisecond", "microsecond", "nanosecond"];
+
+    let (date_fields, time_fields, timezone_field, epoch_fields) = match name {
+        "date" => (true, false, false, false),
+        "localtime" => (false, true, false, false),
+        "time" => (false, true, true, false),
+        "localdatetime" => (true, true, false, false),
+        "datetime" => (true, true, true, true),
+        _ => unreachable!("Unexpected temporal constructor: {}", name),
+    };
+
+    for key in map.keys() {
+        let known = (date_fields && DATE_FIELDS.contains(&key.as_str()))
+            || (time_fields && TIME_FIELDS.contains(&key.as_str()))
+            || (timezone_field && key == "timezone")
+            || (epoch_fields && (key == "epochSeconds" || key == "epochMillis"));
+        if !known {
+            return Err(format!("unknown field `{}`", key));
+        }
+    }
+
+    let timezone = match map.get("timezone") {
+        Some(Constant::String(timezone)) => Some(parse_time_zone_name(timezone)?),
+        Some(_) => return Err("field `timezone` must be a string".to_string()),
+        None => None,
+    };
+
+    if map.contains_key("epochSeconds") || map.contains_key("epochMillis") {
+        return datetime_from_epoch(map, timezone).map(|datetime| datetime.map(Atom::TemporalLiteral));
+    }
+
+    let temporal = match name {
+        "date" => TemporalLiteral::Date(date_from_map(map)?),
+        "localtime" => TemporalLiteral::LocalTime(time_from_map(map, true)?),
+        "time" => TemporalLiteral::Time(Time { time: time_from_map(map, true)?, timezone }),
+        "localdatetime" => {
+            TemporalLiteral::LocalDateTime(LocalDateTime { date: date_from_map(map)?, time: time_from_map(map, false)? })
+        }
+        "datetime" => {
+            TemporalLiteral::DateTime(DateTime { date: date_from_map(map)?, time: time_from_map(map, false)?, timezone })
+        }
+        _ => unreachable!("Unexpected temporal constructor: {}", name),
+    };
+
+    Ok(Some(Atom::TemporalLiteral(temporal)))
+}
+
+fn integer_field(map: &HashMap<String, Constant>, key: &str) -> Result<Option<i64>, String> {
+    map.get(key).map(|value| value.as_integer(key)).transpose()
+}
+
+fn date_from_map(map: &HashMap<String, Constant>) -> Result<Date, String> {
+    let field = |key| integer_field(map, key);
+    let year = field("year")?.ok_or("field `year` is required")?;
+    let (month, day) = (field("month")?, field("day")?);
+    let (week, day_of_week) = (field("week")?, field("dayOfWeek")?);
+    let (quarter, day_of_quarter) = (field("quarter")?, field("dayOfQuarter")?);
+    let ordinal_day = field("ordinalDay")?;
+
+    let groups = [month.is_some() || day.is_some(), week.is_some() || day_of_week.is_some(),
+        quarter.is_some() || day_of_quarter.is_some(), ordinal_day.is_some()];
+    if groups.iter().filter(|group| **group).count() > 1 {
+        return Err("calendar, week, quarter and ordinal date fields cannot be combined".to_string());
+    }
+
+    if let Some(ordinal_day) = ordinal_day {
+        ordinal_date(year, ordinal_day)
+    }
+    else if week.is_some() || day_of_week.is_some() {
+        let week = week.ok_or("field `dayOfWeek` requires `week`")?;
+        week_date(year, week, day_of_week.unwrap_or(1))
+    }
+    else if quarter.is_some() || day_of_quarter.is_some() {
+        let quarter = quarter.ok_or("field `dayOfQuarter` requires `quarter`")?;
+        quarter_date(year, quarter, day_of_quarter.unwrap_or(1))
+    }
+    else {
+        if day.is_some() && month.is_none() {
+            return Err("field `day` requires `month`".to_string());
+        }
+        calendar_date(year, month.unwrap_or(1), day.unwrap_or(1))
+    }
+}
+
+fn time_from_map(map: &HashMap<String, Constant>, hour_required: bool) -> Result<LocalTime, String> {
+    let field = |key| integer_field(map, key);
+    let (hour, minute, second) = (field("hour")?, field("minute")?, field("second")?);
+    let (millisecond, microsecond, nanosecond) = (field("millisecond")?, field("microsecond")?, field("nanosecond")?);
+    let sub_second = millisecond.is_some() || microsecond.is_some() || nanosecond.is_some();
+
+    if hour.is_none() && (hour_required || minute.is_some()) {
+        return Err("field `hour` is required".to_string());
+    }
+    if minute.is_none() && second.is_some() {
+        return Err("field `second` requires `minute`".to_string());
+    }
+    if second.is_none() && sub_second {
+        return Err("sub-second fields require `second`".to_string());
+    }
+
+    let millisecond = bounded(millisecond.unwrap_or(0), 0, 999, "millisecond")?;
+    let microsecond = bounded(microsecond.unwrap_or(0), 0, if millisecond != 0 { 999 } else { 999_999 }, "microsecond")?;
+    let nanosecond = bounded(
+        nanosecond.unwrap_or(0),
+        0,
+        if millisecond != 0 || microsecond != 0 { 999 } else { 999_999_999 },
+        "nanosecond",
+    )?;
+
+    local_time(
+        hour.unwrap_or(0),
+        minute.unwrap_or(0),
+        second.unwrap_or(0),
+        millisecond * 1_000_000 + microsecond * 1_000 + nanosecond,
+    )
+}
+
+/// Returns `None` for named time zones, whose offsets depend on a time zone
+/// database that is not available here.
+fn datetime_from_epoch(map: &HashMap<String, Constant>, timezone: Option<TimeZone>) -> Result<Option<TemporalLiteral>, String> {
+    if map.keys().any(|key| !matches!(key.as_str(), "epochSeconds" | "epochMillis" | "nanosecond" | "timezone")) {
+        return Err("epoch fields cannot be combined with date or time fields".to_string());
+    }
+    let offset = match &timezone {
+        Some(TimeZone::Offset(offset)) => *offset as i128,
+        Some(TimeZone::Named { .. }) => return Ok(None),
+        None => 0,
+    };
+
+    let nanos = match (integer_field(map, "epochSeconds")?, integer_field(map, "epochMillis")?) {
+        (Some(seconds), None) => seconds as i128 * NANOS_PER_SECOND,
+        (None, Some(millis)) => millis as i128 * 1_000_000,
+        _ => return Err("fields `epochSeconds` and `epochMillis` cannot be combined".to_string()),
+    };
+    let nanos = nanos + bounded(integer_field(map, "nanosecond")?.unwrap_or(0), 0, 999_999_999, "nanosecond")? as i128
+        + offset * NANOS_PER_SECOND;
+
+    let days = nanos.div_euclid(SECONDS_PER_DAY * NANOS_PER_SECOND);
+    let nanos_of_day = nanos.rem_euclid(SECONDS_PER_DAY * NANOS_PER_SECOND);
+    let seconds_of_day = (nanos_of_day / NANOS_PER_SECOND) as i64;
+    let date = date_from_days(days as i64)?;
+    let time = local_time(seconds_of_day / 3600, seconds_of_day / 60 % 60, seconds_of_day % 60, (nanos_of_day % NANOS_PER_SECOND) as i64)?;
+
+    Ok(Some(TemporalLiteral::DateTime(DateTime { date, time, timezone })))
+}
+
+fn bounded(value: i64, min: i64, max: i64, name: &str) -> Result<i64, String> {
+    if (min..=max).contains(&value) {
+        Ok(value)
+    }
+    else {
+        Err(format!("{} must be between {} and {}, got {}", name, min, max, value))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn date_from_days(days: i64) -> Result<Date, String> {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    calendar_date(year, month, day)
+}
+
+/// ISO day of the week, Monday being 1.
+fn day_of_week(days: i64) -> i64 {
+    (days + 3).rem_euclid(7) + 1
+}
+
+fn weeks_in_year(year: i64) -> i64 {
+    let first_day = day_of_week(days_from_civil(year, 1, 1));
+    if first_day == 4 || (first_day == 3 && is_leap_year(year)) { 53 } else { 52 }
+}
+
+fn calendar_date(year: i64, month: i64, day: i64) -> Result<Date, String> {
+    bounded(year, MIN_YEAR, MAX_YEAR, "year")?;
+    bounded(month, 1, 12, "month")?;
+    bounded(day, 1, days_in_month(year, month), "day")?;
+    Ok(Date { year, month: month as u8, day: day as u8 })
+}
+
+fn ordinal_date(year: i64, ordinal_day: i64) -> Result<Date, String> {
+    bounded(year, MIN_YEAR, MAX_YEAR, "year")?;
+    bounded(ordinal_day, 1, if is_leap_year(year) { 366 } else { 365 }, "ordinalDay")?;
+    date_from_days(days_from_civil(year, 1, 1) + ordinal_day - 1)
+}
+
+fn week_date(year: i64, week: i64, weekday: i64) -> Result<Date, String> {
+    bounded(year, MIN_YEAR, MAX_YEAR, "year")?;
+    bounded(week, 1, weeks_in_year(year), "week")?;
+    bounded(weekday, 1, 7, "dayOfWeek")?;
+    let january_4 = days_from_civil(year, 1, 4);
+    let first_monday = january_4 - (day_of_week(january_4) - 1);
+    date_from_days(first_monday + (week - 1) * 7 + weekday - 1)
+}
+
+fn quarter_date(year: i64, quarter: i64, day_of_quarter: i64) -> Result<Date, String> {
+    bounded(year, MIN_YEAR, MAX_YEAR, "year")?;
+    bounded(quarter, 1, 4, "quarter")?;
+    let first_month = (quarter - 1) * 3 + 1;
+    let days = (first_month..first_month + 3).map(|month| days_in_month(year, month)).sum();
+    bounded(day_of_quarter, 1, days, "dayOfQuarter")?;
+    date_from_days(days_from_civil(year, first_month, 1) + day_of_quarter - 1)
+}
+
+fn local_time(hour: i64, minute: i64, second: i64, nanosecond: i64) -> Result<LocalTime, String> {
+    Ok(LocalTime {
+        hour: bounded(hour, 0, 23, "hour")? as u8,
+        minute: bounded(minute, 0, 59, "minute")? as u8,
+        second: bounded(second, 0, 59, "second")? as u8,
+        nanosecond: bounded(nanosecond, 0, 999_999_999, "nanosecond")? as u32,
+    })
+}
+
+/// Splits off exactly `count` leading ASCII digits.
+fn digits(text: &str, count: usize) -> Result<(i64, &str), String> {
+    match text.get(..count) {
+        Some(number) if number.bytes().all(|byte| byte.is_ascii_digit()) => {
+            let number = number.parse().map_err(|_| format!("`{}` is out of range", number))?;
+            Ok((number, &text[count..]))
+        }
+        _ => Err(format!("expected {} digits in `{}`", count, text)),
+    }
+}
+
+fn expect_end(text: &str) -> Result<(), String> {
+    if text.is_empty() { Ok(()) } else { Err(format!("unexpected `{}`", text)) }
+}
+
+/// Parses the ISO-8601 calendar (`2015-07-21`, `20150721`, `2015-07`, `2015`),
+/// week (`2015-W30-2`, `2015W302`, `2015-W30`) and ordinal (`2015-202`) forms.
+fn parse_date(text: &str) -> Result<Date, String> {
+    let (year, rest) = match text.strip_prefix(['+', '-']) {
+        Some(rest) => {
+            let length = rest.bytes().take_while(u8::is_ascii_digit).count();
+            let (year, rest) = digits(rest, length.max(4))?;
+            (if text.starts_with('-') { -year } else { year }, rest)
+        }
+        None => digits(text, 4)?,
+    };
+
+    let (hyphen, rest) = match rest.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    if let Some(rest) = rest.strip_prefix('W') {
+        let (week, rest) = digits(rest, 2)?;
+        let weekday = match rest {
+            "" => 1,
+            _ => {
+                let rest = if hyphen { rest.strip_prefix('-').ok_or(format!("expected `-` in `{}`", text))? } else { rest };
+                let (weekday, rest) = digits(rest, 1)?;
+                expect_end(rest)?;
+                weekday
+            }
+        };
+        return week_date(year, week, weekday);
+    }
+
+    match (hyphen, rest.len()) {
+        (false, 0) => calendar_date(year, 1, 1),
+        (_, 3) => ordinal_date(year, digits(rest, 3)?.0),
+        (_, 2) => calendar_date(year, digits(rest, 2)?.0, 1),
+        (true, 5) => {
+            let (month, rest) = digits(rest, 2)?;
+            let rest = rest.strip_prefix('-').ok_or(format!("expected `-` in `{}`", text))?;
+            calendar_date(year, month, digits(rest, 2)?.0)
+        }
+        (false, 4) => {
+            let (month, rest) = digits(rest, 2)?;
+            calendar_date(year, month, digits(rest, 2)?.0)
+        }
+        _ => Err(format!("`{}` is not a valid date", text)),
+    }
+}
+
+/// Parses `21:40:32.142`, `214032.142`, `21:40`, `2140` and `21`.
+fn parse_local_time(text: &str) -> Result<LocalTime, String> {
+    let (hour, rest) = digits(text, 2)?;
+    let (mut minute, mut second, mut nanosecond) = (0, 0, 0);
+
+    if !rest.is_empty() {
+        let colon = rest.starts_with(':');
+        let (value, rest) = digits(if colon { &rest[1..] } else { rest }, 2)?;
+        minute = value;
+
+        if !rest.is_empty() {
+            let rest = if colon { rest.strip_prefix(':').ok_or(format!("expected `:` in `{}`", text))? } else { rest };
+            let (value, rest) = digits(rest, 2)?;
+            second = value;
+
+            if !rest.is_empty() {
+                let fraction = rest.strip_prefix(['.', ',']).ok_or(format!("unexpected `{}`", rest))?;
+                if fraction.is_empty() || fraction.len() > 9 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Err(format!("`{}` is not a valid fraction of a second", fraction));
+                }
+                nanosecond = format!("{:0<9}", fraction).parse().unwrap();
+            }
+        }
+    }
+
+    local_time(hour, minute, second, nanosecond)
+}
+
+fn split_date_time(text: &str) -> (&str, Option<&str>) {
+    match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    }
+}
+
+fn split_time_zone(text: &str) -> (&str, &str) {
+    let index = text.find(['Z', '+', '-', '[']).unwrap_or(text.len());
+    text.split_at(index)
+}
+
+/// Parses the zone part of a time string: an offset, a bracketed zone name or both.
+fn parse_time_zone(text: &str) -> Result<Option<TimeZone>, String> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let (offset, name) = match text.split_once('[') {
+        Some((offset, name)) => {
+            let name = name.strip_suffix(']').ok_or(format!("expected `]` in `{}`", text))?;
+            (offset, Some(name))
+        }
+        None => (text, None),
+    };
+    let offset = if offset.is_empty() { None } else { Some(parse_offset(offset)?) };
+
+    match (name, offset) {
+        (Some(name), offset) => match parse_time_zone_name(name)? {
+            TimeZone::Named { name, .. } => Ok(Some(TimeZone::Named { name, offset })),
+            TimeZone::Offset(zone_offset) if offset.is_none() || offset == Some(zone_offset) => Ok(Some(TimeZone::Offset(zone_offset))),
+            TimeZone::Offset(_) => Err(format!("conflicting offsets in `{}`", text)),
+        },
+        (None, Some(offset)) => Ok(Some(TimeZone::Offset(offset))),
+        (None, None) => unreachable!(),
+    }
+}
+
+/// Parses the value of a `timezone` field, which is either an offset or a zone name.
+fn parse_time_zone_name(text: &str) -> Result<TimeZone, String> {
+    if text.starts_with(['Z', '+', '-']) {
+        return parse_offset(text).map(TimeZone::Offset);
+    }
+
+    let valid = !text.is_empty()
+        && text.bytes().all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'/' | b'_' | b'-' | b'+'))
+        && !text.starts_with('/')
+        && !text.ends_with('/');
+    if valid {
+        Ok(TimeZone::Named { name: text.to_string(), offset: None })
+    }
+    else {
+        Err(format!("`{}` is not a valid time zone", text))
+    }
+}
+
+/// Parses `Z`, `+01`, `+0100` and `+01:00` into seconds east of UTC.
+fn parse_offset(text: &str) -> Result<i32, String> {
+    if text == "Z" {
+        return Ok(0);
+    }
+
+    let sign = match text.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Err(format!("`{}` is not a valid offset", text)),
+    };
+    let (hours, rest) = digits(&text[1..], 2)?;
+    let minutes = match rest {
+        "" => 0,
+        _ => {
+            let (minutes, rest) = digits(rest.strip_prefix(':').unwrap_or(rest), 2)?;
+            expect_end(rest)?;
+            bounded(minutes, 0, 59, "offset minutes")?
+        }
+    };
+
+    let offset = sign * (hours * 3600 + minutes * 60) as i32;
+    if offset.abs() > MAX_OFFSET_SECONDS {
+        return Err(format!("offset `{}` is out of range", text));
+    }
+    Ok(offset)
+}
+
+/// Parses a decimal number into a value scaled by 10^9.
+fn parse_scaled(text: &str) -> Result<i128, String> {
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text),
+    };
+    let (integer, fraction) = unsigned.split_once(['.', ',']).unwrap_or((unsigned, ""));
+    let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if integer.is_empty() || !all_digits(integer) || !all_digits(fraction) || fraction.len() > 9 {
+        return Err(format!("`{}` is not a valid number", text));
+    }
+
+    let integer: i128 = integer.parse().map_err(|_| format!("`{}` is out of range", text))?;
+    let fraction: i128 = format!("{:0<9}", fraction).parse().unwrap();
+    let value = integer.checked_mul(NANOS_PER_SECOND).ok_or(format!("`{}` is out of range", text))? + fraction;
+    Ok(if negative { -value } else { value })
+}
+
+/// Parses `P1Y2M3W4DT5H6M7.5S` and the alternative `P0001-02-03T04:05:06` form.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let body = text.strip_prefix('P').ok_or(format!("`{}` is not a valid duration", text))?;
+    let (date, time) = match body.split_once('T') {
+        Some((date, time)) if !time.is_empty() => (date, Some(time)),
+        Some(_) => return Err(format!("`{}` is not a valid duration", text)),
+        None => (body, None),
+    };
+
+    let (mut months, mut days, mut nanos) = (0, 0, 0);
+
+    let alternative_form = (!date.is_empty() && !date.contains(|c: char| c.is_ascii_alphabetic()))
+        || time.is_some_and(|time| time.contains(':'));
+    if alternative_form {
+        if !date.is_empty() {
+            let (years, rest) = digits(date, 4)?;
+            let (month_count, rest) = digits(rest.strip_prefix('-').unwrap_or(rest), 2)?;
+            let (day_count, rest) = digits(rest.strip_prefix('-').unwrap_or(rest), 2)?;
+            expect_end(rest)?;
+            months = (years as i128 * 12 + bounded(month_count, 0, 12, "months")? as i128) * NANOS_PER_SECOND;
+            days = bounded(day_count, 0, 31, "days")? as i128 * NANOS_PER_SECOND;
+        }
+        if let Some(time) = time {
+            let time = parse_local_time(time)?;
+            nanos = (time.hour as i128 * 3600 + time.minute as i128 * 60 + time.second as i128) * NANOS_PER_SECOND
+                + time.nanosecond as i128;
+        }
+        return normalize_duration(months, days, nanos);
+    }
+
+    let mut components = Vec::new();
+    for (part, units, is_time) in [(date, "YMWD", false), (time.unwrap_or(""), "HMS", true)] {
+        let mut rest = part;
+        let mut allowed = units;
+        while !rest.is_empty() {
+            let index = rest.find(|c: char| c.is_ascii_alphabetic()).ok_or(format!("missing unit in `{}`", text))?;
+            let unit = rest[index..].chars().next().unwrap();
+            let position = allowed.find(unit).ok_or(format!("unexpected unit `{}` in `{}`", unit, text))?;
+            components.push((is_time, unit, parse_scaled(&rest[..index])?));
+            allowed = &allowed[position + 1..];
+            rest = &rest[index + 1..];
+        }
+    }
+    if components.is_empty() {
+        return Err(format!("`{}` is not a valid duration", text));
+    }
+
+    for (is_time, unit, value) in components {
+        match (is_time, unit) {
+            (false, 'Y') => accumulate(&mut months, value, 12, 1)?,
+            (false, 'M') => accumulate(&mut months, value, 1, 1)?,
+            (false, 'W') => accumulate(&mut days, value, 7, 1)?,
+            (false, 'D') => accumulate(&mut days, value, 1, 1)?,
+            (true, 'H') => accumulate(&mut nanos, value, 3600, 1)?,
+            (true, 'M') => accumulate(&mut nanos, value, 60, 1)?,
+            (true, 'S') => accumulate(&mut nanos, value, 1, 1)?,
+            _ => unreachable!(),
+        }
+    }
+
+    normalize_duration(months, days, nanos)
+}
+
+fn duration_from_map(map: &HashMap<String, Constant>) -> Result<Duration, String> {
+    let (mut months, mut days, mut nanos) = (0i128, 0i128, 0i128);
+
+    for (key, value) in map {
+        let value = value.as_scaled(key)?;
+        let (total, factor, divisor) = match key.as_str() {
+            "years" => (&mut months, 12, 1),
+            "quarters" => (&mut months, 3, 1),
+            "months" => (&mut months, 1, 1),
+            "weeks" => (&mut days, 7, 1),
+            "days" => (&mut days, 1, 1),
+            "hours" => (&mut nanos, 3600, 1),
+            "minutes" => (&mut nanos, 60, 1),
+            "seconds" => (&mut nanos, 1, 1),
+            "milliseconds" => (&mut nanos, 1, 1_000),
+            "microseconds" => (&mut nanos, 1, 1_000_000),
+            "nanoseconds" => (&mut nanos, 1, NANOS_PER_SECOND),
+            _ => return Err(format!("unknown field `{}`", key)),
+        };
+        accumulate(total, value, factor, divisor)?;
+    }
+
+    normalize_duration(months, days, nanos)
+}
+
+/// Adds `value * factor / divisor` to a duration component, failing instead of overflowing.
+fn accumulate(total: &mut i128, value: i128, factor: i128, divisor: i128) -> Result<(), String> {
+    *total = value.checked_mul(factor)
+        .and_then(|value| total.checked_add(value / divisor))
+        .ok_or_else(|| "duration is out of range".to_string())?;
+    Ok(())
+}
+
+/// Builds a duration from months and days scaled by 10^9 and nanoseconds,
+/// spilling fractional months into days and fractional days into seconds.
+fn normalize_duration(months: i128, days: i128, nanos: i128) -> Result<Duration, String> {
+    let out_of_range = || "duration is out of range".to_string();
+    let nanos_per_day = SECONDS_PER_DAY * NANOS_PER_SECOND;
+    let whole_months = months / NANOS_PER_SECOND;
+    let spilled_nanos = months % NANOS_PER_SECOND * SECONDS_PER_MONTH;
+    let days = days.checked_add(spilled_nanos / nanos_per_day * NANOS_PER_SECOND).ok_or_else(out_of_range)?;
+    let nanos = nanos.checked_add(spilled_nanos % nanos_per_day).ok_or_else(out_of_range)?;
+
+    let whole_days = days / NANOS_PER_SECOND;
+    let nanos = nanos.checked_add(days % NANOS_PER_SECOND * SECONDS_PER_DAY).ok_or_else(out_of_range)?;
+
+    Ok(Duration {
+        months: i64::try_from(whole_months).map_err(|_| out_of_range())?,
+        days: i64::try_from(whole_days).map_err(|_| out_of_range())?,
+        seconds: i64::try_from(nanos.div_euclid(NANOS_PER_SECOND)).map_err(|_| out_of_range())?,
+        nanoseconds: nanos.rem_euclid(NANOS_PER_SECOND) as i32,
+    })
+}
+
+fn point(map: &HashMap<String, Constant>) -> Result<PointLiteral, String> {
+    for key in map.keys() {
+        if !matches!(key.as_str(), "x" | "y" | "z" | "longitude" | "latitude" | "height" | "crs" | "srid") {
+            return Err(format!("unknown field `{}`", key));
+        }
+    }
+
+    let number = |key: &str| map.get(key).map(|value| value.as_float(key)).transpose();
+    let cartesian = [number("x")?, number("y")?, number("z")?];
+    let geographic = [number("longitude")?, number("latitude")?, number("height")?];
+
+    let (coordinates, geographic) = match (cartesian, geographic) {
+        ([Some(x), Some(y), z], [None, None, None]) => (std::iter::once(x).chain([y]).chain(z).collect::<Vec<_>>(), false),
+        ([None, None, None], [Some(longitude), Some(latitude), height]) => {
+            bounded_float(latitude, -90.0, 90.0, "latitude")?;
+            (std::iter::once(longitude).chain([latitude]).chain(height).collect(), true)
+        }
+        ([None, None, None], [None, None, None]) => return Err("a point requires coordinates".to_string()),
+        ([_, _, _], [None, None, None]) => return Err("a cartesian point requires both `x` and `y`".to_string()),
+        ([None, None, None], [_, _, _]) => return Err("a geographic point requires both `longitude` and `latitude`".to_string()),
+        _ => return Err("cartesian and geographic coordinates cannot be combined".to_string()),
+    };
+
+    let crs = match (map.get("crs"), map.get("srid")) {
+        (Some(_), Some(_)) => return Err("fields `crs` and `srid` cannot be combined".to_string()),
+        (Some(Constant::String(name)), None) => crs_by(|crs| crs.name().eq_ignore_ascii_case(name))
+            .ok_or(format!("unknown coordinate reference system `{}`", name))?,
+        (Some(_), None) => return Err("field `crs` must be a string".to_string()),
+        (None, Some(srid)) => {
+            let srid = srid.as_integer("srid")?;
+            crs_by(|crs| crs.srid() as i64 == srid).ok_or(format!("unknown srid {}", srid))?
+        }
+        (None, None) => match (geographic, coordinates.len()) {
+            (false, 2) => CoordinateReferenceSystem::Cartesian,
+            (false, _) => CoordinateReferenceSystem::Cartesian3D,
+            (true, 2) => CoordinateReferenceSystem::Wgs84,
+            (true, _) => CoordinateReferenceSystem::Wgs843D,
+        },
+    };
+
+    if crs.dimensions() != coordinates.len() {
+        return Err(format!("{} requires {} coordinates, got {}", crs.name(), crs.dimensions(), coordinates.len()));
+    }
+
+    Ok(PointLiteral { crs, coordinates })
+}
+
+fn crs_by(predicate: impl Fn(&CoordinateReferenceSystem) -> bool) -> Option<CoordinateReferenceSystem> {
+    [
+        CoordinateReferenceSystem::Cartesian,
+        CoordinateReferenceSystem::Cartesian3D,
+        CoordinateReferenceSystem::Wgs84,
+        CoordinateReferenceSystem::Wgs843D,
+    ]
+    .into_iter()
+    .find(predicate)
+}
+
+fn bounded_float(value: f64, min: f64, max: f64, name: &str) -> Result<f64, String> {
+    if (min..=max).contains(&value) {
+        Ok(value)
+    }
+    else {
+        Err(format!("{} must be between {} and {}, got {}", name, min, max, value))
+    }
+}
@@ -0,0 +1,12 @@
+//! Optional analyses and rewrites over the Cypher AST.
+
+pub mod access;
+pub mod aggregation;
+pub mod fold;
+pub mod functions;
+pub mod literals;
+pub mod normalize;
+pub mod procedures;
+pub mod pushdown;
+pub mod semantic;
+pub mod types;
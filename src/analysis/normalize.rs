@@ -0,0 +1,116 @@
+//! Normalization of queries to their shape.
+//!
+//! Queries that only differ in their string, number and list literals,
+//! whitespace, keyword case or map key order normalize to the same text.
+//! [`normalize`] replaces the literals with positional parameters, writes the
+//! query in its canonical form and fingerprints the result, so that for
+//! example slow queries can be grouped by shape.
+
+use crate::ast::cypher::visit::{walk_atom as walk_atom_ref, Visitor};
+use crate::ast::cypher::visit_mut::{walk_atom, VisitorMut};
+use crate::ast::cypher::{Atom, Expression, Literal, Properties, Query};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedQuery {
+    /// The canonical text of the query with literals replaced by parameters.
+    pub text: String,
+    /// The FNV-1a hash of `text`, which does not change between runs or builds.
+    pub fingerprint: u64,
+    /// The names of the parameters without the `$` and the literals they
+    /// replace, in the order they appear in `text`.
+    pub parameters: Vec<(String, Literal)>,
+}
+
+/// Normalizes the query.
+///
+/// String and number literals become parameters, as do list literals that
+/// consist of constants only. The parameters are numbered `$0`, `$1`, ... in
+/// the order they appear in the normalized text, starting after the highest
+/// positional parameter the query already uses. Boolean and `null` literals
+/// are kept since they usually change the meaning of a query rather than its
+/// input.
+pub fn normalize(query: &Query) -> NormalizedQuery {
+    let mut positional = PositionalParameters { next: 0 };
+    positional.visit_query(query);
+
+    let mut query = query.clone();
+    let mut extractor = LiteralExtractor { first: positional.next, parameters: Vec::new() };
+    extractor.visit_query(&mut query);
+
+    let text = query.to_string();
+    NormalizedQuery { fingerprint: fingerprint(&text), text, parameters: extractor.parameters }
+}
+
+/// The 64-bit FNV-1a hash of the text.
+pub fn fingerprint(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+/// Finds the number after the highest positional parameter such as `$0`.
+struct PositionalParameters {
+    next: usize,
+}
+
+impl<'ast> Visitor<'ast> for PositionalParameters {
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        if let Atom::Parameter(name) = atom {
+            if let Ok(index) = name.trim_start_matches('$').parse::<usize>() {
+                self.next = self.next.max(index + 1);
+            }
+        }
+        walk_atom_ref(self, atom);
+    }
+}
+
+struct LiteralExtractor {
+    first: usize,
+    parameters: Vec<(String, Literal)>,
+}
+
+impl LiteralExtractor {
+    fn extract(&mut self, atom: &mut Atom) {
+        let name = (self.first + self.parameters.len()).to_string();
+        if let Atom::Literal(literal) = std::mem::replace(atom, Atom::Parameter(format!("${}", name))) {
+            self.parameters.push((name, literal));
+        }
+    }
+
+    /// Visits the values in the order of their sorted keys, which is the
+    /// order they are written in.
+    fn visit_map(&mut self, map: &mut std::collections::HashMap<String, Expression>) {
+        let mut keys: Vec<String> = map.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            if let Some(expression) = map.get_mut(&key) {
+                self.visit_expression(expression);
+            }
+        }
+    }
+}
+
+fn is_constant(expression: &Expression) -> bool {
+    match expression.as_atom() {
+        Some(Atom::Literal(Literal::ListLiteral(list))) => list.iter().all(is_constant),
+        Some(Atom::Literal(Literal::MapLiteral(_))) => false,
+        Some(Atom::Literal(_)) => true,
+        _ => false,
+    }
+}
+
+impl VisitorMut for LiteralExtractor {
+    fn visit_properties(&mut self, properties: &mut Properties) {
+        match properties {
+            Properties::MapLiteral(map) => self.visit_map(map),
+            Properties::Parameter(_) => (),
+        }
+    }
+
+    fn visit_atom(&mut self, atom: &mut Atom) {
+        match atom {
+            Atom::Literal(Literal::String(_) | Literal::Number(_)) => self.extract(atom),
+            Atom::Literal(Literal::ListLiteral(list)) if list.iter().all(is_constant) => self.extract(atom),
+            Atom::Literal(Literal::MapLiteral(map)) => self.visit_map(map),
+            _ => walk_atom(self, atom),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Validation of procedure calls against a catalog of procedure signatures.
+//!
+//! Which procedures exist depends on the database a query is sent to, so the
+//! signatures are looked up through the [`ProcedureCatalog`] trait.
+//! [`ProcedureRegistry`] is a simple catalog that can be filled by hand and
+//! starts out with the common `db.*` and `dbms.*` procedures.
+
+use std::collections::HashMap;
+
+use crate::analysis::semantic::{SemanticError, SemanticErrorKind};
+use crate::ast::cypher::visit::{walk_reading_clause, Visitor};
+use crate::ast::cypher::{
+    CypherType, ExplicitProcedureInvocation, InQueryCall, ProcedureInvocation, QualifiedName, Query, ReadingClause, Span,
+    StandaloneCall, StarOrYieldItems, YieldItems,
+};
+
+/// What a procedure may access, from least to most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProcedureMode {
+    Read,
+    Write,
+    Schema,
+    Dbms,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcedureField {
+    pub name: String,
+    pub type_: CypherType,
+    /// Inputs only: whether the argument may be omitted.
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcedureSignature {
+    pub name: QualifiedName,
+    pub inputs: Vec<ProcedureField>,
+    pub outputs: Vec<ProcedureField>,
+    pub mode: ProcedureMode,
+}
+
+impl ProcedureSignature {
+    /// Creates a signature without inputs or outputs. `name` may contain a
+    /// namespace, e.g. `db.labels`.
+    pub fn new(name: &str, mode: ProcedureMode) -> Self {
+        let mut parts: Vec<String> = name.split('.').map(str::to_string).collect();
+        let name = parts.pop().unwrap_or_default();
+        ProcedureSignature { name: QualifiedName { namespace: parts, name }, inputs: Vec::new(), outputs: Vec::new(), mode }
+    }
+
+    pub fn input(mut self, name: &str, type_: CypherType) -> Self {
+        self.inputs.push(ProcedureField { name: name.to_string(), type_, optional: false });
+        self
+    }
+
+    pub fn optional_input(mut self, name: &str, type_: CypherType) -> Self {
+        self.inputs.push(ProcedureField { name: name.to_string(), type_, optional: true });
+        self
+    }
+
+    pub fn output(mut self, name: &str, type_: CypherType) -> Self {
+        self.outputs.push(ProcedureField { name: name.to_string(), type_, optional: false });
+        self
+    }
+
+    pub fn min_arguments(&self) -> usize {
+        self.inputs.iter().filter(|input| !input.optional).count()
+    }
+
+    pub fn max_arguments(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns whether the procedure produces no records, e.g. `db.awaitIndexes()`.
+    pub fn is_void(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    pub fn output_field(&self, name: &str) -> Option<&ProcedureField> {
+        self.outputs.iter().find(|output| output.name == name)
+    }
+}
+
+/// A source of procedure signatures.
+pub trait ProcedureCatalog {
+    fn lookup(&self, name: &QualifiedName) -> Option<&ProcedureSignature>;
+}
+
+/// Procedures by their qualified name. Unlike functions, procedure names are
+/// case sensitive.
+#[derive(Debug, Clone, Default)]
+pub struct ProcedureRegistry {
+    procedures: HashMap<QualifiedName, ProcedureSignature>,
+}
+
+impl ProcedureRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        ProcedureRegistry::default()
+    }
+
+    /// Creates a registry containing the common built-in procedures.
+    pub fn builtin() -> Self {
+        let mut registry = ProcedureRegistry::new();
+        for signature in builtin_procedures() {
+            registry.register(signature);
+        }
+        registry
+    }
+
+    /// Adds a procedure, replacing and returning a procedure of the same name.
+    pub fn register(&mut self, signature: ProcedureSignature) -> Option<ProcedureSignature> {
+        self.procedures.insert(signature.name.clone(), signature)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProcedureSignature> {
+        self.procedures.values()
+    }
+}
+
+impl ProcedureCatalog for ProcedureRegistry {
+    fn lookup(&self, name: &QualifiedName) -> Option<&ProcedureSignature> {
+        self.procedures.get(name)
+    }
+}
+
+/// Checks all procedure calls of the query against the catalog.
+///
+/// Reports unknown procedures, wrong argument counts and yielded fields the
+/// procedure does not produce. A call inside a larger query must name the
+/// fields it yields explicitly, so `YIELD *` and omitting `YIELD` for a
+/// procedure with outputs are only allowed in a standalone `CALL`.
+pub fn check_procedures(query: &Query, catalog: &dyn ProcedureCatalog) -> Vec<SemanticError> {
+    let mut checker = ProcedureChecker { catalog, errors: Vec::new() };
+    match query {
+        Query::StandaloneCall(call) => checker.check_standalone_call(call),
+        _ => checker.visit_query(query),
+    }
+    checker.errors
+}
+
+struct ProcedureChecker<'c> {
+    catalog: &'c dyn ProcedureCatalog,
+    errors: Vec<SemanticError>,
+}
+
+impl ProcedureChecker<'_> {
+    fn error(&mut self, kind: SemanticErrorKind, span: Span, message: String) {
+        self.errors.push(SemanticError { kind, span, message });
+    }
+
+    fn lookup(&mut self, name: &QualifiedName, span: Span) -> Option<&ProcedureSignature> {
+        let signature = self.catalog.lookup(name);
+        if signature.is_none() {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::ProcedureNotFound,
+                span,
+                message: format!("unknown procedure `{}`", name),
+            });
+        }
+        signature
+    }
+
+    fn check_arguments(&mut self, invocation: &ExplicitProcedureInvocation, signature: &ProcedureSignature) {
+        let count = invocation.arguments.len();
+        if count < signature.min_arguments() || count > signature.max_arguments() {
+            let expected = if signature.min_arguments() == signature.max_arguments() {
+                signature.max_arguments().to_string()
+            }
+            else {
+                format!("{} to {}", signature.min_arguments(), signature.max_arguments())
+            };
+            self.error(
+                SemanticErrorKind::InvalidNumberOfArguments,
+                invocation.span,
+                format!("procedure `{}` expects {} arguments but got {}", invocation.name, expected, count),
+            );
+        }
+    }
+
+    fn check_yield_items(&mut self, items: &YieldItems, signature: &ProcedureSignature) {
+        let YieldItems::Items { items, .. } = items else { return };
+        for item in items {
+            let field = item.procedure_result_field.as_deref().unwrap_or(item.variable.as_str());
+            if signature.output_field(field).is_none() {
+                self.error(
+                    SemanticErrorKind::UndefinedVariable,
+                    item.variable.span,
+                    format!("procedure `{}` has no output `{}`", signature.name, field),
+                );
+            }
+        }
+    }
+
+    fn check_standalone_call(&mut self, call: &StandaloneCall) {
+        let (name, span) = match &call.invocation {
+            ProcedureInvocation::Explicit(invocation) => (&invocation.name, invocation.span),
+            ProcedureInvocation::Implicit(invocation) => (&invocation.name, invocation.span),
+        };
+        let Some(signature) = self.lookup(name, span).cloned() else { return };
+
+        // Implicit calls take their arguments from query parameters of the same names.
+        if let ProcedureInvocation::Explicit(invocation) = &call.invocation {
+            self.check_arguments(invocation, &signature);
+        }
+        if let Some(StarOrYieldItems::YieldItems(items)) = &call.yield_ {
+            self.check_yield_items(items, &signature);
+        }
+        if call.yield_.is_some() && signature.is_void() {
+            self.error(SemanticErrorKind::InvalidYield, span, format!("procedure `{}` has no outputs to yield", name));
+        }
+    }
+
+    fn check_in_query_call(&mut self, call: &InQueryCall) {
+        let invocation = &call.invocation;
+        let Some(signature) = self.lookup(&invocation.name, invocation.span).cloned() else { return };
+
+        self.check_arguments(invocation, &signature);
+        match &call.yield_items {
+            Some(YieldItems::Star) => self.error(
+                SemanticErrorKind::InvalidYield,
+                invocation.span,
+                "YIELD * can only be used in a standalone CALL".to_string(),
+            ),
+            Some(items) if signature.is_void() => {
+                self.check_yield_items(items, &signature);
+                self.error(
+                    SemanticErrorKind::InvalidYield,
+                    invocation.span,
+                    format!("procedure `{}` has no outputs to yield", invocation.name),
+                );
+            }
+            Some(items) => self.check_yield_items(items, &signature),
+            None if !signature.is_void() => self.error(
+                SemanticErrorKind::InvalidYield,
+                invocation.span,
+                format!("a CALL of `{}` inside a query must YIELD the outputs it uses", invocation.name),
+            ),
+            None => (),
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for ProcedureChecker<'_> {
+    fn visit_reading_clause(&mut self, clause: &'ast ReadingClause) {
+        if let ReadingClause::InQueryCall(call) = clause {
+            self.check_in_query_call(call);
+        }
+        walk_reading_clause(self, clause);
+    }
+}
+
+fn builtin_procedures() -> Vec<ProcedureSignature> {
+    use CypherType::*;
+    use ProcedureMode::{Dbms, Read, Schema, Write};
+
+    let p = ProcedureSignature::new;
+    let strings = || List(Box::new(String));
+
+    vec![
+        p("db.labels", Read).output("label", String),
+        p("db.relationshipTypes", Read).output("relationshipType", String),
+        p("db.propertyKeys", Read).output("propertyKey", String),
+        p("db.info", Read).output("id", String).output("name", String).output("creationDate", String),
+        p("db.ping", Read).output("success", Boolean),
+        p("db.schema.visualization", Read)
+            .output("nodes", List(Box::new(Node)))
+            .output("relationships", List(Box::new(Relationship))),
+        p("db.schema.nodeTypeProperties", Read)
+            .output("nodeType", String)
+            .output("nodeLabels", strings())
+            .output("propertyName", String)
+            .output("propertyTypes", strings())
+            .output("mandatory", Boolean),
+        p("db.schema.relTypeProperties", Read)
+            .output("relType", String)
+            .output("propertyName", String)
+            .output("propertyTypes", strings())
+            .output("mandatory", Boolean),
+        p("db.index.fulltext.queryNodes", Read)
+            .input("indexName", String)
+            .input("queryString", String)
+            .optional_input("options", Map)
+            .output("node", Node)
+            .output("score", Float),
+        p("db.index.fulltext.queryRelationships", Read)
+            .input("indexName", String)
+            .input("queryString", String)
+            .optional_input("options", Map)
+            .output("relationship", Relationship)
+            .output("score", Float),
+        p("db.index.vector.queryNodes", Read)
+            .input("indexName", String)
+            .input("numberOfNearestNeighbours", Integer)
+            .input("query", List(Box::new(Float)))
+            .output("node", Node)
+            .output("score", Float),
+        p("db.createLabel", Write).input("newLabel", String),
+        p("db.createProperty", Write).input("newProperty", String),
+        p("db.createRelationshipType", Write).input("newRelationshipType", String),
+        p("db.awaitIndex", Schema).input("indexName", String).optional_input("timeOutSeconds", Integer),
+        p("db.awaitIndexes", Schema).optional_input("timeOutSeconds", Integer),
+        p("db.resampleIndex", Schema).input("indexName", String),
+        p("db.clearQueryCaches", Dbms).output("value", String),
+        p("dbms.components", Dbms).output("name", String).output("versions", strings()).output("edition", String),
+        p("dbms.listConfig", Dbms)
+            .optional_input("searchString", String)
+            .output("name", String)
+            .output("description", String)
+            .output("value", String)
+            .output("dynamic", Boolean),
+        p("dbms.procedures", Dbms).output("name", String).output("signature", String).output("description", String),
+        p("dbms.functions", Dbms).output("name", String).output("signature", String).output("description", String),
+    ]
+}
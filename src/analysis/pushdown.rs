@@ -0,0 +1,372 @@
+//! Moving predicates between the `WHERE` of a `MATCH` and its pattern.
+//!
+//! `MATCH (n:Person {name: 'Alice'})` and `MATCH (n) WHERE n:Person AND
+//! n.name = 'Alice'` find the same nodes. [`Match::push_down_predicates`]
+//! rewrites the second form into the first, so that indexes can be selected
+//! from the pattern alone, and [`Match::pull_up_predicates`] rewrites the first
+//! into the second, so that all predicates can be inspected in one place.
+//! [`Match::conjuncts`] splits the `WHERE` into the predicates combined with
+//! `AND` and reports the pattern variable each of them is about.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ast::cypher::visit::{walk_atom as walk_atom_ref, walk_node_pattern, walk_relationship_pattern, Visitor};
+use crate::ast::cypher::visit_mut::{walk_reading_clause, VisitorMut};
+use crate::ast::cypher::{
+    AddOrSubtractExpression, AndExpression, Atom, ComparisonExpression, ComparisonOperator, Expression, FilterExpression,
+    LabelExpression, MapProjectionItem, Match, NodePattern, NotExpression, OrExpression, Pattern, Properties,
+    PropertyOrLabelsExpression, Query, ReadingClause, RelationshipPattern, Variable, XorExpression,
+};
+
+/// One of the predicates of a `WHERE` that are combined with `AND`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conjunct {
+    pub expression: Expression,
+    /// The variables the conjunct references. Variables that are introduced
+    /// by an `EXISTS` subquery or a pattern comprehension in the conjunct are
+    /// included as well.
+    pub variables: BTreeSet<String>,
+    /// The variable if the conjunct references a single variable and it is
+    /// declared by the pattern of the `MATCH`.
+    pub pattern_variable: Option<String>,
+}
+
+impl Match {
+    /// Returns the node, relationship and path variables of the pattern.
+    pub fn pattern_variables(&self) -> BTreeSet<String> {
+        let mut variables = BTreeSet::new();
+        for part in &self.pattern.parts {
+            variables.extend(part.variable.iter().map(|variable| variable.name.clone()));
+            let element = &part.pattern_element;
+            variables.extend(element.node_pattern.variable.iter().map(|variable| variable.name.clone()));
+            for (relationship, node) in &element.relationship_patterns {
+                if let Some(detail) = relationship.detail() {
+                    variables.extend(detail.variable.iter().map(|variable| variable.name.clone()));
+                }
+                variables.extend(node.variable.iter().map(|variable| variable.name.clone()));
+            }
+        }
+        variables
+    }
+
+    /// Splits the `WHERE` of the match into its conjuncts.
+    pub fn conjuncts(&self) -> Vec<Conjunct> {
+        let pattern_variables = self.pattern_variables();
+        let conjuncts = self.where_.as_ref().map(split_conjuncts).unwrap_or_default();
+        conjuncts
+            .into_iter()
+            .map(|expression| {
+                let variables = referenced_variables(&expression);
+                let pattern_variable = match variables.iter().collect::<Vec<_>>().as_slice() {
+                    [variable] if pattern_variables.contains(*variable) => Some((*variable).clone()),
+                    _ => None,
+                };
+                Conjunct { expression, variables, pattern_variable }
+            })
+            .collect()
+    }
+
+    /// Moves label predicates such as `n:Person` and property equalities such
+    /// as `n.name = 'Alice'` from the `WHERE` into the node or relationship
+    /// pattern of the variable.
+    ///
+    /// Only property equalities whose value does not reference any variable
+    /// are moved, and neither are predicates on variable length
+    /// relationships, on nodes whose properties are a parameter, or on a
+    /// property the pattern already compares to a different value.
+    pub fn push_down_predicates(&self) -> Match {
+        let mut match_ = self.clone();
+        let mut remaining = Vec::new();
+        let mut pushed = false;
+        for conjunct in self.conjuncts() {
+            let pushed_down = conjunct.pattern_variable.as_deref().is_some_and(|variable| {
+                Predicate::of(&conjunct.expression, variable)
+                    .is_some_and(|predicate| push_down(&mut match_.pattern, variable, predicate))
+            });
+            if pushed_down {
+                pushed = true;
+            }
+            else {
+                remaining.push(conjunct.expression);
+            }
+        }
+        if pushed {
+            match_.where_ = conjunction(remaining);
+        }
+        match_
+    }
+
+    /// Moves the labels and properties of the node and relationship patterns
+    /// into the `WHERE`, in front of its existing predicates.
+    ///
+    /// Patterns without a variable, variable length relationships and
+    /// properties given as a parameter stay in the pattern.
+    pub fn pull_up_predicates(&self) -> Match {
+        let mut match_ = self.clone();
+        let mut conjuncts = Vec::new();
+        for part in &mut match_.pattern.parts {
+            let element = &mut part.pattern_element;
+            conjuncts.extend(element.node_pattern.take_inline_predicates());
+            for (relationship, node) in &mut element.relationship_patterns {
+                if let Some(detail) = relationship.detail_mut().filter(|detail| detail.range.is_none()) {
+                    conjuncts.extend(detail.take_inline_predicates());
+                }
+                conjuncts.extend(node.take_inline_predicates());
+            }
+        }
+        if !conjuncts.is_empty() {
+            conjuncts.extend(self.where_.iter().flat_map(split_conjuncts));
+            match_.where_ = conjunction(conjuncts);
+        }
+        match_
+    }
+}
+
+/// Applies [`Match::push_down_predicates`] to every `MATCH` of the query.
+pub fn push_down_predicates(query: &Query) -> Query {
+    let mut query = query.clone();
+    MatchRewriter(Match::push_down_predicates).visit_query(&mut query);
+    query
+}
+
+/// Applies [`Match::pull_up_predicates`] to every `MATCH` of the query.
+pub fn pull_up_predicates(query: &Query) -> Query {
+    let mut query = query.clone();
+    MatchRewriter(Match::pull_up_predicates).visit_query(&mut query);
+    query
+}
+
+struct MatchRewriter(fn(&Match) -> Match);
+
+impl VisitorMut for MatchRewriter {
+    fn visit_reading_clause(&mut self, clause: &mut ReadingClause) {
+        if let ReadingClause::Match(match_) = clause {
+            *match_ = (self.0)(match_);
+        }
+        walk_reading_clause(self, clause);
+    }
+}
+
+/// Splits the expression into the operands of its top level `AND`, looking
+/// through parentheses, e.g. `a AND (b AND c)` into `a`, `b` and `c`.
+pub fn split_conjuncts(expression: &Expression) -> Vec<Expression> {
+    let [xor] = expression.0.as_slice() else { return vec![expression.clone()] };
+    let [and] = xor.0.as_slice() else { return vec![expression.clone()] };
+    let mut conjuncts = Vec::new();
+    for operand in &and.0 {
+        let operand = Expression::from_not(operand.clone());
+        match operand.as_atom() {
+            Some(Atom::ParenthesizedExpression(inner)) => conjuncts.extend(split_conjuncts(inner)),
+            _ => conjuncts.push(operand),
+        }
+    }
+    conjuncts
+}
+
+/// Combines the expressions with `AND`, or returns `None` if there are none.
+pub fn conjunction(conjuncts: Vec<Expression>) -> Option<Expression> {
+    if conjuncts.len() <= 1 {
+        return conjuncts.into_iter().next();
+    }
+    let mut operands = Vec::new();
+    for conjunct in conjuncts {
+        match <[XorExpression; 1]>::try_from(conjunct.0) {
+            Ok([xor]) if xor.0.len() == 1 => operands.extend(xor.0.into_iter().flat_map(|and| and.0)),
+            Ok([xor]) => operands.push(not_from_atom(Atom::ParenthesizedExpression(OrExpression(vec![xor])))),
+            Err(xors) => operands.push(not_from_atom(Atom::ParenthesizedExpression(OrExpression(xors)))),
+        }
+    }
+    Some(OrExpression(vec![XorExpression(vec![AndExpression(operands)])]))
+}
+
+/// Returns the variables the expression references.
+///
+/// Variables bound by list comprehensions, quantifiers and `reduce` inside
+/// the expression are not included.
+pub fn referenced_variables(expression: &Expression) -> BTreeSet<String> {
+    let mut collector = VariableCollector { bound: Vec::new(), variables: BTreeSet::new() };
+    collector.visit_expression(expression);
+    collector.variables
+}
+
+struct VariableCollector {
+    bound: Vec<String>,
+    variables: BTreeSet<String>,
+}
+
+impl VariableCollector {
+    fn reference(&mut self, variable: &Variable) {
+        if !self.bound.contains(&variable.name) {
+            self.variables.insert(variable.name.clone());
+        }
+    }
+
+    fn filter(&mut self, filter: &FilterExpression, expression: Option<&Expression>) {
+        self.visit_expression(&filter.id_in_coll.expression);
+        self.bound.push(filter.id_in_coll.variable.name.clone());
+        if let Some(where_) = &filter.where_ {
+            self.visit_expression(where_);
+        }
+        if let Some(expression) = expression {
+            self.visit_expression(expression);
+        }
+        self.bound.pop();
+    }
+}
+
+impl<'ast> Visitor<'ast> for VariableCollector {
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        match atom {
+            Atom::Variable(variable) => self.reference(variable),
+            Atom::ListComprehension(comprehension) => {
+                self.filter(&comprehension.filter_expression, comprehension.expression.as_ref())
+            }
+            Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => self.filter(filter, None),
+            Atom::Reduce(reduce) => {
+                self.visit_expression(&reduce.initial_value);
+                self.visit_expression(&reduce.id_in_coll.expression);
+                self.bound.push(reduce.accumulator.name.clone());
+                self.bound.push(reduce.id_in_coll.variable.name.clone());
+                self.visit_expression(&reduce.expression);
+                self.bound.truncate(self.bound.len() - 2);
+            }
+            Atom::MapProjection(projection) => {
+                self.reference(&projection.variable);
+                for item in &projection.items {
+                    match item {
+                        MapProjectionItem::Literal { expression, .. } => self.visit_expression(expression),
+                        MapProjectionItem::Variable(variable) => self.reference(variable),
+                        MapProjectionItem::Property(_) | MapProjectionItem::AllProperties => (),
+                    }
+                }
+            }
+            _ => walk_atom_ref(self, atom),
+        }
+    }
+
+    fn visit_node_pattern(&mut self, node: &'ast NodePattern) {
+        if let Some(variable) = &node.variable {
+            self.reference(variable);
+        }
+        walk_node_pattern(self, node);
+    }
+
+    fn visit_relationship_pattern(&mut self, relationship: &'ast RelationshipPattern) {
+        if let Some(variable) = relationship.detail().and_then(|detail| detail.variable.as_ref()) {
+            self.reference(variable);
+        }
+        walk_relationship_pattern(self, relationship);
+    }
+}
+
+/// A predicate that can be expressed in a node or relationship pattern.
+enum Predicate {
+    Labels(LabelExpression),
+    Property(String, Expression),
+}
+
+impl Predicate {
+    /// Recognizes `variable:Label` and `variable.key = value`, where the
+    /// value references no variables, in either order.
+    fn of(expression: &Expression, variable: &str) -> Option<Predicate> {
+        if let Some(expression) = expression.as_property_or_labels_expression() {
+            return match (&expression.atom, expression.property_lookup.as_slice(), &expression.label_expression) {
+                (Atom::Variable(v), [], Some(labels)) if v.name == variable => Some(Predicate::Labels(labels.clone())),
+                _ => None,
+            };
+        }
+
+        let [xor] = expression.0.as_slice() else { return None };
+        let [and] = xor.0.as_slice() else { return None };
+        let [not] = and.0.as_slice() else { return None };
+        let [(ComparisonOperator::Equal, right)] = not.expression.comparisons.as_slice() else { return None };
+        if not.not {
+            return None;
+        }
+        let left = Expression::from_add_or_subtract(not.expression.expression.clone());
+        let right = Expression::from_add_or_subtract(right.clone());
+        for (property, value) in [(&left, &right), (&right, &left)] {
+            let Some(property) = property.as_property_or_labels_expression() else { continue };
+            match (&property.atom, property.property_lookup.as_slice(), &property.label_expression) {
+                (Atom::Variable(v), [key], None) if v.name == variable && referenced_variables(value).is_empty() => {
+                    return Some(Predicate::Property(key.clone(), value.clone()));
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Adds the predicate to the labels and properties of a pattern, and
+    /// returns whether the pattern now implies it.
+    fn apply(self, label_expression: &mut Option<LabelExpression>, properties: &mut Option<Properties>) -> bool {
+        match self {
+            Predicate::Labels(labels) => {
+                *label_expression = Some(match label_expression.take() {
+                    None => labels,
+                    Some(existing) => {
+                        let mut operands = and_operands(existing);
+                        for label in and_operands(labels) {
+                            if !operands.contains(&label) {
+                                operands.push(label);
+                            }
+                        }
+                        LabelExpression::And(operands)
+                    }
+                });
+                true
+            }
+            Predicate::Property(key, value) => match properties {
+                None => {
+                    *properties = Some(Properties::MapLiteral(HashMap::from([(key, value)])));
+                    true
+                }
+                Some(Properties::MapLiteral(map)) => match map.get(&key) {
+                    None => {
+                        map.insert(key, value);
+                        true
+                    }
+                    Some(existing) => existing.to_string() == value.to_string(),
+                },
+                Some(Properties::Parameter(_)) => false,
+            },
+        }
+    }
+}
+
+fn and_operands(label_expression: LabelExpression) -> Vec<LabelExpression> {
+    match label_expression {
+        LabelExpression::And(operands) => operands,
+        other => vec![other],
+    }
+}
+
+/// Applies the predicate to the first pattern of the variable.
+fn push_down(pattern: &mut Pattern, variable: &str, predicate: Predicate) -> bool {
+    let declares = |declared: &Option<Variable>| declared.as_ref().is_some_and(|declared| declared.name == variable);
+    for part in &mut pattern.parts {
+        let element = &mut part.pattern_element;
+        let nodes = std::iter::once(&mut element.node_pattern).chain(element.relationship_patterns.iter_mut().map(|(_, node)| node));
+        for node in nodes {
+            if declares(&node.variable) {
+                return predicate.apply(&mut node.label_expression, &mut node.properties);
+            }
+        }
+        for (relationship, _) in &mut element.relationship_patterns {
+            if let Some(detail) = relationship.detail_mut().filter(|detail| declares(&detail.variable)) {
+                return detail.range.is_none() && predicate.apply(&mut detail.label_expression, &mut detail.properties);
+            }
+        }
+    }
+    false
+}
+
+fn not_from_atom(atom: Atom) -> NotExpression {
+    NotExpression {
+        not: false,
+        expression: ComparisonExpression {
+            expression: AddOrSubtractExpression::from_property_or_labels(PropertyOrLabelsExpression::plain(atom)),
+            comparisons: Vec::new(),
+        },
+    }
+}
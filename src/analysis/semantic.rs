@@ -0,0 +1,619 @@
+//! Semantic checks on variable scoping.
+//!
+//! [`check_variables`] follows the scope of variables through a query the way
+//! openCypher defines it: patterns, `UNWIND`, `LOAD CSV` and `YIELD` bind new
+//! variables, `WITH` replaces the scope by its projection, `UNION` parts are
+//! checked independently, `CALL { ... }` subqueries add the variables they
+//! return, and comprehensions, quantifiers, `reduce()` and
+//! `EXISTS` subqueries introduce local scopes. Violations are reported with
+//! the error codes used by the openCypher TCK.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::analysis::aggregation::is_aggregating;
+use crate::analysis::functions::FunctionRegistry;
+use crate::ast::cypher::visit::{
+    walk_atom, walk_expression, walk_node_pattern, walk_pattern, walk_property_or_labels_expression, walk_relationship_pattern, Visitor,
+};
+use crate::ast::cypher::{
+    Atom, CallSubquery, ExistentialSubquery, Expression, FilterExpression, LabelOrType, MapProjectionItem, MergeAction, NodePattern, OrderSkipLimit, Pattern, PatternPart,
+    ProcedureInvocation, ProjectionBody, ProjectionItem, PropertyKey, PropertyOrLabelsExpression, Query, ReadingClause, RegularQuery,
+    RelationshipPattern, RemoveItem, Set, SetItem, SingleQuery, SinglePartQuery, Span, StarOrYieldItems,
+    UpdatingClause, Variable, YieldItems,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticErrorKind {
+    UndefinedVariable,
+    VariableAlreadyBound,
+    VariableTypeConflict,
+    AmbiguousAggregationExpression,
+    NestedAggregation,
+    InvalidAggregation,
+    InvalidArgumentType,
+    UnknownFunction,
+    InvalidNumberOfArguments,
+    InvalidArgumentPassingMode,
+    ProcedureNotFound,
+    InvalidYield,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticError {
+    pub kind: SemanticErrorKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}..{}: {}", self.kind, self.span.start, self.span.end, self.message)
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+/// What is known statically about the value a variable is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableKind {
+    Node,
+    Relationship,
+    /// The relationships matched by a variable length relationship pattern.
+    RelationshipList,
+    Path,
+    /// A value that is known not to be a node, relationship or path.
+    Value,
+    /// A value of unknown type, e.g. the result of a function call.
+    Any,
+}
+
+impl VariableKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            VariableKind::Node => "a node",
+            VariableKind::Relationship => "a relationship",
+            VariableKind::RelationshipList => "a list of relationships",
+            VariableKind::Path => "a path",
+            VariableKind::Value => "a value",
+            VariableKind::Any => "an unknown value",
+        }
+    }
+}
+
+pub type Scope = HashMap<String, VariableKind>;
+
+/// Checks that every variable is bound before it is used and that bindings
+/// neither shadow nor contradict earlier ones.
+pub fn check_variables(query: &Query) -> Vec<SemanticError> {
+    let mut checker = ScopeChecker { scope: Scope::new(), registry: FunctionRegistry::builtin(), order_keys: Vec::new(), errors: Vec::new() };
+
+    match query {
+        Query::RegularQuery(query) => checker.check_regular_query(query),
+        Query::StandaloneCall(call) => {
+            if let ProcedureInvocation::Explicit(invocation) = &call.invocation {
+                for argument in &invocation.arguments {
+                    checker.visit_expression(argument);
+                }
+            }
+            if let Some(StarOrYieldItems::YieldItems(items)) = &call.yield_ {
+                checker.check_yield_items(items);
+            }
+        }
+    }
+
+    checker.errors
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BindMode {
+    /// `MATCH` and pattern predicates: bound variables may be referenced again.
+    Match,
+    /// `CREATE` and `MERGE`: only nodes may be referenced again, and only as
+    /// plain endpoints of relationships.
+    Create,
+}
+
+struct ScopeChecker {
+    scope: Scope,
+    registry: FunctionRegistry,
+    /// The items of a `DISTINCT` or aggregating projection while its
+    /// `ORDER BY` is checked, which may be used even if they refer to
+    /// variables that are not projected.
+    order_keys: Vec<Expression>,
+    errors: Vec<SemanticError>,
+}
+
+impl ScopeChecker {
+    fn error(&mut self, kind: SemanticErrorKind, span: Span, message: String) {
+        self.errors.push(SemanticError { kind, span, message });
+    }
+
+    fn use_variable(&mut self, variable: &Variable) {
+        if !self.scope.contains_key(&variable.name) {
+            self.error(SemanticErrorKind::UndefinedVariable, variable.span, format!("variable `{}` is not defined", variable));
+        }
+    }
+
+    /// Binds a variable that must not be bound yet, as done by `UNWIND`, `AS` and `YIELD`.
+    fn bind_new(&mut self, variable: &Variable, kind: VariableKind) {
+        if self.scope.contains_key(&variable.name) {
+            self.error(SemanticErrorKind::VariableAlreadyBound, variable.span, format!("variable `{}` is already bound", variable));
+        }
+        self.scope.insert(variable.name.clone(), kind);
+    }
+
+    /// Binds a pattern variable, which may refer to an existing binding of a compatible kind.
+    fn bind_entity(&mut self, variable: &Variable, kind: VariableKind) {
+        match self.scope.get(&variable.name).copied() {
+            None | Some(VariableKind::Any) => {
+                self.scope.insert(variable.name.clone(), kind);
+            }
+            Some(existing) if existing == kind => (),
+            Some(existing) => self.error(
+                SemanticErrorKind::VariableTypeConflict,
+                variable.span,
+                format!("variable `{}` is {} and cannot be used as {}", variable, existing.describe(), kind.describe()),
+            ),
+        }
+    }
+
+    /// Runs `check` in a nested scope whose bindings are discarded afterwards.
+    fn nested(&mut self, check: impl FnOnce(&mut Self)) {
+        let scope = self.scope.clone();
+        check(self);
+        self.scope = scope;
+    }
+
+    fn check_regular_query(&mut self, query: &RegularQuery) {
+        let outer = self.scope.clone();
+        let parts = std::iter::once(&query.query).chain(query.union.iter().map(|union| &union.query));
+
+        for part in parts {
+            self.scope = outer.clone();
+            self.check_single_query(part);
+        }
+        self.scope = outer;
+    }
+
+    /// Checks each part of a `CALL { ... }` subquery in the outer scope, then
+    /// adds the variables it returns, which must not be bound yet.
+    fn check_call_subquery(&mut self, subquery: &CallSubquery) {
+        let outer = self.scope.clone();
+        let mut returned = Scope::new();
+        let parts = std::iter::once(&subquery.query.query).chain(subquery.query.union.iter().map(|union| &union.query));
+
+        for part in parts {
+            self.scope = outer.clone();
+            self.check_single_query(part);
+            if part.final_part().return_.is_some() {
+                returned = std::mem::take(&mut self.scope);
+            }
+        }
+        self.scope = outer;
+
+        if let Some(return_) = &subquery.query.query.final_part().return_ {
+            for item in &return_.items.items {
+                let variable = match item {
+                    ProjectionItem::AsVariable { variable, .. } => variable,
+                    ProjectionItem::Expression(expression) => match expression.as_atom() {
+                        Some(Atom::Variable(variable)) => variable,
+                        _ => continue,
+                    },
+                };
+                self.bind_new(variable, returned.get(&variable.name).copied().unwrap_or(VariableKind::Any));
+            }
+        }
+        for (name, kind) in returned {
+            self.scope.entry(name).or_insert(kind);
+        }
+    }
+
+    fn check_single_query(&mut self, query: &SingleQuery) {
+        let query = match query {
+            SingleQuery::SinglePartQuery(query) => query,
+            SingleQuery::MultiPartQuery(query) => {
+                for part in &query.parts {
+                    self.check_clauses(&part.reading_clauses, &part.updating_clauses);
+                    self.check_projection(&part.with.projection_body);
+                    if let Some(where_) = &part.with.where_ {
+                        self.visit_expression(where_);
+                    }
+                }
+                &query.query
+            }
+        };
+
+        self.check_final_part(query);
+    }
+
+    fn check_final_part(&mut self, query: &SinglePartQuery) {
+        self.check_clauses(&query.reading_clauses, &query.updating_clauses);
+        if let Some(return_) = &query.return_ {
+            self.check_projection(return_);
+        }
+    }
+
+    fn check_clauses(&mut self, reading_clauses: &[ReadingClause], updating_clauses: &[UpdatingClause]) {
+        for clause in reading_clauses {
+            self.check_reading_clause(clause);
+        }
+        for clause in updating_clauses {
+            self.check_updating_clause(clause);
+        }
+    }
+
+    fn check_reading_clause(&mut self, clause: &ReadingClause) {
+        match clause {
+            ReadingClause::Match(match_) => {
+                self.check_match_pattern(&match_.pattern);
+                if let Some(where_) = &match_.where_ {
+                    self.visit_expression(where_);
+                }
+            }
+            ReadingClause::Unwind(unwind) => {
+                self.visit_expression(&unwind.expression);
+                self.bind_new(&unwind.variable, VariableKind::Any);
+            }
+            ReadingClause::LoadCsv(load_csv) => {
+                self.visit_expression(&load_csv.source);
+                self.bind_new(&load_csv.variable, VariableKind::Value);
+            }
+            ReadingClause::CallSubquery(subquery) => self.check_call_subquery(subquery),
+            ReadingClause::InQueryCall(call) => {
+                for argument in &call.invocation.arguments {
+                    self.visit_expression(argument);
+                }
+                if let Some(items) = &call.yield_items {
+                    self.check_yield_items(items);
+                }
+            }
+            ReadingClause::Filter(filter) => self.visit_expression(&filter.expression),
+            ReadingClause::OrderSkipLimit(clause) => self.check_order_skip_limit(clause),
+        }
+    }
+
+    fn check_order_skip_limit(&mut self, clause: &OrderSkipLimit) {
+        for item in clause.order.iter().flat_map(|order| &order.items) {
+            self.visit_expression(&item.expression);
+        }
+    }
+
+    fn check_yield_items(&mut self, items: &YieldItems) {
+        if let YieldItems::Items { items, where_ } = items {
+            for item in items {
+                self.bind_new(&item.variable, VariableKind::Any);
+            }
+            if let Some(where_) = where_ {
+                self.visit_expression(where_);
+            }
+        }
+    }
+
+    fn check_updating_clause(&mut self, clause: &UpdatingClause) {
+        match clause {
+            UpdatingClause::Create(create) => {
+                for part in &create.pattern.parts {
+                    self.bind_pattern_part(part, BindMode::Create);
+                }
+            }
+            UpdatingClause::Merge(merge) => {
+                self.bind_pattern_part(&merge.pattern_part, BindMode::Create);
+                for action in &merge.actions {
+                    match action {
+                        MergeAction::Match(set) | MergeAction::Create(set) => self.check_set(set),
+                    }
+                }
+            }
+            UpdatingClause::Delete(delete) => {
+                for expression in &delete.expressions {
+                    self.visit_expression(expression);
+                }
+            }
+            UpdatingClause::Set(set) => self.check_set(set),
+            UpdatingClause::Remove(remove) => {
+                for item in &remove.items {
+                    match item {
+                        RemoveItem::VariableNodeLabels { variable, node_labels } => {
+                            self.use_variable(variable);
+                            self.check_labels(node_labels);
+                        }
+                        RemoveItem::PropertyExpression(expression) => {
+                            self.visit_atom(&expression.atom);
+                            self.check_property_keys(&expression.property_path);
+                        }
+                    }
+                }
+            }
+            UpdatingClause::Foreach(foreach) => {
+                self.visit_expression(&foreach.expression);
+                self.nested(|checker| {
+                    checker.bind_new(&foreach.variable, VariableKind::Any);
+                    for clause in &foreach.clauses {
+                        checker.check_updating_clause(clause);
+                    }
+                });
+            }
+            UpdatingClause::Filter(filter) => self.visit_expression(&filter.expression),
+            UpdatingClause::OrderSkipLimit(clause) => self.check_order_skip_limit(clause),
+        }
+    }
+
+    fn check_set(&mut self, set: &Set) {
+        for item in &set.items {
+            match item {
+                SetItem::AssignPropertyExpression { property_expression, expression } => {
+                    self.visit_atom(&property_expression.atom);
+                    self.check_property_keys(&property_expression.property_path);
+                    self.visit_expression(expression);
+                }
+                SetItem::AssignVariable { variable, expression } | SetItem::IncrementVariable { variable, expression } => {
+                    self.use_variable(variable);
+                    self.visit_expression(expression);
+                }
+                SetItem::VariableNodeLabels { variable, node_labels } => {
+                    self.use_variable(variable);
+                    self.check_labels(node_labels);
+                }
+            }
+        }
+    }
+
+    fn check_labels(&mut self, labels: &[LabelOrType]) {
+        for label in labels {
+            if let LabelOrType::Dynamic(expression) = label {
+                self.visit_expression(expression);
+            }
+        }
+    }
+
+    fn check_property_keys(&mut self, keys: &[PropertyKey]) {
+        for key in keys {
+            if let PropertyKey::Dynamic(expression) = key {
+                self.visit_expression(expression);
+            }
+        }
+    }
+
+    /// Binds all variables of a `MATCH` pattern before checking the property
+    /// and label expressions, which may refer to any variable of the pattern.
+    fn check_match_pattern(&mut self, pattern: &Pattern) {
+        for part in &pattern.parts {
+            self.bind_pattern_part(part, BindMode::Match);
+        }
+        walk_pattern(self, pattern);
+    }
+
+    fn bind_pattern_part(&mut self, part: &PatternPart, mode: BindMode) {
+        let element = &part.pattern_element;
+        let lone_node = element.relationship_patterns.is_empty();
+
+        self.bind_node(&element.node_pattern, mode, lone_node);
+        for (relationship, node) in &element.relationship_patterns {
+            self.bind_relationship(relationship, mode);
+            self.bind_node(node, mode, false);
+        }
+
+        if let Some(variable) = &part.variable {
+            self.bind_new(variable, VariableKind::Path);
+        }
+    }
+
+    fn bind_node(&mut self, node: &NodePattern, mode: BindMode, lone_node: bool) {
+        if mode == BindMode::Create {
+            walk_node_pattern(self, node);
+        }
+
+        let Some(variable) = &node.variable else { return };
+        let redeclared = lone_node || node.label_expression.is_some() || node.properties.is_some();
+        if mode == BindMode::Create && redeclared && self.scope.contains_key(&variable.name) {
+            self.error(
+                SemanticErrorKind::VariableAlreadyBound,
+                variable.span,
+                format!("node `{}` is already bound and cannot be created again", variable),
+            );
+            return;
+        }
+
+        self.bind_entity(variable, VariableKind::Node);
+    }
+
+    fn bind_relationship(&mut self, relationship: &RelationshipPattern, mode: BindMode) {
+        if mode == BindMode::Create {
+            walk_relationship_pattern(self, relationship);
+        }
+
+        let (RelationshipPattern::LeftAndRight(detail)
+            | RelationshipPattern::Left(detail)
+            | RelationshipPattern::Right(detail)
+            | RelationshipPattern::Undirected(detail)) = relationship;
+        let Some(detail) = detail else { return };
+        let Some(variable) = &detail.variable else { return };
+
+        if mode == BindMode::Create {
+            self.bind_new(variable, VariableKind::Relationship);
+        }
+        else if detail.range.is_some() {
+            self.bind_entity(variable, VariableKind::RelationshipList);
+        }
+        else {
+            self.bind_entity(variable, VariableKind::Relationship);
+        }
+    }
+
+    /// Checks the projected expressions in the current scope and replaces
+    /// the scope by the projected variables.
+    fn check_projection(&mut self, body: &ProjectionBody) {
+        let mut projected = if body.items.star { self.scope.clone() } else { Scope::new() };
+
+        for item in &body.items.items {
+            match item {
+                ProjectionItem::Expression(expression) => {
+                    self.visit_expression(expression);
+                    if let Some(Atom::Variable(variable)) = expression.as_atom() {
+                        projected.insert(variable.name.clone(), self.kind_of(expression));
+                    }
+                }
+                ProjectionItem::AsVariable { expression, variable } => {
+                    self.visit_expression(expression);
+                    // With `*`, the variables in scope are projected as well.
+                    if projected.contains_key(&variable.name) {
+                        self.error(
+                            SemanticErrorKind::VariableAlreadyBound,
+                            variable.span,
+                            format!("variable `{}` is projected more than once", variable),
+                        );
+                    }
+                    projected.insert(variable.name.clone(), self.kind_of(expression));
+                }
+            }
+        }
+
+        // `ORDER BY` sees both the projected variables and the ones in scope
+        // before the projection, unless the projection removes duplicates or
+        // aggregates. Then the variables before it are gone, and only the
+        // projected items themselves, e.g. `a.x` in
+        // `RETURN DISTINCT a.x ORDER BY a.x`, can still be used.
+        if body.distinct || is_aggregating(body, &self.registry) {
+            self.scope = projected.clone();
+            self.order_keys = body
+                .items
+                .items
+                .iter()
+                .map(|(ProjectionItem::Expression(expression) | ProjectionItem::AsVariable { expression, .. })| expression.clone())
+                .collect();
+        }
+        else {
+            self.scope.extend(projected.iter().map(|(name, kind)| (name.clone(), *kind)));
+        }
+        for item in body.order.iter().flat_map(|order| &order.items) {
+            self.visit_expression(&item.expression);
+        }
+        self.order_keys.clear();
+
+        self.scope = projected;
+    }
+
+    fn kind_of(&self, expression: &Expression) -> VariableKind {
+        match expression.as_atom() {
+            Some(Atom::Variable(variable)) => self.scope.get(&variable.name).copied().unwrap_or(VariableKind::Any),
+            Some(
+                Atom::Literal(_)
+                | Atom::CountStar(_)
+                | Atom::ListComprehension(_)
+                | Atom::PatternComprehension(_)
+                | Atom::All(_)
+                | Atom::Any(_)
+                | Atom::None(_)
+                | Atom::Single(_)
+                | Atom::ExistentialSubquery(_)
+                | Atom::MapProjection(_)
+                | Atom::TemporalLiteral(_)
+                | Atom::PointLiteral(_),
+            ) => VariableKind::Value,
+            _ => VariableKind::Any,
+        }
+    }
+
+    fn check_filter_expression(&mut self, filter: &FilterExpression, expression: Option<&Expression>) {
+        self.visit_expression(&filter.id_in_coll.expression);
+        self.nested(|checker| {
+            checker.scope.insert(filter.id_in_coll.variable.name.clone(), VariableKind::Any);
+            if let Some(where_) = &filter.where_ {
+                checker.visit_expression(where_);
+            }
+            if let Some(expression) = expression {
+                checker.visit_expression(expression);
+            }
+        });
+    }
+
+    /// Pattern predicates in expressions may not introduce new variables.
+    fn check_pattern_predicate_variable(&mut self, variable: &Option<Variable>) {
+        if let Some(variable) = variable {
+            self.use_variable(variable);
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for ScopeChecker {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if !self.order_keys.contains(expression) {
+            walk_expression(self, expression);
+        }
+    }
+
+    fn visit_property_or_labels_expression(&mut self, expression: &'ast PropertyOrLabelsExpression) {
+        let is_key = |key: &Expression| key.as_property_or_labels_expression() == Some(expression);
+        if !self.order_keys.iter().any(is_key) {
+            walk_property_or_labels_expression(self, expression);
+        }
+    }
+
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        match atom {
+            Atom::Variable(variable) => self.use_variable(variable),
+            Atom::ListComprehension(comprehension) => {
+                self.check_filter_expression(&comprehension.filter_expression, comprehension.expression.as_ref())
+            }
+            Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => {
+                self.check_filter_expression(filter, None)
+            }
+            Atom::Reduce(reduce) => {
+                self.visit_expression(&reduce.initial_value);
+                self.visit_expression(&reduce.id_in_coll.expression);
+                self.nested(|checker| {
+                    checker.scope.insert(reduce.accumulator.name.clone(), VariableKind::Any);
+                    checker.scope.insert(reduce.id_in_coll.variable.name.clone(), VariableKind::Any);
+                    checker.visit_expression(&reduce.expression);
+                });
+            }
+            Atom::PatternComprehension(comprehension) => self.nested(|checker| {
+                checker.bind_relationship(&comprehension.relationship_pattern, BindMode::Match);
+                if let Some(variable) = &comprehension.variable {
+                    checker.bind_new(variable, VariableKind::Path);
+                }
+                walk_relationship_pattern(checker, &comprehension.relationship_pattern);
+                if let Some(where_) = &comprehension.where_ {
+                    checker.visit_expression(where_);
+                }
+                checker.visit_expression(&comprehension.expression);
+            }),
+            Atom::RelationshipsPattern(pattern) => {
+                self.check_pattern_predicate_variable(&pattern.node.variable);
+                walk_node_pattern(self, &pattern.node);
+                for (relationship, node) in &pattern.relationships {
+                    let (RelationshipPattern::LeftAndRight(detail)
+                        | RelationshipPattern::Left(detail)
+                        | RelationshipPattern::Right(detail)
+                        | RelationshipPattern::Undirected(detail)) = relationship;
+                    if let Some(detail) = detail {
+                        self.check_pattern_predicate_variable(&detail.variable);
+                    }
+                    walk_relationship_pattern(self, relationship);
+                    self.check_pattern_predicate_variable(&node.variable);
+                    walk_node_pattern(self, node);
+                }
+            }
+            Atom::ExistentialSubquery(subquery) => match subquery {
+                ExistentialSubquery::RegularQuery(query) => self.nested(|checker| checker.check_regular_query(query)),
+                ExistentialSubquery::PatternWhere { pattern, where_ } => self.nested(|checker| {
+                    checker.check_match_pattern(pattern);
+                    if let Some(where_) = where_ {
+                        checker.visit_expression(where_);
+                    }
+                }),
+            },
+            Atom::MapProjection(projection) => {
+                self.use_variable(&projection.variable);
+                walk_atom(self, atom);
+                for item in &projection.items {
+                    if let MapProjectionItem::Variable(variable) = item {
+                        self.use_variable(variable);
+                    }
+                }
+            }
+            _ => walk_atom(self, atom),
+        }
+    }
+}
@@ -0,0 +1,912 @@
+//! Static type inference over Cypher expressions.
+//!
+//! [`infer_types`] assigns a [`CypherType`] to every expression of a query.
+//! Variables are typed from the patterns, `UNWIND` lists and projections that
+//! bind them, literals by their kind and function calls by the signatures in
+//! a [`FunctionRegistry`]. Whenever an operand is known to have a type that an
+//! operator or function cannot accept, e.g. in `1 + true`, an
+//! `InvalidArgumentType` error is reported. Types that cannot be determined
+//! statically are `ANY` and never cause errors. A pattern expression such as
+//! `(a)-->()` is a `LIST<PATH>`, which is also accepted where a predicate is
+//! expected.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::functions::FunctionRegistry;
+use crate::analysis::semantic::{SemanticError, SemanticErrorKind};
+use crate::ast::cypher::{
+    AddOrSubtractExpression, AndExpression, Atom, CallSubquery, ComparisonExpression, CypherType, ExistentialSubquery, Expression,
+    FilterExpression, FunctionInvocation, LabelExpression, LabelOrType, ListOperatorExpression, Literal,
+    MapProjectionItem, MergeAction, MultiplyDivideModuloExpression, MultiplyDivideModuloOperator, NotExpression, NumberLiteral, OrderSkipLimit, Pattern, PatternPart,
+    PlusMinusOperator, PowerOfExpression, ProcedureInvocation, ProjectionBody, ProjectionItem, Properties, PropertyKey,
+    PropertyOrLabelsExpression, Query, ReadingClause, RegularQuery, RelationshipPattern, RemoveItem, Set, SetItem,
+    SingleQuery, Span, StarOrYieldItems, StringListNullOperatorExpression, StringListNullOperatorExpressionInner,
+    TemporalLiteral, UnaryAddOrSubtractExpression, UpdatingClause, XorExpression, YieldItems,
+};
+
+/// The result of type inference.
+#[derive(Debug, Default)]
+pub struct TypeInfo {
+    /// The inferred type of every expression, keyed by [`Expression::span`].
+    /// Expressions without a span, such as the ones built by rewrites, are
+    /// left out since they cannot be told apart.
+    pub types: HashMap<Span, CypherType>,
+    pub errors: Vec<SemanticError>,
+}
+
+impl TypeInfo {
+    pub fn type_of(&self, expression: &Expression) -> Option<&CypherType> {
+        self.types.get(&expression.span())
+    }
+}
+
+/// Infers the types of all expressions in the query, typing function calls
+/// by the built-in functions.
+pub fn infer_types(query: &Query) -> TypeInfo {
+    infer_types_with(query, &FunctionRegistry::builtin())
+}
+
+/// Infers the types of all expressions in the query, typing function calls
+/// by the functions in the registry.
+pub fn infer_types_with(query: &Query, registry: &FunctionRegistry) -> TypeInfo {
+    let mut inference = TypeInference { registry, scope: HashMap::new(), patterns: HashSet::new(), info: TypeInfo::default() };
+
+    match query {
+        Query::RegularQuery(query) => inference.regular_query(query),
+        Query::StandaloneCall(call) => {
+            if let ProcedureInvocation::Explicit(invocation) = &call.invocation {
+                for argument in &invocation.arguments {
+                    inference.expression(argument);
+                }
+            }
+            if let Some(StarOrYieldItems::YieldItems(items)) = &call.yield_ {
+                inference.yield_items(items);
+            }
+        }
+    }
+
+    inference.info
+}
+
+pub(crate) fn number() -> CypherType {
+    CypherType::Union(vec![CypherType::Integer, CypherType::Float])
+}
+
+pub(crate) fn list(element: CypherType) -> CypherType {
+    CypherType::List(Box::new(element))
+}
+
+/// Returns whether the type says nothing definite about the value.
+fn is_unknown(type_: &CypherType) -> bool {
+    match type_ {
+        CypherType::Any | CypherType::Null | CypherType::Nothing | CypherType::PropertyValue => true,
+        CypherType::NotNull(type_) => is_unknown(type_),
+        CypherType::Union(types) => !types.iter().all(is_number),
+        _ => false,
+    }
+}
+
+fn is_number(type_: &CypherType) -> bool {
+    match type_ {
+        CypherType::Integer | CypherType::Float => true,
+        CypherType::NotNull(type_) => is_number(type_),
+        CypherType::Union(types) => types.iter().all(is_number),
+        _ => false,
+    }
+}
+
+fn is_temporal(type_: &CypherType) -> bool {
+    matches!(
+        type_,
+        CypherType::Date
+            | CypherType::LocalTime
+            | CypherType::ZonedTime
+            | CypherType::LocalDateTime
+            | CypherType::ZonedDateTime
+    )
+}
+
+/// Returns whether a value of type `actual` may be used where `expected` is required.
+pub fn accepts(expected: &CypherType, actual: &CypherType) -> bool {
+    match (expected, actual) {
+        (CypherType::Any, _) | (_, CypherType::Any | CypherType::Null | CypherType::Nothing) => true,
+        (_, CypherType::NotNull(actual)) => accepts(expected, actual),
+        (CypherType::NotNull(expected), _) => accepts(expected, actual),
+        (CypherType::Union(expected), _) => expected.iter().any(|expected| accepts(expected, actual)),
+        (_, CypherType::Union(actual)) => actual.iter().any(|actual| accepts(expected, actual)),
+        (CypherType::PropertyValue, actual) | (actual, CypherType::PropertyValue) => {
+            !matches!(actual, CypherType::Node | CypherType::Relationship | CypherType::Path | CypherType::Map)
+        }
+        (CypherType::List(expected), CypherType::List(actual)) => accepts(expected, actual),
+        (expected, actual) => expected == actual,
+    }
+}
+
+/// Returns the most specific type covering both types.
+pub fn join(left: &CypherType, right: &CypherType) -> CypherType {
+    match (left, right) {
+        (left, right) if left == right => left.clone(),
+        (CypherType::Null | CypherType::Nothing, other) | (other, CypherType::Null | CypherType::Nothing) => other.clone(),
+        (left, right) if is_number(left) && is_number(right) => number(),
+        (CypherType::List(left), CypherType::List(right)) => list(join(left, right)),
+        _ => CypherType::Any,
+    }
+}
+
+pub(crate) fn element_type(type_: &CypherType) -> CypherType {
+    match type_ {
+        CypherType::List(element) => (**element).clone(),
+        CypherType::NotNull(type_) => element_type(type_),
+        _ => CypherType::Any,
+    }
+}
+
+fn numeric_result(left: &CypherType, right: &CypherType) -> CypherType {
+    match (left, right) {
+        (CypherType::Integer, CypherType::Integer) => CypherType::Integer,
+        (CypherType::Float, _) | (_, CypherType::Float) => CypherType::Float,
+        _ => number(),
+    }
+}
+
+fn add(left: &CypherType, right: &CypherType) -> Option<CypherType> {
+    match (left, right) {
+        (CypherType::List(left), CypherType::List(right)) => Some(list(join(left, right))),
+        (CypherType::List(element), other) | (other, CypherType::List(element)) => Some(list(join(element, other))),
+        (left, right) if is_unknown(left) || is_unknown(right) => Some(CypherType::Any),
+        (left, right) if is_number(left) && is_number(right) => Some(numeric_result(left, right)),
+        (CypherType::String, other) | (other, CypherType::String) if *other == CypherType::String || is_number(other) => {
+            Some(CypherType::String)
+        }
+        (CypherType::Duration, CypherType::Duration) => Some(CypherType::Duration),
+        (temporal, CypherType::Duration) | (CypherType::Duration, temporal) if is_temporal(temporal) => Some(temporal.clone()),
+        _ => None,
+    }
+}
+
+fn subtract(left: &CypherType, right: &CypherType) -> Option<CypherType> {
+    match (left, right) {
+        (left, right) if is_unknown(left) || is_unknown(right) => Some(CypherType::Any),
+        (left, right) if is_number(left) && is_number(right) => Some(numeric_result(left, right)),
+        (CypherType::Duration, CypherType::Duration) => Some(CypherType::Duration),
+        (temporal, CypherType::Duration) if is_temporal(temporal) => Some(temporal.clone()),
+        _ => None,
+    }
+}
+
+fn concatenate(left: &CypherType, right: &CypherType) -> Option<CypherType> {
+    match (left, right) {
+        (CypherType::String, CypherType::String) => Some(CypherType::String),
+        (CypherType::List(left), CypherType::List(right)) => Some(list(join(left, right))),
+        (known, unknown) | (unknown, known) if is_unknown(unknown) => match known {
+            CypherType::String | CypherType::List(_) => Some(known.clone()),
+            known if is_unknown(known) => Some(CypherType::Any),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn multiply_divide_modulo(operator: &MultiplyDivideModuloOperator, left: &CypherType, right: &CypherType) -> Option<CypherType> {
+    match (operator, left, right) {
+        (_, left, right) if is_unknown(left) || is_unknown(right) => Some(CypherType::Any),
+        (_, left, right) if is_number(left) && is_number(right) => Some(numeric_result(left, right)),
+        (MultiplyDivideModuloOperator::Multiply, CypherType::Duration, factor)
+        | (MultiplyDivideModuloOperator::Multiply, factor, CypherType::Duration)
+        | (MultiplyDivideModuloOperator::Divide, CypherType::Duration, factor)
+            if is_number(factor) =>
+        {
+            Some(CypherType::Duration)
+        }
+        _ => None,
+    }
+}
+
+/// A type paired with the span of the expression it was inferred for.
+type Typed = (CypherType, Span);
+
+struct TypeInference<'r> {
+    registry: &'r FunctionRegistry,
+    scope: HashMap<String, CypherType>,
+    /// The spans of the pattern expressions, which are predicates as well.
+    patterns: HashSet<Span>,
+    info: TypeInfo,
+}
+
+impl TypeInference<'_> {
+    fn error(&mut self, span: Span, message: String) {
+        self.info.errors.push(SemanticError { kind: SemanticErrorKind::InvalidArgumentType, span, message });
+    }
+
+    fn expect(&mut self, expected: &CypherType, (actual, span): &Typed, context: &str) {
+        if !accepts(expected, actual) {
+            self.error(*span, format!("{} expects {} but got {}", context, expected, actual));
+        }
+    }
+
+    /// Like [`TypeInference::expect`] for `BOOLEAN`, but also accepts a
+    /// pattern expression.
+    fn expect_predicate(&mut self, typed: &Typed, context: &str) {
+        if !self.patterns.contains(&typed.1) {
+            self.expect(&CypherType::Boolean, typed, context);
+        }
+    }
+
+    fn bind(&mut self, name: &str, type_: CypherType) {
+        self.scope.insert(name.to_string(), type_);
+    }
+
+    /// Runs `infer` in a nested scope whose bindings are discarded afterwards.
+    fn nested<T>(&mut self, infer: impl FnOnce(&mut Self) -> T) -> T {
+        let scope = self.scope.clone();
+        let result = infer(self);
+        self.scope = scope;
+        result
+    }
+
+    fn regular_query(&mut self, query: &RegularQuery) {
+        let outer = self.scope.clone();
+        for part in std::iter::once(&query.query).chain(query.union.iter().map(|union| &union.query)) {
+            self.scope = outer.clone();
+            self.single_query(part);
+        }
+        self.scope = outer;
+    }
+
+    /// Infers each part of a `CALL { ... }` subquery in the outer scope, then
+    /// binds the variables it returns.
+    fn call_subquery(&mut self, subquery: &CallSubquery) {
+        let outer = self.scope.clone();
+        let mut returned = HashMap::new();
+        for part in std::iter::once(&subquery.query.query).chain(subquery.query.union.iter().map(|union| &union.query)) {
+            self.scope = outer.clone();
+            self.single_query(part);
+            if part.final_part().return_.is_some() {
+                returned = std::mem::take(&mut self.scope);
+            }
+        }
+        self.scope = outer;
+        self.scope.extend(returned);
+    }
+
+    fn single_query(&mut self, query: &SingleQuery) {
+        let query = match query {
+            SingleQuery::SinglePartQuery(query) => query,
+            SingleQuery::MultiPartQuery(query) => {
+                for part in &query.parts {
+                    self.clauses(&part.reading_clauses, &part.updating_clauses);
+                    self.projection(&part.with.projection_body);
+                    if let Some(where_) = &part.with.where_ {
+                        self.predicate(where_, "WHERE");
+                    }
+                }
+                &query.query
+            }
+        };
+
+        self.clauses(&query.reading_clauses, &query.updating_clauses);
+        if let Some(return_) = &query.return_ {
+            self.projection(return_);
+        }
+    }
+
+    fn clauses(&mut self, reading_clauses: &[ReadingClause], updating_clauses: &[UpdatingClause]) {
+        for clause in reading_clauses {
+            self.reading_clause(clause);
+        }
+        for clause in updating_clauses {
+            self.updating_clause(clause);
+        }
+    }
+
+    fn reading_clause(&mut self, clause: &ReadingClause) {
+        match clause {
+            ReadingClause::Match(match_) => {
+                self.pattern(&match_.pattern);
+                if let Some(where_) = &match_.where_ {
+                    self.predicate(where_, "WHERE");
+                }
+            }
+            ReadingClause::Unwind(unwind) => {
+                let type_ = self.expression(&unwind.expression);
+                self.expect(&list(CypherType::Any), &(type_.clone(), unwind.expression.span()), "UNWIND");
+                self.bind(&unwind.variable.name, element_type(&type_));
+            }
+            ReadingClause::LoadCsv(load_csv) => {
+                let source = self.expression(&load_csv.source);
+                self.expect(&CypherType::String, &(source, load_csv.source.span()), "LOAD CSV");
+                let row = if load_csv.with_headers { CypherType::Map } else { list(CypherType::String) };
+                self.bind(&load_csv.variable.name, row);
+            }
+            ReadingClause::CallSubquery(subquery) => self.call_subquery(subquery),
+            ReadingClause::InQueryCall(call) => {
+                for argument in &call.invocation.arguments {
+                    self.expression(argument);
+                }
+                if let Some(items) = &call.yield_items {
+                    self.yield_items(items);
+                }
+            }
+            ReadingClause::Filter(filter) => self.predicate(&filter.expression, "FILTER"),
+            ReadingClause::OrderSkipLimit(clause) => self.order_skip_limit(clause),
+        }
+    }
+
+    fn order_skip_limit(&mut self, clause: &OrderSkipLimit) {
+        for item in clause.order.iter().flat_map(|order| &order.items) {
+            self.expression(&item.expression);
+        }
+        self.skip_limit(clause.skip.as_ref().map(|skip| &skip.expression), clause.limit.as_ref().map(|limit| &limit.expression));
+    }
+
+    fn yield_items(&mut self, items: &YieldItems) {
+        if let YieldItems::Items { items, where_ } = items {
+            for item in items {
+                self.bind(&item.variable.name, CypherType::Any);
+            }
+            if let Some(where_) = where_ {
+                self.predicate(where_, "WHERE");
+            }
+        }
+    }
+
+    fn updating_clause(&mut self, clause: &UpdatingClause) {
+        match clause {
+            UpdatingClause::Create(create) => self.pattern(&create.pattern),
+            UpdatingClause::Merge(merge) => {
+                self.pattern_part(&merge.pattern_part);
+                for action in &merge.actions {
+                    match action {
+                        MergeAction::Match(set) | MergeAction::Create(set) => self.set(set),
+                    }
+                }
+            }
+            UpdatingClause::Delete(delete) => {
+                let deletable =
+                    CypherType::Union(vec![CypherType::Node, CypherType::Relationship, CypherType::Path, list(CypherType::Any)]);
+                for expression in &delete.expressions {
+                    let type_ = self.expression(expression);
+                    self.expect(&deletable, &(type_, expression.span()), "DELETE");
+                }
+            }
+            UpdatingClause::Set(set) => self.set(set),
+            UpdatingClause::Remove(remove) => {
+                for item in &remove.items {
+                    match item {
+                        RemoveItem::VariableNodeLabels { node_labels, .. } => self.labels(node_labels),
+                        RemoveItem::PropertyExpression(expression) => {
+                            self.atom(&expression.atom);
+                            self.property_keys(&expression.property_path);
+                        }
+                    }
+                }
+            }
+            UpdatingClause::Foreach(foreach) => {
+                let type_ = self.expression(&foreach.expression);
+                self.expect(&list(CypherType::Any), &(type_.clone(), foreach.expression.span()), "FOREACH");
+                self.nested(|inference| {
+                    inference.bind(&foreach.variable.name, element_type(&type_));
+                    for clause in &foreach.clauses {
+                        inference.updating_clause(clause);
+                    }
+                });
+            }
+            UpdatingClause::Filter(filter) => self.predicate(&filter.expression, "FILTER"),
+            UpdatingClause::OrderSkipLimit(clause) => self.order_skip_limit(clause),
+        }
+    }
+
+    fn set(&mut self, set: &Set) {
+        for item in &set.items {
+            match item {
+                SetItem::AssignPropertyExpression { property_expression, expression } => {
+                    self.atom(&property_expression.atom);
+                    self.property_keys(&property_expression.property_path);
+                    let type_ = self.expression(expression);
+                    self.expect(&CypherType::PropertyValue, &(type_, expression.span()), "SET");
+                }
+                SetItem::AssignVariable { expression, .. } | SetItem::IncrementVariable { expression, .. } => {
+                    let type_ = self.expression(expression);
+                    let expected = CypherType::Union(vec![CypherType::Map, CypherType::Node, CypherType::Relationship]);
+                    self.expect(&expected, &(type_, expression.span()), "SET");
+                }
+                SetItem::VariableNodeLabels { node_labels, .. } => self.labels(node_labels),
+            }
+        }
+    }
+
+    fn labels(&mut self, labels: &[LabelOrType]) {
+        for label in labels {
+            if let LabelOrType::Dynamic(expression) = label {
+                self.dynamic_label(expression);
+            }
+        }
+    }
+
+    fn dynamic_label(&mut self, expression: &Expression) {
+        let type_ = self.expression(expression);
+        let expected = CypherType::Union(vec![CypherType::String, list(CypherType::String)]);
+        self.expect(&expected, &(type_, expression.span()), "a dynamic label");
+    }
+
+    fn label_expression(&mut self, label_expression: &LabelExpression) {
+        match label_expression {
+            LabelExpression::Label(label) => self.labels(std::slice::from_ref(label)),
+            LabelExpression::AnyOf(expression) => self.dynamic_label(expression),
+            LabelExpression::Wildcard => (),
+            LabelExpression::Not(expression) => self.label_expression(expression),
+            LabelExpression::And(expressions) | LabelExpression::Or(expressions) => {
+                for expression in expressions {
+                    self.label_expression(expression);
+                }
+            }
+        }
+    }
+
+    fn property_keys(&mut self, keys: &[PropertyKey]) {
+        for key in keys {
+            if let PropertyKey::Dynamic(expression) = key {
+                let type_ = self.expression(expression);
+                self.expect(&CypherType::String, &(type_, expression.span()), "a dynamic property key");
+            }
+        }
+    }
+
+    fn properties(&mut self, properties: &Option<Properties>) {
+        if let Some(Properties::MapLiteral(map)) = properties {
+            for value in map.values() {
+                let type_ = self.expression(value);
+                self.expect(&CypherType::PropertyValue, &(type_, value.span()), "a property");
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        for part in &pattern.parts {
+            self.pattern_part(part);
+        }
+    }
+
+    fn pattern_part(&mut self, part: &PatternPart) {
+        let element = &part.pattern_element;
+        let nodes = std::iter::once(&element.node_pattern).chain(element.relationship_patterns.iter().map(|(_, node)| node));
+        for node in nodes {
+            if let Some(variable) = &node.variable {
+                self.bind(&variable.name, CypherType::Node);
+            }
+        }
+        for (relationship, _) in &element.relationship_patterns {
+            self.relationship(relationship);
+        }
+        if let Some(variable) = &part.variable {
+            self.bind(&variable.name, CypherType::Path);
+        }
+
+        let nodes = std::iter::once(&element.node_pattern).chain(element.relationship_patterns.iter().map(|(_, node)| node));
+        for node in nodes {
+            if let Some(label_expression) = &node.label_expression {
+                self.label_expression(label_expression);
+            }
+            self.properties(&node.properties);
+        }
+    }
+
+    fn relationship(&mut self, relationship: &RelationshipPattern) {
+        let (RelationshipPattern::LeftAndRight(Some(detail))
+            | RelationshipPattern::Left(Some(detail))
+            | RelationshipPattern::Right(Some(detail))
+            | RelationshipPattern::Undirected(Some(detail))) = relationship
+        else {
+            return;
+        };
+
+        if let Some(variable) = &detail.variable {
+            let type_ = if detail.range.is_some() { list(CypherType::Relationship) } else { CypherType::Relationship };
+            self.bind(&variable.name, type_);
+        }
+        if let Some(label_expression) = &detail.label_expression {
+            self.label_expression(label_expression);
+        }
+        self.properties(&detail.properties);
+    }
+
+    fn projection(&mut self, body: &ProjectionBody) {
+        let mut projected = if body.items.star { self.scope.clone() } else { HashMap::new() };
+
+        for item in &body.items.items {
+            match item {
+                ProjectionItem::Expression(expression) => {
+                    let type_ = self.expression(expression);
+                    if let Some(Atom::Variable(variable)) = expression.as_atom() {
+                        projected.insert(variable.name.clone(), type_);
+                    }
+                }
+                ProjectionItem::AsVariable { expression, variable } => {
+                    let type_ = self.expression(expression);
+                    projected.insert(variable.name.clone(), type_);
+                }
+            }
+        }
+
+        self.scope.extend(projected.iter().map(|(name, type_)| (name.clone(), type_.clone())));
+        for item in body.order.iter().flat_map(|order| &order.items) {
+            self.expression(&item.expression);
+        }
+        self.skip_limit(body.skip.as_ref().map(|skip| &skip.expression), body.limit.as_ref().map(|limit| &limit.expression));
+
+        self.scope = projected;
+    }
+
+    fn skip_limit(&mut self, skip: Option<&Expression>, limit: Option<&Expression>) {
+        for (expression, clause) in [(skip, "SKIP"), (limit, "LIMIT")] {
+            if let Some(expression) = expression {
+                let type_ = self.expression(expression);
+                self.expect(&CypherType::Integer, &(type_, expression.span()), clause);
+            }
+        }
+    }
+
+    fn predicate(&mut self, expression: &Expression, context: &str) {
+        let type_ = self.expression(expression);
+        self.expect_predicate(&(type_, expression.span()), context);
+    }
+
+    /// Infers and records the type of an expression.
+    fn expression(&mut self, expression: &Expression) -> CypherType {
+        let (type_, span) = self.boolean_operands(&expression.0, Self::xor, "OR");
+        if span != Span::default() {
+            self.info.types.insert(span, type_.clone());
+        }
+        type_
+    }
+
+    fn boolean_operands<T>(&mut self, operands: &[T], infer: fn(&mut Self, &T) -> Typed, operator: &str) -> Typed {
+        if let [operand] = operands {
+            return infer(self, operand);
+        }
+
+        let mut span: Option<Span> = None;
+        for operand in operands {
+            let typed = infer(self, operand);
+            self.expect_predicate(&typed, operator);
+            span = Some(span.map_or(typed.1, |span| span.to(typed.1)));
+        }
+        (CypherType::Boolean, span.unwrap_or_default())
+    }
+
+    fn xor(&mut self, expression: &XorExpression) -> Typed {
+        self.boolean_operands(&expression.0, Self::and, "XOR")
+    }
+
+    fn and(&mut self, expression: &AndExpression) -> Typed {
+        self.boolean_operands(&expression.0, Self::not, "AND")
+    }
+
+    fn not(&mut self, expression: &NotExpression) -> Typed {
+        let typed = self.comparison(&expression.expression);
+        if !expression.not {
+            return typed;
+        }
+
+        self.expect_predicate(&typed, "NOT");
+        (CypherType::Boolean, typed.1)
+    }
+
+    fn comparison(&mut self, expression: &ComparisonExpression) -> Typed {
+        let typed = self.add_or_subtract(&expression.expression);
+        if expression.comparisons.is_empty() {
+            return typed;
+        }
+
+        // Comparing values of different types yields null rather than an error.
+        for (_, operand) in &expression.comparisons {
+            self.add_or_subtract(operand);
+        }
+        (CypherType::Boolean, expression.span())
+    }
+
+    fn add_or_subtract(&mut self, expression: &AddOrSubtractExpression) -> Typed {
+        let (mut type_, mut span) = self.multiply_divide_modulo(&expression.expression);
+
+        for (operator, operand) in &expression.operations {
+            let (right, right_span) = self.multiply_divide_modulo(operand);
+            span = span.to(right_span);
+            let (result, symbol) = match operator {
+                PlusMinusOperator::Plus => (add(&type_, &right), "+"),
+                PlusMinusOperator::Minus => (subtract(&type_, &right), "-"),
+                PlusMinusOperator::Concatenate => (concatenate(&type_, &right), "||"),
+            };
+            type_ = result.unwrap_or_else(|| {
+                self.error(span, format!("operator `{}` cannot be applied to {} and {}", symbol, type_, right));
+                CypherType::Any
+            });
+        }
+
+        (type_, span)
+    }
+
+    fn multiply_divide_modulo(&mut self, expression: &MultiplyDivideModuloExpression) -> Typed {
+        let (mut type_, mut span) = self.power_of(&expression.expression);
+
+        for (operator, operand) in &expression.operations {
+            let (right, right_span) = self.power_of(operand);
+            span = span.to(right_span);
+            let symbol = match operator {
+                MultiplyDivideModuloOperator::Multiply => "*",
+                MultiplyDivideModuloOperator::Divide => "/",
+                MultiplyDivideModuloOperator::Modulo => "%",
+            };
+            type_ = multiply_divide_modulo(operator, &type_, &right).unwrap_or_else(|| {
+                self.error(span, format!("operator `{}` cannot be applied to {} and {}", symbol, type_, right));
+                CypherType::Any
+            });
+        }
+
+        (type_, span)
+    }
+
+    fn power_of(&mut self, expression: &PowerOfExpression) -> Typed {
+        let [first, rest @ ..] = expression.0.as_slice() else { return (CypherType::Any, Span::default()) };
+        let (mut type_, mut span) = self.unary(first);
+
+        for operand in rest {
+            let (right, right_span) = self.unary(operand);
+            span = span.to(right_span);
+            if !(is_unknown(&type_) || is_number(&type_)) || !(is_unknown(&right) || is_number(&right)) {
+                self.error(span, format!("operator `^` cannot be applied to {} and {}", type_, right));
+            }
+            type_ = CypherType::Float;
+        }
+
+        (type_, span)
+    }
+
+    fn unary(&mut self, expression: &UnaryAddOrSubtractExpression) -> Typed {
+        let (type_, span) = self.string_list_null(&expression.expression);
+        if expression.negate && !(is_unknown(&type_) || is_number(&type_) || type_ == CypherType::Duration) {
+            self.error(span, format!("unary `-` cannot be applied to {}", type_));
+            return (CypherType::Any, span);
+        }
+
+        (type_, span)
+    }
+
+    fn string_list_null(&mut self, expression: &StringListNullOperatorExpression) -> Typed {
+        let (mut type_, mut span) = self.property_or_labels(&expression.expression);
+
+        for operation in &expression.operations {
+            type_ = match operation {
+                StringListNullOperatorExpressionInner::StringOperator(operator) => {
+                    let right = self.property_or_labels(&operator.expression);
+                    self.expect(&CypherType::String, &(type_, span), "a string operator");
+                    self.expect(&CypherType::String, &right, "a string operator");
+                    span = span.to(right.1);
+                    CypherType::Boolean
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::In(list_expression)) => {
+                    let right = self.property_or_labels(list_expression);
+                    self.expect(&list(CypherType::Any), &right, "IN");
+                    span = span.to(right.1);
+                    CypherType::Boolean
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Index(index)) => {
+                    let index_type = self.expression(index);
+                    let element = self.index(type_, span, (index_type, index.span()));
+                    span = span.to(index.span());
+                    element
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Range(from, to)) => {
+                    for bound in [from, to].into_iter().flatten() {
+                        let bound_type = self.expression(bound);
+                        self.expect(&CypherType::Integer, &(bound_type, bound.span()), "a list slice");
+                    }
+                    self.expect(&list(CypherType::Any), &(type_.clone(), span), "a list slice");
+                    for bound in [from, to].into_iter().flatten() {
+                        span = span.to(bound.span());
+                    }
+                    type_
+                }
+                StringListNullOperatorExpressionInner::NullOperator(_)
+                | StringListNullOperatorExpressionInner::TypePredicate(_) => CypherType::Boolean,
+                StringListNullOperatorExpressionInner::NormalizedPredicate(_) => {
+                    self.expect(&CypherType::String, &(type_, span), "IS NORMALIZED");
+                    CypherType::Boolean
+                }
+            };
+        }
+
+        (type_, span)
+    }
+
+    fn index(&mut self, type_: CypherType, span: Span, index: Typed) -> CypherType {
+        match &type_ {
+            CypherType::List(element) => {
+                self.expect(&CypherType::Integer, &index, "a list index");
+                (**element).clone()
+            }
+            CypherType::Map | CypherType::Node | CypherType::Relationship => {
+                self.expect(&CypherType::String, &index, "a property key");
+                CypherType::Any
+            }
+            type_ if is_unknown(type_) => CypherType::Any,
+            _ => {
+                self.error(span, format!("{} cannot be indexed", type_));
+                CypherType::Any
+            }
+        }
+    }
+
+    fn property_or_labels(&mut self, expression: &PropertyOrLabelsExpression) -> Typed {
+        let mut type_ = self.atom(&expression.atom);
+        if matches!(expression.atom, Atom::RelationshipsPattern(_)) {
+            self.patterns.insert(expression.span);
+        }
+
+        for key in &expression.property_lookup {
+            let has_properties = is_unknown(&type_)
+                || is_temporal(&type_)
+                || matches!(
+                    type_,
+                    CypherType::Node | CypherType::Relationship | CypherType::Map | CypherType::Duration | CypherType::Point
+                );
+            if !has_properties {
+                self.error(expression.span, format!("cannot access property `{}` of {}", key, type_));
+            }
+            type_ = CypherType::Any;
+        }
+
+        if let Some(label_expression) = &expression.label_expression {
+            self.label_expression(label_expression);
+            let expected = CypherType::Union(vec![CypherType::Node, CypherType::Relationship]);
+            self.expect(&expected, &(type_, expression.span), "a label predicate");
+            type_ = CypherType::Boolean;
+        }
+
+        (type_, expression.span)
+    }
+
+    fn filter_expression(&mut self, filter: &FilterExpression) -> CypherType {
+        let source = self.expression(&filter.id_in_coll.expression);
+        self.expect(&list(CypherType::Any), &(source.clone(), filter.id_in_coll.expression.span()), "IN");
+        self.bind(&filter.id_in_coll.variable.name, element_type(&source));
+        if let Some(where_) = &filter.where_ {
+            self.predicate(where_, "WHERE");
+        }
+        element_type(&source)
+    }
+
+    fn atom(&mut self, atom: &Atom) -> CypherType {
+        match atom {
+            Atom::Literal(literal) => self.literal(literal),
+            Atom::Parameter(_) => CypherType::Any,
+            Atom::CaseExpression(case) => {
+                if let Some(expression) = &case.expression {
+                    self.expression(expression);
+                }
+                let mut type_ = CypherType::Nothing;
+                for (when, then) in &case.alternatives {
+                    if case.expression.is_some() {
+                        self.expression(when);
+                    }
+                    else {
+                        self.predicate(when, "WHEN");
+                    }
+                    type_ = join(&type_, &self.expression(then));
+                }
+                match &case.else_ {
+                    Some(else_) => join(&type_, &self.expression(else_)),
+                    None => join(&type_, &CypherType::Null),
+                }
+            }
+            Atom::CountStar(_) => CypherType::Integer,
+            Atom::ListComprehension(comprehension) => self.nested(|inference| {
+                let element = inference.filter_expression(&comprehension.filter_expression);
+                match &comprehension.expression {
+                    Some(expression) => list(inference.expression(expression)),
+                    None => list(element),
+                }
+            }),
+            Atom::PatternComprehension(comprehension) => self.nested(|inference| {
+                if let Some(variable) = &comprehension.variable {
+                    inference.bind(&variable.name, CypherType::Path);
+                }
+                inference.relationship(&comprehension.relationship_pattern);
+                if let Some(where_) = &comprehension.where_ {
+                    inference.predicate(where_, "WHERE");
+                }
+                list(inference.expression(&comprehension.expression))
+            }),
+            Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => {
+                self.nested(|inference| inference.filter_expression(filter));
+                CypherType::Boolean
+            }
+            Atom::Reduce(reduce) => {
+                let initial = self.expression(&reduce.initial_value);
+                let source = self.expression(&reduce.id_in_coll.expression);
+                self.expect(&list(CypherType::Any), &(source.clone(), reduce.id_in_coll.expression.span()), "reduce()");
+                self.nested(|inference| {
+                    inference.bind(&reduce.accumulator.name, initial.clone());
+                    inference.bind(&reduce.id_in_coll.variable.name, element_type(&source));
+                    join(&initial, &inference.expression(&reduce.expression))
+                })
+            }
+            Atom::RelationshipsPattern(pattern) => {
+                for node in std::iter::once(&pattern.node).chain(pattern.relationships.iter().map(|(_, node)| node)) {
+                    if let Some(label_expression) = &node.label_expression {
+                        self.label_expression(label_expression);
+                    }
+                    self.properties(&node.properties);
+                }
+                list(CypherType::Path)
+            }
+            Atom::ParenthesizedExpression(expression) => self.expression(expression),
+            Atom::FunctionInvocation(function) => self.function(function),
+            Atom::ExistentialSubquery(subquery) => {
+                self.nested(|inference| match subquery {
+                    ExistentialSubquery::RegularQuery(query) => inference.regular_query(query),
+                    ExistentialSubquery::PatternWhere { pattern, where_ } => {
+                        inference.pattern(pattern);
+                        if let Some(where_) = where_ {
+                            inference.predicate(where_, "WHERE");
+                        }
+                    }
+                });
+                CypherType::Boolean
+            }
+            Atom::MapProjection(projection) => {
+                for item in &projection.items {
+                    if let MapProjectionItem::Literal { expression, .. } = item {
+                        self.expression(expression);
+                    }
+                }
+                CypherType::Map
+            }
+            Atom::TemporalLiteral(literal) => match literal {
+                TemporalLiteral::Date(_) => CypherType::Date,
+                TemporalLiteral::LocalTime(_) => CypherType::LocalTime,
+                TemporalLiteral::Time(_) => CypherType::ZonedTime,
+                TemporalLiteral::LocalDateTime(_) => CypherType::LocalDateTime,
+                TemporalLiteral::DateTime(_) => CypherType::ZonedDateTime,
+                TemporalLiteral::Duration(_) => CypherType::Duration,
+            },
+            Atom::PointLiteral(_) => CypherType::Point,
+            Atom::Variable(variable) => self.scope.get(&variable.name).cloned().unwrap_or(CypherType::Any),
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal) -> CypherType {
+        match literal {
+            Literal::String(_) => CypherType::String,
+            Literal::Number(NumberLiteral::Integer(_)) => CypherType::Integer,
+            Literal::Number(NumberLiteral::Double(_)) => CypherType::Float,
+            Literal::Boolean(_) => CypherType::Boolean,
+            Literal::Null => CypherType::Null,
+            Literal::MapLiteral(map) => {
+                for value in map.values() {
+                    self.expression(value);
+                }
+                CypherType::Map
+            }
+            Literal::ListLiteral(elements) => {
+                let element = elements.iter().fold(CypherType::Nothing, |element, expression| {
+                    join(&element, &self.expression(expression))
+                });
+                list(element)
+            }
+        }
+    }
+
+    fn function(&mut self, function: &FunctionInvocation) -> CypherType {
+        let arguments: Vec<Typed> =
+            function.arguments.iter().map(|argument| (self.expression(argument), argument.span())).collect();
+        let Some(signature) = self.registry.lookup(&function.function_name) else { return CypherType::Any };
+
+        for (index, argument) in arguments.iter().enumerate() {
+            if let Some(parameter) = signature.parameter_at(index) {
+                self.expect(&parameter.type_, argument, &format!("{}()", function.function_name));
+            }
+        }
+        let types: Vec<CypherType> = arguments.into_iter().map(|(type_, _)| type_).collect();
+        signature.return_type(&types)
+    }
+}
@@ -1,112 +1,267 @@
 use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Debug)]
+mod display;
+pub(crate) use display::Name;
+pub mod value;
+pub mod visit;
+pub mod visit_mut;
+
+/// Byte offsets of a node in the parsed query text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub options: Vec<CypherOptions>,
+    pub mode: ExecutionMode,
+    pub body: StatementBody,
+}
+
+/// What a statement does: run a query, or change the schema.
+///
+/// Schema commands are not queries; they are neither planned nor executed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementBody {
+    Query(Query),
+    SchemaCommand(SchemaCommand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CypherOptions {
+    pub version: Option<String>,
+    pub options: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionMode {
+    Normal,
+    Explain,
+    Profile,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Query {
     RegularQuery(RegularQuery),
     StandaloneCall(StandaloneCall),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SingleQuery {
     SinglePartQuery(SinglePartQuery),
     MultiPartQuery(MultiPartQuery),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RegularQuery {
+    pub use_: Option<Use>,
     pub query: SingleQuery,
     pub union: Vec<Union>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Union {
     pub all: bool,
+    pub use_: Option<Use>,
     pub query: SingleQuery,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Use {
+    pub graph: GraphReference,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphReference {
+    Name(Vec<String>),
+    ByName(Expression),
+    ByElementId(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaCommand {
+    CreateIndex(CreateIndex),
+    DropIndex(DropIndex),
+    CreateConstraint(CreateConstraint),
+    DropConstraint(DropConstraint),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateIndex {
+    pub kind: IndexKind,
+    pub name: Option<String>,
+    pub if_not_exists: bool,
+    pub target: SchemaTarget,
+    pub properties: Vec<SchemaProperty>,
+    pub options: Option<HashMap<String, Expression>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexKind {
+    Range,
+    Text,
+    Point,
+    Fulltext,
+    Vector,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropIndex {
+    pub name: String,
+    pub if_exists: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateConstraint {
+    pub name: Option<String>,
+    pub if_not_exists: bool,
+    pub target: SchemaTarget,
+    pub properties: Vec<SchemaProperty>,
+    /// The entity spelled out in `IS NODE KEY` or `IS RELATIONSHIP UNIQUE`.
+    pub entity: Option<ConstraintEntity>,
+    pub kind: ConstraintKind,
+    pub options: Option<HashMap<String, Expression>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstraintKind {
+    Unique,
+    Key,
+    NotNull,
+    Type(CypherType),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstraintEntity {
+    Node,
+    Relationship,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropConstraint {
+    pub name: String,
+    pub if_exists: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaTarget {
+    Node {
+        variable: Variable,
+        labels: Vec<String>,
+    },
+    Relationship {
+        variable: Variable,
+        relationship_types: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaProperty {
+    pub variable: Variable,
+    pub property_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SinglePartQuery {
     pub reading_clauses: Vec<ReadingClause>,
     pub updating_clauses: Vec<UpdatingClause>,
     pub return_: Option<ProjectionBody>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MultiPartQuery {
     pub parts: Vec<MultiPartQueryPart>,
     pub query: SinglePartQuery,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MultiPartQueryPart {
     pub reading_clauses: Vec<ReadingClause>,
     pub updating_clauses: Vec<UpdatingClause>,
     pub with: With,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct With {
     pub projection_body: ProjectionBody,
     pub where_: Option<Expression>,
 }
 
-#[derive(Debug)]
+/// A clause of the updating part of a query. `FILTER` and standalone
+/// `ORDER BY`/`SKIP`/`LIMIT` clauses can be placed between updating clauses
+/// too, where they work on the updated rows without writing themselves.
+#[derive(Debug, Clone, PartialEq)]
 pub enum UpdatingClause {
     Create(Create),
     Merge(Merge),
     Delete(Delete),
     Set(Set),
     Remove(Remove),
+    Foreach(Foreach),
+    Filter(Filter),
+    OrderSkipLimit(OrderSkipLimit),
+}
+
+/// `FOREACH (variable IN expression | clauses)`, which runs the updating
+/// clauses once for every element of the list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Foreach {
+    pub variable: Variable,
+    pub expression: Expression,
+    pub clauses: Vec<UpdatingClause>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Merge {
     pub pattern_part: PatternPart,
     pub actions: Vec<MergeAction>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MergeAction {
     Match(Set),
     Create(Set),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Create {
     pub pattern: Pattern,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pattern {
     pub parts: Vec<PatternPart>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PatternPart {
-    pub variable: Option<String>,
+    pub variable: Option<Variable>,
     pub pattern_element: PatternElement,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PatternElement {
     pub node_pattern: NodePattern,
     pub relationship_patterns: Vec<(RelationshipPattern, NodePattern)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct NodePattern {
-    pub variable: Option<String>,
-    pub node_labels: Vec<String>,
+    pub variable: Option<Variable>,
+    pub label_expression: Option<LabelExpression>,
     pub properties: Option<Properties>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RelationshipsPattern {
     pub node: NodePattern,
     pub relationships: Vec<(RelationshipPattern, NodePattern)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RelationshipPattern {
     LeftAndRight(Option<RelationshipDetail>),
     Left(Option<RelationshipDetail>),
@@ -114,166 +269,260 @@ pub enum RelationshipPattern {
     Undirected(Option<RelationshipDetail>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RelationshipDetail {
-    pub variable: Option<String>,
-    pub relationship_types: Vec<String>,
+    pub variable: Option<Variable>,
+    pub label_expression: Option<LabelExpression>,
+    pub range: Option<RangeLiteral>,
     pub properties: Option<Properties>,
 }
 
-#[derive(Debug)]
+/// The length bounds of a variable length relationship such as `*`, `*2` or `*1..3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeLiteral {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelExpression {
+    Label(LabelOrType),
+    /// `$any(expression)`, which matches if any of the labels or types the
+    /// expression evaluates to is present.
+    AnyOf(Expression),
+    Wildcard,
+    Not(Box<LabelExpression>),
+    And(Vec<LabelExpression>),
+    Or(Vec<LabelExpression>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelOrType {
+    Static(String),
+    Dynamic(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Properties {
     Parameter(String),
     MapLiteral(HashMap<String, Expression>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Set {
     pub items: Vec<SetItem>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SetItem {
     AssignPropertyExpression {
-        property_expression: PropertyExpression,
+        property_expression: Box<PropertyExpression>,
         expression: Expression,
     },
     AssignVariable {
-        variable: String,
+        variable: Variable,
         expression: Expression,
     },
     IncrementVariable {
-        variable: String,
+        variable: Variable,
         expression: Expression,
     },
     VariableNodeLabels {
-        variable: String,
-        node_labels: Vec<String>,
+        variable: Variable,
+        node_labels: Vec<LabelOrType>,
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Delete {
     pub detach: bool,
     pub expressions: Vec<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Remove {
     pub items: Vec<RemoveItem>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RemoveItem {
     VariableNodeLabels {
-        variable: String,
-        node_labels: Vec<String>,
+        variable: Variable,
+        node_labels: Vec<LabelOrType>,
     },
-    PropertyExpression(PropertyExpression),
+    PropertyExpression(Box<PropertyExpression>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PropertyExpression {
     pub atom: Atom,
-    pub property_path: Vec<String>,
+    pub property_path: Vec<PropertyKey>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyKey {
+    Static(String),
+    Dynamic(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Atom {
     Literal(Literal),
     Parameter(String),
     CaseExpression(CaseExpression),
-    CountStar,
+    CountStar(CountStar),
     ListComprehension(ListComprehension),
     PatternComprehension(PatternComprehension),
     All(FilterExpression),
     Any(FilterExpression),
     None(FilterExpression),
     Single(FilterExpression),
+    Reduce(ReduceExpression),
     RelationshipsPattern(RelationshipsPattern),
     ParenthesizedExpression(Expression),
     FunctionInvocation(FunctionInvocation),
     ExistentialSubquery(ExistentialSubquery),
-    Variable(String),
+    MapProjection(MapProjection),
+    TemporalLiteral(TemporalLiteral),
+    PointLiteral(PointLiteral),
+    Variable(Variable),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapProjection {
+    pub variable: Variable,
+    pub items: Vec<MapProjectionItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapProjectionItem {
+    Property(String),
+    Literal {
+        key: String,
+        expression: Expression,
+    },
+    Variable(Variable),
+    AllProperties,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PatternComprehension {
-    pub variable: Option<String>,
+    pub variable: Option<Variable>,
     pub relationship_pattern: RelationshipPattern,
     pub where_: Option<Expression>,
     pub expression: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ListComprehension {
     pub filter_expression: FilterExpression,
     pub expression: Option<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IdInColl {
-    pub variable: String,
+    pub variable: Variable,
     pub expression: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FilterExpression {
     pub id_in_coll: IdInColl,
     pub where_: Option<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReduceExpression {
+    pub accumulator: Variable,
+    pub initial_value: Expression,
+    pub id_in_coll: IdInColl,
+    pub expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CaseExpression {
     pub expression: Option<Expression>,
     pub alternatives: Vec<(Expression, Expression)>,
     pub else_: Option<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ReadingClause {
     Match(Match),
     Unwind(Unwind),
+    LoadCsv(LoadCsv),
+    CallSubquery(CallSubquery),
     InQueryCall(InQueryCall),
+    Filter(Filter),
+    OrderSkipLimit(OrderSkipLimit),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Match {
     pub optional: bool,
     pub pattern: Pattern,
     pub where_: Option<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Unwind {
     pub expression: Expression,
-    pub variable: String,
+    pub variable: Variable,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadCsv {
+    pub with_headers: bool,
+    pub source: Expression,
+    pub variable: Variable,
+    pub field_terminator: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub expression: Expression,
+}
+
+/// A standalone `ORDER BY`, `SKIP`/`OFFSET` and/or `LIMIT` clause placed between other clauses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderSkipLimit {
+    pub order: Option<Order>,
+    pub skip: Option<Skip>,
+    pub limit: Option<Limit>,
+}
+
+/// A `CALL { ... }` subquery, run once for each incoming row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallSubquery {
+    pub query: RegularQuery,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InQueryCall {
     pub invocation: ExplicitProcedureInvocation,
     pub yield_items: Option<YieldItems>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExplicitProcedureInvocation {
-    pub name: String,
+    pub name: QualifiedName,
     pub arguments: Vec<Expression>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ImplicitProcedureInvocation {
-    pub name: String,
+    pub name: QualifiedName,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ProcedureInvocation {
     Explicit(ExplicitProcedureInvocation),
     Implicit(ImplicitProcedureInvocation),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProjectionBody {
     pub distinct: bool,
     pub items: ProjectionItems,
@@ -282,55 +531,68 @@ pub struct ProjectionBody {
     pub limit: Option<Limit>
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Order {
     pub items: Vec<SortItem>,
 }
 
-#[derive(Debug)]
-pub enum SortItem {
-    Ascending(Expression),
-    Descending(Expression),
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortItem {
+    pub expression: Expression,
+    pub direction: SortDirection,
+    pub nulls: Option<NullsOrder>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Skip {
     pub expression: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Limit {
     pub expression: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProjectionItems {
     pub star: bool,
     pub items: Vec<ProjectionItem>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ProjectionItem {
     Expression(Expression),
     AsVariable {
         expression: Expression,
-        variable: String,
+        variable: Variable,
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct StandaloneCall {
     pub invocation: ProcedureInvocation,
     pub yield_: Option<StarOrYieldItems>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StarOrYieldItems {
     Star,
     YieldItems(YieldItems),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum YieldItems {
     Star,
     Items {
@@ -339,40 +601,40 @@ pub enum YieldItems {
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct YieldItem {
     pub procedure_result_field: Option<String>,
-    pub variable: String,
+    pub variable: Variable,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProcedureResultField {
     pub name: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OrExpression(pub Vec<XorExpression>);
 pub type Expression = OrExpression;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct XorExpression(pub Vec<AndExpression>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AndExpression(pub Vec<NotExpression>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct NotExpression {
     pub not: bool,
     pub expression: ComparisonExpression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ComparisonExpression {
     pub expression: AddOrSubtractExpression,
     pub comparisons: Vec<(ComparisonOperator, AddOrSubtractExpression)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ComparisonOperator {
     Equal,
     NotEqual,
@@ -382,93 +644,168 @@ pub enum ComparisonOperator {
     GreaterEqual,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AddOrSubtractExpression {
     pub expression: MultiplyDivideModuloExpression,
     pub operations: Vec<(PlusMinusOperator, MultiplyDivideModuloExpression)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PlusMinusOperator {
     Plus,
     Minus,
+    Concatenate,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MultiplyDivideModuloExpression {
     pub expression: PowerOfExpression,
     pub operations: Vec<(MultiplyDivideModuloOperator, PowerOfExpression)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MultiplyDivideModuloOperator {
     Multiply,
     Divide,
     Modulo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PowerOfExpression(pub Vec<UnaryAddOrSubtractExpression>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UnaryAddOrSubtractExpression {
     pub negate: bool,
     pub expression: StringListNullOperatorExpression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct StringListNullOperatorExpression {
     pub expression: PropertyOrLabelsExpression,
     pub operations: Vec<StringListNullOperatorExpressionInner>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StringListNullOperatorExpressionInner {
-    StringOperator(StringOperatorExpression),
+    StringOperator(Box<StringOperatorExpression>),
     ListOperator(ListOperatorExpression),
     NullOperator(NullOperatorExpression),
+    NormalizedPredicate(NormalizedPredicateExpression),
+    TypePredicate(TypePredicateExpression),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct StringOperatorExpression {
     pub operator: StringOperator,
     pub expression: PropertyOrLabelsExpression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ListOperatorExpression {
-    In(PropertyOrLabelsExpression),
+    In(Box<PropertyOrLabelsExpression>),
     Index(Expression),
     Range(Option<Expression>, Option<Expression>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StringOperator {
     StartsWith,
     EndsWith,
     Contains,
+    RegularExpression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct NullOperatorExpression {
     pub not: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedPredicateExpression {
+    pub not: bool,
+    pub normal_form: NormalForm,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NormalForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypePredicateExpression {
+    pub not: bool,
+    pub type_: CypherType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CypherType {
+    Nothing,
+    Null,
+    Any,
+    Boolean,
+    String,
+    Integer,
+    Float,
+    Date,
+    LocalTime,
+    ZonedTime,
+    LocalDateTime,
+    ZonedDateTime,
+    Duration,
+    Point,
+    Node,
+    Relationship,
+    Map,
+    Path,
+    PropertyValue,
+    List(Box<CypherType>),
+    Union(Vec<CypherType>),
+    NotNull(Box<CypherType>),
+}
+
+#[derive(Debug, Clone)]
 pub struct PropertyOrLabelsExpression {
     pub atom: Atom,
     pub property_lookup: Vec<String>,
-    pub node_labels: Vec<String>,
+    pub label_expression: Option<LabelExpression>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FunctionInvocation {
-    pub function_name: String,
+    pub function_name: QualifiedName,
     pub distinct: bool,
     pub arguments: Vec<Expression>,
+    pub span: Span,
+}
+
+/// The `count(*)` aggregation.
+#[derive(Debug, Clone, Copy)]
+pub struct CountStar {
+    pub span: Span,
+}
+
+/// An unescaped symbolic name.
+pub type Ident = String;
+
+/// A variable name together with the position it was written at.
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub name: Ident,
+    pub span: Span,
+}
+
+/// A possibly namespaced function or procedure name such as `db.index.fulltext.queryNodes`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QualifiedName {
+    pub namespace: Vec<Ident>,
+    pub name: Ident,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExistentialSubquery {
     RegularQuery(RegularQuery),
     PatternWhere {
@@ -477,7 +814,7 @@ pub enum ExistentialSubquery {
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     String(String),
     Number(NumberLiteral),
@@ -487,8 +824,575 @@ pub enum Literal {
     ListLiteral(Vec<Expression>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum NumberLiteral {
     Integer(i64),
     Double(f64),
-}
\ No newline at end of file
+}
+
+/// A temporal value whose constructor call, e.g. `date('2020-01-01')`, was
+/// evaluated statically by [`crate::analysis::literals`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemporalLiteral {
+    Date(Date),
+    LocalTime(LocalTime),
+    Time(Time),
+    LocalDateTime(LocalDateTime),
+    DateTime(DateTime),
+    Duration(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct LocalTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Time {
+    pub time: LocalTime,
+    /// `None` stands for the default time zone of the session.
+    pub timezone: Option<TimeZone>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LocalDateTime {
+    pub date: Date,
+    pub time: LocalTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: LocalTime,
+    /// `None` stands for the default time zone of the session.
+    pub timezone: Option<TimeZone>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeZone {
+    /// A fixed offset from UTC in seconds.
+    Offset(i32),
+    /// A named zone such as `Europe/Stockholm`, optionally with an explicit offset.
+    Named { name: String, offset: Option<i32> },
+}
+
+/// A duration as stored by Cypher: months, days and seconds are kept apart
+/// because their lengths in seconds are not fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Duration {
+    pub months: i64,
+    pub days: i64,
+    pub seconds: i64,
+    pub nanoseconds: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLiteral {
+    pub crs: CoordinateReferenceSystem,
+    pub coordinates: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateReferenceSystem {
+    Cartesian,
+    Cartesian3D,
+    Wgs84,
+    Wgs843D,
+}
+
+impl CoordinateReferenceSystem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoordinateReferenceSystem::Cartesian => "cartesian",
+            CoordinateReferenceSystem::Cartesian3D => "cartesian-3d",
+            CoordinateReferenceSystem::Wgs84 => "wgs-84",
+            CoordinateReferenceSystem::Wgs843D => "wgs-84-3d",
+        }
+    }
+
+    pub fn srid(&self) -> u32 {
+        match self {
+            CoordinateReferenceSystem::Cartesian => 7203,
+            CoordinateReferenceSystem::Cartesian3D => 9157,
+            CoordinateReferenceSystem::Wgs84 => 4326,
+            CoordinateReferenceSystem::Wgs843D => 4979,
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        match self {
+            CoordinateReferenceSystem::Cartesian | CoordinateReferenceSystem::Wgs84 => 2,
+            CoordinateReferenceSystem::Cartesian3D | CoordinateReferenceSystem::Wgs843D => 3,
+        }
+    }
+}
+
+impl Query {
+    /// Returns the names of all graphs targeted by `USE` clauses that can be
+    /// resolved without evaluating the query, i.e. plain graph names and
+    /// `graph.byName()` calls with a string literal argument. `USE` clauses of
+    /// `CALL { ... }` subqueries are included.
+    pub fn target_graphs(&self) -> Vec<String> {
+        struct GraphCollector {
+            graphs: Vec<String>,
+        }
+
+        impl<'ast> visit::Visitor<'ast> for GraphCollector {
+            fn visit_regular_query(&mut self, query: &'ast RegularQuery) {
+                let uses = std::iter::once(&query.use_).chain(query.union.iter().map(|union| &union.use_));
+                for use_ in uses.flatten() {
+                    if let Some(name) = use_.graph.static_name() {
+                        if !self.graphs.contains(&name) {
+                            self.graphs.push(name);
+                        }
+                    }
+                }
+                visit::walk_regular_query(self, query);
+            }
+        }
+
+        let mut collector = GraphCollector { graphs: Vec::new() };
+        if let Query::RegularQuery(query) = self {
+            visit::Visitor::visit_regular_query(&mut collector, query);
+        }
+        collector.graphs
+    }
+}
+
+impl SingleQuery {
+    /// Returns the last part of the query, the one that ends in `RETURN` if any.
+    pub fn final_part(&self) -> &SinglePartQuery {
+        match self {
+            SingleQuery::SinglePartQuery(query) => query,
+            SingleQuery::MultiPartQuery(query) => &query.query,
+        }
+    }
+}
+
+impl Variable {
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Variables compare by name only, so that the same expression written at
+/// different positions compares equal.
+impl PartialEq for Variable {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq<str> for Variable {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for Variable {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl PartialEq for FunctionInvocation {
+    fn eq(&self, other: &Self) -> bool {
+        self.function_name == other.function_name && self.distinct == other.distinct && self.arguments == other.arguments
+    }
+}
+
+impl PartialEq for PropertyOrLabelsExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.atom == other.atom && self.property_lookup == other.property_lookup && self.label_expression == other.label_expression
+    }
+}
+
+impl PartialEq for ExplicitProcedureInvocation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.arguments == other.arguments
+    }
+}
+
+impl PartialEq for ImplicitProcedureInvocation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for CountStar {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl QualifiedName {
+    /// Returns the namespace parts followed by the name.
+    pub fn parts(&self) -> impl Iterator<Item = &str> {
+        self.namespace.iter().chain(std::iter::once(&self.name)).map(String::as_str)
+    }
+
+    /// Returns whether the name lies in `namespace` or one of its nested
+    /// namespaces, e.g. `apoc.coll.sum` lies in `["apoc"]` and `["apoc", "coll"]`.
+    pub fn is_in_namespace(&self, namespace: &[&str]) -> bool {
+        self.namespace.len() >= namespace.len() && self.namespace.iter().zip(namespace).all(|(part, expected)| part == expected)
+    }
+
+    /// Matches the name against a dotted pattern whose last part may be `*`,
+    /// as used by procedure allow-lists: `apoc.*` matches everything in the
+    /// `apoc` namespace and below, `db.labels` only that exact name.
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern: Vec<&str> = pattern.split('.').collect();
+        match pattern.split_last() {
+            Some((&"*", namespace)) => self.is_in_namespace(namespace),
+            _ => self.parts().eq(pattern.iter().copied()),
+        }
+    }
+}
+
+impl fmt::Display for CypherType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CypherType::Nothing => f.write_str("NOTHING"),
+            CypherType::Null => f.write_str("NULL"),
+            CypherType::Any => f.write_str("ANY"),
+            CypherType::Boolean => f.write_str("BOOLEAN"),
+            CypherType::String => f.write_str("STRING"),
+            CypherType::Integer => f.write_str("INTEGER"),
+            CypherType::Float => f.write_str("FLOAT"),
+            CypherType::Date => f.write_str("DATE"),
+            CypherType::LocalTime => f.write_str("LOCAL TIME"),
+            CypherType::ZonedTime => f.write_str("ZONED TIME"),
+            CypherType::LocalDateTime => f.write_str("LOCAL DATETIME"),
+            CypherType::ZonedDateTime => f.write_str("ZONED DATETIME"),
+            CypherType::Duration => f.write_str("DURATION"),
+            CypherType::Point => f.write_str("POINT"),
+            CypherType::Node => f.write_str("NODE"),
+            CypherType::Relationship => f.write_str("RELATIONSHIP"),
+            CypherType::Map => f.write_str("MAP"),
+            CypherType::Path => f.write_str("PATH"),
+            CypherType::PropertyValue => f.write_str("PROPERTY VALUE"),
+            CypherType::List(element) => write!(f, "LIST<{}>", element),
+            CypherType::Union(types) => {
+                for (index, type_) in types.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{}", type_)?;
+                }
+                Ok(())
+            }
+            CypherType::NotNull(type_) => write!(f, "{} NOT NULL", type_),
+        }
+    }
+}
+
+impl fmt::Display for QualifiedName {
+    /// Writes the dotted name, escaping parts that are not plain identifiers with backticks.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, part) in self.parts().enumerate() {
+            if index > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", display::Name(part))?;
+        }
+        Ok(())
+    }
+}
+
+impl GraphReference {
+    /// Returns the graph name if it is statically known. Parts of a dotted name
+    /// that are not plain identifiers are escaped with backticks, so that
+    /// `` `a.b` `` and `a.b` name different graphs.
+    pub fn static_name(&self) -> Option<String> {
+        match self {
+            GraphReference::Name(parts) => {
+                let parts: Vec<String> = parts.iter().map(|part| display::Name(part).to_string()).collect();
+                Some(parts.join("."))
+            }
+            GraphReference::ByName(expression) => match expression.as_atom() {
+                Some(Atom::Literal(Literal::String(name))) => Some(name.clone()),
+                _ => None,
+            },
+            GraphReference::ByElementId(_) => None,
+        }
+    }
+}
+
+impl ComparisonExpression {
+    /// Returns the span from the first to the last operand of the comparison.
+    pub fn span(&self) -> Span {
+        let first = self.expression.span();
+        match self.comparisons.last() {
+            Some((_, last)) => first.to(last.span()),
+            None => first,
+        }
+    }
+}
+
+impl AddOrSubtractExpression {
+    pub fn span(&self) -> Span {
+        let first = self.expression.span();
+        match self.operations.last() {
+            Some((_, last)) => first.to(last.span()),
+            None => first,
+        }
+    }
+}
+
+impl MultiplyDivideModuloExpression {
+    pub fn span(&self) -> Span {
+        let first = self.expression.span();
+        match self.operations.last() {
+            Some((_, last)) => first.to(last.span()),
+            None => first,
+        }
+    }
+}
+
+impl PowerOfExpression {
+    pub fn span(&self) -> Span {
+        match (self.0.first(), self.0.last()) {
+            (Some(first), Some(last)) => first.expression.span().to(last.expression.span()),
+            _ => Span::default(),
+        }
+    }
+}
+
+impl StringListNullOperatorExpression {
+    /// Returns the span from the operand to the last operand of the string
+    /// and list operators, including list indexes and slice bounds.
+    pub fn span(&self) -> Span {
+        let mut span = self.expression.span;
+        for operation in &self.operations {
+            match operation {
+                StringListNullOperatorExpressionInner::StringOperator(operator) => span = span.to(operator.expression.span),
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::In(expression)) => {
+                    span = span.to(expression.span)
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Index(index)) => {
+                    span = span.to(index.span())
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Range(from, to)) => {
+                    for bound in [from, to].into_iter().flatten() {
+                        span = span.to(bound.span());
+                    }
+                }
+                _ => (),
+            }
+        }
+        span
+    }
+}
+
+impl Span {
+    /// Returns the span covering both this and the other span.
+    pub fn to(self, other: Span) -> Span {
+        Span { start: self.start.min(other.start), end: self.end.max(other.end) }
+    }
+}
+
+impl OrExpression {
+    /// Returns the span from the first to the last operand of the expression.
+    /// A leading `NOT` and trailing postfix operators such as `IS NULL` are
+    /// not included.
+    pub fn span(&self) -> Span {
+        let first = self.0.first().and_then(|xor| xor.0.first()).and_then(|and| and.0.first());
+        let last = self.0.last().and_then(|xor| xor.0.last()).and_then(|and| and.0.last());
+        match (first, last) {
+            (Some(first), Some(last)) => first.expression.span().to(last.expression.span()),
+            _ => Span::default(),
+        }
+    }
+
+    /// Returns the atom if this expression consists of nothing but a single
+    /// atom, without any operators, property lookups or label predicates.
+    pub fn as_atom(&self) -> Option<&Atom> {
+        match self.as_signed_atom() {
+            Some((false, atom)) => Some(atom),
+            _ => None,
+        }
+    }
+
+    /// Like [`OrExpression::as_atom`], but also accepts a single unary minus
+    /// in front of the atom, which is reported in the returned flag.
+    pub fn as_signed_atom(&self) -> Option<(bool, &Atom)> {
+        let (negate, property_or_labels) = self.as_signed_property_or_labels_expression()?;
+        if !property_or_labels.property_lookup.is_empty() || property_or_labels.label_expression.is_some() {
+            return None;
+        }
+
+        Some((negate, &property_or_labels.atom))
+    }
+
+    /// Returns the property or label expression if this expression consists
+    /// of nothing but a single one, e.g. `n.name` or `n:Person`.
+    pub fn as_property_or_labels_expression(&self) -> Option<&PropertyOrLabelsExpression> {
+        match self.as_signed_property_or_labels_expression() {
+            Some((false, expression)) => Some(expression),
+            _ => None,
+        }
+    }
+
+    /// Wraps the operand of an `AND` into a whole expression.
+    pub fn from_not(not: NotExpression) -> Expression {
+        OrExpression(vec![XorExpression(vec![AndExpression(vec![not])])])
+    }
+
+    /// Wraps the operand of a comparison into a whole expression.
+    pub fn from_add_or_subtract(expression: AddOrSubtractExpression) -> Expression {
+        OrExpression::from_not(NotExpression { not: false, expression: ComparisonExpression { expression, comparisons: Vec::new() } })
+    }
+
+    /// Wraps the atom into a whole expression.
+    pub fn from_atom(atom: Atom) -> Expression {
+        OrExpression::from_add_or_subtract(AddOrSubtractExpression::from_property_or_labels(PropertyOrLabelsExpression::plain(atom)))
+    }
+
+    fn as_signed_property_or_labels_expression(&self) -> Option<(bool, &PropertyOrLabelsExpression)> {
+        let [xor] = self.0.as_slice() else { return None };
+        let [and] = xor.0.as_slice() else { return None };
+        let [not] = and.0.as_slice() else { return None };
+        if not.not || !not.expression.comparisons.is_empty() {
+            return None;
+        }
+
+        let add_or_subtract = &not.expression.expression;
+        let multiply_divide_modulo = &add_or_subtract.expression;
+        if !add_or_subtract.operations.is_empty() || !multiply_divide_modulo.operations.is_empty() {
+            return None;
+        }
+
+        let [unary] = multiply_divide_modulo.expression.0.as_slice() else { return None };
+        if !unary.expression.operations.is_empty() {
+            return None;
+        }
+
+        Some((unary.negate, &unary.expression.expression))
+    }
+}
+
+impl AddOrSubtractExpression {
+    /// Wraps a property or label expression into the operand of a comparison.
+    pub fn from_property_or_labels(expression: PropertyOrLabelsExpression) -> AddOrSubtractExpression {
+        let unary = UnaryAddOrSubtractExpression {
+            negate: false,
+            expression: StringListNullOperatorExpression { expression, operations: Vec::new() },
+        };
+        AddOrSubtractExpression {
+            expression: MultiplyDivideModuloExpression { expression: PowerOfExpression(vec![unary]), operations: Vec::new() },
+            operations: Vec::new(),
+        }
+    }
+
+    /// Builds `variable.key1.key2:Label` as the operand of a comparison.
+    pub fn property_or_labels(variable: &Variable, property_lookup: Vec<String>, label_expression: Option<LabelExpression>) -> AddOrSubtractExpression {
+        AddOrSubtractExpression::from_property_or_labels(PropertyOrLabelsExpression {
+            atom: Atom::Variable(variable.clone()),
+            property_lookup,
+            label_expression,
+            span: Span::default(),
+        })
+    }
+
+    /// Returns the expression as the operand of a comparison, in parentheses
+    /// unless it binds at least as tightly.
+    fn from_expression(expression: Expression) -> AddOrSubtractExpression {
+        if let [xor] = expression.0.as_slice() {
+            if let [and] = xor.0.as_slice() {
+                if let [not] = and.0.as_slice() {
+                    if !not.not && not.expression.comparisons.is_empty() {
+                        return not.expression.expression.clone();
+                    }
+                }
+            }
+        }
+        AddOrSubtractExpression::from_property_or_labels(PropertyOrLabelsExpression::plain(Atom::ParenthesizedExpression(expression)))
+    }
+}
+
+impl PropertyOrLabelsExpression {
+    /// Returns the atom without property lookups or label predicates.
+    pub fn plain(atom: Atom) -> PropertyOrLabelsExpression {
+        PropertyOrLabelsExpression { atom, property_lookup: Vec::new(), label_expression: None, span: Span::default() }
+    }
+}
+
+impl RelationshipPattern {
+    /// Returns the part in brackets, whatever the direction.
+    pub fn detail(&self) -> Option<&RelationshipDetail> {
+        let (RelationshipPattern::LeftAndRight(detail)
+            | RelationshipPattern::Left(detail)
+            | RelationshipPattern::Right(detail)
+            | RelationshipPattern::Undirected(detail)) = self;
+        detail.as_ref()
+    }
+
+    /// Returns the part in brackets, whatever the direction.
+    pub fn detail_mut(&mut self) -> Option<&mut RelationshipDetail> {
+        let (RelationshipPattern::LeftAndRight(detail)
+            | RelationshipPattern::Left(detail)
+            | RelationshipPattern::Right(detail)
+            | RelationshipPattern::Undirected(detail)) = self;
+        detail.as_mut()
+    }
+}
+
+impl NodePattern {
+    /// Removes the labels and the property map from the pattern and returns
+    /// them as predicates on its variable, e.g. `n:Person` and `n.name = 'A'`
+    /// for `(n:Person {name: 'A'})`. A pattern without a variable and a
+    /// property map given as a parameter are left as they are.
+    pub fn take_inline_predicates(&mut self) -> Vec<Expression> {
+        take_inline_predicates(&self.variable, &mut self.label_expression, &mut self.properties)
+    }
+}
+
+impl RelationshipDetail {
+    /// Like [`NodePattern::take_inline_predicates`], for relationships.
+    pub fn take_inline_predicates(&mut self) -> Vec<Expression> {
+        take_inline_predicates(&self.variable, &mut self.label_expression, &mut self.properties)
+    }
+}
+
+fn take_inline_predicates(
+    variable: &Option<Variable>,
+    label_expression: &mut Option<LabelExpression>,
+    properties: &mut Option<Properties>,
+) -> Vec<Expression> {
+    let mut predicates = Vec::new();
+    let Some(variable) = variable else { return predicates };
+    if let Some(labels) = label_expression.take() {
+        predicates.push(OrExpression::from_add_or_subtract(AddOrSubtractExpression::property_or_labels(variable, Vec::new(), Some(labels))));
+    }
+    match properties.take() {
+        Some(Properties::MapLiteral(map)) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, value) in entries {
+                let comparison = ComparisonExpression {
+                    expression: AddOrSubtractExpression::property_or_labels(variable, vec![key], None),
+                    comparisons: vec![(ComparisonOperator::Equal, AddOrSubtractExpression::from_expression(value))],
+                };
+                predicates.push(OrExpression::from_not(NotExpression { not: false, expression: comparison }));
+            }
+        }
+        other => *properties = other,
+    }
+    predicates
+}
@@ -0,0 +1,1152 @@
+//! Canonical Cypher text of the AST.
+//!
+//! The `Display` implementations write keywords in upper case, separate
+//! tokens by single spaces, sort map keys and escape names that are not
+//! plain identifiers. Parsing the written text again yields an equal AST, up
+//! to the integer notation and the spelling of keyword synonyms.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    Atom, CaseExpression, ComparisonOperator, ConstraintEntity, ConstraintKind, CreateConstraint, CreateIndex, CypherOptions, Date,
+    DateTime, Duration, ExecutionMode, ExistentialSubquery, ExplicitProcedureInvocation, Expression, FilterExpression,
+    FunctionInvocation, GraphReference, IdInColl, IndexKind, LabelExpression, LabelOrType, ListOperatorExpression, Literal,
+    LocalDateTime, LocalTime, MapProjection, MapProjectionItem, Match, MergeAction, MultiPartQueryPart,
+    MultiplyDivideModuloOperator, NodePattern,
+    NormalForm, NullsOrder, NumberLiteral, Order, OrderSkipLimit, Pattern, PatternPart, PlusMinusOperator, PointLiteral,
+    ProcedureInvocation, ProjectionBody, ProjectionItem, Properties, PropertyExpression, PropertyKey,
+    PropertyOrLabelsExpression, Query, RangeLiteral, ReadingClause, RegularQuery, RelationshipDetail,
+    RelationshipPattern, RemoveItem, SchemaCommand, SchemaProperty, SchemaTarget, Set, SetItem, SingleQuery,
+    SinglePartQuery, SortDirection, SortItem, StandaloneCall, StarOrYieldItems, Statement, StatementBody,
+    StringListNullOperatorExpressionInner, StringOperator, TemporalLiteral, Time, TimeZone, UpdatingClause, Use,
+    With, YieldItem, YieldItems,
+};
+
+/// Writes the items separated by `separator`.
+struct Separated<'a, T>(&'a [T], &'static str);
+
+impl<T: Display> Display for Separated<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, item) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(self.1)?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// A variable or other unescaped symbolic name, escaped with backticks unless
+/// it is a plain identifier. Labels, relationship types and property keys are
+/// kept as written by the parser and need no escaping.
+pub(crate) struct Name<'a>(pub &'a str);
+
+impl Display for Name<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = self.0;
+        let plain = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if plain {
+            f.write_str(name)
+        }
+        else {
+            write!(f, "`{}`", name.replace('`', "``"))
+        }
+    }
+}
+
+/// Writes a map with its keys in sorted order.
+fn write_map(f: &mut Formatter<'_>, map: &std::collections::HashMap<String, Expression>) -> fmt::Result {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    f.write_str("{")?;
+    for (index, (key, value)) in entries.into_iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: {}", key, value)?;
+    }
+    f.write_str("}")
+}
+
+fn write_string(f: &mut Formatter<'_>, text: &str) -> fmt::Result {
+    f.write_str("'")?;
+    for c in text.chars() {
+        match c {
+            '\'' => f.write_str("\\'")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("'")
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for options in &self.options {
+            write!(f, "{} ", options)?;
+        }
+        match self.mode {
+            ExecutionMode::Normal => (),
+            ExecutionMode::Explain => f.write_str("EXPLAIN ")?,
+            ExecutionMode::Profile => f.write_str("PROFILE ")?,
+        }
+        match &self.body {
+            StatementBody::Query(query) => write!(f, "{}", query),
+            StatementBody::SchemaCommand(command) => write!(f, "{}", command),
+        }
+    }
+}
+
+impl Display for CypherOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("CYPHER")?;
+        if let Some(version) = &self.version {
+            write!(f, " {}", version)?;
+        }
+        for (key, value) in &self.options {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Query::RegularQuery(query) => write!(f, "{}", query),
+            Query::StandaloneCall(call) => write!(f, "{}", call),
+        }
+    }
+}
+
+impl Display for RegularQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(use_) = &self.use_ {
+            write!(f, "{} ", use_)?;
+        }
+        write!(f, "{}", self.query)?;
+        for union in &self.union {
+            f.write_str(if union.all { " UNION ALL " } else { " UNION " })?;
+            if let Some(use_) = &union.use_ {
+                write!(f, "{} ", use_)?;
+            }
+            write!(f, "{}", union.query)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Use {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.graph {
+            GraphReference::Name(parts) => {
+                let parts: Vec<Name> = parts.iter().map(|part| Name(part)).collect();
+                write!(f, "USE {}", Separated(&parts, "."))
+            }
+            GraphReference::ByName(expression) => write!(f, "USE graph.byName({})", expression),
+            GraphReference::ByElementId(expression) => write!(f, "USE graph.byElementId({})", expression),
+        }
+    }
+}
+
+impl Display for SingleQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SingleQuery::SinglePartQuery(query) => write!(f, "{}", query),
+            SingleQuery::MultiPartQuery(query) => {
+                for part in &query.parts {
+                    write!(f, "{} ", part)?;
+                }
+                write!(f, "{}", query.query)
+            }
+        }
+    }
+}
+
+impl Display for MultiPartQueryPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for clause in &self.reading_clauses {
+            write!(f, "{} ", clause)?;
+        }
+        for clause in &self.updating_clauses {
+            write!(f, "{} ", clause)?;
+        }
+        write!(f, "{}", self.with)
+    }
+}
+
+impl Display for SinglePartQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut clauses: Vec<&dyn Display> = Vec::new();
+        clauses.extend(self.reading_clauses.iter().map(|clause| clause as &dyn Display));
+        clauses.extend(self.updating_clauses.iter().map(|clause| clause as &dyn Display));
+        for (index, clause) in clauses.into_iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", clause)?;
+        }
+        if let Some(return_) = &self.return_ {
+            if !self.reading_clauses.is_empty() || !self.updating_clauses.is_empty() {
+                f.write_str(" ")?;
+            }
+            write!(f, "RETURN {}", return_)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for With {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "WITH {}", self.projection_body)?;
+        if let Some(where_) = &self.where_ {
+            write!(f, " WHERE {}", where_)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ProjectionBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.distinct {
+            f.write_str("DISTINCT ")?;
+        }
+        if self.items.star {
+            f.write_str("*")?;
+            if !self.items.items.is_empty() {
+                f.write_str(", ")?;
+            }
+        }
+        write!(f, "{}", Separated(&self.items.items, ", "))?;
+        if let Some(order) = &self.order {
+            write!(f, " {}", order)?;
+        }
+        if let Some(skip) = &self.skip {
+            write!(f, " SKIP {}", skip.expression)?;
+        }
+        if let Some(limit) = &self.limit {
+            write!(f, " LIMIT {}", limit.expression)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ProjectionItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectionItem::Expression(expression) => write!(f, "{}", expression),
+            ProjectionItem::AsVariable { expression, variable } => write!(f, "{} AS {}", expression, Name(&variable.name)),
+        }
+    }
+}
+
+impl Display for Order {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "ORDER BY {}", Separated(&self.items, ", "))
+    }
+}
+
+impl Display for SortItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)?;
+        if self.direction == SortDirection::Descending {
+            f.write_str(" DESC")?;
+        }
+        match self.nulls {
+            Some(NullsOrder::First) => f.write_str(" NULLS FIRST"),
+            Some(NullsOrder::Last) => f.write_str(" NULLS LAST"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for ReadingClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadingClause::Match(match_) => write!(f, "{}", match_),
+            ReadingClause::Unwind(unwind) => write!(f, "UNWIND {} AS {}", unwind.expression, Name(&unwind.variable.name)),
+            ReadingClause::LoadCsv(load_csv) => {
+                f.write_str("LOAD CSV ")?;
+                if load_csv.with_headers {
+                    f.write_str("WITH HEADERS ")?;
+                }
+                write!(f, "FROM {} AS {}", load_csv.source, Name(&load_csv.variable.name))?;
+                if let Some(terminator) = &load_csv.field_terminator {
+                    f.write_str(" FIELDTERMINATOR ")?;
+                    write_string(f, terminator)?;
+                }
+                Ok(())
+            }
+            ReadingClause::CallSubquery(subquery) => write!(f, "CALL {{ {} }}", subquery.query),
+            ReadingClause::InQueryCall(call) => {
+                write!(f, "CALL {}", call.invocation)?;
+                if let Some(items) = &call.yield_items {
+                    write!(f, " YIELD {}", items)?;
+                }
+                Ok(())
+            }
+            ReadingClause::Filter(filter) => write!(f, "FILTER {}", filter.expression),
+            ReadingClause::OrderSkipLimit(clause) => write!(f, "{}", clause),
+        }
+    }
+}
+
+impl Display for Match {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.optional {
+            f.write_str("OPTIONAL ")?;
+        }
+        write!(f, "MATCH {}", self.pattern)?;
+        if let Some(where_) = &self.where_ {
+            write!(f, " WHERE {}", where_)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ExplicitProcedureInvocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.name, Separated(&self.arguments, ", "))
+    }
+}
+
+impl Display for StandaloneCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.invocation {
+            ProcedureInvocation::Explicit(invocation) => write!(f, "CALL {}", invocation)?,
+            ProcedureInvocation::Implicit(invocation) => write!(f, "CALL {}", invocation.name)?,
+        }
+        match &self.yield_ {
+            Some(StarOrYieldItems::Star) => f.write_str(" YIELD *"),
+            Some(StarOrYieldItems::YieldItems(items)) => write!(f, " YIELD {}", items),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for YieldItems {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            YieldItems::Star => f.write_str("*"),
+            YieldItems::Items { items, where_ } => {
+                write!(f, "{}", Separated(items, ", "))?;
+                if let Some(where_) = where_ {
+                    write!(f, " WHERE {}", where_)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for YieldItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(field) = &self.procedure_result_field {
+            write!(f, "{} AS ", field)?;
+        }
+        write!(f, "{}", Name(&self.variable.name))
+    }
+}
+
+impl Display for OrderSkipLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(order) = &self.order {
+            parts.push(order.to_string());
+        }
+        if let Some(skip) = &self.skip {
+            parts.push(format!("SKIP {}", skip.expression));
+        }
+        if let Some(limit) = &self.limit {
+            parts.push(format!("LIMIT {}", limit.expression));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Display for UpdatingClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdatingClause::Create(create) => write!(f, "CREATE {}", create.pattern),
+            UpdatingClause::Merge(merge) => {
+                write!(f, "MERGE {}", merge.pattern_part)?;
+                for action in &merge.actions {
+                    match action {
+                        MergeAction::Match(set) => write!(f, " ON MATCH {}", set)?,
+                        MergeAction::Create(set) => write!(f, " ON CREATE {}", set)?,
+                    }
+                }
+                Ok(())
+            }
+            UpdatingClause::Delete(delete) => {
+                if delete.detach {
+                    f.write_str("DETACH ")?;
+                }
+                write!(f, "DELETE {}", Separated(&delete.expressions, ", "))
+            }
+            UpdatingClause::Set(set) => write!(f, "{}", set),
+            UpdatingClause::Remove(remove) => write!(f, "REMOVE {}", Separated(&remove.items, ", ")),
+            UpdatingClause::Foreach(foreach) => write!(
+                f,
+                "FOREACH ({} IN {} | {})",
+                Name(&foreach.variable.name),
+                foreach.expression,
+                Separated(&foreach.clauses, " ")
+            ),
+            UpdatingClause::Filter(filter) => write!(f, "FILTER {}", filter.expression),
+            UpdatingClause::OrderSkipLimit(clause) => write!(f, "{}", clause),
+        }
+    }
+}
+
+impl Display for Set {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SET {}", Separated(&self.items, ", "))
+    }
+}
+
+fn write_node_labels(f: &mut Formatter<'_>, labels: &[LabelOrType]) -> fmt::Result {
+    for label in labels {
+        write!(f, ":{}", label)?;
+    }
+    Ok(())
+}
+
+impl Display for SetItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SetItem::AssignPropertyExpression { property_expression, expression } => {
+                write!(f, "{} = {}", property_expression, expression)
+            }
+            SetItem::AssignVariable { variable, expression } => write!(f, "{} = {}", Name(&variable.name), expression),
+            SetItem::IncrementVariable { variable, expression } => write!(f, "{} += {}", Name(&variable.name), expression),
+            SetItem::VariableNodeLabels { variable, node_labels } => {
+                write!(f, "{}", Name(&variable.name))?;
+                write_node_labels(f, node_labels)
+            }
+        }
+    }
+}
+
+impl Display for RemoveItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoveItem::VariableNodeLabels { variable, node_labels } => {
+                write!(f, "{}", Name(&variable.name))?;
+                write_node_labels(f, node_labels)
+            }
+            RemoveItem::PropertyExpression(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+impl Display for PropertyExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.atom)?;
+        for key in &self.property_path {
+            match key {
+                PropertyKey::Static(key) => write!(f, ".{}", key)?,
+                PropertyKey::Dynamic(expression) => write!(f, "[{}]", expression)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for SchemaCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaCommand::CreateIndex(index) => write!(f, "{}", index),
+            SchemaCommand::DropIndex(index) => {
+                write!(f, "DROP INDEX {}", index.name)?;
+                if index.if_exists {
+                    f.write_str(" IF EXISTS")?;
+                }
+                Ok(())
+            }
+            SchemaCommand::CreateConstraint(constraint) => write!(f, "{}", constraint),
+            SchemaCommand::DropConstraint(constraint) => {
+                write!(f, "DROP CONSTRAINT {}", constraint.name)?;
+                if constraint.if_exists {
+                    f.write_str(" IF EXISTS")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_schema_header(f: &mut Formatter<'_>, name: &Option<String>, if_not_exists: bool, target: &SchemaTarget) -> fmt::Result {
+    if let Some(name) = name {
+        write!(f, " {}", name)?;
+    }
+    if if_not_exists {
+        f.write_str(" IF NOT EXISTS")?;
+    }
+    write!(f, " FOR {}", target)
+}
+
+fn write_schema_options(f: &mut Formatter<'_>, options: &Option<std::collections::HashMap<String, Expression>>) -> fmt::Result {
+    if let Some(options) = options {
+        f.write_str(" OPTIONS ")?;
+        write_map(f, options)?;
+    }
+    Ok(())
+}
+
+impl Display for CreateIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("CREATE ")?;
+        match self.kind {
+            IndexKind::Range => (),
+            IndexKind::Text => f.write_str("TEXT ")?,
+            IndexKind::Point => f.write_str("POINT ")?,
+            IndexKind::Fulltext => f.write_str("FULLTEXT ")?,
+            IndexKind::Vector => f.write_str("VECTOR ")?,
+        }
+        f.write_str("INDEX")?;
+        write_schema_header(f, &self.name, self.if_not_exists, &self.target)?;
+        if self.kind == IndexKind::Fulltext {
+            write!(f, " ON EACH [{}]", Separated(&self.properties, ", "))?;
+        }
+        else {
+            write!(f, " ON ({})", Separated(&self.properties, ", "))?;
+        }
+        write_schema_options(f, &self.options)
+    }
+}
+
+impl Display for CreateConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("CREATE CONSTRAINT")?;
+        write_schema_header(f, &self.name, self.if_not_exists, &self.target)?;
+        if self.properties.len() == 1 {
+            write!(f, " REQUIRE {}", self.properties[0])?;
+        }
+        else {
+            write!(f, " REQUIRE ({})", Separated(&self.properties, ", "))?;
+        }
+        let entity = match self.entity {
+            Some(ConstraintEntity::Node) => "NODE ",
+            Some(ConstraintEntity::Relationship) => "RELATIONSHIP ",
+            None => "",
+        };
+        match &self.kind {
+            ConstraintKind::Unique => write!(f, " IS {}UNIQUE", entity)?,
+            ConstraintKind::Key => write!(f, " IS {}KEY", entity)?,
+            ConstraintKind::NotNull => f.write_str(" IS NOT NULL")?,
+            ConstraintKind::Type(type_) => write!(f, " IS :: {}", type_)?,
+        }
+        write_schema_options(f, &self.options)
+    }
+}
+
+impl Display for SchemaTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaTarget::Node { variable, labels } => {
+                write!(f, "({}:{})", Name(&variable.name), labels.join("|"))
+            }
+            SchemaTarget::Relationship { variable, relationship_types } => {
+                write!(f, "()-[{}:{}]-()", Name(&variable.name), relationship_types.join("|"))
+            }
+        }
+    }
+}
+
+impl Display for SchemaProperty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", Name(&self.variable.name), self.property_key)
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Separated(&self.parts, ", "))
+    }
+}
+
+impl Display for PatternPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(variable) = &self.variable {
+            write!(f, "{} = ", Name(&variable.name))?;
+        }
+        write!(f, "{}", self.pattern_element.node_pattern)?;
+        for (relationship, node) in &self.pattern_element.relationship_patterns {
+            write!(f, "{}{}", relationship, node)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_pattern_contents(
+    f: &mut Formatter<'_>,
+    variable: Option<&super::Variable>,
+    label_expression: Option<&LabelExpression>,
+    range: Option<&RangeLiteral>,
+    properties: Option<&Properties>,
+) -> fmt::Result {
+    if let Some(variable) = variable {
+        write!(f, "{}", Name(&variable.name))?;
+    }
+    if let Some(label_expression) = label_expression {
+        write!(f, ":{}", label_expression)?;
+    }
+    if let Some(range) = range {
+        write!(f, "{}", range)?;
+    }
+    if let Some(properties) = properties {
+        if variable.is_some() || label_expression.is_some() || range.is_some() {
+            f.write_str(" ")?;
+        }
+        write!(f, "{}", properties)?;
+    }
+    Ok(())
+}
+
+impl Display for NodePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        write_pattern_contents(f, self.variable.as_ref(), self.label_expression.as_ref(), None, self.properties.as_ref())?;
+        f.write_str(")")
+    }
+}
+
+impl Display for RelationshipPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (left, detail, right) = match self {
+            RelationshipPattern::LeftAndRight(detail) => ("<-", detail, "->"),
+            RelationshipPattern::Left(detail) => ("<-", detail, "-"),
+            RelationshipPattern::Right(detail) => ("-", detail, "->"),
+            RelationshipPattern::Undirected(detail) => ("-", detail, "-"),
+        };
+        f.write_str(left)?;
+        if let Some(detail) = detail {
+            write!(f, "{}", detail)?;
+        }
+        f.write_str(right)
+    }
+}
+
+impl Display for RelationshipDetail {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        write_pattern_contents(
+            f,
+            self.variable.as_ref(),
+            self.label_expression.as_ref(),
+            self.range.as_ref(),
+            self.properties.as_ref(),
+        )?;
+        f.write_str("]")
+    }
+}
+
+impl Display for RangeLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("*")?;
+        match (self.min, self.max) {
+            (None, None) => Ok(()),
+            (Some(min), Some(max)) if min == max => write!(f, "{}", min),
+            (min, max) => {
+                if let Some(min) = min {
+                    write!(f, "{}", min)?;
+                }
+                f.write_str("..")?;
+                if let Some(max) = max {
+                    write!(f, "{}", max)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Properties {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Properties::Parameter(name) => f.write_str(name),
+            Properties::MapLiteral(map) => write_map(f, map),
+        }
+    }
+}
+
+impl Display for LabelExpression {
+    /// Writes the expression without the leading colon.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelExpression::Label(label) => write!(f, "{}", label),
+            LabelExpression::AnyOf(expression) => write!(f, "$any({})", expression),
+            LabelExpression::Wildcard => f.write_str("%"),
+            LabelExpression::Not(inner) => match **inner {
+                LabelExpression::And(_) | LabelExpression::Or(_) => write!(f, "!({})", inner),
+                _ => write!(f, "!{}", inner),
+            },
+            LabelExpression::And(inner) => {
+                for (index, operand) in inner.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str("&")?;
+                    }
+                    match operand {
+                        LabelExpression::Or(_) => write!(f, "({})", operand)?,
+                        _ => write!(f, "{}", operand)?,
+                    }
+                }
+                Ok(())
+            }
+            LabelExpression::Or(inner) => write!(f, "{}", Separated(inner, "|")),
+        }
+    }
+}
+
+impl Display for LabelOrType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelOrType::Static(name) => f.write_str(name),
+            LabelOrType::Dynamic(expression) => write!(f, "$({})", expression),
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, xor) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" OR ")?;
+            }
+            for (index, and) in xor.0.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(" XOR ")?;
+                }
+                for (index, not) in and.0.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" AND ")?;
+                    }
+                    if not.not {
+                        f.write_str("NOT ")?;
+                    }
+                    let comparison = &not.expression;
+                    write!(f, "{}", comparison.expression)?;
+                    for (operator, operand) in &comparison.comparisons {
+                        let operator = match operator {
+                            ComparisonOperator::Equal => "=",
+                            ComparisonOperator::NotEqual => "<>",
+                            ComparisonOperator::LessThan => "<",
+                            ComparisonOperator::GreaterThan => ">",
+                            ComparisonOperator::LessEqual => "<=",
+                            ComparisonOperator::GreaterEqual => ">=",
+                        };
+                        write!(f, " {} {}", operator, operand)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for super::AddOrSubtractExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)?;
+        for (operator, operand) in &self.operations {
+            let operator = match operator {
+                PlusMinusOperator::Plus => "+",
+                PlusMinusOperator::Minus => "-",
+                PlusMinusOperator::Concatenate => "||",
+            };
+            write!(f, " {} {}", operator, operand)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for super::MultiplyDivideModuloExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)?;
+        for (operator, operand) in &self.operations {
+            let operator = match operator {
+                MultiplyDivideModuloOperator::Multiply => "*",
+                MultiplyDivideModuloOperator::Divide => "/",
+                MultiplyDivideModuloOperator::Modulo => "%",
+            };
+            write!(f, " {} {}", operator, operand)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for super::PowerOfExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Separated(&self.0, " ^ "))
+    }
+}
+
+impl Display for super::UnaryAddOrSubtractExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.negate {
+            f.write_str("-")?;
+        }
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl Display for super::StringListNullOperatorExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)?;
+        for operation in &self.operations {
+            match operation {
+                StringListNullOperatorExpressionInner::StringOperator(operation) => {
+                    let operator = match operation.operator {
+                        StringOperator::StartsWith => "STARTS WITH",
+                        StringOperator::EndsWith => "ENDS WITH",
+                        StringOperator::Contains => "CONTAINS",
+                        StringOperator::RegularExpression => "=~",
+                    };
+                    write!(f, " {} {}", operator, operation.expression)?;
+                }
+                StringListNullOperatorExpressionInner::ListOperator(operation) => match operation {
+                    ListOperatorExpression::In(expression) => write!(f, " IN {}", expression)?,
+                    ListOperatorExpression::Index(expression) => write!(f, "[{}]", expression)?,
+                    ListOperatorExpression::Range(from, to) => {
+                        f.write_str("[")?;
+                        if let Some(from) = from {
+                            write!(f, "{}", from)?;
+                        }
+                        f.write_str("..")?;
+                        if let Some(to) = to {
+                            write!(f, "{}", to)?;
+                        }
+                        f.write_str("]")?;
+                    }
+                },
+                StringListNullOperatorExpressionInner::NullOperator(operation) => {
+                    f.write_str(if operation.not { " IS NOT NULL" } else { " IS NULL" })?;
+                }
+                StringListNullOperatorExpressionInner::NormalizedPredicate(operation) => {
+                    let form = match operation.normal_form {
+                        NormalForm::Nfc => "NFC",
+                        NormalForm::Nfd => "NFD",
+                        NormalForm::Nfkc => "NFKC",
+                        NormalForm::Nfkd => "NFKD",
+                    };
+                    let not = if operation.not { "NOT " } else { "" };
+                    write!(f, " IS {}{} NORMALIZED", not, form)?;
+                }
+                StringListNullOperatorExpressionInner::TypePredicate(operation) => {
+                    let not = if operation.not { "NOT " } else { "" };
+                    write!(f, " IS {}:: {}", not, operation.type_)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for PropertyOrLabelsExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.atom)?;
+        for key in &self.property_lookup {
+            write!(f, ".{}", key)?;
+        }
+        if let Some(label_expression) = &self.label_expression {
+            write!(f, ":{}", label_expression)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Atom {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Atom::Literal(literal) => write!(f, "{}", literal),
+            Atom::Parameter(name) => f.write_str(name),
+            Atom::CaseExpression(case) => write!(f, "{}", case),
+            Atom::CountStar(_) => f.write_str("count(*)"),
+            Atom::ListComprehension(comprehension) => {
+                write!(f, "[{}", comprehension.filter_expression)?;
+                if let Some(expression) = &comprehension.expression {
+                    write!(f, " | {}", expression)?;
+                }
+                f.write_str("]")
+            }
+            Atom::PatternComprehension(comprehension) => {
+                f.write_str("[")?;
+                if let Some(variable) = &comprehension.variable {
+                    write!(f, "{} = ", Name(&variable.name))?;
+                }
+                write!(f, "{}", comprehension.relationship_pattern)?;
+                if let Some(where_) = &comprehension.where_ {
+                    write!(f, " WHERE {}", where_)?;
+                }
+                write!(f, " | {}]", comprehension.expression)
+            }
+            Atom::All(filter) => write!(f, "all({})", filter),
+            Atom::Any(filter) => write!(f, "any({})", filter),
+            Atom::None(filter) => write!(f, "none({})", filter),
+            Atom::Single(filter) => write!(f, "single({})", filter),
+            Atom::Reduce(reduce) => write!(
+                f,
+                "reduce({} = {}, {} | {})",
+                Name(&reduce.accumulator.name),
+                reduce.initial_value,
+                reduce.id_in_coll,
+                reduce.expression
+            ),
+            Atom::RelationshipsPattern(pattern) => {
+                write!(f, "{}", pattern.node)?;
+                for (relationship, node) in &pattern.relationships {
+                    write!(f, "{}{}", relationship, node)?;
+                }
+                Ok(())
+            }
+            Atom::ParenthesizedExpression(expression) => write!(f, "({})", expression),
+            Atom::FunctionInvocation(function) => write!(f, "{}", function),
+            Atom::ExistentialSubquery(subquery) => match subquery {
+                ExistentialSubquery::RegularQuery(query) => write!(f, "EXISTS {{ {} }}", query),
+                ExistentialSubquery::PatternWhere { pattern, where_ } => {
+                    write!(f, "EXISTS {{ {}", pattern)?;
+                    if let Some(where_) = where_ {
+                        write!(f, " WHERE {}", where_)?;
+                    }
+                    f.write_str(" }")
+                }
+            },
+            Atom::MapProjection(projection) => write!(f, "{}", projection),
+            Atom::TemporalLiteral(temporal) => write!(f, "{}", temporal),
+            Atom::PointLiteral(point) => write!(f, "{}", point),
+            Atom::Variable(variable) => write!(f, "{}", Name(&variable.name)),
+        }
+    }
+}
+
+impl Display for CaseExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("CASE")?;
+        if let Some(expression) = &self.expression {
+            write!(f, " {}", expression)?;
+        }
+        for (when, then) in &self.alternatives {
+            write!(f, " WHEN {} THEN {}", when, then)?;
+        }
+        if let Some(else_) = &self.else_ {
+            write!(f, " ELSE {}", else_)?;
+        }
+        f.write_str(" END")
+    }
+}
+
+impl Display for IdInColl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} IN {}", Name(&self.variable.name), self.expression)
+    }
+}
+
+impl Display for FilterExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id_in_coll)?;
+        if let Some(where_) = &self.where_ {
+            write!(f, " WHERE {}", where_)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FunctionInvocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.function_name)?;
+        if self.distinct {
+            f.write_str("DISTINCT ")?;
+        }
+        write!(f, "{})", Separated(&self.arguments, ", "))
+    }
+}
+
+impl Display for MapProjection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{{}}}", Name(&self.variable.name), Separated(&self.items, ", "))
+    }
+}
+
+impl Display for MapProjectionItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MapProjectionItem::Property(key) => write!(f, ".{}", key),
+            MapProjectionItem::Literal { key, expression } => write!(f, "{}: {}", key, expression),
+            MapProjectionItem::Variable(variable) => write!(f, "{}", Name(&variable.name)),
+            MapProjectionItem::AllProperties => f.write_str(".*"),
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::String(text) => write_string(f, text),
+            Literal::Number(number) => write!(f, "{}", number),
+            Literal::Boolean(value) => write!(f, "{}", value),
+            Literal::Null => f.write_str("null"),
+            Literal::MapLiteral(map) => write_map(f, map),
+            Literal::ListLiteral(list) => write!(f, "[{}]", Separated(list, ", ")),
+        }
+    }
+}
+
+impl Display for NumberLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberLiteral::Integer(value) => write!(f, "{}", value),
+            // The debug notation keeps the decimal point of integral values and
+            // switches to an exponent for large and small ones, as in `1.5e20`.
+            NumberLiteral::Double(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl Display for TemporalLiteral {
+    /// Writes the constructor call with an ISO 8601 string that creates the value.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TemporalLiteral::Date(date) => write!(f, "date('{}')", date),
+            TemporalLiteral::LocalTime(time) => write!(f, "localtime('{}')", time),
+            TemporalLiteral::Time(time) => write!(f, "time('{}')", time),
+            TemporalLiteral::LocalDateTime(datetime) => write!(f, "localdatetime('{}')", datetime),
+            TemporalLiteral::DateTime(datetime) => write!(f, "datetime('{}')", datetime),
+            TemporalLiteral::Duration(duration) => write!(f, "duration('{}')", duration),
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if (0..=9999).contains(&self.year) {
+            write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+        }
+        else {
+            write!(f, "{:+05}-{:02}-{:02}", self.year, self.month, self.day)
+        }
+    }
+}
+
+impl Display for LocalTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+fn write_offset(f: &mut Formatter<'_>, offset: i32) -> fmt::Result {
+    if offset == 0 {
+        return f.write_str("Z");
+    }
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs();
+    write!(f, "{}{:02}:{:02}", sign, offset / 3600, offset / 60 % 60)?;
+    if !offset.is_multiple_of(60) {
+        write!(f, ":{:02}", offset % 60)?;
+    }
+    Ok(())
+}
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeZone::Offset(offset) => write_offset(f, *offset),
+            TimeZone::Named { name, offset } => {
+                if let Some(offset) = offset {
+                    write_offset(f, *offset)?;
+                }
+                write!(f, "[{}]", name)
+            }
+        }
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.time)?;
+        if let Some(timezone) = &self.timezone {
+            write!(f, "{}", timezone)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for LocalDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        if let Some(timezone) = &self.timezone {
+            write!(f, "{}", timezone)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("P")?;
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            write!(f, "{}Y", years)?;
+        }
+        if months != 0 {
+            write!(f, "{}M", months)?;
+        }
+        if self.days != 0 {
+            write!(f, "{}D", self.days)?;
+        }
+        let (hours, minutes, seconds) = (self.seconds / 3600, self.seconds / 60 % 60, self.seconds % 60);
+        if hours != 0 || minutes != 0 || seconds != 0 || self.nanoseconds != 0 || (self.months == 0 && self.days == 0) {
+            f.write_str("T")?;
+            if hours != 0 {
+                write!(f, "{}H", hours)?;
+            }
+            if minutes != 0 {
+                write!(f, "{}M", minutes)?;
+            }
+            if seconds != 0 || self.nanoseconds != 0 || (hours == 0 && minutes == 0) {
+                // Seconds and nanoseconds carry the same sign.
+                let sign = if seconds < 0 || self.nanoseconds < 0 { "-" } else { "" };
+                write!(f, "{}{}", sign, seconds.unsigned_abs())?;
+                if self.nanoseconds != 0 {
+                    let fraction = format!("{:09}", self.nanoseconds.unsigned_abs());
+                    write!(f, ".{}", fraction.trim_end_matches('0'))?;
+                }
+                f.write_str("S")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for PointLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let geographic = matches!(
+            self.crs,
+            super::CoordinateReferenceSystem::Wgs84 | super::CoordinateReferenceSystem::Wgs843D
+        );
+        let keys: &[&str] = if geographic { &["longitude", "latitude", "height"] } else { &["x", "y", "z"] };
+        f.write_str("point({")?;
+        for (key, coordinate) in keys.iter().zip(&self.coordinates) {
+            write!(f, "{}: {:?}, ", key, coordinate)?;
+        }
+        write!(f, "crs: '{}'}})", self.crs.name())
+    }
+}
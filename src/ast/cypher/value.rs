@@ -0,0 +1,558 @@
+//! The values expressions evaluate to, and how they compare.
+//!
+//! openCypher compares values in three ways. Equality (`=`) follows the
+//! three-valued logic: comparing with `null` gives `null`, and so does
+//! comparing lists or maps that only differ in `null`s. Comparability (`<`,
+//! `<=`, `>`, `>=`) is defined only between values of the same kind, e.g. two
+//! numbers, strings or dates, and gives `null` otherwise. The global sort
+//! order used by `ORDER BY` orders any two values, and values it orders
+//! equal are equivalent, which is what `DISTINCT` and grouping use: unlike
+//! equality, `null` is equivalent to `null` and `NaN` to `NaN`.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    Atom, CoordinateReferenceSystem, Date, DateTime, Duration, Expression, Literal, LocalDateTime, LocalTime, Name,
+    NumberLiteral, PointLiteral, TemporalLiteral, Time, TimeZone,
+};
+
+/// A node of the graph, with its labels and properties at the time it was
+/// read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: u64,
+    pub labels: Vec<String>,
+    pub properties: BTreeMap<String, Value>,
+}
+
+/// A relationship of the graph, with its properties at the time it was read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relationship {
+    pub id: u64,
+    pub type_: String,
+    pub start: u64,
+    pub end: u64,
+    pub properties: BTreeMap<String, Value>,
+}
+
+/// A path through the graph. It has one node more than relationships, and
+/// every relationship connects the nodes before and after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub nodes: Vec<Node>,
+    pub relationships: Vec<Relationship>,
+}
+
+/// An instant in time, with or without a date and a time zone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Temporal {
+    Date(Date),
+    LocalTime(LocalTime),
+    Time(Time),
+    LocalDateTime(LocalDateTime),
+    DateTime(DateTime),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+    Node(Node),
+    Relationship(Relationship),
+    Path(Path),
+    Temporal(Temporal),
+    Duration(Duration),
+    Point(PointLiteral),
+}
+
+impl Value {
+    /// Returns the value of a literal, or `None` if it is a list or map with
+    /// elements that are not literals themselves.
+    pub fn from_literal(literal: &Literal) -> Option<Value> {
+        match literal {
+            Literal::Null => Some(Value::Null),
+            Literal::Boolean(value) => Some(Value::Boolean(*value)),
+            Literal::Number(number) => Some(Value::from(number.clone())),
+            Literal::String(value) => Some(Value::String(value.clone())),
+            Literal::ListLiteral(expressions) => expressions.iter().map(Value::from_expression).collect::<Option<_>>().map(Value::List),
+            Literal::MapLiteral(map) => map
+                .iter()
+                .map(|(key, expression)| Some((unescape(key), Value::from_expression(expression)?)))
+                .collect::<Option<_>>()
+                .map(Value::Map),
+        }
+    }
+
+    /// Returns the value of an expression that is a literal, possibly negated.
+    fn from_expression(expression: &Expression) -> Option<Value> {
+        let (negate, atom) = expression.as_signed_atom()?;
+        let value = match atom {
+            Atom::Literal(literal) => Value::from_literal(literal)?,
+            Atom::TemporalLiteral(literal) => Value::from(literal.clone()),
+            Atom::PointLiteral(point) => Value::Point(point.clone()),
+            _ => return None,
+        };
+        match (negate, value) {
+            (false, value) => Some(value),
+            (true, Value::Integer(value)) => value.checked_neg().map(Value::Integer),
+            (true, Value::Float(value)) => Some(Value::Float(-value)),
+            (true, _) => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the name of the type of the value as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "NULL",
+            Value::Boolean(_) => "BOOLEAN",
+            Value::Integer(_) => "INTEGER",
+            Value::Float(_) => "FLOAT",
+            Value::String(_) => "STRING",
+            Value::List(_) => "LIST",
+            Value::Map(_) => "MAP",
+            Value::Node(_) => "NODE",
+            Value::Relationship(_) => "RELATIONSHIP",
+            Value::Path(_) => "PATH",
+            Value::Temporal(Temporal::Date(_)) => "DATE",
+            Value::Temporal(Temporal::LocalTime(_)) => "LOCAL TIME",
+            Value::Temporal(Temporal::Time(_)) => "ZONED TIME",
+            Value::Temporal(Temporal::LocalDateTime(_)) => "LOCAL DATETIME",
+            Value::Temporal(Temporal::DateTime(_)) => "ZONED DATETIME",
+            Value::Duration(_) => "DURATION",
+            Value::Point(_) => "POINT",
+        }
+    }
+
+    /// Compares the values with `=`: `None` if the result is `null`, i.e. if
+    /// a `null` is compared with anything. Values of different types are
+    /// not equal, except integers and floats, which are compared by value.
+    pub fn equals(&self, other: &Value) -> Option<bool> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (Value::List(a), Value::List(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                all_equal(a.iter().zip(b))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                if a.len() != b.len() || a.keys().ne(b.keys()) {
+                    return Some(false);
+                }
+                all_equal(a.values().zip(b.values()))
+            }
+            (Value::Node(a), Value::Node(b)) => Some(a.id == b.id),
+            (Value::Relationship(a), Value::Relationship(b)) => Some(a.id == b.id),
+            (Value::Path(a), Value::Path(b)) => Some(
+                a.nodes.iter().map(|node| node.id).eq(b.nodes.iter().map(|node| node.id))
+                    && a.relationships.iter().map(|relationship| relationship.id).eq(b.relationships.iter().map(|relationship| relationship.id)),
+            ),
+            (Value::Temporal(a), Value::Temporal(b)) => Some(a.compare(b) == Some(Ordering::Equal)),
+            (Value::Duration(a), Value::Duration(b)) => Some(a == b),
+            (Value::Point(a), Value::Point(b)) => Some(a == b),
+            (Value::String(a), Value::String(b)) => Some(a == b),
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a == b),
+            _ => match (self.number(), other.number()) {
+                (Some(a), Some(b)) => Some(a.compare(b) == Some(Ordering::Equal)),
+                _ => Some(false),
+            },
+        }
+    }
+
+    /// Compares the values with `<`, `<=`, `>` and `>=`: `None` if the
+    /// result is `null`, i.e. if the values are not comparable. Only numbers,
+    /// strings, booleans, temporal values of the same kind and lists of
+    /// comparable values are; lists are compared element by element.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Temporal(a), Value::Temporal(b)) => a.compare(b),
+            (Value::List(a), Value::List(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    match a.compare(b)? {
+                        Ordering::Equal => (),
+                        ordering => return Some(ordering),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            _ => self.number()?.compare(other.number()?),
+        }
+    }
+
+    /// Orders the values the way `ORDER BY` does. Values of different types
+    /// are ordered maps, nodes, relationships, lists, paths, zoned date
+    /// times, local date times, dates, zoned times, local times, durations,
+    /// points, strings, booleans, numbers and finally `null`. Values of the
+    /// same type are ordered by value, lists element by element, durations
+    /// by their months, days, seconds and nanoseconds, and points by their
+    /// coordinate system and coordinates.
+    pub fn order(&self, other: &Value) -> Ordering {
+        let class = self.order_class().cmp(&other.order_class());
+        if class != Ordering::Equal {
+            return class;
+        }
+        match (self, other) {
+            (Value::Map(a), Value::Map(b)) => {
+                let a: Vec<_> = a.iter().collect();
+                let b: Vec<_> = b.iter().collect();
+                order_sequences(a.iter().map(|(key, _)| key), b.iter().map(|(key, _)| key), |a, b| a.cmp(b))
+                    .then_with(|| order_sequences(a.iter().map(|(_, value)| *value), b.iter().map(|(_, value)| *value), |a, b| a.order(b)))
+            }
+            (Value::Node(a), Value::Node(b)) => a.id.cmp(&b.id),
+            (Value::Relationship(a), Value::Relationship(b)) => a.id.cmp(&b.id),
+            (Value::List(a), Value::List(b)) => order_sequences(a.iter(), b.iter(), |a, b| a.order(b)),
+            (Value::Path(a), Value::Path(b)) => order_sequences(a.nodes.iter(), b.nodes.iter(), |a, b| a.id.cmp(&b.id))
+                .then_with(|| order_sequences(a.relationships.iter(), b.relationships.iter(), |a, b| a.id.cmp(&b.id))),
+            // The order classes keep the kinds of temporal values apart.
+            (Value::Temporal(a), Value::Temporal(b)) => a.compare(b).unwrap_or(Ordering::Equal),
+            (Value::Duration(a), Value::Duration(b)) => {
+                (a.months, a.days, a.seconds, a.nanoseconds).cmp(&(b.months, b.days, b.seconds, b.nanoseconds))
+            }
+            (Value::Point(a), Value::Point(b)) => crs_order(a.crs)
+                .cmp(&crs_order(b.crs))
+                .then_with(|| order_sequences(a.coordinates.iter(), b.coordinates.iter(), |a, b| a.total_cmp(b))),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Null, Value::Null) => Ordering::Equal,
+            _ => match (self.number(), other.number()) {
+                // NaN is ordered after all other numbers.
+                (Some(a), Some(b)) => match (a.is_nan(), b.is_nan()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => a.compare(b).unwrap_or(Ordering::Equal),
+                },
+                _ => Ordering::Equal,
+            },
+        }
+    }
+
+    /// Returns whether the values are the same for `DISTINCT` and grouping.
+    pub fn is_equivalent(&self, other: &Value) -> bool {
+        self.order(other) == Ordering::Equal
+    }
+
+    fn order_class(&self) -> u8 {
+        match self {
+            Value::Map(_) => 0,
+            Value::Node(_) => 1,
+            Value::Relationship(_) => 2,
+            Value::List(_) => 3,
+            Value::Path(_) => 4,
+            Value::Temporal(Temporal::DateTime(_)) => 5,
+            Value::Temporal(Temporal::LocalDateTime(_)) => 6,
+            Value::Temporal(Temporal::Date(_)) => 7,
+            Value::Temporal(Temporal::Time(_)) => 8,
+            Value::Temporal(Temporal::LocalTime(_)) => 9,
+            Value::Duration(_) => 10,
+            Value::Point(_) => 11,
+            Value::String(_) => 12,
+            Value::Boolean(_) => 13,
+            Value::Integer(_) | Value::Float(_) => 14,
+            Value::Null => 15,
+        }
+    }
+
+    fn number(&self) -> Option<Number> {
+        match self {
+            Value::Integer(value) => Some(Number::Integer(*value)),
+            Value::Float(value) => Some(Number::Float(*value)),
+            _ => None,
+        }
+    }
+}
+
+impl Temporal {
+    /// Compares temporal values of the same kind, or returns `None`. Values
+    /// with a time zone are compared by the instant they stand for, taking
+    /// a missing or unknown offset as UTC.
+    pub fn compare(&self, other: &Temporal) -> Option<Ordering> {
+        match (self, other) {
+            (Temporal::Date(a), Temporal::Date(b)) => Some(a.cmp(b)),
+            (Temporal::LocalTime(a), Temporal::LocalTime(b)) => Some(a.cmp(b)),
+            (Temporal::Time(a), Temporal::Time(b)) => {
+                let instant = |time: &Time| nanoseconds_of_day(&time.time) - offset(&time.timezone) * NANOSECONDS;
+                Some(instant(a).cmp(&instant(b)))
+            }
+            (Temporal::LocalDateTime(a), Temporal::LocalDateTime(b)) => Some(a.cmp(b)),
+            (Temporal::DateTime(a), Temporal::DateTime(b)) => {
+                let instant = |datetime: &DateTime| {
+                    days_since_epoch(&datetime.date) * 86_400 * NANOSECONDS + nanoseconds_of_day(&datetime.time)
+                        - offset(&datetime.timezone) * NANOSECONDS
+                };
+                Some(instant(a).cmp(&instant(b)))
+            }
+            _ => None,
+        }
+    }
+}
+
+const NANOSECONDS: i128 = 1_000_000_000;
+
+fn nanoseconds_of_day(time: &LocalTime) -> i128 {
+    (time.hour as i128 * 3600 + time.minute as i128 * 60 + time.second as i128) * NANOSECONDS + time.nanosecond as i128
+}
+
+fn offset(timezone: &Option<TimeZone>) -> i128 {
+    match timezone {
+        Some(TimeZone::Offset(offset) | TimeZone::Named { offset: Some(offset), .. }) => *offset as i128,
+        Some(TimeZone::Named { offset: None, .. }) | None => 0,
+    }
+}
+
+/// Counts the days from 1970-01-01 in the proleptic Gregorian calendar.
+fn days_since_epoch(date: &Date) -> i128 {
+    let year = date.year as i128 - (date.month <= 2) as i128;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = date.month as i128;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + date.day as i128 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn crs_order(crs: CoordinateReferenceSystem) -> u8 {
+    match crs {
+        CoordinateReferenceSystem::Cartesian => 0,
+        CoordinateReferenceSystem::Cartesian3D => 1,
+        CoordinateReferenceSystem::Wgs84 => 2,
+        CoordinateReferenceSystem::Wgs843D => 3,
+    }
+}
+
+fn unescape(key: &str) -> String {
+    match key.strip_prefix('`').and_then(|key| key.strip_suffix('`')) {
+        Some(key) => key.replace("``", "`"),
+        None => key.to_string(),
+    }
+}
+
+fn all_equal<'a>(pairs: impl Iterator<Item = (&'a Value, &'a Value)>) -> Option<bool> {
+    let mut result = Some(true);
+    for (a, b) in pairs {
+        match a.equals(b) {
+            Some(false) => return Some(false),
+            None => result = None,
+            Some(true) => (),
+        }
+    }
+    result
+}
+
+fn order_sequences<T>(a: impl Iterator<Item = T>, mut b: impl Iterator<Item = T>, order: impl Fn(&T, &T) -> Ordering) -> Ordering {
+    for a in a {
+        let Some(b) = b.next() else { return Ordering::Greater };
+        match order(&a, &b) {
+            Ordering::Equal => (),
+            ordering => return ordering,
+        }
+    }
+    if b.next().is_some() {
+        Ordering::Less
+    }
+    else {
+        Ordering::Equal
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn is_nan(self) -> bool {
+        matches!(self, Number::Float(value) if value.is_nan())
+    }
+
+    fn compare(self, other: Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(&b)),
+            (Number::Integer(a), Number::Float(b)) => (a as f64).partial_cmp(&b),
+            (Number::Float(a), Number::Integer(b)) => a.partial_cmp(&(b as f64)),
+            (Number::Float(a), Number::Float(b)) => a.partial_cmp(&b),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<NumberLiteral> for Value {
+    fn from(number: NumberLiteral) -> Self {
+        match number {
+            NumberLiteral::Integer(value) => Value::Integer(value),
+            NumberLiteral::Double(value) => Value::Float(value),
+        }
+    }
+}
+
+impl From<TemporalLiteral> for Value {
+    fn from(literal: TemporalLiteral) -> Self {
+        match literal {
+            TemporalLiteral::Date(date) => Value::Temporal(Temporal::Date(date)),
+            TemporalLiteral::LocalTime(time) => Value::Temporal(Temporal::LocalTime(time)),
+            TemporalLiteral::Time(time) => Value::Temporal(Temporal::Time(time)),
+            TemporalLiteral::LocalDateTime(datetime) => Value::Temporal(Temporal::LocalDateTime(datetime)),
+            TemporalLiteral::DateTime(datetime) => Value::Temporal(Temporal::DateTime(datetime)),
+            TemporalLiteral::Duration(duration) => Value::Duration(duration),
+        }
+    }
+}
+
+impl From<PointLiteral> for Value {
+    fn from(point: PointLiteral) -> Self {
+        Value::Point(point)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Value::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Writes the value as a Cypher literal. Nodes, relationships and paths are
+/// written as patterns, and temporal values and points as the constructor
+/// calls that create them.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Float(value) if value.is_finite() && value.fract() == 0.0 => write!(f, "{:.1}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+            Value::List(values) => {
+                f.write_str("[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(map) => write_map(f, map),
+            Value::Node(node) => write!(f, "{}", node),
+            Value::Relationship(relationship) => write!(f, "{}", relationship),
+            Value::Path(path) => write!(f, "{}", path),
+            Value::Temporal(temporal) => write!(f, "{}", temporal),
+            Value::Duration(duration) => write!(f, "duration('{}')", duration),
+            Value::Point(point) => write!(f, "{}", point),
+        }
+    }
+}
+
+impl Display for Temporal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Temporal::Date(date) => write!(f, "date('{}')", date),
+            Temporal::LocalTime(time) => write!(f, "localtime('{}')", time),
+            Temporal::Time(time) => write!(f, "time('{}')", time),
+            Temporal::LocalDateTime(datetime) => write!(f, "localdatetime('{}')", datetime),
+            Temporal::DateTime(datetime) => write!(f, "datetime('{}')", datetime),
+        }
+    }
+}
+
+fn write_map(f: &mut Formatter<'_>, map: &BTreeMap<String, Value>) -> fmt::Result {
+    f.write_str("{")?;
+    for (index, (key, value)) in map.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: {}", Name(key), value)?;
+    }
+    f.write_str("}")
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        for label in &self.labels {
+            write!(f, ":{}", Name(label))?;
+        }
+        if !self.properties.is_empty() {
+            if !self.labels.is_empty() {
+                f.write_str(" ")?;
+            }
+            write_map(f, &self.properties)?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Display for Relationship {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[:{}", Name(&self.type_))?;
+        if !self.properties.is_empty() {
+            f.write_str(" ")?;
+            write_map(f, &self.properties)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nodes[0])?;
+        for (relationship, node) in self.relationships.iter().zip(&self.nodes[1..]) {
+            if relationship.end == node.id {
+                write!(f, "-{}->{}", relationship, node)?;
+            }
+            else {
+                write!(f, "<-{}-{}", relationship, node)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,547 @@
+//! Read-only traversal of the Cypher AST.
+//!
+//! Every `visit_*` method of [`Visitor`] defaults to the matching `walk_*`
+//! function, which visits the children of the node. Implementations override
+//! the methods for the nodes they are interested in and call the `walk_*`
+//! function themselves if they still want to descend.
+
+use super::{
+    Atom, CaseExpression, ExistentialSubquery, Expression, FilterExpression, FunctionInvocation, GraphReference,
+    LabelExpression, LabelOrType, ListOperatorExpression, Literal, MapProjectionItem, MergeAction, NodePattern,
+    OrderSkipLimit, Pattern, PatternPart, ProcedureInvocation, ProjectionBody, ProjectionItem, Properties, PropertyExpression,
+    PropertyKey, PropertyOrLabelsExpression, Query, ReadingClause, RegularQuery, RelationshipPattern, RemoveItem,
+    SchemaCommand, Set, SetItem, SingleQuery, SortItem, StandaloneCall, StarOrYieldItems, Statement, StatementBody,
+    StringListNullOperatorExpressionInner, UpdatingClause, With, YieldItems,
+};
+
+pub trait Visitor<'ast> {
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_query(&mut self, query: &'ast Query) {
+        walk_query(self, query)
+    }
+
+    fn visit_regular_query(&mut self, query: &'ast RegularQuery) {
+        walk_regular_query(self, query)
+    }
+
+    fn visit_single_query(&mut self, query: &'ast SingleQuery) {
+        walk_single_query(self, query)
+    }
+
+    fn visit_standalone_call(&mut self, call: &'ast StandaloneCall) {
+        walk_standalone_call(self, call)
+    }
+
+    fn visit_schema_command(&mut self, command: &'ast SchemaCommand) {
+        walk_schema_command(self, command)
+    }
+
+    fn visit_reading_clause(&mut self, clause: &'ast ReadingClause) {
+        walk_reading_clause(self, clause)
+    }
+
+    fn visit_updating_clause(&mut self, clause: &'ast UpdatingClause) {
+        walk_updating_clause(self, clause)
+    }
+
+    fn visit_with(&mut self, with: &'ast With) {
+        walk_with(self, with)
+    }
+
+    fn visit_projection_body(&mut self, body: &'ast ProjectionBody) {
+        walk_projection_body(self, body)
+    }
+
+    fn visit_sort_item(&mut self, item: &'ast SortItem) {
+        walk_sort_item(self, item)
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_pattern_part(&mut self, part: &'ast PatternPart) {
+        walk_pattern_part(self, part)
+    }
+
+    fn visit_node_pattern(&mut self, node: &'ast NodePattern) {
+        walk_node_pattern(self, node)
+    }
+
+    fn visit_relationship_pattern(&mut self, relationship: &'ast RelationshipPattern) {
+        walk_relationship_pattern(self, relationship)
+    }
+
+    fn visit_label_expression(&mut self, label_expression: &'ast LabelExpression) {
+        walk_label_expression(self, label_expression)
+    }
+
+    fn visit_properties(&mut self, properties: &'ast Properties) {
+        walk_properties(self, properties)
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_property_or_labels_expression(&mut self, expression: &'ast PropertyOrLabelsExpression) {
+        walk_property_or_labels_expression(self, expression)
+    }
+
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        walk_atom(self, atom)
+    }
+
+    fn visit_function_invocation(&mut self, function: &'ast FunctionInvocation) {
+        walk_function_invocation(self, function)
+    }
+
+    fn visit_literal(&mut self, literal: &'ast Literal) {
+        walk_literal(self, literal)
+    }
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, statement: &'ast Statement) {
+    match &statement.body {
+        StatementBody::Query(query) => visitor.visit_query(query),
+        StatementBody::SchemaCommand(command) => visitor.visit_schema_command(command),
+    }
+}
+
+pub fn walk_query<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, query: &'ast Query) {
+    match query {
+        Query::RegularQuery(query) => visitor.visit_regular_query(query),
+        Query::StandaloneCall(call) => visitor.visit_standalone_call(call),
+    }
+}
+
+pub fn walk_regular_query<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, query: &'ast RegularQuery) {
+    if let Some(use_) = &query.use_ {
+        walk_graph_reference(visitor, &use_.graph);
+    }
+    visitor.visit_single_query(&query.query);
+    for union in &query.union {
+        if let Some(use_) = &union.use_ {
+            walk_graph_reference(visitor, &use_.graph);
+        }
+        visitor.visit_single_query(&union.query);
+    }
+}
+
+fn walk_graph_reference<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, graph: &'ast GraphReference) {
+    match graph {
+        GraphReference::Name(_) => (),
+        GraphReference::ByName(expression) | GraphReference::ByElementId(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_single_query<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, query: &'ast SingleQuery) {
+    let query = match query {
+        SingleQuery::SinglePartQuery(query) => query,
+        SingleQuery::MultiPartQuery(query) => {
+            for part in &query.parts {
+                for clause in &part.reading_clauses {
+                    visitor.visit_reading_clause(clause);
+                }
+                for clause in &part.updating_clauses {
+                    visitor.visit_updating_clause(clause);
+                }
+                visitor.visit_with(&part.with);
+            }
+            &query.query
+        }
+    };
+
+    for clause in &query.reading_clauses {
+        visitor.visit_reading_clause(clause);
+    }
+    for clause in &query.updating_clauses {
+        visitor.visit_updating_clause(clause);
+    }
+    if let Some(return_) = &query.return_ {
+        visitor.visit_projection_body(return_);
+    }
+}
+
+pub fn walk_standalone_call<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, call: &'ast StandaloneCall) {
+    if let ProcedureInvocation::Explicit(invocation) = &call.invocation {
+        for argument in &invocation.arguments {
+            visitor.visit_expression(argument);
+        }
+    }
+    if let Some(StarOrYieldItems::YieldItems(items)) = &call.yield_ {
+        walk_yield_items(visitor, items);
+    }
+}
+
+fn walk_yield_items<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, items: &'ast YieldItems) {
+    if let YieldItems::Items { where_: Some(where_), .. } = items {
+        visitor.visit_expression(where_);
+    }
+}
+
+pub fn walk_schema_command<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, command: &'ast SchemaCommand) {
+    let options = match command {
+        SchemaCommand::CreateIndex(index) => index.options.as_ref(),
+        SchemaCommand::CreateConstraint(constraint) => constraint.options.as_ref(),
+        SchemaCommand::DropIndex(_) | SchemaCommand::DropConstraint(_) => None,
+    };
+    for expression in options.into_iter().flat_map(|options| options.values()) {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_reading_clause<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, clause: &'ast ReadingClause) {
+    match clause {
+        ReadingClause::Match(match_) => {
+            visitor.visit_pattern(&match_.pattern);
+            if let Some(where_) = &match_.where_ {
+                visitor.visit_expression(where_);
+            }
+        }
+        ReadingClause::Unwind(unwind) => visitor.visit_expression(&unwind.expression),
+        ReadingClause::LoadCsv(load_csv) => visitor.visit_expression(&load_csv.source),
+        ReadingClause::CallSubquery(subquery) => visitor.visit_regular_query(&subquery.query),
+        ReadingClause::InQueryCall(call) => {
+            for argument in &call.invocation.arguments {
+                visitor.visit_expression(argument);
+            }
+            if let Some(items) = &call.yield_items {
+                walk_yield_items(visitor, items);
+            }
+        }
+        ReadingClause::Filter(filter) => visitor.visit_expression(&filter.expression),
+        ReadingClause::OrderSkipLimit(clause) => walk_order_skip_limit(visitor, clause),
+    }
+}
+
+pub fn walk_order_skip_limit<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, clause: &'ast OrderSkipLimit) {
+    for item in clause.order.iter().flat_map(|order| &order.items) {
+        visitor.visit_sort_item(item);
+    }
+    if let Some(skip) = &clause.skip {
+        visitor.visit_expression(&skip.expression);
+    }
+    if let Some(limit) = &clause.limit {
+        visitor.visit_expression(&limit.expression);
+    }
+}
+
+pub fn walk_updating_clause<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, clause: &'ast UpdatingClause) {
+    match clause {
+        UpdatingClause::Create(create) => visitor.visit_pattern(&create.pattern),
+        UpdatingClause::Merge(merge) => {
+            visitor.visit_pattern_part(&merge.pattern_part);
+            for action in &merge.actions {
+                match action {
+                    MergeAction::Match(set) | MergeAction::Create(set) => walk_set(visitor, set),
+                }
+            }
+        }
+        UpdatingClause::Delete(delete) => {
+            for expression in &delete.expressions {
+                visitor.visit_expression(expression);
+            }
+        }
+        UpdatingClause::Set(set) => walk_set(visitor, set),
+        UpdatingClause::Remove(remove) => {
+            for item in &remove.items {
+                match item {
+                    RemoveItem::VariableNodeLabels { node_labels, .. } => walk_labels(visitor, node_labels),
+                    RemoveItem::PropertyExpression(expression) => walk_property_expression(visitor, expression),
+                }
+            }
+        }
+        UpdatingClause::Foreach(foreach) => {
+            visitor.visit_expression(&foreach.expression);
+            for clause in &foreach.clauses {
+                visitor.visit_updating_clause(clause);
+            }
+        }
+        UpdatingClause::Filter(filter) => visitor.visit_expression(&filter.expression),
+        UpdatingClause::OrderSkipLimit(clause) => walk_order_skip_limit(visitor, clause),
+    }
+}
+
+fn walk_set<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, set: &'ast Set) {
+    for item in &set.items {
+        match item {
+            SetItem::AssignPropertyExpression { property_expression, expression } => {
+                walk_property_expression(visitor, property_expression);
+                visitor.visit_expression(expression);
+            }
+            SetItem::AssignVariable { expression, .. } | SetItem::IncrementVariable { expression, .. } => {
+                visitor.visit_expression(expression)
+            }
+            SetItem::VariableNodeLabels { node_labels, .. } => walk_labels(visitor, node_labels),
+        }
+    }
+}
+
+fn walk_labels<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, labels: &'ast [LabelOrType]) {
+    for label in labels {
+        match label {
+            LabelOrType::Static(_) => (),
+            LabelOrType::Dynamic(expression) => visitor.visit_expression(expression),
+        }
+    }
+}
+
+fn walk_property_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expression: &'ast PropertyExpression) {
+    visitor.visit_atom(&expression.atom);
+    for key in &expression.property_path {
+        if let PropertyKey::Dynamic(expression) = key {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_with<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, with: &'ast With) {
+    visitor.visit_projection_body(&with.projection_body);
+    if let Some(where_) = &with.where_ {
+        visitor.visit_expression(where_);
+    }
+}
+
+pub fn walk_projection_body<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, body: &'ast ProjectionBody) {
+    for item in &body.items.items {
+        match item {
+            ProjectionItem::Expression(expression) | ProjectionItem::AsVariable { expression, .. } => {
+                visitor.visit_expression(expression)
+            }
+        }
+    }
+    for item in body.order.iter().flat_map(|order| &order.items) {
+        visitor.visit_sort_item(item);
+    }
+    if let Some(skip) = &body.skip {
+        visitor.visit_expression(&skip.expression);
+    }
+    if let Some(limit) = &body.limit {
+        visitor.visit_expression(&limit.expression);
+    }
+}
+
+pub fn walk_sort_item<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, item: &'ast SortItem) {
+    visitor.visit_expression(&item.expression);
+}
+
+pub fn walk_pattern<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, pattern: &'ast Pattern) {
+    for part in &pattern.parts {
+        visitor.visit_pattern_part(part);
+    }
+}
+
+pub fn walk_pattern_part<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, part: &'ast PatternPart) {
+    visitor.visit_node_pattern(&part.pattern_element.node_pattern);
+    for (relationship, node) in &part.pattern_element.relationship_patterns {
+        visitor.visit_relationship_pattern(relationship);
+        visitor.visit_node_pattern(node);
+    }
+}
+
+pub fn walk_node_pattern<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: &'ast NodePattern) {
+    if let Some(label_expression) = &node.label_expression {
+        visitor.visit_label_expression(label_expression);
+    }
+    if let Some(properties) = &node.properties {
+        visitor.visit_properties(properties);
+    }
+}
+
+pub fn walk_relationship_pattern<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, relationship: &'ast RelationshipPattern) {
+    let (RelationshipPattern::LeftAndRight(detail)
+        | RelationshipPattern::Left(detail)
+        | RelationshipPattern::Right(detail)
+        | RelationshipPattern::Undirected(detail)) = relationship;
+
+    if let Some(detail) = detail {
+        if let Some(label_expression) = &detail.label_expression {
+            visitor.visit_label_expression(label_expression);
+        }
+        if let Some(properties) = &detail.properties {
+            visitor.visit_properties(properties);
+        }
+    }
+}
+
+pub fn walk_label_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, label_expression: &'ast LabelExpression) {
+    match label_expression {
+        LabelExpression::Label(label) => walk_labels(visitor, std::slice::from_ref(label)),
+        LabelExpression::AnyOf(expression) => visitor.visit_expression(expression),
+        LabelExpression::Wildcard => (),
+        LabelExpression::Not(inner) => visitor.visit_label_expression(inner),
+        LabelExpression::And(inner) | LabelExpression::Or(inner) => {
+            for label_expression in inner {
+                visitor.visit_label_expression(label_expression);
+            }
+        }
+    }
+}
+
+pub fn walk_properties<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, properties: &'ast Properties) {
+    if let Properties::MapLiteral(map) = properties {
+        for expression in map.values() {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expression: &'ast Expression) {
+    for xor in &expression.0 {
+        for and in &xor.0 {
+            for not in &and.0 {
+                let comparison = &not.expression;
+                let operands = std::iter::once(&comparison.expression)
+                    .chain(comparison.comparisons.iter().map(|(_, operand)| operand));
+                for add_or_subtract in operands {
+                    let operands = std::iter::once(&add_or_subtract.expression)
+                        .chain(add_or_subtract.operations.iter().map(|(_, operand)| operand));
+                    for multiply_divide_modulo in operands {
+                        let operands = std::iter::once(&multiply_divide_modulo.expression)
+                            .chain(multiply_divide_modulo.operations.iter().map(|(_, operand)| operand));
+                        for unary in operands.flat_map(|power_of| &power_of.0) {
+                            visitor.visit_property_or_labels_expression(&unary.expression.expression);
+                            for operation in &unary.expression.operations {
+                                walk_string_list_null_operation(visitor, operation);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn walk_string_list_null_operation<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    operation: &'ast StringListNullOperatorExpressionInner,
+) {
+    match operation {
+        StringListNullOperatorExpressionInner::StringOperator(operation) => {
+            visitor.visit_property_or_labels_expression(&operation.expression)
+        }
+        StringListNullOperatorExpressionInner::ListOperator(operation) => match operation {
+            ListOperatorExpression::In(expression) => visitor.visit_property_or_labels_expression(expression),
+            ListOperatorExpression::Index(expression) => visitor.visit_expression(expression),
+            ListOperatorExpression::Range(from, to) => {
+                for expression in from.iter().chain(to) {
+                    visitor.visit_expression(expression);
+                }
+            }
+        },
+        StringListNullOperatorExpressionInner::NullOperator(_)
+        | StringListNullOperatorExpressionInner::NormalizedPredicate(_)
+        | StringListNullOperatorExpressionInner::TypePredicate(_) => (),
+    }
+}
+
+pub fn walk_property_or_labels_expression<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    expression: &'ast PropertyOrLabelsExpression,
+) {
+    visitor.visit_atom(&expression.atom);
+    if let Some(label_expression) = &expression.label_expression {
+        visitor.visit_label_expression(label_expression);
+    }
+}
+
+pub fn walk_atom<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, atom: &'ast Atom) {
+    match atom {
+        Atom::Literal(literal) => visitor.visit_literal(literal),
+        Atom::Parameter(_) | Atom::CountStar(_) | Atom::Variable(_) => (),
+        Atom::TemporalLiteral(_) | Atom::PointLiteral(_) => (),
+        Atom::CaseExpression(case) => walk_case_expression(visitor, case),
+        Atom::ListComprehension(comprehension) => {
+            walk_filter_expression(visitor, &comprehension.filter_expression);
+            if let Some(expression) = &comprehension.expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        Atom::PatternComprehension(comprehension) => {
+            visitor.visit_relationship_pattern(&comprehension.relationship_pattern);
+            if let Some(where_) = &comprehension.where_ {
+                visitor.visit_expression(where_);
+            }
+            visitor.visit_expression(&comprehension.expression);
+        }
+        Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => {
+            walk_filter_expression(visitor, filter)
+        }
+        Atom::Reduce(reduce) => {
+            visitor.visit_expression(&reduce.initial_value);
+            visitor.visit_expression(&reduce.id_in_coll.expression);
+            visitor.visit_expression(&reduce.expression);
+        }
+        Atom::RelationshipsPattern(pattern) => {
+            visitor.visit_node_pattern(&pattern.node);
+            for (relationship, node) in &pattern.relationships {
+                visitor.visit_relationship_pattern(relationship);
+                visitor.visit_node_pattern(node);
+            }
+        }
+        Atom::ParenthesizedExpression(expression) => visitor.visit_expression(expression),
+        Atom::FunctionInvocation(function) => visitor.visit_function_invocation(function),
+        Atom::ExistentialSubquery(subquery) => match subquery {
+            ExistentialSubquery::RegularQuery(query) => visitor.visit_regular_query(query),
+            ExistentialSubquery::PatternWhere { pattern, where_ } => {
+                visitor.visit_pattern(pattern);
+                if let Some(where_) = where_ {
+                    visitor.visit_expression(where_);
+                }
+            }
+        },
+        Atom::MapProjection(projection) => {
+            for item in &projection.items {
+                if let MapProjectionItem::Literal { expression, .. } = item {
+                    visitor.visit_expression(expression);
+                }
+            }
+        }
+    }
+}
+
+fn walk_case_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, case: &'ast CaseExpression) {
+    if let Some(expression) = &case.expression {
+        visitor.visit_expression(expression);
+    }
+    for (when, then) in &case.alternatives {
+        visitor.visit_expression(when);
+        visitor.visit_expression(then);
+    }
+    if let Some(else_) = &case.else_ {
+        visitor.visit_expression(else_);
+    }
+}
+
+fn walk_filter_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, filter: &'ast FilterExpression) {
+    visitor.visit_expression(&filter.id_in_coll.expression);
+    if let Some(where_) = &filter.where_ {
+        visitor.visit_expression(where_);
+    }
+}
+
+pub fn walk_function_invocation<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, function: &'ast FunctionInvocation) {
+    for argument in &function.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_literal<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, literal: &'ast Literal) {
+    match literal {
+        Literal::MapLiteral(map) => {
+            for expression in map.values() {
+                visitor.visit_expression(expression);
+            }
+        }
+        Literal::ListLiteral(list) => {
+            for expression in list {
+                visitor.visit_expression(expression);
+            }
+        }
+        Literal::String(_) | Literal::Number(_) | Literal::Boolean(_) | Literal::Null => (),
+    }
+}
@@ -0,0 +1,548 @@
+//! Mutable traversal of the Cypher AST, mirroring [`super::visit`].
+//!
+//! Every `visit_*` method of [`VisitorMut`] defaults to the matching `walk_*`
+//! function, which visits the children of the node. Implementations override
+//! the methods for the nodes they are interested in and call the `walk_*`
+//! function themselves if they still want to descend. Nodes may be replaced
+//! in place, which is how rewriting passes are implemented.
+
+use super::{
+    Atom, CaseExpression, ExistentialSubquery, Expression, FilterExpression, FunctionInvocation, GraphReference,
+    LabelExpression, LabelOrType, ListOperatorExpression, Literal, MapProjectionItem, MergeAction, NodePattern,
+    OrderSkipLimit, Pattern, PatternPart, ProcedureInvocation, ProjectionBody, ProjectionItem, Properties, PropertyExpression,
+    PropertyKey, PropertyOrLabelsExpression, Query, ReadingClause, RegularQuery, RelationshipPattern, RemoveItem,
+    SchemaCommand, Set, SetItem, SingleQuery, SortItem, StandaloneCall, StarOrYieldItems, Statement, StatementBody,
+    StringListNullOperatorExpressionInner, UpdatingClause, With, YieldItems,
+};
+
+pub trait VisitorMut {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_query(&mut self, query: &mut Query) {
+        walk_query(self, query)
+    }
+
+    fn visit_regular_query(&mut self, query: &mut RegularQuery) {
+        walk_regular_query(self, query)
+    }
+
+    fn visit_single_query(&mut self, query: &mut SingleQuery) {
+        walk_single_query(self, query)
+    }
+
+    fn visit_standalone_call(&mut self, call: &mut StandaloneCall) {
+        walk_standalone_call(self, call)
+    }
+
+    fn visit_schema_command(&mut self, command: &mut SchemaCommand) {
+        walk_schema_command(self, command)
+    }
+
+    fn visit_reading_clause(&mut self, clause: &mut ReadingClause) {
+        walk_reading_clause(self, clause)
+    }
+
+    fn visit_updating_clause(&mut self, clause: &mut UpdatingClause) {
+        walk_updating_clause(self, clause)
+    }
+
+    fn visit_with(&mut self, with: &mut With) {
+        walk_with(self, with)
+    }
+
+    fn visit_projection_body(&mut self, body: &mut ProjectionBody) {
+        walk_projection_body(self, body)
+    }
+
+    fn visit_sort_item(&mut self, item: &mut SortItem) {
+        walk_sort_item(self, item)
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_pattern_part(&mut self, part: &mut PatternPart) {
+        walk_pattern_part(self, part)
+    }
+
+    fn visit_node_pattern(&mut self, node: &mut NodePattern) {
+        walk_node_pattern(self, node)
+    }
+
+    fn visit_relationship_pattern(&mut self, relationship: &mut RelationshipPattern) {
+        walk_relationship_pattern(self, relationship)
+    }
+
+    fn visit_label_expression(&mut self, label_expression: &mut LabelExpression) {
+        walk_label_expression(self, label_expression)
+    }
+
+    fn visit_properties(&mut self, properties: &mut Properties) {
+        walk_properties(self, properties)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_property_or_labels_expression(&mut self, expression: &mut PropertyOrLabelsExpression) {
+        walk_property_or_labels_expression(self, expression)
+    }
+
+    fn visit_atom(&mut self, atom: &mut Atom) {
+        walk_atom(self, atom)
+    }
+
+    fn visit_function_invocation(&mut self, function: &mut FunctionInvocation) {
+        walk_function_invocation(self, function)
+    }
+
+    fn visit_literal(&mut self, literal: &mut Literal) {
+        walk_literal(self, literal)
+    }
+}
+
+pub fn walk_statement<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match &mut statement.body {
+        StatementBody::Query(query) => visitor.visit_query(query),
+        StatementBody::SchemaCommand(command) => visitor.visit_schema_command(command),
+    }
+}
+
+pub fn walk_query<V: VisitorMut + ?Sized>(visitor: &mut V, query: &mut Query) {
+    match query {
+        Query::RegularQuery(query) => visitor.visit_regular_query(query),
+        Query::StandaloneCall(call) => visitor.visit_standalone_call(call),
+    }
+}
+
+pub fn walk_regular_query<V: VisitorMut + ?Sized>(visitor: &mut V, query: &mut RegularQuery) {
+    if let Some(use_) = &mut query.use_ {
+        walk_graph_reference(visitor, &mut use_.graph);
+    }
+    visitor.visit_single_query(&mut query.query);
+    for union in &mut query.union {
+        if let Some(use_) = &mut union.use_ {
+            walk_graph_reference(visitor, &mut use_.graph);
+        }
+        visitor.visit_single_query(&mut union.query);
+    }
+}
+
+fn walk_graph_reference<V: VisitorMut + ?Sized>(visitor: &mut V, graph: &mut GraphReference) {
+    match graph {
+        GraphReference::Name(_) => (),
+        GraphReference::ByName(expression) | GraphReference::ByElementId(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_single_query<V: VisitorMut + ?Sized>(visitor: &mut V, query: &mut SingleQuery) {
+    let query = match query {
+        SingleQuery::SinglePartQuery(query) => query,
+        SingleQuery::MultiPartQuery(query) => {
+            for part in &mut query.parts {
+                for clause in &mut part.reading_clauses {
+                    visitor.visit_reading_clause(clause);
+                }
+                for clause in &mut part.updating_clauses {
+                    visitor.visit_updating_clause(clause);
+                }
+                visitor.visit_with(&mut part.with);
+            }
+            &mut query.query
+        }
+    };
+
+    for clause in &mut query.reading_clauses {
+        visitor.visit_reading_clause(clause);
+    }
+    for clause in &mut query.updating_clauses {
+        visitor.visit_updating_clause(clause);
+    }
+    if let Some(return_) = &mut query.return_ {
+        visitor.visit_projection_body(return_);
+    }
+}
+
+pub fn walk_standalone_call<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut StandaloneCall) {
+    if let ProcedureInvocation::Explicit(invocation) = &mut call.invocation {
+        for argument in &mut invocation.arguments {
+            visitor.visit_expression(argument);
+        }
+    }
+    if let Some(StarOrYieldItems::YieldItems(items)) = &mut call.yield_ {
+        walk_yield_items(visitor, items);
+    }
+}
+
+fn walk_yield_items<V: VisitorMut + ?Sized>(visitor: &mut V, items: &mut YieldItems) {
+    if let YieldItems::Items { where_: Some(where_), .. } = items {
+        visitor.visit_expression(where_);
+    }
+}
+
+pub fn walk_schema_command<V: VisitorMut + ?Sized>(visitor: &mut V, command: &mut SchemaCommand) {
+    let options = match command {
+        SchemaCommand::CreateIndex(index) => index.options.as_mut(),
+        SchemaCommand::CreateConstraint(constraint) => constraint.options.as_mut(),
+        SchemaCommand::DropIndex(_) | SchemaCommand::DropConstraint(_) => None,
+    };
+    for expression in options.into_iter().flat_map(|options| options.values_mut()) {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_reading_clause<V: VisitorMut + ?Sized>(visitor: &mut V, clause: &mut ReadingClause) {
+    match clause {
+        ReadingClause::Match(match_) => {
+            visitor.visit_pattern(&mut match_.pattern);
+            if let Some(where_) = &mut match_.where_ {
+                visitor.visit_expression(where_);
+            }
+        }
+        ReadingClause::Unwind(unwind) => visitor.visit_expression(&mut unwind.expression),
+        ReadingClause::LoadCsv(load_csv) => visitor.visit_expression(&mut load_csv.source),
+        ReadingClause::CallSubquery(subquery) => visitor.visit_regular_query(&mut subquery.query),
+        ReadingClause::InQueryCall(call) => {
+            for argument in &mut call.invocation.arguments {
+                visitor.visit_expression(argument);
+            }
+            if let Some(items) = &mut call.yield_items {
+                walk_yield_items(visitor, items);
+            }
+        }
+        ReadingClause::Filter(filter) => visitor.visit_expression(&mut filter.expression),
+        ReadingClause::OrderSkipLimit(clause) => walk_order_skip_limit(visitor, clause),
+    }
+}
+
+pub fn walk_order_skip_limit<V: VisitorMut + ?Sized>(visitor: &mut V, clause: &mut OrderSkipLimit) {
+    for item in clause.order.iter_mut().flat_map(|order| &mut order.items) {
+        visitor.visit_sort_item(item);
+    }
+    if let Some(skip) = &mut clause.skip {
+        visitor.visit_expression(&mut skip.expression);
+    }
+    if let Some(limit) = &mut clause.limit {
+        visitor.visit_expression(&mut limit.expression);
+    }
+}
+
+pub fn walk_updating_clause<V: VisitorMut + ?Sized>(visitor: &mut V, clause: &mut UpdatingClause) {
+    match clause {
+        UpdatingClause::Create(create) => visitor.visit_pattern(&mut create.pattern),
+        UpdatingClause::Merge(merge) => {
+            visitor.visit_pattern_part(&mut merge.pattern_part);
+            for action in &mut merge.actions {
+                match action {
+                    MergeAction::Match(set) | MergeAction::Create(set) => walk_set(visitor, set),
+                }
+            }
+        }
+        UpdatingClause::Delete(delete) => {
+            for expression in &mut delete.expressions {
+                visitor.visit_expression(expression);
+            }
+        }
+        UpdatingClause::Set(set) => walk_set(visitor, set),
+        UpdatingClause::Remove(remove) => {
+            for item in &mut remove.items {
+                match item {
+                    RemoveItem::VariableNodeLabels { node_labels, .. } => walk_labels(visitor, node_labels),
+                    RemoveItem::PropertyExpression(expression) => walk_property_expression(visitor, expression),
+                }
+            }
+        }
+        UpdatingClause::Foreach(foreach) => {
+            visitor.visit_expression(&mut foreach.expression);
+            for clause in &mut foreach.clauses {
+                visitor.visit_updating_clause(clause);
+            }
+        }
+        UpdatingClause::Filter(filter) => visitor.visit_expression(&mut filter.expression),
+        UpdatingClause::OrderSkipLimit(clause) => walk_order_skip_limit(visitor, clause),
+    }
+}
+
+fn walk_set<V: VisitorMut + ?Sized>(visitor: &mut V, set: &mut Set) {
+    for item in &mut set.items {
+        match item {
+            SetItem::AssignPropertyExpression { property_expression, expression } => {
+                walk_property_expression(visitor, property_expression);
+                visitor.visit_expression(expression);
+            }
+            SetItem::AssignVariable { expression, .. } | SetItem::IncrementVariable { expression, .. } => {
+                visitor.visit_expression(expression)
+            }
+            SetItem::VariableNodeLabels { node_labels, .. } => walk_labels(visitor, node_labels),
+        }
+    }
+}
+
+fn walk_labels<V: VisitorMut + ?Sized>(visitor: &mut V, labels: &mut [LabelOrType]) {
+    for label in labels {
+        match label {
+            LabelOrType::Static(_) => (),
+            LabelOrType::Dynamic(expression) => visitor.visit_expression(expression),
+        }
+    }
+}
+
+fn walk_property_expression<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut PropertyExpression) {
+    visitor.visit_atom(&mut expression.atom);
+    for key in &mut expression.property_path {
+        if let PropertyKey::Dynamic(expression) = key {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_with<V: VisitorMut + ?Sized>(visitor: &mut V, with: &mut With) {
+    visitor.visit_projection_body(&mut with.projection_body);
+    if let Some(where_) = &mut with.where_ {
+        visitor.visit_expression(where_);
+    }
+}
+
+pub fn walk_projection_body<V: VisitorMut + ?Sized>(visitor: &mut V, body: &mut ProjectionBody) {
+    for item in &mut body.items.items {
+        match item {
+            ProjectionItem::Expression(expression) | ProjectionItem::AsVariable { expression, .. } => {
+                visitor.visit_expression(expression)
+            }
+        }
+    }
+    for item in body.order.iter_mut().flat_map(|order| &mut order.items) {
+        visitor.visit_sort_item(item);
+    }
+    if let Some(skip) = &mut body.skip {
+        visitor.visit_expression(&mut skip.expression);
+    }
+    if let Some(limit) = &mut body.limit {
+        visitor.visit_expression(&mut limit.expression);
+    }
+}
+
+pub fn walk_sort_item<V: VisitorMut + ?Sized>(visitor: &mut V, item: &mut SortItem) {
+    visitor.visit_expression(&mut item.expression);
+}
+
+pub fn walk_pattern<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    for part in &mut pattern.parts {
+        visitor.visit_pattern_part(part);
+    }
+}
+
+pub fn walk_pattern_part<V: VisitorMut + ?Sized>(visitor: &mut V, part: &mut PatternPart) {
+    visitor.visit_node_pattern(&mut part.pattern_element.node_pattern);
+    for (relationship, node) in &mut part.pattern_element.relationship_patterns {
+        visitor.visit_relationship_pattern(relationship);
+        visitor.visit_node_pattern(node);
+    }
+}
+
+pub fn walk_node_pattern<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut NodePattern) {
+    if let Some(label_expression) = &mut node.label_expression {
+        visitor.visit_label_expression(label_expression);
+    }
+    if let Some(properties) = &mut node.properties {
+        visitor.visit_properties(properties);
+    }
+}
+
+pub fn walk_relationship_pattern<V: VisitorMut + ?Sized>(visitor: &mut V, relationship: &mut RelationshipPattern) {
+    let (RelationshipPattern::LeftAndRight(detail)
+        | RelationshipPattern::Left(detail)
+        | RelationshipPattern::Right(detail)
+        | RelationshipPattern::Undirected(detail)) = relationship;
+
+    if let Some(detail) = detail {
+        if let Some(label_expression) = &mut detail.label_expression {
+            visitor.visit_label_expression(label_expression);
+        }
+        if let Some(properties) = &mut detail.properties {
+            visitor.visit_properties(properties);
+        }
+    }
+}
+
+pub fn walk_label_expression<V: VisitorMut + ?Sized>(visitor: &mut V, label_expression: &mut LabelExpression) {
+    match label_expression {
+        LabelExpression::Label(label) => walk_labels(visitor, std::slice::from_mut(label)),
+        LabelExpression::AnyOf(expression) => visitor.visit_expression(expression),
+        LabelExpression::Wildcard => (),
+        LabelExpression::Not(inner) => visitor.visit_label_expression(inner),
+        LabelExpression::And(inner) | LabelExpression::Or(inner) => {
+            for label_expression in inner {
+                visitor.visit_label_expression(label_expression);
+            }
+        }
+    }
+}
+
+pub fn walk_properties<V: VisitorMut + ?Sized>(visitor: &mut V, properties: &mut Properties) {
+    if let Properties::MapLiteral(map) = properties {
+        for expression in map.values_mut() {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_expression<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    for xor in &mut expression.0 {
+        for and in &mut xor.0 {
+            for not in &mut and.0 {
+                let comparison = &mut not.expression;
+                let operands = std::iter::once(&mut comparison.expression)
+                    .chain(comparison.comparisons.iter_mut().map(|(_, operand)| operand));
+                for add_or_subtract in operands {
+                    let operands = std::iter::once(&mut add_or_subtract.expression)
+                        .chain(add_or_subtract.operations.iter_mut().map(|(_, operand)| operand));
+                    for multiply_divide_modulo in operands {
+                        let operands = std::iter::once(&mut multiply_divide_modulo.expression)
+                            .chain(multiply_divide_modulo.operations.iter_mut().map(|(_, operand)| operand));
+                        for unary in operands.flat_map(|power_of| &mut power_of.0) {
+                            visitor.visit_property_or_labels_expression(&mut unary.expression.expression);
+                            for operation in &mut unary.expression.operations {
+                                walk_string_list_null_operation(visitor, operation);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn walk_string_list_null_operation<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    operation: &mut StringListNullOperatorExpressionInner,
+) {
+    match operation {
+        StringListNullOperatorExpressionInner::StringOperator(operation) => {
+            visitor.visit_property_or_labels_expression(&mut operation.expression)
+        }
+        StringListNullOperatorExpressionInner::ListOperator(operation) => match operation {
+            ListOperatorExpression::In(expression) => visitor.visit_property_or_labels_expression(expression),
+            ListOperatorExpression::Index(expression) => visitor.visit_expression(expression),
+            ListOperatorExpression::Range(from, to) => {
+                for expression in from.iter_mut().chain(to) {
+                    visitor.visit_expression(expression);
+                }
+            }
+        },
+        StringListNullOperatorExpressionInner::NullOperator(_)
+        | StringListNullOperatorExpressionInner::NormalizedPredicate(_)
+        | StringListNullOperatorExpressionInner::TypePredicate(_) => (),
+    }
+}
+
+pub fn walk_property_or_labels_expression<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    expression: &mut PropertyOrLabelsExpression,
+) {
+    visitor.visit_atom(&mut expression.atom);
+    if let Some(label_expression) = &mut expression.label_expression {
+        visitor.visit_label_expression(label_expression);
+    }
+}
+
+pub fn walk_atom<V: VisitorMut + ?Sized>(visitor: &mut V, atom: &mut Atom) {
+    match atom {
+        Atom::Literal(literal) => visitor.visit_literal(literal),
+        Atom::Parameter(_) | Atom::CountStar(_) | Atom::Variable(_) => (),
+        Atom::TemporalLiteral(_) | Atom::PointLiteral(_) => (),
+        Atom::CaseExpression(case) => walk_case_expression(visitor, case),
+        Atom::ListComprehension(comprehension) => {
+            walk_filter_expression(visitor, &mut comprehension.filter_expression);
+            if let Some(expression) = &mut comprehension.expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        Atom::PatternComprehension(comprehension) => {
+            visitor.visit_relationship_pattern(&mut comprehension.relationship_pattern);
+            if let Some(where_) = &mut comprehension.where_ {
+                visitor.visit_expression(where_);
+            }
+            visitor.visit_expression(&mut comprehension.expression);
+        }
+        Atom::All(filter) | Atom::Any(filter) | Atom::None(filter) | Atom::Single(filter) => {
+            walk_filter_expression(visitor, filter)
+        }
+        Atom::Reduce(reduce) => {
+            visitor.visit_expression(&mut reduce.initial_value);
+            visitor.visit_expression(&mut reduce.id_in_coll.expression);
+            visitor.visit_expression(&mut reduce.expression);
+        }
+        Atom::RelationshipsPattern(pattern) => {
+            visitor.visit_node_pattern(&mut pattern.node);
+            for (relationship, node) in &mut pattern.relationships {
+                visitor.visit_relationship_pattern(relationship);
+                visitor.visit_node_pattern(node);
+            }
+        }
+        Atom::ParenthesizedExpression(expression) => visitor.visit_expression(expression),
+        Atom::FunctionInvocation(function) => visitor.visit_function_invocation(function),
+        Atom::ExistentialSubquery(subquery) => match subquery {
+            ExistentialSubquery::RegularQuery(query) => visitor.visit_regular_query(query),
+            ExistentialSubquery::PatternWhere { pattern, where_ } => {
+                visitor.visit_pattern(pattern);
+                if let Some(where_) = where_ {
+                    visitor.visit_expression(where_);
+                }
+            }
+        },
+        Atom::MapProjection(projection) => {
+            for item in &mut projection.items {
+                if let MapProjectionItem::Literal { expression, .. } = item {
+                    visitor.visit_expression(expression);
+                }
+            }
+        }
+    }
+}
+
+fn walk_case_expression<V: VisitorMut + ?Sized>(visitor: &mut V, case: &mut CaseExpression) {
+    if let Some(expression) = &mut case.expression {
+        visitor.visit_expression(expression);
+    }
+    for (when, then) in &mut case.alternatives {
+        visitor.visit_expression(when);
+        visitor.visit_expression(then);
+    }
+    if let Some(else_) = &mut case.else_ {
+        visitor.visit_expression(else_);
+    }
+}
+
+fn walk_filter_expression<V: VisitorMut + ?Sized>(visitor: &mut V, filter: &mut FilterExpression) {
+    visitor.visit_expression(&mut filter.id_in_coll.expression);
+    if let Some(where_) = &mut filter.where_ {
+        visitor.visit_expression(where_);
+    }
+}
+
+pub fn walk_function_invocation<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut FunctionInvocation) {
+    for argument in &mut function.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_literal<V: VisitorMut + ?Sized>(visitor: &mut V, literal: &mut Literal) {
+    match literal {
+        Literal::MapLiteral(map) => {
+            for expression in map.values_mut() {
+                visitor.visit_expression(expression);
+            }
+        }
+        Literal::ListLiteral(list) => {
+            for expression in list {
+                visitor.visit_expression(expression);
+            }
+        }
+        Literal::String(_) | Literal::Number(_) | Literal::Boolean(_) | Literal::Null => (),
+    }
+}
@@ -0,0 +1,107 @@
+//! An in-memory property graph and an interpreter for queries against it.
+//!
+//! The engine is meant as an embedded backend for tests and tools rather
+//! than as a database: the graph lives in memory, queries are planned with
+//! [`crate::plan`] and the plan is interpreted one operator at a time, every
+//! operator computing all of its rows before the next one starts. A query
+//! either succeeds as a whole or leaves the graph unchanged.
+//!
+//! ```
+//! use open_cypher::engine::{Graph, Value};
+//!
+//! let mut graph = Graph::new();
+//! graph.run("CREATE (:Person {name: 'Alice'})-[:KNOWS]->(:Person {name: 'Bob'})").unwrap();
+//! let result = graph.run("MATCH (a)-[:KNOWS]->(b) RETURN a.name, b.name").unwrap();
+//! assert_eq!(result.rows, vec![vec![Value::from("Alice"), Value::from("Bob")]]);
+//! ```
+//!
+//! Temporal values and points can be created with constructor calls whose
+//! arguments are literals, e.g. `date('2020-01-01')`, and compared and
+//! stored, but there is no arithmetic on them. `LOAD CSV`, `USE`,
+//! `CALL { ... }`, `FOREACH` and schema commands are not supported, and of
+//! the procedures, only `db.labels`, `db.relationshipTypes` and
+//! `db.propertyKeys` are. Functions take the arguments of their
+//! [`crate::analysis::functions::FunctionRegistry::builtin`] signature, but
+//! only a subset is implemented: none of the non-deterministic, temporal or
+//! spatial functions are. As lists are built in memory, `range()` fails
+//! rather than create more than a million elements.
+
+mod eval;
+mod exec;
+mod functions;
+mod graph;
+
+use std::collections::HashMap;
+use std::fmt;
+
+pub use graph::Graph;
+pub use crate::ast::cypher::value::{Node, Path, Relationship, Temporal, Value};
+
+use crate::analysis::literals::recognize_literals;
+use crate::ast::cypher::{Query, StatementBody};
+use crate::plan::PlanError;
+
+/// The columns and rows a query returns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl QueryResult {
+    /// Returns the values of a column.
+    pub fn column(&self, name: &str) -> Option<Vec<&Value>> {
+        let index = self.columns.iter().position(|column| column == name)?;
+        Some(self.rows.iter().map(|row| &row[index]).collect())
+    }
+}
+
+/// A query that failed to run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionError {
+    pub message: String,
+}
+
+impl ExecutionError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        ExecutionError { message: message.into() }
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+impl From<PlanError> for ExecutionError {
+    fn from(error: PlanError) -> Self {
+        ExecutionError { message: error.message }
+    }
+}
+
+impl Graph {
+    /// Runs the query with the parameters, given by their names without `$`.
+    pub fn execute(&mut self, query: &Query, parameters: &HashMap<String, Value>) -> Result<QueryResult, ExecutionError> {
+        let mut query = query.clone();
+        if let Some(error) = recognize_literals(&mut query).into_iter().next() {
+            return Err(ExecutionError::new(error.to_string()));
+        }
+        let plan = crate::plan::plan(&query)?;
+        let mut graph = self.clone();
+        let result = exec::Executor::new(&mut graph, parameters).execute(&plan)?;
+        *self = graph;
+        Ok(result)
+    }
+
+    /// Parses and runs a query without parameters.
+    pub fn run(&mut self, text: &str) -> Result<QueryResult, ExecutionError> {
+        let statement = crate::parse_cypher(text).map_err(|error| ExecutionError::new(error.to_string()))?;
+        match &statement.body {
+            StatementBody::Query(query) => self.execute(query, &HashMap::new()),
+            StatementBody::SchemaCommand(_) => Err(ExecutionError::new("schema commands are not supported")),
+        }
+    }
+}
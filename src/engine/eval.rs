@@ -0,0 +1,674 @@
+//! Evaluation of expressions for a row.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::exec::{Executor, Row};
+use super::ExecutionError;
+use crate::ast::cypher::value::{Node, Temporal, Value};
+use crate::ast::cypher::{
+    AddOrSubtractExpression, AndExpression, Atom, CaseExpression, ComparisonExpression, ComparisonOperator, CypherType,
+    ExistentialSubquery, Expression, FilterExpression, LabelExpression, LabelOrType, ListOperatorExpression, Literal,
+    MapProjection, MapProjectionItem, MultiplyDivideModuloExpression, MultiplyDivideModuloOperator, NotExpression,
+    NumberLiteral, Pattern, PatternElement, PatternPart, PlusMinusOperator, PowerOfExpression, PropertyOrLabelsExpression,
+    ReduceExpression, StringListNullOperatorExpression, StringListNullOperatorExpressionInner, StringOperator,
+    UnaryAddOrSubtractExpression, XorExpression,
+};
+use crate::plan::{plan_pattern, plan_subquery};
+
+type Result<T> = std::result::Result<T, ExecutionError>;
+
+/// Returns a label, relationship type or key without the backticks it was
+/// escaped with.
+pub(super) fn unescape(name: &str) -> String {
+    match name.strip_prefix('`').and_then(|name| name.strip_suffix('`')) {
+        Some(name) => name.replace("``", "`"),
+        None => name.to_string(),
+    }
+}
+
+/// Returns the truth value of a boolean or `null` operand of a logical operator.
+fn truth(value: Value, operator: &str) -> Result<Option<bool>> {
+    match value {
+        Value::Boolean(value) => Ok(Some(value)),
+        Value::Null => Ok(None),
+        value => Err(ExecutionError::new(format!("{} expects a BOOLEAN but got {}", operator, value.type_name()))),
+    }
+}
+
+fn type_error(operator: &str, a: &Value, b: &Value) -> ExecutionError {
+    ExecutionError::new(format!("cannot apply {} to {} and {}", operator, a.type_name(), b.type_name()))
+}
+
+fn overflow() -> ExecutionError {
+    ExecutionError::new("integer overflow")
+}
+
+impl Executor<'_> {
+    pub(super) fn evaluate(&mut self, expression: &Expression, row: &Row) -> Result<Value> {
+        if let [operand] = expression.0.as_slice() {
+            return self.xor(operand, row);
+        }
+        let mut result = Some(false);
+        for operand in &expression.0 {
+            match truth(self.xor(operand, row)?, "OR")? {
+                Some(true) => return Ok(Value::Boolean(true)),
+                Some(false) => (),
+                None => result = None,
+            }
+        }
+        Ok(result.into())
+    }
+
+    /// Evaluates a predicate, for which `null` counts as false.
+    pub(super) fn predicate(&mut self, expression: &Expression, row: &Row) -> Result<bool> {
+        Ok(truth(self.evaluate(expression, row)?, "a predicate")? == Some(true))
+    }
+
+    fn xor(&mut self, expression: &XorExpression, row: &Row) -> Result<Value> {
+        if let [operand] = expression.0.as_slice() {
+            return self.and(operand, row);
+        }
+        let mut result = Some(false);
+        for operand in &expression.0 {
+            let value = truth(self.and(operand, row)?, "XOR")?;
+            result = result.zip(value).map(|(a, b)| a ^ b);
+        }
+        Ok(result.into())
+    }
+
+    fn and(&mut self, expression: &AndExpression, row: &Row) -> Result<Value> {
+        if let [operand] = expression.0.as_slice() {
+            return self.not(operand, row);
+        }
+        let mut result = Some(true);
+        for operand in &expression.0 {
+            match truth(self.not(operand, row)?, "AND")? {
+                Some(false) => return Ok(Value::Boolean(false)),
+                Some(true) => (),
+                None => result = None,
+            }
+        }
+        Ok(result.into())
+    }
+
+    fn not(&mut self, expression: &NotExpression, row: &Row) -> Result<Value> {
+        let value = self.comparison(&expression.expression, row)?;
+        if expression.not {
+            Ok(truth(value, "NOT")?.map(|value| !value).into())
+        }
+        else {
+            Ok(value)
+        }
+    }
+
+    fn comparison(&mut self, expression: &ComparisonExpression, row: &Row) -> Result<Value> {
+        let mut left = self.add_or_subtract(&expression.expression, row)?;
+        if expression.comparisons.is_empty() {
+            return Ok(left);
+        }
+        // `a < b < c` means `a < b AND b < c`.
+        let mut result = Some(true);
+        for (operator, operand) in &expression.comparisons {
+            let right = self.add_or_subtract(operand, row)?;
+            let ordering = || left.compare(&right);
+            let value = match operator {
+                ComparisonOperator::Equal => left.equals(&right),
+                ComparisonOperator::NotEqual => left.equals(&right).map(|equal| !equal),
+                ComparisonOperator::LessThan => ordering().map(|ordering| ordering == Ordering::Less),
+                ComparisonOperator::GreaterThan => ordering().map(|ordering| ordering == Ordering::Greater),
+                ComparisonOperator::LessEqual => ordering().map(|ordering| ordering != Ordering::Greater),
+                ComparisonOperator::GreaterEqual => ordering().map(|ordering| ordering != Ordering::Less),
+            };
+            match value {
+                Some(false) => return Ok(Value::Boolean(false)),
+                Some(true) => (),
+                None => result = None,
+            }
+            left = right;
+        }
+        Ok(result.into())
+    }
+
+    fn add_or_subtract(&mut self, expression: &AddOrSubtractExpression, row: &Row) -> Result<Value> {
+        let mut value = self.multiply_divide_modulo(&expression.expression, row)?;
+        for (operator, operand) in &expression.operations {
+            let operand = self.multiply_divide_modulo(operand, row)?;
+            value = match operator {
+                PlusMinusOperator::Plus => add(value, operand)?,
+                PlusMinusOperator::Minus => arithmetic("-", value, operand, i64::checked_sub, |a, b| a - b)?,
+                PlusMinusOperator::Concatenate => match (value, operand) {
+                    (Value::Null, _) | (_, Value::Null) => Value::Null,
+                    (Value::String(a), Value::String(b)) => Value::String(a + &b),
+                    (Value::List(mut a), Value::List(b)) => {
+                        a.extend(b);
+                        Value::List(a)
+                    }
+                    (a, b) => return Err(type_error("||", &a, &b)),
+                },
+            };
+        }
+        Ok(value)
+    }
+
+    fn multiply_divide_modulo(&mut self, expression: &MultiplyDivideModuloExpression, row: &Row) -> Result<Value> {
+        let mut value = self.power_of(&expression.expression, row)?;
+        for (operator, operand) in &expression.operations {
+            let operand = self.power_of(operand, row)?;
+            if matches!(operator, MultiplyDivideModuloOperator::Divide | MultiplyDivideModuloOperator::Modulo)
+                && matches!((&value, &operand), (Value::Integer(_), Value::Integer(0)))
+            {
+                return Err(ExecutionError::new("division by zero"));
+            }
+            value = match operator {
+                MultiplyDivideModuloOperator::Multiply => arithmetic("*", value, operand, i64::checked_mul, |a, b| a * b)?,
+                MultiplyDivideModuloOperator::Divide => arithmetic("/", value, operand, i64::checked_div, |a, b| a / b)?,
+                MultiplyDivideModuloOperator::Modulo => arithmetic("%", value, operand, i64::checked_rem, |a, b| a % b)?,
+            };
+        }
+        Ok(value)
+    }
+
+    fn power_of(&mut self, expression: &PowerOfExpression, row: &Row) -> Result<Value> {
+        let mut operands = expression.0.iter();
+        let mut value = self.unary(operands.next().expect("an operand"), row)?;
+        for operand in operands {
+            let operand = self.unary(operand, row)?;
+            value = match (value, operand) {
+                (Value::Null, _) | (_, Value::Null) => Value::Null,
+                (a, b) => match (as_float(&a), as_float(&b)) {
+                    (Some(a), Some(b)) => Value::Float(a.powf(b)),
+                    _ => return Err(type_error("^", &a, &b)),
+                },
+            };
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self, expression: &UnaryAddOrSubtractExpression, row: &Row) -> Result<Value> {
+        let value = self.string_list_null(&expression.expression, row)?;
+        if !expression.negate {
+            return Ok(value);
+        }
+        match value {
+            Value::Integer(value) => value.checked_neg().map(Value::Integer).ok_or_else(overflow),
+            Value::Float(value) => Ok(Value::Float(-value)),
+            Value::Null => Ok(Value::Null),
+            value => Err(ExecutionError::new(format!("cannot negate {}", value.type_name()))),
+        }
+    }
+
+    fn string_list_null(&mut self, expression: &StringListNullOperatorExpression, row: &Row) -> Result<Value> {
+        let mut value = self.property_or_labels(&expression.expression, row)?;
+        for operation in &expression.operations {
+            value = match operation {
+                StringListNullOperatorExpressionInner::StringOperator(operation) => {
+                    let operand = self.property_or_labels(&operation.expression, row)?;
+                    let (Value::String(text), Value::String(operand)) = (&value, &operand) else {
+                        value = Value::Null;
+                        continue;
+                    };
+                    Value::Boolean(match operation.operator {
+                        StringOperator::StartsWith => text.starts_with(operand.as_str()),
+                        StringOperator::EndsWith => text.ends_with(operand.as_str()),
+                        StringOperator::Contains => text.contains(operand.as_str()),
+                        StringOperator::RegularExpression => regex::Regex::new(&format!("^(?:{})$", operand))
+                            .map_err(|error| ExecutionError::new(format!("invalid regular expression: {}", error)))?
+                            .is_match(text),
+                    })
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::In(list)) => {
+                    let list = self.property_or_labels(list, row)?;
+                    contains(&list, &value)?
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Index(index)) => {
+                    let index = self.evaluate(index, row)?;
+                    self.index(value, index)?
+                }
+                StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::Range(from, to)) => {
+                    let from = from.as_ref().map(|from| self.evaluate(from, row)).transpose()?;
+                    let to = to.as_ref().map(|to| self.evaluate(to, row)).transpose()?;
+                    slice(value, from, to)?
+                }
+                StringListNullOperatorExpressionInner::NullOperator(operation) => Value::Boolean(value.is_null() != operation.not),
+                StringListNullOperatorExpressionInner::NormalizedPredicate(_) => {
+                    return Err(ExecutionError::new("IS NORMALIZED is not supported"));
+                }
+                StringListNullOperatorExpressionInner::TypePredicate(predicate) => {
+                    Value::Boolean(has_type(&value, &predicate.type_) != predicate.not)
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    fn index(&mut self, value: Value, index: Value) -> Result<Value> {
+        match (value, index) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::List(list), Value::Integer(index)) => {
+                let index = if index < 0 { index + list.len() as i64 } else { index };
+                Ok(usize::try_from(index).ok().and_then(|index| list.get(index).cloned()).unwrap_or(Value::Null))
+            }
+            (value @ (Value::Map(_) | Value::Node(_) | Value::Relationship(_)), Value::String(key)) => self.property(value, &key),
+            (value, index) => Err(ExecutionError::new(format!("cannot index {} with {}", value.type_name(), index.type_name()))),
+        }
+    }
+
+    fn property_or_labels(&mut self, expression: &PropertyOrLabelsExpression, row: &Row) -> Result<Value> {
+        let mut value = self.atom(&expression.atom, row)?;
+        for key in &expression.property_lookup {
+            value = self.property(value, &unescape(key))?;
+        }
+        let Some(label_expression) = &expression.label_expression else { return Ok(value) };
+        match value {
+            Value::Null => Ok(Value::Null),
+            Value::Node(node) => {
+                let labels = self.current_node(&node).labels;
+                Ok(Value::Boolean(self.matches_labels(label_expression, &labels, row)?))
+            }
+            Value::Relationship(relationship) => {
+                Ok(Value::Boolean(self.matches_labels(label_expression, &[relationship.type_], row)?))
+            }
+            value => Err(ExecutionError::new(format!("cannot check the labels of {}", value.type_name()))),
+        }
+    }
+
+    /// Returns a property of a node, relationship or map, or `null` if it has none.
+    pub(super) fn property(&self, value: Value, key: &str) -> Result<Value> {
+        match value {
+            Value::Null => Ok(Value::Null),
+            Value::Map(mut map) => Ok(map.remove(key).unwrap_or(Value::Null)),
+            Value::Node(node) => Ok(self.current_node(&node).properties.remove(key).unwrap_or(Value::Null)),
+            Value::Relationship(relationship) => {
+                let relationship = self.graph.relationship(relationship.id).cloned().unwrap_or(relationship);
+                Ok(relationship.properties.get(key).cloned().unwrap_or(Value::Null))
+            }
+            value => Err(ExecutionError::new(format!("cannot read property `{}` of {}", key, value.type_name()))),
+        }
+    }
+
+    /// Returns the node as it is now, or as it was last read if it has been deleted.
+    pub(super) fn current_node(&self, node: &Node) -> Node {
+        self.graph.node(node.id).cloned().unwrap_or_else(|| node.clone())
+    }
+
+    /// Returns whether the labels, or the type of a relationship, match the label expression.
+    pub(super) fn matches_labels(&mut self, expression: &LabelExpression, labels: &[String], row: &Row) -> Result<bool> {
+        Ok(match expression {
+            LabelExpression::Label(LabelOrType::Static(label)) => labels.contains(&unescape(label)),
+            LabelExpression::Label(LabelOrType::Dynamic(expression)) => {
+                self.dynamic_labels(expression, row)?.iter().all(|label| labels.contains(label))
+            }
+            LabelExpression::AnyOf(expression) => {
+                self.dynamic_labels(expression, row)?.iter().any(|label| labels.contains(label))
+            }
+            LabelExpression::Wildcard => !labels.is_empty(),
+            LabelExpression::Not(expression) => !self.matches_labels(expression, labels, row)?,
+            LabelExpression::And(expressions) => {
+                for expression in expressions {
+                    if !self.matches_labels(expression, labels, row)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            LabelExpression::Or(expressions) => {
+                for expression in expressions {
+                    if self.matches_labels(expression, labels, row)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+        })
+    }
+
+    /// Evaluates the labels of `$(expression)`, a string or a list of strings.
+    pub(super) fn dynamic_labels(&mut self, expression: &Expression, row: &Row) -> Result<Vec<String>> {
+        match self.evaluate(expression, row)? {
+            Value::String(label) => Ok(vec![label]),
+            Value::List(labels) => labels
+                .into_iter()
+                .map(|label| match label {
+                    Value::String(label) => Ok(label),
+                    label => Err(ExecutionError::new(format!("expected a STRING label but got {}", label.type_name()))),
+                })
+                .collect(),
+            value => Err(ExecutionError::new(format!("expected a STRING label but got {}", value.type_name()))),
+        }
+    }
+
+    fn atom(&mut self, atom: &Atom, row: &Row) -> Result<Value> {
+        match atom {
+            Atom::Literal(literal) => self.literal(literal, row),
+            Atom::Parameter(name) => {
+                let name = name.trim_start_matches('$');
+                self.parameters.get(name).cloned().ok_or_else(|| ExecutionError::new(format!("missing parameter ${}", name)))
+            }
+            Atom::CaseExpression(case) => self.case(case, row),
+            Atom::CountStar(_) => Err(ExecutionError::new("count(*) can only be used in RETURN and WITH")),
+            Atom::ListComprehension(comprehension) => {
+                let Some(elements) = self.filter(&comprehension.filter_expression, row)? else { return Ok(Value::Null) };
+                let Some(expression) = &comprehension.expression else { return Ok(Value::List(elements)) };
+                let variable = &comprehension.filter_expression.id_in_coll.variable.name;
+                let mut row = row.clone();
+                let mut values = Vec::new();
+                for element in elements {
+                    row.insert(variable.clone(), element);
+                    values.push(self.evaluate(expression, &row)?);
+                }
+                Ok(Value::List(values))
+            }
+            Atom::PatternComprehension(_) => Err(ExecutionError::new("pattern comprehensions are not supported")),
+            Atom::All(filter) => self.quantifier(filter, row, |matches, total| matches == total),
+            Atom::Any(filter) => self.quantifier(filter, row, |matches, _| matches > 0),
+            Atom::None(filter) => self.quantifier(filter, row, |matches, _| matches == 0),
+            Atom::Single(filter) => self.quantifier(filter, row, |matches, _| matches == 1),
+            Atom::Reduce(reduce) => self.reduce(reduce, row),
+            Atom::RelationshipsPattern(pattern) => {
+                let pattern = Pattern {
+                    parts: vec![PatternPart {
+                        variable: None,
+                        pattern_element: PatternElement {
+                            node_pattern: pattern.node.clone(),
+                            relationship_patterns: pattern.relationships.clone(),
+                        },
+                    }],
+                };
+                let plan = plan_pattern(&pattern, None, &scope(row))?;
+                Ok(Value::Boolean(!self.run(&plan, row)?.is_empty()))
+            }
+            Atom::ParenthesizedExpression(expression) => self.evaluate(expression, row),
+            Atom::FunctionInvocation(function) => self.call_function(function, row),
+            Atom::ExistentialSubquery(subquery) => {
+                let plan = match subquery {
+                    ExistentialSubquery::RegularQuery(query) => plan_subquery(query, &scope(row))?,
+                    ExistentialSubquery::PatternWhere { pattern, where_ } => plan_pattern(pattern, where_.as_ref(), &scope(row))?,
+                };
+                Ok(Value::Boolean(!self.run(&plan, row)?.is_empty()))
+            }
+            Atom::MapProjection(projection) => self.map_projection(projection, row),
+            Atom::TemporalLiteral(literal) => Ok(Value::from(literal.clone())),
+            Atom::PointLiteral(point) => Ok(Value::Point(point.clone())),
+            Atom::Variable(variable) => {
+                row.get(&variable.name).cloned().ok_or_else(|| ExecutionError::new(format!("variable `{}` is not defined", variable.name)))
+            }
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal, row: &Row) -> Result<Value> {
+        match literal {
+            Literal::String(value) => Ok(Value::String(value.clone())),
+            Literal::Number(NumberLiteral::Integer(value)) => Ok(Value::Integer(*value)),
+            Literal::Number(NumberLiteral::Double(value)) => Ok(Value::Float(*value)),
+            Literal::Boolean(value) => Ok(Value::Boolean(*value)),
+            Literal::Null => Ok(Value::Null),
+            Literal::MapLiteral(map) => {
+                let mut values = BTreeMap::new();
+                for (key, expression) in map {
+                    values.insert(unescape(key), self.evaluate(expression, row)?);
+                }
+                Ok(Value::Map(values))
+            }
+            Literal::ListLiteral(expressions) => {
+                expressions.iter().map(|expression| self.evaluate(expression, row)).collect::<Result<_>>().map(Value::List)
+            }
+        }
+    }
+
+    fn case(&mut self, case: &CaseExpression, row: &Row) -> Result<Value> {
+        let operand = case.expression.as_ref().map(|expression| self.evaluate(expression, row)).transpose()?;
+        for (when, then) in &case.alternatives {
+            let when = self.evaluate(when, row)?;
+            let matches = match &operand {
+                Some(operand) => operand.equals(&when) == Some(true),
+                None => matches!(when, Value::Boolean(true)),
+            };
+            if matches {
+                return self.evaluate(then, row);
+            }
+        }
+        match &case.else_ {
+            Some(else_) => self.evaluate(else_, row),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Returns the elements of the list of `x IN list WHERE predicate` the
+    /// predicate holds for, or `None` if the list is `null`.
+    fn filter(&mut self, filter: &FilterExpression, row: &Row) -> Result<Option<Vec<Value>>> {
+        let elements = match self.evaluate(&filter.id_in_coll.expression, row)? {
+            Value::Null => return Ok(None),
+            Value::List(elements) => elements,
+            value => return Err(ExecutionError::new(format!("expected a LIST but got {}", value.type_name()))),
+        };
+        let Some(predicate) = &filter.where_ else { return Ok(Some(elements)) };
+        let mut row = row.clone();
+        let mut matching = Vec::new();
+        for element in elements {
+            row.insert(filter.id_in_coll.variable.name.clone(), element.clone());
+            if self.predicate(predicate, &row)? {
+                matching.push(element);
+            }
+        }
+        Ok(Some(matching))
+    }
+
+    /// Evaluates `all`, `any`, `none` and `single` from the number of
+    /// elements the predicate holds for and the number of elements.
+    fn quantifier(&mut self, filter: &FilterExpression, row: &Row, holds: fn(usize, usize) -> bool) -> Result<Value> {
+        let elements = match self.evaluate(&filter.id_in_coll.expression, row)? {
+            Value::Null => return Ok(Value::Null),
+            Value::List(elements) => elements,
+            value => return Err(ExecutionError::new(format!("expected a LIST but got {}", value.type_name()))),
+        };
+        let total = elements.len();
+        let Some(predicate) = &filter.where_ else { return Ok(Value::Boolean(holds(total, total))) };
+        let (mut matches, mut unknown) = (0, 0);
+        let mut row = row.clone();
+        for element in elements {
+            row.insert(filter.id_in_coll.variable.name.clone(), element);
+            match truth(self.evaluate(predicate, &row)?, "a predicate")? {
+                Some(true) => matches += 1,
+                Some(false) => (),
+                None => unknown += 1,
+            }
+        }
+        // The result is unknown if the elements the predicate is unknown for could change it.
+        let result = holds(matches, total);
+        if (0..=unknown).all(|more| holds(matches + more, total) == result) {
+            Ok(Value::Boolean(result))
+        }
+        else {
+            Ok(Value::Null)
+        }
+    }
+
+    fn reduce(&mut self, reduce: &ReduceExpression, row: &Row) -> Result<Value> {
+        let mut accumulator = self.evaluate(&reduce.initial_value, row)?;
+        let elements = match self.evaluate(&reduce.id_in_coll.expression, row)? {
+            Value::Null => return Ok(Value::Null),
+            Value::List(elements) => elements,
+            value => return Err(ExecutionError::new(format!("expected a LIST but got {}", value.type_name()))),
+        };
+        let mut row = row.clone();
+        for element in elements {
+            row.insert(reduce.accumulator.name.clone(), accumulator);
+            row.insert(reduce.id_in_coll.variable.name.clone(), element);
+            accumulator = self.evaluate(&reduce.expression, &row)?;
+        }
+        Ok(accumulator)
+    }
+
+    fn map_projection(&mut self, projection: &MapProjection, row: &Row) -> Result<Value> {
+        let name = &projection.variable.name;
+        let value = row.get(name).cloned().ok_or_else(|| ExecutionError::new(format!("variable `{}` is not defined", name)))?;
+        let properties = match &value {
+            Value::Null => return Ok(Value::Null),
+            Value::Map(map) => map.clone(),
+            Value::Node(node) => self.current_node(node).properties,
+            Value::Relationship(relationship) => {
+                self.graph.relationship(relationship.id).unwrap_or(relationship).properties.clone()
+            }
+            value => return Err(ExecutionError::new(format!("cannot project {}", value.type_name()))),
+        };
+        let mut map = BTreeMap::new();
+        for item in &projection.items {
+            match item {
+                MapProjectionItem::Property(key) => {
+                    let key = unescape(key);
+                    map.insert(key.clone(), properties.get(&key).cloned().unwrap_or(Value::Null));
+                }
+                MapProjectionItem::Literal { key, expression } => {
+                    map.insert(unescape(key), self.evaluate(expression, row)?);
+                }
+                MapProjectionItem::Variable(variable) => {
+                    let value = row.get(&variable.name).cloned().unwrap_or(Value::Null);
+                    map.insert(variable.name.clone(), value);
+                }
+                MapProjectionItem::AllProperties => map.extend(properties.clone()),
+            }
+        }
+        Ok(Value::Map(map))
+    }
+}
+
+/// The variables a row binds, which a subquery can refer to.
+fn scope(row: &Row) -> Vec<String> {
+    let mut scope: Vec<String> = row.keys().cloned().collect();
+    scope.sort();
+    scope
+}
+
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(value) => Some(*value as f64),
+        Value::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Applies an arithmetic operator to numbers: to integers if both are, with
+/// an error on overflow, otherwise to floats.
+fn arithmetic(operator: &str, a: Value, b: Value, integer: fn(i64, i64) -> Option<i64>, float: fn(f64, f64) -> f64) -> Result<Value> {
+    match (a, b) {
+        (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+        (Value::Integer(a), Value::Integer(b)) => integer(a, b).map(Value::Integer).ok_or_else(overflow),
+        (a, b) => match (as_float(&a), as_float(&b)) {
+            (Some(a), Some(b)) => Ok(Value::Float(float(a, b))),
+            _ => Err(type_error(operator, &a, &b)),
+        },
+    }
+}
+
+/// `+` adds numbers and concatenates strings and lists. A string and a
+/// number are concatenated, and an element is added to a list.
+fn add(a: Value, b: Value) -> Result<Value> {
+    match (a, b) {
+        (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+        (Value::List(mut a), Value::List(b)) => {
+            a.extend(b);
+            Ok(Value::List(a))
+        }
+        (Value::List(mut a), b) => {
+            a.push(b);
+            Ok(Value::List(a))
+        }
+        (a, Value::List(mut b)) => {
+            b.insert(0, a);
+            Ok(Value::List(b))
+        }
+        (Value::String(a), Value::String(b)) => Ok(Value::String(a + &b)),
+        (Value::String(a), b @ (Value::Integer(_) | Value::Float(_))) => Ok(Value::String(format!("{}{}", a, b))),
+        (a @ (Value::Integer(_) | Value::Float(_)), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+        (a, b) => arithmetic("+", a, b, i64::checked_add, |a, b| a + b),
+    }
+}
+
+/// `element IN list`, which is `null` if the element is not found but the
+/// list contains values it cannot be compared with.
+fn contains(list: &Value, element: &Value) -> Result<Value> {
+    let elements = match list {
+        Value::Null => return Ok(Value::Null),
+        Value::List(elements) => elements,
+        value => return Err(ExecutionError::new(format!("IN expects a LIST but got {}", value.type_name()))),
+    };
+    let mut result = Some(false);
+    for candidate in elements {
+        match element.equals(candidate) {
+            Some(true) => return Ok(Value::Boolean(true)),
+            Some(false) => (),
+            None => result = None,
+        }
+    }
+    Ok(result.into())
+}
+
+/// `list[from..to]`, where negative bounds count from the end.
+fn slice(value: Value, from: Option<Value>, to: Option<Value>) -> Result<Value> {
+    let elements = match value {
+        Value::Null => return Ok(Value::Null),
+        Value::List(elements) => elements,
+        value => return Err(ExecutionError::new(format!("cannot slice {}", value.type_name()))),
+    };
+    let length = elements.len() as i64;
+    let bound = |bound: Option<Value>, default: i64| -> Result<Option<i64>> {
+        match bound {
+            None => Ok(Some(default)),
+            Some(Value::Null) => Ok(None),
+            Some(Value::Integer(bound)) => Ok(Some(if bound < 0 { (length + bound).max(0) } else { bound.min(length) })),
+            Some(bound) => Err(ExecutionError::new(format!("expected an INTEGER bound but got {}", bound.type_name()))),
+        }
+    };
+    let (Some(from), Some(to)) = (bound(from, 0)?, bound(to, length)?) else { return Ok(Value::Null) };
+    if from >= to {
+        return Ok(Value::List(Vec::new()));
+    }
+    Ok(Value::List(elements[from as usize..to as usize].to_vec()))
+}
+
+/// Returns whether the value has the type, which includes `null` unless the
+/// type is `NOT NULL`.
+fn has_type(value: &Value, type_: &CypherType) -> bool {
+    match (type_, value) {
+        (CypherType::Any, _) => true,
+        (CypherType::Nothing, _) => false,
+        (CypherType::NotNull(type_), value) => !value.is_null() && has_type(value, type_),
+        (CypherType::Union(types), value) => types.iter().any(|type_| has_type(value, type_)),
+        (_, Value::Null) => true,
+        (CypherType::Boolean, Value::Boolean(_))
+        | (CypherType::String, Value::String(_))
+        | (CypherType::Integer, Value::Integer(_))
+        | (CypherType::Float, Value::Float(_))
+        | (CypherType::Map, Value::Map(_))
+        | (CypherType::Node, Value::Node(_))
+        | (CypherType::Relationship, Value::Relationship(_))
+        | (CypherType::Path, Value::Path(_))
+        | (CypherType::Date, Value::Temporal(Temporal::Date(_)))
+        | (CypherType::LocalTime, Value::Temporal(Temporal::LocalTime(_)))
+        | (CypherType::ZonedTime, Value::Temporal(Temporal::Time(_)))
+        | (CypherType::LocalDateTime, Value::Temporal(Temporal::LocalDateTime(_)))
+        | (CypherType::ZonedDateTime, Value::Temporal(Temporal::DateTime(_)))
+        | (CypherType::Duration, Value::Duration(_))
+        | (CypherType::Point, Value::Point(_)) => true,
+        (CypherType::List(element), Value::List(elements)) => elements.iter().all(|value| has_type(value, element)),
+        (CypherType::PropertyValue, value) => is_property_value(value),
+        _ => false,
+    }
+}
+
+/// Returns whether the value can be stored as a property: a boolean, number,
+/// string, temporal value or point, or a list of them.
+pub(super) fn is_property_value(value: &Value) -> bool {
+    match value {
+        Value::List(elements) => elements.iter().all(is_simple_property_value),
+        value => is_simple_property_value(value),
+    }
+}
+
+fn is_simple_property_value(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Boolean(_) | Value::Integer(_) | Value::Float(_) | Value::String(_) | Value::Temporal(_) | Value::Duration(_) | Value::Point(_)
+    )
+}
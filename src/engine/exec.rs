@@ -0,0 +1,868 @@
+//! The interpreter of logical plans.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::eval::{is_property_value, unescape};
+use super::functions::aggregate;
+use super::graph::Graph;
+use super::{ExecutionError, QueryResult};
+use crate::ast::cypher::value::{Node, Path, Relationship, Value};
+use crate::ast::cypher::{
+    Atom, Expression, LabelExpression, LabelOrType, NodePattern, NullsOrder, PatternPart, Properties, PropertyExpression, PropertyKey,
+    QualifiedName, RelationshipPattern, RemoveItem, SetItem, SortDirection,
+};
+use crate::plan::{Direction, LogicalPlan};
+
+type Result<T> = std::result::Result<T, ExecutionError>;
+
+/// The values of the variables bound for a row.
+pub(super) type Row = HashMap<String, Value>;
+
+/// Runs plans against a graph.
+pub(super) struct Executor<'a> {
+    pub(super) graph: &'a mut Graph,
+    pub(super) parameters: &'a HashMap<String, Value>,
+}
+
+impl<'a> Executor<'a> {
+    pub(super) fn new(graph: &'a mut Graph, parameters: &'a HashMap<String, Value>) -> Self {
+        Executor { graph, parameters }
+    }
+
+    /// Runs the plan of a query and returns its results, with nodes and
+    /// relationships as they are at the end of the query.
+    pub(super) fn execute(&mut self, plan: &LogicalPlan) -> Result<QueryResult> {
+        let rows = self.run(plan, &Row::new())?;
+        let mut columns = plan.columns().to_vec();
+        if let LogicalPlan::ProduceResults { input, .. } = plan {
+            if let LogicalPlan::ProcedureCall { name, yields: None, .. } = input.as_ref() {
+                if columns.is_empty() {
+                    columns = procedure_outputs(name)?.iter().map(|output| output.to_string()).collect();
+                }
+            }
+        }
+        let rows = if columns.is_empty() {
+            Vec::new()
+        }
+        else {
+            rows.into_iter()
+                .map(|row| columns.iter().map(|column| self.refresh(row.get(column).cloned().unwrap_or(Value::Null))).collect())
+                .collect()
+        };
+        Ok(QueryResult { columns, rows })
+    }
+
+    /// Runs a plan for the argument row and returns the rows it produces.
+    pub(super) fn run(&mut self, plan: &LogicalPlan, argument: &Row) -> Result<Vec<Row>> {
+        match plan {
+            LogicalPlan::Argument { .. } => Ok(vec![argument.clone()]),
+            LogicalPlan::NodeScan { input, variable, label_expression } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let nodes: Vec<Node> = self.graph.nodes().cloned().collect();
+                    for node in nodes {
+                        if let Some(label_expression) = label_expression {
+                            if !self.matches_labels(label_expression, &node.labels, &row)? {
+                                continue;
+                            }
+                        }
+                        let mut row = row.clone();
+                        row.insert(variable.clone(), Value::Node(node));
+                        rows.push(row);
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Expand { input, from, relationship, to, direction, types, into } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let Some(Value::Node(node)) = row.get(from) else { continue };
+                    for (found, other) in self.neighbours(node.id, *direction) {
+                        if let Some(types) = types {
+                            if !self.matches_labels(types, std::slice::from_ref(&found.type_), &row)? {
+                                continue;
+                            }
+                        }
+                        if *into && !matches!(row.get(to), Some(Value::Node(node)) if node.id == other) {
+                            continue;
+                        }
+                        let Some(other) = self.graph.node(other).cloned() else { continue };
+                        let mut row = row.clone();
+                        row.insert(relationship.clone(), Value::Relationship(found));
+                        row.insert(to.clone(), Value::Node(other));
+                        rows.push(row);
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::VarLengthExpand { input, from, relationship, to, direction, types, properties, min, max, into } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let Some(Value::Node(node)) = row.get(from) else { continue };
+                    let properties = match properties {
+                        Some(properties) => self.properties(properties, &row)?,
+                        None => BTreeMap::new(),
+                    };
+                    let mut paths = Vec::new();
+                    let mut expansion = VarLength { direction: *direction, types: types.as_ref(), properties: &properties, min: *min, max: *max };
+                    self.expand_paths(&mut expansion, node.id, &mut Vec::new(), &row, &mut paths)?;
+                    for (relationships, end) in paths {
+                        if *into && !matches!(row.get(to), Some(Value::Node(node)) if node.id == end) {
+                            continue;
+                        }
+                        let Some(end) = self.graph.node(end).cloned() else { continue };
+                        let mut row = row.clone();
+                        row.insert(relationship.clone(), Value::List(relationships.into_iter().map(Value::Relationship).collect()));
+                        row.insert(to.clone(), Value::Node(end));
+                        rows.push(row);
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Filter { input, predicate } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    if self.predicate(predicate, &row)? {
+                        rows.push(row);
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Projection { input, items } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let mut projected = Row::new();
+                    for (name, expression) in items {
+                        projected.insert(name.clone(), self.evaluate(expression, &row)?);
+                    }
+                    rows.push(projected);
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Aggregate { input, grouping, aggregates } => {
+                let rows = self.run(input, argument)?;
+                self.aggregate(rows, grouping, aggregates)
+            }
+            LogicalPlan::Sort { input, items } => {
+                let rows = self.run(input, argument)?;
+                let mut keyed = Vec::new();
+                for row in rows {
+                    let mut keys = Vec::new();
+                    for item in items {
+                        keys.push(self.evaluate(&item.expression, &row)?);
+                    }
+                    keyed.push((keys, row));
+                }
+                keyed.sort_by(|(a, _), (b, _)| {
+                    for (item, (a, b)) in items.iter().zip(a.iter().zip(b)) {
+                        let ordering = match item.nulls {
+                            Some(nulls) if a.is_null() != b.is_null() => {
+                                let first = if a.is_null() { Ordering::Less } else { Ordering::Greater };
+                                if nulls == NullsOrder::First { first } else { first.reverse() }
+                            }
+                            _ if item.direction == SortDirection::Descending => a.order(b).reverse(),
+                            _ => a.order(b),
+                        };
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                    Ordering::Equal
+                });
+                Ok(keyed.into_iter().map(|(_, row)| row).collect())
+            }
+            LogicalPlan::Skip { input, count } => {
+                let count = self.count(count, "SKIP")?;
+                Ok(self.run(input, argument)?.into_iter().skip(count).collect())
+            }
+            LogicalPlan::Limit { input, count } => {
+                let count = self.count(count, "LIMIT")?;
+                Ok(self.run(input, argument)?.into_iter().take(count).collect())
+            }
+            LogicalPlan::Unwind { input, expression, variable } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let values = match self.evaluate(expression, &row)? {
+                        Value::List(values) => values,
+                        Value::Null => Vec::new(),
+                        value => vec![value],
+                    };
+                    for value in values {
+                        let mut row = row.clone();
+                        row.insert(variable.clone(), value);
+                        rows.push(row);
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Optional { input, variables } => {
+                let rows = self.run(input, argument)?;
+                if !rows.is_empty() {
+                    return Ok(rows);
+                }
+                let mut row = argument.clone();
+                for variable in variables {
+                    row.insert(variable.clone(), Value::Null);
+                }
+                Ok(vec![row])
+            }
+            LogicalPlan::Union { left, right, all } => {
+                let mut rows = self.run(left, argument)?;
+                rows.extend(self.run(right, argument)?);
+                if *all {
+                    return Ok(rows);
+                }
+                let mut seen = BTreeSet::new();
+                Ok(rows
+                    .into_iter()
+                    .filter(|row| {
+                        let mut names: Vec<&String> = row.keys().collect();
+                        names.sort();
+                        seen.insert(Key(names.into_iter().map(|name| row[name].clone()).collect()))
+                    })
+                    .collect())
+            }
+            LogicalPlan::Apply { input, plan } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    rows.extend(self.run(plan, &row)?);
+                }
+                Ok(rows)
+            }
+            LogicalPlan::ProcedureCall { input, name, arguments, yields } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let mut values = Vec::new();
+                    for argument in arguments.iter().flatten() {
+                        values.push(self.evaluate(argument, &row)?);
+                    }
+                    let outputs = procedure_outputs(name)?;
+                    for record in self.call_procedure(name, values)? {
+                        let mut row = row.clone();
+                        match yields {
+                            Some(yields) => {
+                                for (field, variable) in yields {
+                                    let index = outputs
+                                        .iter()
+                                        .position(|output| output == field)
+                                        .ok_or_else(|| ExecutionError::new(format!("{} has no output `{}`", name, field)))?;
+                                    row.insert(variable.clone(), record[index].clone());
+                                }
+                            }
+                            None => row.extend(outputs.iter().map(|output| output.to_string()).zip(record)),
+                        }
+                        rows.push(row);
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Create { input, pattern } => {
+                let mut rows = Vec::new();
+                for mut row in self.run(input, argument)? {
+                    for part in &pattern.parts {
+                        self.create_part(part, &mut row, false)?;
+                    }
+                    rows.push(row);
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Merge { input, plan, pattern, on_match, on_create } => {
+                let mut rows = Vec::new();
+                for row in self.run(input, argument)? {
+                    let matched = self.run(plan, &row)?;
+                    if matched.is_empty() {
+                        let mut row = row;
+                        self.create_part(pattern, &mut row, true)?;
+                        self.set_items(on_create, &row)?;
+                        rows.push(row);
+                    }
+                    else {
+                        for row in matched {
+                            self.set_items(on_match, &row)?;
+                            rows.push(row);
+                        }
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Delete { input, expressions, detach } => {
+                let rows = self.run(input, argument)?;
+                let (mut nodes, mut relationships) = (BTreeSet::new(), BTreeSet::new());
+                for row in &rows {
+                    for expression in expressions {
+                        match self.evaluate(expression, row)? {
+                            Value::Null => (),
+                            Value::Node(node) => {
+                                nodes.insert(node.id);
+                            }
+                            Value::Relationship(relationship) => {
+                                relationships.insert(relationship.id);
+                            }
+                            Value::Path(path) => {
+                                nodes.extend(path.nodes.iter().map(|node| node.id));
+                                relationships.extend(path.relationships.iter().map(|relationship| relationship.id));
+                            }
+                            value => return Err(ExecutionError::new(format!("cannot delete {}", value.type_name()))),
+                        }
+                    }
+                }
+                for relationship in relationships {
+                    self.graph.delete_relationship(relationship);
+                }
+                for node in nodes {
+                    self.graph.delete_node(node, *detach)?;
+                }
+                Ok(rows)
+            }
+            LogicalPlan::SetProperty { input, items } => {
+                let rows = self.run(input, argument)?;
+                for row in &rows {
+                    self.set_items(items, row)?;
+                }
+                Ok(rows)
+            }
+            LogicalPlan::Remove { input, items } => {
+                let rows = self.run(input, argument)?;
+                for row in &rows {
+                    for item in items {
+                        self.remove_item(item, row)?;
+                    }
+                }
+                Ok(rows)
+            }
+            LogicalPlan::ProjectPath { input, part } => {
+                let mut rows = Vec::new();
+                for mut row in self.run(input, argument)? {
+                    let path = self.project_path(part, &row)?;
+                    let variable = part.variable.as_ref().expect("projected paths are named");
+                    row.insert(variable.name.clone(), path);
+                    rows.push(row);
+                }
+                Ok(rows)
+            }
+            LogicalPlan::ProduceResults { input, .. } => self.run(input, argument),
+        }
+    }
+
+    /// Returns the relationships of a node in the direction and the node at
+    /// their other end. A relationship from the node to itself is returned
+    /// once.
+    fn neighbours(&self, id: u64, direction: Direction) -> Vec<(Relationship, u64)> {
+        self.graph
+            .relationships_of(id)
+            .filter_map(|relationship| match direction {
+                Direction::Outgoing if relationship.start == id => Some((relationship.clone(), relationship.end)),
+                Direction::Incoming if relationship.end == id => Some((relationship.clone(), relationship.start)),
+                Direction::Both if relationship.start == id => Some((relationship.clone(), relationship.end)),
+                Direction::Both => Some((relationship.clone(), relationship.start)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Finds the paths from a node that match a variable length relationship,
+    /// never following a relationship twice.
+    fn expand_paths(
+        &mut self,
+        expansion: &mut VarLength<'_>,
+        id: u64,
+        path: &mut Vec<Relationship>,
+        row: &Row,
+        paths: &mut Vec<(Vec<Relationship>, u64)>,
+    ) -> Result<()> {
+        let length = path.len() as u64;
+        if length >= expansion.min {
+            paths.push((path.clone(), id));
+        }
+        if expansion.max.is_some_and(|max| length >= max) {
+            return Ok(());
+        }
+        for (relationship, other) in self.neighbours(id, expansion.direction) {
+            if path.iter().any(|seen| seen.id == relationship.id) {
+                continue;
+            }
+            if let Some(types) = expansion.types {
+                if !self.matches_labels(types, std::slice::from_ref(&relationship.type_), row)? {
+                    continue;
+                }
+            }
+            let matches = expansion.properties.iter().all(|(key, value)| {
+                relationship.properties.get(key).is_some_and(|property| property.equals(value) == Some(true))
+            });
+            if !matches {
+                continue;
+            }
+            path.push(relationship);
+            self.expand_paths(expansion, other, path, row, paths)?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// Evaluates the properties of a pattern.
+    fn properties(&mut self, properties: &Properties, row: &Row) -> Result<BTreeMap<String, Value>> {
+        match properties {
+            Properties::MapLiteral(map) => {
+                let mut values = BTreeMap::new();
+                for (key, expression) in map {
+                    values.insert(unescape(key), self.evaluate(expression, row)?);
+                }
+                Ok(values)
+            }
+            Properties::Parameter(name) => {
+                let name = name.trim_start_matches('$');
+                match self.parameters.get(name) {
+                    Some(Value::Map(map)) => Ok(map.clone()),
+                    Some(value) => Err(ExecutionError::new(format!("expected a MAP of properties but got {}", value.type_name()))),
+                    None => Err(ExecutionError::new(format!("missing parameter ${}", name))),
+                }
+            }
+        }
+    }
+
+    /// Evaluates the count of a `SKIP` or `LIMIT`.
+    fn count(&mut self, expression: &Expression, clause: &str) -> Result<usize> {
+        match self.evaluate(expression, &Row::new())? {
+            Value::Integer(count) if count >= 0 => Ok(count as usize),
+            value => Err(ExecutionError::new(format!("{} requires a non-negative INTEGER but got {}", clause, value))),
+        }
+    }
+
+    fn aggregate(&mut self, rows: Vec<Row>, grouping: &[(String, Expression)], aggregates: &[(String, Expression)]) -> Result<Vec<Row>> {
+        let mut groups: Vec<(Vec<Value>, Vec<Vec<Value>>)> = Vec::new();
+        let mut percentiles = vec![None; aggregates.len()];
+        let mut index: BTreeMap<Key, usize> = BTreeMap::new();
+        for row in &rows {
+            let mut keys = Vec::new();
+            for (_, expression) in grouping {
+                keys.push(self.evaluate(expression, row)?);
+            }
+            let group = *index.entry(Key(keys.clone())).or_insert_with(|| {
+                groups.push((keys, vec![Vec::new(); aggregates.len()]));
+                groups.len() - 1
+            });
+            for (position, (_, expression)) in aggregates.iter().enumerate() {
+                let value = match expression.as_atom() {
+                    Some(Atom::CountStar(_)) => Value::Boolean(true),
+                    Some(Atom::FunctionInvocation(function)) => {
+                        let argument = function
+                            .arguments
+                            .first()
+                            .ok_or_else(|| ExecutionError::new(format!("{}() requires an argument", function.function_name)))?;
+                        if let Some(percentile) = function.arguments.get(1) {
+                            if percentiles[position].is_none() {
+                                percentiles[position] = Some(self.evaluate(percentile, row)?);
+                            }
+                        }
+                        self.evaluate(argument, row)?
+                    }
+                    _ => unreachable!("aggregates are function calls"),
+                };
+                groups[group].1[position].push(value);
+            }
+        }
+        if groups.is_empty() && grouping.is_empty() {
+            groups.push((Vec::new(), vec![Vec::new(); aggregates.len()]));
+        }
+
+        let mut result = Vec::new();
+        for (keys, values) in groups {
+            let mut row: Row = grouping.iter().map(|(name, _)| name.clone()).zip(keys).collect();
+            for (position, ((name, expression), values)) in aggregates.iter().zip(values).enumerate() {
+                let value = match expression.as_atom() {
+                    Some(Atom::FunctionInvocation(function)) => {
+                        let function_name = function.function_name.parts().collect::<Vec<_>>().join(".").to_lowercase();
+                        aggregate(&function_name, values, function.distinct, percentiles[position].as_ref())?
+                    }
+                    _ => Value::Integer(values.len() as i64),
+                };
+                row.insert(name.clone(), value);
+            }
+            result.push(row);
+        }
+        Ok(result)
+    }
+
+    /// Creates the nodes and relationships of a pattern part that are not
+    /// bound yet and binds them. Only `MERGE` creates relationships without
+    /// a direction, from left to right.
+    fn create_part(&mut self, part: &PatternPart, row: &mut Row, merge: bool) -> Result<()> {
+        let element = &part.pattern_element;
+        let mut nodes = vec![self.create_node(&element.node_pattern, row)?];
+        let mut relationships = Vec::new();
+        for (relationship, node_pattern) in &element.relationship_patterns {
+            let node = self.create_node(node_pattern, row)?;
+            let left = nodes.last().expect("a pattern starts with a node").id;
+            let (detail, start, end) = match relationship {
+                RelationshipPattern::Right(detail) => (detail, left, node.id),
+                RelationshipPattern::Left(detail) => (detail, node.id, left),
+                RelationshipPattern::Undirected(detail) | RelationshipPattern::LeftAndRight(detail) if merge => (detail, left, node.id),
+                _ => return Err(ExecutionError::new("only relationships with a direction can be created")),
+            };
+            let detail = detail.as_ref().ok_or_else(|| ExecutionError::new("a relationship needs a type to be created"))?;
+            if detail.range.is_some() {
+                return Err(ExecutionError::new("variable length relationships cannot be created"));
+            }
+            if let Some(variable) = &detail.variable {
+                if row.contains_key(&variable.name) {
+                    return Err(ExecutionError::new(format!("variable `{}` is already declared", variable.name)));
+                }
+            }
+            let type_ = match &detail.label_expression {
+                Some(LabelExpression::Label(LabelOrType::Static(type_))) => unescape(type_),
+                Some(LabelExpression::Label(LabelOrType::Dynamic(expression))) => {
+                    match self.dynamic_labels(expression, row)?.as_slice() {
+                        [type_] => type_.clone(),
+                        _ => return Err(ExecutionError::new("a relationship needs exactly one type to be created")),
+                    }
+                }
+                _ => return Err(ExecutionError::new("a relationship needs exactly one type to be created")),
+            };
+            let properties = self.stored_properties(detail.properties.as_ref(), row)?;
+            let id = self.graph.create_relationship(&type_, start, end, properties)?;
+            let created = self.graph.relationship(id).cloned().expect("the relationship was created");
+            if let Some(variable) = &detail.variable {
+                row.insert(variable.name.clone(), Value::Relationship(created.clone()));
+            }
+            relationships.push(created);
+            nodes.push(node);
+        }
+        if let Some(variable) = &part.variable {
+            row.insert(variable.name.clone(), Value::Path(Path { nodes, relationships }));
+        }
+        Ok(())
+    }
+
+    /// Returns the node bound to the variable of the pattern, or creates it.
+    fn create_node(&mut self, pattern: &NodePattern, row: &mut Row) -> Result<Node> {
+        if let Some(variable) = &pattern.variable {
+            match row.get(&variable.name) {
+                Some(Value::Node(node)) if pattern.label_expression.is_none() && pattern.properties.is_none() => {
+                    return Ok(self.current_node(node));
+                }
+                Some(_) => return Err(ExecutionError::new(format!("variable `{}` is already declared", variable.name))),
+                None => (),
+            }
+        }
+        let mut labels = Vec::new();
+        if let Some(label_expression) = &pattern.label_expression {
+            self.static_labels(label_expression, row, &mut labels)?;
+        }
+        let properties = self.stored_properties(pattern.properties.as_ref(), row)?;
+        let id = self.graph.create_node(labels, properties);
+        let node = self.graph.node(id).cloned().expect("the node was created");
+        if let Some(variable) = &pattern.variable {
+            row.insert(variable.name.clone(), Value::Node(node.clone()));
+        }
+        Ok(node)
+    }
+
+    /// Collects the labels of a conjunction of labels, which a created node gets.
+    fn static_labels(&mut self, expression: &LabelExpression, row: &Row, labels: &mut Vec<String>) -> Result<()> {
+        match expression {
+            LabelExpression::Label(LabelOrType::Static(label)) => labels.push(unescape(label)),
+            LabelExpression::Label(LabelOrType::Dynamic(expression)) => {
+                labels.extend(self.dynamic_labels(expression, row)?);
+            }
+            LabelExpression::And(expressions) => {
+                for expression in expressions {
+                    self.static_labels(expression, row, labels)?;
+                }
+            }
+            _ => return Err(ExecutionError::new("only a conjunction of labels can be created")),
+        }
+        Ok(())
+    }
+
+    /// Evaluates the properties of a created node or relationship.
+    fn stored_properties(&mut self, properties: Option<&Properties>, row: &Row) -> Result<BTreeMap<String, Value>> {
+        let Some(properties) = properties else { return Ok(BTreeMap::new()) };
+        let properties = self.properties(properties, row)?;
+        for (key, value) in &properties {
+            check_property(key, value)?;
+        }
+        Ok(properties)
+    }
+
+    fn set_items(&mut self, items: &[SetItem], row: &Row) -> Result<()> {
+        for item in items {
+            match item {
+                SetItem::AssignPropertyExpression { property_expression, expression } => {
+                    let value = self.evaluate(expression, row)?;
+                    let Some((target, key)) = self.property_target(property_expression, row)? else { continue };
+                    check_property(&key, &value)?;
+                    self.update_properties(&target, |properties| {
+                        if value.is_null() {
+                            properties.remove(&key);
+                        }
+                        else {
+                            properties.insert(key, value);
+                        }
+                    })?;
+                }
+                SetItem::AssignVariable { variable, expression } | SetItem::IncrementVariable { variable, expression } => {
+                    let target = self.variable(&variable.name, row)?;
+                    let values = match self.evaluate(expression, row)? {
+                        Value::Map(map) => map,
+                        Value::Node(node) => self.current_node(&node).properties,
+                        Value::Relationship(relationship) => {
+                            self.graph.relationship(relationship.id).cloned().unwrap_or(relationship).properties
+                        }
+                        value => return Err(ExecutionError::new(format!("cannot set the properties to {}", value.type_name()))),
+                    };
+                    for (key, value) in &values {
+                        check_property(key, value)?;
+                    }
+                    let replace = matches!(item, SetItem::AssignVariable { .. });
+                    self.update_properties(&target, |properties| {
+                        if replace {
+                            properties.clear();
+                        }
+                        for (key, value) in values {
+                            if value.is_null() {
+                                properties.remove(&key);
+                            }
+                            else {
+                                properties.insert(key, value);
+                            }
+                        }
+                    })?;
+                }
+                SetItem::VariableNodeLabels { variable, node_labels } => {
+                    let Value::Node(node) = self.variable(&variable.name, row)? else { continue };
+                    let labels = self.node_labels(node_labels, row)?;
+                    if let Some(node) = self.graph.node_mut(node.id) {
+                        for label in labels {
+                            if !node.labels.contains(&label) {
+                                node.labels.push(label);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_item(&mut self, item: &RemoveItem, row: &Row) -> Result<()> {
+        match item {
+            RemoveItem::VariableNodeLabels { variable, node_labels } => {
+                let Value::Node(node) = self.variable(&variable.name, row)? else { return Ok(()) };
+                let labels = self.node_labels(node_labels, row)?;
+                if let Some(node) = self.graph.node_mut(node.id) {
+                    node.labels.retain(|label| !labels.contains(label));
+                }
+            }
+            RemoveItem::PropertyExpression(property_expression) => {
+                let Some((target, key)) = self.property_target(property_expression, row)? else { return Ok(()) };
+                self.update_properties(&target, |properties| {
+                    properties.remove(&key);
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn variable(&self, name: &str, row: &Row) -> Result<Value> {
+        row.get(name).cloned().ok_or_else(|| ExecutionError::new(format!("variable `{}` is not defined", name)))
+    }
+
+    fn node_labels(&mut self, labels: &[LabelOrType], row: &Row) -> Result<Vec<String>> {
+        let mut result = Vec::new();
+        for label in labels {
+            match label {
+                LabelOrType::Static(label) => result.push(unescape(label)),
+                LabelOrType::Dynamic(expression) => {
+                    result.extend(self.dynamic_labels(expression, row)?);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Evaluates the entity and key of a property expression such as
+    /// `n.address.city`, or `None` if the entity is `null`.
+    fn property_target(&mut self, expression: &PropertyExpression, row: &Row) -> Result<Option<(Value, String)>> {
+        let (last, path) = expression.property_path.split_last().expect("a property expression has a key");
+        let mut target = self.evaluate(&atom_expression(&expression.atom), row)?;
+        for key in path {
+            let key = self.property_key(key, row)?;
+            target = self.property(target, &key)?;
+        }
+        if target.is_null() {
+            return Ok(None);
+        }
+        Ok(Some((target, self.property_key(last, row)?)))
+    }
+
+    fn property_key(&mut self, key: &PropertyKey, row: &Row) -> Result<String> {
+        match key {
+            PropertyKey::Static(key) => Ok(unescape(key)),
+            PropertyKey::Dynamic(expression) => match self.evaluate(expression, row)? {
+                Value::String(key) => Ok(key),
+                value => Err(ExecutionError::new(format!("expected a STRING key but got {}", value.type_name()))),
+            },
+        }
+    }
+
+    /// Changes the properties of a node or relationship. Entities deleted
+    /// before are left alone, and `null` is ignored.
+    fn update_properties(&mut self, target: &Value, update: impl FnOnce(&mut BTreeMap<String, Value>)) -> Result<()> {
+        match target {
+            Value::Null => (),
+            Value::Node(node) => {
+                if let Some(node) = self.graph.node_mut(node.id) {
+                    update(&mut node.properties);
+                }
+            }
+            Value::Relationship(relationship) => {
+                if let Some(relationship) = self.graph.relationship_mut(relationship.id) {
+                    update(&mut relationship.properties);
+                }
+            }
+            value => return Err(ExecutionError::new(format!("cannot set properties of {}", value.type_name()))),
+        }
+        Ok(())
+    }
+
+    /// Builds the path of a pattern part whose nodes and relationships are bound.
+    fn project_path(&mut self, part: &PatternPart, row: &Row) -> Result<Value> {
+        let element = &part.pattern_element;
+        let start = match element.node_pattern.variable.as_ref().and_then(|variable| row.get(&variable.name)) {
+            Some(Value::Node(node)) => node.clone(),
+            _ => return Ok(Value::Null),
+        };
+        let mut path = Path { nodes: vec![start], relationships: Vec::new() };
+        for (relationship, _) in &element.relationship_patterns {
+            let (RelationshipPattern::LeftAndRight(detail)
+            | RelationshipPattern::Left(detail)
+            | RelationshipPattern::Right(detail)
+            | RelationshipPattern::Undirected(detail)) = relationship;
+            let variable = detail.as_ref().and_then(|detail| detail.variable.as_ref()).expect("projected relationships are named");
+            let relationships = match row.get(&variable.name) {
+                Some(Value::Relationship(relationship)) => vec![relationship.clone()],
+                Some(Value::List(list)) => {
+                    let mut relationships = Vec::new();
+                    for value in list {
+                        match value {
+                            Value::Relationship(relationship) => relationships.push(relationship.clone()),
+                            _ => return Ok(Value::Null),
+                        }
+                    }
+                    relationships
+                }
+                _ => return Ok(Value::Null),
+            };
+            for relationship in relationships {
+                let last = path.nodes.last().expect("a path starts with a node").id;
+                let next = if relationship.start == last { relationship.end } else { relationship.start };
+                let node = self.graph.node(next).cloned().ok_or_else(|| ExecutionError::new("the path contains a deleted node"))?;
+                path.relationships.push(relationship);
+                path.nodes.push(node);
+            }
+        }
+        Ok(Value::Path(path))
+    }
+
+    /// Returns the value with its nodes and relationships as they are now.
+    fn refresh(&self, value: Value) -> Value {
+        match value {
+            Value::Node(node) => Value::Node(self.current_node(&node)),
+            Value::Relationship(relationship) => {
+                Value::Relationship(self.graph.relationship(relationship.id).cloned().unwrap_or(relationship))
+            }
+            Value::Path(path) => Value::Path(Path {
+                nodes: path.nodes.iter().map(|node| self.current_node(node)).collect(),
+                relationships: path
+                    .relationships
+                    .into_iter()
+                    .map(|relationship| self.graph.relationship(relationship.id).cloned().unwrap_or(relationship))
+                    .collect(),
+            }),
+            Value::List(values) => Value::List(values.into_iter().map(|value| self.refresh(value)).collect()),
+            Value::Map(map) => Value::Map(map.into_iter().map(|(key, value)| (key, self.refresh(value))).collect()),
+            value => value,
+        }
+    }
+
+    fn call_procedure(&mut self, name: &QualifiedName, arguments: Vec<Value>) -> Result<Vec<Vec<Value>>> {
+        if !arguments.is_empty() {
+            return Err(ExecutionError::new(format!("{} takes no arguments", name)));
+        }
+        let values: BTreeSet<String> = match name.to_string().as_str() {
+            "db.labels" => self.graph.nodes().flat_map(|node| node.labels.iter().cloned()).collect(),
+            "db.relationshipTypes" => self.graph.relationships().map(|relationship| relationship.type_.clone()).collect(),
+            "db.propertyKeys" => self
+                .graph
+                .nodes()
+                .flat_map(|node| node.properties.keys().cloned())
+                .chain(self.graph.relationships().flat_map(|relationship| relationship.properties.keys().cloned()))
+                .collect(),
+            _ => return Err(ExecutionError::new(format!("unsupported procedure {}", name))),
+        };
+        Ok(values.into_iter().map(|value| vec![Value::String(value)]).collect())
+    }
+}
+
+/// The variable length relationship [`Executor::expand_paths`] follows.
+struct VarLength<'p> {
+    direction: Direction,
+    types: Option<&'p LabelExpression>,
+    properties: &'p BTreeMap<String, Value>,
+    min: u64,
+    max: Option<u64>,
+}
+
+/// Returns the output fields of a supported procedure.
+fn procedure_outputs(name: &QualifiedName) -> Result<&'static [&'static str]> {
+    match name.to_string().as_str() {
+        "db.labels" => Ok(&["label"]),
+        "db.relationshipTypes" => Ok(&["relationshipType"]),
+        "db.propertyKeys" => Ok(&["propertyKey"]),
+        _ => Err(ExecutionError::new(format!("unsupported procedure {}", name))),
+    }
+}
+
+fn check_property(key: &str, value: &Value) -> Result<()> {
+    if value.is_null() || is_property_value(value) {
+        Ok(())
+    }
+    else {
+        Err(ExecutionError::new(format!("property `{}` cannot be set to {}", key, value.type_name())))
+    }
+}
+
+fn atom_expression(atom: &Atom) -> Expression {
+    Expression::from_atom(atom.clone())
+}
+
+/// Values compared by their `ORDER BY` order, to group rows and to remove
+/// duplicates.
+struct Key(Vec<Value>);
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (a, b) in self.0.iter().zip(&other.0) {
+            match a.order(b) {
+                Ordering::Equal => (),
+                ordering => return ordering,
+            }
+        }
+        self.0.len().cmp(&other.0.len())
+    }
+}
@@ -0,0 +1,424 @@
+//! The built-in functions.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use super::exec::{Executor, Row};
+use super::ExecutionError;
+use crate::analysis::functions::{FunctionRegistry, FunctionSignature};
+use crate::ast::cypher::value::Value;
+use crate::ast::cypher::{Atom, FunctionInvocation};
+
+type Result<T> = std::result::Result<T, ExecutionError>;
+
+fn argument_error(function: &str, value: &Value) -> ExecutionError {
+    ExecutionError::new(format!("{}() does not accept {}", function, value.type_name()))
+}
+
+/// The most elements `range()` creates, as its list is built in memory.
+const MAX_RANGE_LENGTH: i128 = 1_000_000;
+
+/// Returns whether the function of the name, in lower case, is an aggregate function.
+pub(super) fn is_aggregate(name: &str) -> bool {
+    matches!(name, "avg" | "collect" | "count" | "max" | "min" | "percentilecont" | "percentiledisc" | "stdev" | "stdevp" | "sum")
+}
+
+/// The scalar functions the engine implements, in lower case. Their
+/// arities come from the built-in function catalog.
+const SCALAR_FUNCTIONS: &[&str] = &[
+    "abs", "acos", "asin", "atan", "atan2", "ceil", "char_length", "character_length", "coalesce", "cos", "e", "elementid",
+    "endnode", "exists", "exp", "floor", "head", "id", "isempty", "keys", "labels", "last", "left", "length", "log", "log10",
+    "lower", "ltrim", "nodes", "nullif", "pi", "properties", "range", "relationships", "replace", "reverse", "right", "round",
+    "rtrim", "sign", "sin", "size", "split", "sqrt", "startnode", "substring", "tail", "tan", "toboolean", "tofloat",
+    "tointeger", "tolower", "tostring", "toupper", "trim", "type", "upper",
+];
+
+/// Returns the catalog signature of a function the engine implements.
+fn signature(function: &FunctionInvocation, name: &str) -> Option<&'static FunctionSignature> {
+    static REGISTRY: OnceLock<FunctionRegistry> = OnceLock::new();
+    if !SCALAR_FUNCTIONS.contains(&name) {
+        return None;
+    }
+    REGISTRY.get_or_init(FunctionRegistry::builtin).lookup(&function.function_name)
+}
+
+impl Executor<'_> {
+    pub(super) fn call_function(&mut self, function: &FunctionInvocation, row: &Row) -> Result<Value> {
+        let name = function.function_name.parts().collect::<Vec<_>>().join(".").to_lowercase();
+        if is_aggregate(&name) {
+            return Err(ExecutionError::new(format!("{}() can only be used in RETURN and WITH", function.function_name)));
+        }
+        let signature = signature(function, &name)
+            .ok_or_else(|| ExecutionError::new(format!("unsupported function {}()", function.function_name)))?;
+        let count = function.arguments.len();
+        if count < signature.min_arguments() || signature.max_arguments().is_some_and(|max| count > max) {
+            let expected = match signature.max_arguments() {
+                Some(max) if max == signature.min_arguments() => max.to_string(),
+                Some(max) => format!("{} to {}", signature.min_arguments(), max),
+                None => format!("at least {}", signature.min_arguments()),
+            };
+            return Err(ExecutionError::new(format!("{}() takes {} arguments but got {}", function.function_name, expected, count)));
+        }
+        if name == "round" && count == 3 {
+            return Err(ExecutionError::new("round() does not support a rounding mode"));
+        }
+        let mut arguments = Vec::new();
+        for argument in &function.arguments {
+            arguments.push(self.evaluate(argument, row)?);
+        }
+        if !matches!(name.as_str(), "coalesce" | "nullif" | "exists") && arguments.iter().any(Value::is_null) {
+            return Ok(Value::Null);
+        }
+        let mut arguments = arguments.into_iter();
+        let mut next = || arguments.next().expect("the number of arguments is checked");
+        match name.as_str() {
+            "coalesce" => Ok(std::iter::from_fn(|| arguments.next()).find(|value| !value.is_null()).unwrap_or(Value::Null)),
+            "nullif" => {
+                let (a, b) = (next(), next());
+                Ok(if a.equals(&b) == Some(true) { Value::Null } else { a })
+            }
+            // A pattern already evaluates to whether it exists.
+            "exists" if matches!(function.arguments[0].as_atom(), Some(Atom::RelationshipsPattern(_))) => Ok(next()),
+            "exists" => Ok(Value::Boolean(!next().is_null())),
+            "id" => match next() {
+                Value::Node(node) => Ok(Value::Integer(node.id as i64)),
+                Value::Relationship(relationship) => Ok(Value::Integer(relationship.id as i64)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "elementid" => match next() {
+                Value::Node(node) => Ok(Value::String(format!("node:{}", node.id))),
+                Value::Relationship(relationship) => Ok(Value::String(format!("relationship:{}", relationship.id))),
+                value => Err(argument_error(&name, &value)),
+            },
+            "labels" => match next() {
+                Value::Node(node) => Ok(Value::from(self.current_node(&node).labels)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "type" => match next() {
+                Value::Relationship(relationship) => Ok(Value::String(relationship.type_)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "keys" | "properties" => {
+                let properties = self.properties_of(next(), &name)?;
+                match name.as_str() {
+                    "keys" => Ok(Value::from(properties.into_keys().collect::<Vec<_>>())),
+                    _ => Ok(Value::Map(properties)),
+                }
+            }
+            "startnode" | "endnode" => match next() {
+                Value::Relationship(relationship) => {
+                    let relationship = self.graph.relationship(relationship.id).cloned().unwrap_or(relationship);
+                    let id = if name == "startnode" { relationship.start } else { relationship.end };
+                    Ok(self.graph.node(id).cloned().map_or(Value::Null, Value::Node))
+                }
+                value => Err(argument_error(&name, &value)),
+            },
+            "nodes" => match next() {
+                Value::Path(path) => Ok(Value::List(path.nodes.into_iter().map(Value::Node).collect())),
+                value => Err(argument_error(&name, &value)),
+            },
+            "relationships" => match next() {
+                Value::Path(path) => Ok(Value::List(path.relationships.into_iter().map(Value::Relationship).collect())),
+                value => Err(argument_error(&name, &value)),
+            },
+            "length" => match next() {
+                Value::Path(path) => Ok(Value::Integer(path.relationships.len() as i64)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "size" => match next() {
+                Value::List(list) => Ok(Value::Integer(list.len() as i64)),
+                Value::String(text) => Ok(Value::Integer(text.chars().count() as i64)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "char_length" | "character_length" => match next() {
+                Value::String(text) => Ok(Value::Integer(text.chars().count() as i64)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "isempty" => match next() {
+                Value::List(list) => Ok(Value::Boolean(list.is_empty())),
+                Value::Map(map) => Ok(Value::Boolean(map.is_empty())),
+                Value::String(text) => Ok(Value::Boolean(text.is_empty())),
+                value => Err(argument_error(&name, &value)),
+            },
+            "head" | "last" | "tail" => match next() {
+                Value::List(mut list) => Ok(match name.as_str() {
+                    "head" => list.into_iter().next().unwrap_or(Value::Null),
+                    "last" => list.pop().unwrap_or(Value::Null),
+                    _ => Value::List(list.into_iter().skip(1).collect()),
+                }),
+                value => Err(argument_error(&name, &value)),
+            },
+            "reverse" => match next() {
+                Value::List(mut list) => {
+                    list.reverse();
+                    Ok(Value::List(list))
+                }
+                Value::String(text) => Ok(Value::String(text.chars().rev().collect())),
+                value => Err(argument_error(&name, &value)),
+            },
+            "range" => {
+                let (start, end) = (integer(&name, next())?, integer(&name, next())?);
+                let step = if count == 3 { integer(&name, next())? } else { 1 };
+                if step == 0 {
+                    return Err(ExecutionError::new("range() requires a step other than 0"));
+                }
+                let length = ((i128::from(end) - i128::from(start)) / i128::from(step) + 1).max(0);
+                if length > MAX_RANGE_LENGTH {
+                    return Err(ExecutionError::new(format!("range() would create {} elements, more than {}", length, MAX_RANGE_LENGTH)));
+                }
+                let mut values = Vec::new();
+                let mut value = start;
+                while (step > 0 && value <= end) || (step < 0 && value >= end) {
+                    values.push(Value::Integer(value));
+                    value = match value.checked_add(step) {
+                        Some(value) => value,
+                        None => break,
+                    };
+                }
+                Ok(Value::List(values))
+            }
+            "tostring" => match next() {
+                Value::String(text) => Ok(Value::String(text)),
+                value @ (Value::Integer(_) | Value::Float(_) | Value::Boolean(_)) => Ok(Value::String(value.to_string())),
+                value => Err(argument_error(&name, &value)),
+            },
+            "tointeger" => match next() {
+                Value::Integer(value) => Ok(Value::Integer(value)),
+                Value::Float(value) => Ok(float_to_integer(value)),
+                Value::Boolean(value) => Ok(Value::Integer(value as i64)),
+                Value::String(text) => Ok(match text.trim().parse::<i64>() {
+                    Ok(value) => Value::Integer(value),
+                    Err(_) => text.trim().parse::<f64>().map_or(Value::Null, float_to_integer),
+                }),
+                value => Err(argument_error(&name, &value)),
+            },
+            "tofloat" => match next() {
+                Value::Integer(value) => Ok(Value::Float(value as f64)),
+                Value::Float(value) => Ok(Value::Float(value)),
+                Value::String(text) => Ok(text.trim().parse::<f64>().map_or(Value::Null, Value::Float)),
+                value => Err(argument_error(&name, &value)),
+            },
+            "toboolean" => match next() {
+                Value::Boolean(value) => Ok(Value::Boolean(value)),
+                Value::Integer(value) => Ok(Value::Boolean(value != 0)),
+                Value::String(text) => Ok(match text.trim().to_lowercase().as_str() {
+                    "true" => Value::Boolean(true),
+                    "false" => Value::Boolean(false),
+                    _ => Value::Null,
+                }),
+                value => Err(argument_error(&name, &value)),
+            },
+            "abs" => match next() {
+                Value::Integer(value) => value.checked_abs().map(Value::Integer).ok_or_else(|| ExecutionError::new("integer overflow")),
+                Value::Float(value) => Ok(Value::Float(value.abs())),
+                value => Err(argument_error(&name, &value)),
+            },
+            "sign" => match next() {
+                Value::Integer(value) => Ok(Value::Integer(value.signum())),
+                Value::Float(value) => Ok(Value::Integer(if value > 0.0 { 1 } else if value < 0.0 { -1 } else { 0 })),
+                value => Err(argument_error(&name, &value)),
+            },
+            "round" => {
+                let value = float(&name, next())?;
+                let precision = if count == 2 { integer(&name, next())? } else { 0 };
+                // Beyond 400 digits either way a float is already rounded, or rounds to zero.
+                let scale = 10f64.powi(precision.clamp(-400, 400) as i32);
+                if scale == 0.0 {
+                    return Ok(Value::Float(0.0));
+                }
+                let scaled = value * scale;
+                if !scaled.is_finite() || scaled.abs() >= 2f64.powi(52) {
+                    return Ok(Value::Float(value));
+                }
+                // Halves are rounded towards positive infinity.
+                Ok(Value::Float((scaled + 0.5).floor() / scale))
+            }
+            "pi" => Ok(Value::Float(std::f64::consts::PI)),
+            "e" => Ok(Value::Float(std::f64::consts::E)),
+            "atan2" => {
+                let (y, x) = (float(&name, next())?, float(&name, next())?);
+                Ok(Value::Float(y.atan2(x)))
+            }
+            "ceil" | "floor" | "sqrt" | "exp" | "log" | "log10" | "sin" | "cos" | "tan" | "asin" | "acos" | "atan" => {
+                let value = float(&name, next())?;
+                Ok(Value::Float(match name.as_str() {
+                    "ceil" => value.ceil(),
+                    "floor" => value.floor(),
+                    "sqrt" => value.sqrt(),
+                    "exp" => value.exp(),
+                    "log" => value.ln(),
+                    "log10" => value.log10(),
+                    "sin" => value.sin(),
+                    "cos" => value.cos(),
+                    "tan" => value.tan(),
+                    "asin" => value.asin(),
+                    "acos" => value.acos(),
+                    _ => value.atan(),
+                }))
+            }
+            "toupper" | "upper" => Ok(Value::String(string(&name, next())?.to_uppercase())),
+            "tolower" | "lower" => Ok(Value::String(string(&name, next())?.to_lowercase())),
+            "trim" => Ok(Value::String(string(&name, next())?.trim().to_string())),
+            "ltrim" | "rtrim" => {
+                let text = string(&name, next())?;
+                let characters: Option<Vec<char>> = if count == 2 { Some(string(&name, next())?.chars().collect()) } else { None };
+                let trimmed = match (name.as_str(), &characters) {
+                    ("ltrim", None) => text.trim_start(),
+                    ("ltrim", Some(characters)) => text.trim_start_matches(characters.as_slice()),
+                    (_, None) => text.trim_end(),
+                    (_, Some(characters)) => text.trim_end_matches(characters.as_slice()),
+                };
+                Ok(Value::String(trimmed.to_string()))
+            }
+            "replace" => {
+                let (text, search, replacement) = (string(&name, next())?, string(&name, next())?, string(&name, next())?);
+                Ok(Value::String(text.replace(&search, &replacement)))
+            }
+            "split" => {
+                let (text, delimiter) = (string(&name, next())?, string(&name, next())?);
+                Ok(Value::from(text.split(delimiter.as_str()).map(str::to_string).collect::<Vec<_>>()))
+            }
+            "substring" | "left" | "right" => {
+                let text: Vec<char> = string(&name, next())?.chars().collect();
+                let (start, length) = match name.as_str() {
+                    "substring" => {
+                        let start = index(&name, next())?;
+                        (start, if count == 3 { index(&name, next())? } else { text.len() })
+                    }
+                    "left" => (0, index(&name, next())?),
+                    _ => {
+                        let length = index(&name, next())?;
+                        (text.len().saturating_sub(length), length)
+                    }
+                };
+                Ok(Value::String(text.iter().skip(start).take(length).collect()))
+            }
+            _ => Err(ExecutionError::new(format!("unknown function {}()", function.function_name))),
+        }
+    }
+
+    /// Returns the current properties of a node or relationship, or the entries of a map.
+    fn properties_of(&self, value: Value, function: &str) -> Result<BTreeMap<String, Value>> {
+        match value {
+            Value::Map(map) => Ok(map),
+            Value::Node(node) => Ok(self.current_node(&node).properties),
+            Value::Relationship(relationship) => {
+                Ok(self.graph.relationship(relationship.id).cloned().unwrap_or(relationship).properties)
+            }
+            value => Err(argument_error(function, &value)),
+        }
+    }
+}
+
+fn integer(function: &str, value: Value) -> Result<i64> {
+    match value {
+        Value::Integer(value) => Ok(value),
+        value => Err(argument_error(function, &value)),
+    }
+}
+
+fn index(function: &str, value: Value) -> Result<usize> {
+    let value = integer(function, value)?;
+    usize::try_from(value).map_err(|_| ExecutionError::new(format!("{}() requires a non-negative INTEGER", function)))
+}
+
+fn float(function: &str, value: Value) -> Result<f64> {
+    match value {
+        Value::Integer(value) => Ok(value as f64),
+        Value::Float(value) => Ok(value),
+        value => Err(argument_error(function, &value)),
+    }
+}
+
+fn string(function: &str, value: Value) -> Result<String> {
+    match value {
+        Value::String(value) => Ok(value),
+        value => Err(argument_error(function, &value)),
+    }
+}
+
+fn float_to_integer(value: f64) -> Value {
+    if value.is_finite() && value.trunc() >= i64::MIN as f64 && value.trunc() < i64::MAX as f64 {
+        Value::Integer(value.trunc() as i64)
+    }
+    else {
+        Value::Null
+    }
+}
+
+/// Computes an aggregate function over the values of a group. `null`s are
+/// skipped, and with `distinct`, values equivalent to an earlier one.
+/// Aggregates the values of a group. The percentile of `percentileCont` and
+/// `percentileDisc` is only checked when the group has a value.
+pub(super) fn aggregate(name: &str, values: Vec<Value>, distinct: bool, percentile: Option<&Value>) -> Result<Value> {
+    let mut values: Vec<Value> = values.into_iter().filter(|value| !value.is_null()).collect();
+    if distinct {
+        let mut unique: Vec<Value> = Vec::new();
+        for value in values {
+            if !unique.iter().any(|seen| seen.is_equivalent(&value)) {
+                unique.push(value);
+            }
+        }
+        values = unique;
+    }
+    match name {
+        "count" => Ok(Value::Integer(values.len() as i64)),
+        "collect" => Ok(Value::List(values)),
+        "min" => Ok(values.into_iter().min_by(Value::order).unwrap_or(Value::Null)),
+        "max" => Ok(values.into_iter().max_by(Value::order).unwrap_or(Value::Null)),
+        "sum" => {
+            let mut sum = Value::Integer(0);
+            for value in values {
+                sum = match (sum, value) {
+                    (Value::Integer(a), Value::Integer(b)) => Value::Integer(a.checked_add(b).ok_or_else(|| ExecutionError::new("integer overflow"))?),
+                    (a, b) => Value::Float(float(name, a)? + float(name, b)?),
+                };
+            }
+            Ok(sum)
+        }
+        "avg" => {
+            if values.is_empty() {
+                return Ok(Value::Null);
+            }
+            let count = values.len() as f64;
+            let numbers = values.into_iter().map(|value| float(name, value)).collect::<Result<Vec<_>>>()?;
+            Ok(Value::Float(numbers.iter().sum::<f64>() / count))
+        }
+        "stdev" | "stdevp" => {
+            let numbers = values.into_iter().map(|value| float(name, value)).collect::<Result<Vec<_>>>()?;
+            let count = numbers.len() as f64;
+            let divisor = if name == "stdev" { count - 1.0 } else { count };
+            if divisor <= 0.0 {
+                return Ok(Value::Float(0.0));
+            }
+            let mean = numbers.iter().sum::<f64>() / count;
+            Ok(Value::Float((numbers.iter().map(|number| (number - mean).powi(2)).sum::<f64>() / divisor).sqrt()))
+        }
+        "percentilecont" | "percentiledisc" => {
+            if values.is_empty() {
+                return Ok(Value::Null);
+            }
+            let percentile = match percentile {
+                Some(Value::Integer(value)) => *value as f64,
+                Some(Value::Float(value)) => *value,
+                _ => f64::NAN,
+            };
+            if !(0.0..=1.0).contains(&percentile) {
+                return Err(ExecutionError::new("the percentile must be a number between 0.0 and 1.0"));
+            }
+            let mut values = values;
+            for value in &values {
+                float(name, value.clone())?;
+            }
+            values.sort_by(Value::order);
+            if name == "percentiledisc" {
+                let index = ((percentile * values.len() as f64).ceil() as usize).saturating_sub(1);
+                return Ok(values.swap_remove(index.min(values.len() - 1)));
+            }
+            let position = percentile * (values.len() - 1) as f64;
+            let (lower, upper) = (float(name, values[position.floor() as usize].clone())?, float(name, values[position.ceil() as usize].clone())?);
+            Ok(Value::Float(lower + (upper - lower) * position.fract()))
+        }
+        _ => Err(ExecutionError::new(format!("unknown aggregate function {}()", name))),
+    }
+}
@@ -0,0 +1,116 @@
+//! The in-memory property graph.
+
+use std::collections::BTreeMap;
+
+use super::ExecutionError;
+use crate::ast::cypher::value::{Node, Relationship, Value};
+
+/// Nodes with labels and properties, connected by relationships with a type
+/// and properties. Nodes and relationships are identified by numbers that
+/// are never reused.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: BTreeMap<u64, Node>,
+    relationships: BTreeMap<u64, Relationship>,
+    next_node_id: u64,
+    next_relationship_id: u64,
+}
+
+impl Graph {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Graph::default()
+    }
+
+    pub fn node(&self, id: u64) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    pub fn relationship(&self, id: u64) -> Option<&Relationship> {
+        self.relationships.get(&id)
+    }
+
+    /// Returns the nodes in the order they were created.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.nodes.values()
+    }
+
+    /// Returns the relationships in the order they were created.
+    pub fn relationships(&self) -> impl Iterator<Item = &Relationship> {
+        self.relationships.values()
+    }
+
+    /// Returns the relationships that start or end at the node.
+    pub fn relationships_of(&self, id: u64) -> impl Iterator<Item = &Relationship> {
+        self.relationships.values().filter(move |relationship| relationship.start == id || relationship.end == id)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn relationship_count(&self) -> usize {
+        self.relationships.len()
+    }
+
+    /// Creates a node and returns its id. Properties that are `null` are
+    /// not stored.
+    pub fn create_node(&mut self, labels: Vec<String>, properties: BTreeMap<String, Value>) -> u64 {
+        let id = self.next_node_id;
+        self.next_node_id += 1;
+        let mut unique = Vec::new();
+        for label in labels {
+            if !unique.contains(&label) {
+                unique.push(label);
+            }
+        }
+        let properties = properties.into_iter().filter(|(_, value)| !value.is_null()).collect();
+        self.nodes.insert(id, Node { id, labels: unique, properties });
+        id
+    }
+
+    /// Creates a relationship between two nodes and returns its id.
+    /// Properties that are `null` are not stored.
+    pub fn create_relationship(
+        &mut self,
+        type_: &str,
+        start: u64,
+        end: u64,
+        properties: BTreeMap<String, Value>,
+    ) -> Result<u64, ExecutionError> {
+        if !self.nodes.contains_key(&start) || !self.nodes.contains_key(&end) {
+            return Err(ExecutionError::new("cannot create a relationship to a deleted node"));
+        }
+        let id = self.next_relationship_id;
+        self.next_relationship_id += 1;
+        let properties = properties.into_iter().filter(|(_, value)| !value.is_null()).collect();
+        self.relationships.insert(id, Relationship { id, type_: type_.to_string(), start, end, properties });
+        Ok(id)
+    }
+
+    /// Deletes a node. Unless `detach` is set, the node must not have
+    /// relationships, otherwise they are deleted with it.
+    pub fn delete_node(&mut self, id: u64, detach: bool) -> Result<(), ExecutionError> {
+        let relationships: Vec<u64> = self.relationships_of(id).map(|relationship| relationship.id).collect();
+        if !relationships.is_empty() && !detach {
+            return Err(ExecutionError::new("cannot delete a node that still has relationships, use DETACH DELETE"));
+        }
+        for relationship in relationships {
+            self.relationships.remove(&relationship);
+        }
+        self.nodes.remove(&id);
+        Ok(())
+    }
+
+    pub fn delete_relationship(&mut self, id: u64) {
+        self.relationships.remove(&id);
+    }
+
+    pub(super) fn node_mut(&mut self, id: u64) -> Option<&mut Node> {
+        self.nodes.get_mut(&id)
+    }
+
+    pub(super) fn relationship_mut(&mut self, id: u64) -> Option<&mut Relationship> {
+        self.relationships.get_mut(&id)
+    }
+}
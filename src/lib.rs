@@ -1,5 +1,13 @@
+// Parsers return pest's `Error` unboxed, as pest itself does; it is large, but
+// only built once when a parse fails.
+#![allow(clippy::result_large_err)]
+
+pub mod analysis;
 pub mod ast;
+#[cfg(feature = "engine")]
+pub mod engine;
 pub mod parser;
+pub mod plan;
 
 pub use parser::cypher::parse as parse_cypher;
 pub use parser::iso_39075::parse as parse_gql;
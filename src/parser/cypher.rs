@@ -4,38 +4,29 @@ extern crate pest_derive;
 use std::collections::HashMap;
 
 use pest::Parser;
-use pest::error::Error;
+use pest::error::{Error, ErrorVariant};
 use pest::iterators::Pair;
+use pest::Position;
 use pest_derive::Parser;
 
-use crate::ast::cypher::{AddOrSubtractExpression, PlusMinusOperator, AndExpression, Atom, CaseExpression, ComparisonExpression, ComparisonOperator, Create, Delete, ExplicitProcedureInvocation, Expression, FilterExpression, FunctionInvocation, IdInColl, ImplicitProcedureInvocation, InQueryCall, Limit, ListComprehension, ListOperatorExpression, Literal, Match, Merge, MergeAction, MultiPartQuery, MultiPartQueryPart, MultiplyDivideModuloExpression, MultiplyDivideModuloOperator, NodePattern, NotExpression, NullOperatorExpression, NumberLiteral, OrExpression, Order, Pattern, PatternComprehension, PatternElement, PatternPart, PowerOfExpression, ProcedureInvocation, ProjectionBody, ProjectionItem, ProjectionItems, Properties, PropertyExpression, PropertyOrLabelsExpression, Query, ReadingClause, RegularQuery, RelationshipDetail, RelationshipPattern, RelationshipsPattern, Remove, RemoveItem, Set, SetItem, SinglePartQuery, SingleQuery, Skip, SortItem, StandaloneCall, StarOrYieldItems, StringListNullOperatorExpression, StringListNullOperatorExpressionInner, StringOperator, StringOperatorExpression, UnaryAddOrSubtractExpression, Union, Unwind, UpdatingClause, With, XorExpression, YieldItem, YieldItems};
+use crate::ast::cypher::{AddOrSubtractExpression, PlusMinusOperator, AndExpression, Atom, CallSubquery, CaseExpression, ComparisonExpression, ComparisonOperator, ConstraintEntity, ConstraintKind, CountStar, Create, CreateConstraint, CreateIndex, CypherOptions, CypherType, Delete, DropConstraint, DropIndex, ExecutionMode, ExplicitProcedureInvocation, ExistentialSubquery, Expression, Filter, FilterExpression, Foreach, FunctionInvocation, GraphReference, IdInColl, ImplicitProcedureInvocation, InQueryCall, IndexKind, LabelExpression, LabelOrType, Limit, ListComprehension, ListOperatorExpression, Literal, LoadCsv, MapProjection, MapProjectionItem, Match, Merge, MergeAction, MultiPartQuery, MultiPartQueryPart, MultiplyDivideModuloExpression, MultiplyDivideModuloOperator, NodePattern, NormalForm, NormalizedPredicateExpression, NotExpression, NullsOrder, NullOperatorExpression, NumberLiteral, OrExpression, Order, OrderSkipLimit, Pattern, PatternComprehension, PatternElement, PatternPart, PowerOfExpression, ProcedureInvocation, ProjectionBody, ProjectionItem, ProjectionItems, Properties, PropertyExpression, PropertyKey, PropertyOrLabelsExpression, QualifiedName, Query, RangeLiteral, ReadingClause, ReduceExpression, RegularQuery, RelationshipDetail, RelationshipPattern, RelationshipsPattern, Remove, RemoveItem, SchemaCommand, SchemaProperty, SchemaTarget, Set, SetItem, SinglePartQuery, SingleQuery, Skip, SortDirection, SortItem, Span, StandaloneCall, Statement, StatementBody, StarOrYieldItems, StringListNullOperatorExpression, StringListNullOperatorExpressionInner, StringOperator, StringOperatorExpression, TypePredicateExpression, UnaryAddOrSubtractExpression, Union, Unwind, UpdatingClause, Use, Variable, With, XorExpression, YieldItem, YieldItems};
 
 #[derive(Parser)]
 #[grammar = "cypher.pest"]
 pub struct CypherParser;
 
-pub fn parse(code: &str) -> Result<Query, Error<Rule>> {
-    Query::parse(code)
+pub fn parse(code: &str) -> Result<Statement, Error<Rule>> {
+    Statement::parse(code)
 }
 
-impl Query {
-    pub fn parse(code: &str) -> Result<Query, Error<Rule>> {
+impl Statement {
+    pub fn parse(code: &str) -> Result<Statement, Error<Rule>> {
         for pair in CypherParser::parse(Rule::Cypher, code)? {
             match pair.as_rule() {
                 Rule::Cypher => for pair in pair.into_inner() {
                     match pair.as_rule() {
-                        Rule::Statement => for pair in pair.into_inner() {
-                            match pair.as_rule() {
-                                Rule::Query => for pair in pair.into_inner() {
-                                    match pair.as_rule() {
-                                        Rule::RegularQuery => return Ok(Query::RegularQuery(RegularQuery::parse(pair)?)),
-                                        Rule::StandaloneCall => return Ok(Query::StandaloneCall(StandaloneCall::parse(pair)?)),
-                                        _ => unreachable!("Unexpected rule in Query: {:?}", pair.as_rule()),
-                                    }
-                                },
-                                _ => unreachable!("Unexpected rule in Statement: {:?}", pair.as_rule()),
-                            }
-                        },
+                        Rule::Statement => return parse_statement(pair),
+                        Rule::SP => (),
                         _ => unreachable!("Unexpected rule in Cypher: {:?}", pair.as_rule()),
                     }
                 }
@@ -46,41 +37,448 @@ impl Query {
     }
 }
 
+impl Query {
+    pub fn parse(code: &str) -> Result<Query, Error<Rule>> {
+        match Statement::parse(code)?.body {
+            StatementBody::Query(query) => Ok(query),
+            StatementBody::SchemaCommand(_) => Err(Error::new_from_pos(
+                ErrorVariant::CustomError { message: "expected a query but got a schema command".to_string() },
+                Position::from_start(code),
+            )),
+        }
+    }
+}
+
+#[allow(clippy::never_loop)]
+fn parse_statement(pair: Pair<'_, Rule>) -> Result<Statement, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::Statement));
+
+    let mut options = Vec::new();
+    let mut mode = ExecutionMode::Normal;
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::CypherOptions => options.push(CypherOptions::parse(pair)?),
+            Rule::ExecutionMode => mode = ExecutionMode::parse(pair)?,
+            Rule::Query => for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::RegularQuery => return Ok(Statement { options, mode, body: StatementBody::Query(Query::RegularQuery(RegularQuery::parse(pair)?)) }),
+                    Rule::StandaloneCall => return Ok(Statement { options, mode, body: StatementBody::Query(Query::StandaloneCall(StandaloneCall::parse(pair)?)) }),
+                    _ => unreachable!("Unexpected rule in Query: {:?}", pair.as_rule()),
+                }
+            },
+            Rule::SchemaCommand => return Ok(Statement { options, mode, body: StatementBody::SchemaCommand(SchemaCommand::parse(pair)?) }),
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in Statement: {:?}", pair.as_rule()),
+        }
+    }
+
+    unreachable!("Unexpected missing rule in Statement")
+}
+
+impl CypherOptions {
+    fn parse(pair: Pair<'_, Rule>) -> Result<CypherOptions, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::CypherOptions));
+
+        let mut version = None;
+        let mut options = Vec::new();
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::CypherVersion => version = Some(pair.as_str().to_string()),
+                Rule::CypherOption => {
+                    let mut inner = pair.into_inner().filter(|pair| !matches!(pair.as_rule(), Rule::SP));
+                    let key = inner.next().unwrap().as_str().to_string();
+                    let value = inner.next().unwrap().as_str().to_string();
+                    options.push((key, value));
+                },
+                Rule::SP | Rule::CYPHER => (),
+                _ => unreachable!("Unexpected rule in CypherOptions: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(CypherOptions { version, options })
+    }
+}
+
+impl ExecutionMode {
+    fn parse(pair: Pair<'_, Rule>) -> Result<ExecutionMode, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::ExecutionMode));
+
+        match pair.into_inner().next() {
+            Some(pair) => match pair.as_rule() {
+                Rule::EXPLAIN => Ok(ExecutionMode::Explain),
+                Rule::PROFILE => Ok(ExecutionMode::Profile),
+                _ => unreachable!("Unexpected rule in ExecutionMode: {:?}", pair.as_rule()),
+            },
+            None => unreachable!("Unexpected missing rule in ExecutionMode"),
+        }
+    }
+}
+
+impl SchemaCommand {
+    #[allow(clippy::never_loop)]
+    fn parse(pair: Pair<'_, Rule>) -> Result<SchemaCommand, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::SchemaCommand));
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::CreateIndex => return Ok(SchemaCommand::CreateIndex(CreateIndex::parse(pair)?)),
+                Rule::DropIndex => return Ok(SchemaCommand::DropIndex(DropIndex::parse(pair)?)),
+                Rule::CreateConstraint => return Ok(SchemaCommand::CreateConstraint(CreateConstraint::parse(pair)?)),
+                Rule::DropConstraint => return Ok(SchemaCommand::DropConstraint(DropConstraint::parse(pair)?)),
+                _ => unreachable!("Unexpected rule in SchemaCommand: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in SchemaCommand")
+    }
+}
+
+impl CreateIndex {
+    fn parse(pair: Pair<'_, Rule>) -> Result<CreateIndex, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::CreateIndex));
+
+        let mut kind = IndexKind::Range;
+        let mut name = None;
+        let mut if_not_exists = false;
+        let mut target = None;
+        let mut properties = Vec::new();
+        let mut options = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::IndexKind => kind = IndexKind::parse(pair)?,
+                Rule::SchemaObjectName => name = Some(pair.as_str().to_string()),
+                Rule::IfNotExists => if_not_exists = true,
+                Rule::SchemaTarget => target = Some(SchemaTarget::parse(pair)?),
+                Rule::IndexProperties => properties = parse_schema_properties(pair)?,
+                Rule::SchemaOptions => options = Some(parse_schema_options(pair)?),
+                Rule::SP | Rule::CREATE | Rule::INDEX | Rule::FOR | Rule::ON => (),
+                _ => unreachable!("Unexpected rule in CreateIndex: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(CreateIndex { kind, name, if_not_exists, target: target.unwrap(), properties, options })
+    }
+}
+
+impl IndexKind {
+    fn parse(pair: Pair<'_, Rule>) -> Result<IndexKind, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::IndexKind));
+
+        match pair.into_inner().next() {
+            Some(pair) => match pair.as_rule() {
+                Rule::RANGE => Ok(IndexKind::Range),
+                Rule::TEXT => Ok(IndexKind::Text),
+                Rule::POINT => Ok(IndexKind::Point),
+                Rule::FULLTEXT => Ok(IndexKind::Fulltext),
+                Rule::VECTOR => Ok(IndexKind::Vector),
+                _ => unreachable!("Unexpected rule in IndexKind: {:?}", pair.as_rule()),
+            },
+            None => unreachable!("Unexpected missing rule in IndexKind"),
+        }
+    }
+}
+
+impl DropIndex {
+    fn parse(pair: Pair<'_, Rule>) -> Result<DropIndex, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::DropIndex));
+
+        let mut name = None;
+        let mut if_exists = false;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::SchemaObjectName => name = Some(pair.as_str().to_string()),
+                Rule::IfExists => if_exists = true,
+                Rule::SP | Rule::DROP_ | Rule::INDEX => (),
+                _ => unreachable!("Unexpected rule in DropIndex: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(DropIndex { name: name.unwrap(), if_exists })
+    }
+}
+
+impl CreateConstraint {
+    fn parse(pair: Pair<'_, Rule>) -> Result<CreateConstraint, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::CreateConstraint));
+
+        let mut name = None;
+        let mut if_not_exists = false;
+        let mut target = None;
+        let mut properties = Vec::new();
+        let mut entity = None;
+        let mut kind = None;
+        let mut options = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::SchemaObjectName => name = Some(pair.as_str().to_string()),
+                Rule::IfNotExists => if_not_exists = true,
+                Rule::SchemaTarget => target = Some(SchemaTarget::parse(pair)?),
+                Rule::ConstraintProperties => properties = parse_schema_properties(pair)?,
+                Rule::ConstraintKind => {
+                    entity = pair.clone().into_inner().find(|pair| matches!(pair.as_rule(), Rule::ConstraintEntity)).map(ConstraintEntity::parse).transpose()?;
+                    kind = Some(ConstraintKind::parse(pair)?);
+                },
+                Rule::SchemaOptions => options = Some(parse_schema_options(pair)?),
+                Rule::SP | Rule::CREATE | Rule::CONSTRAINT | Rule::FOR | Rule::REQUIRE => (),
+                _ => unreachable!("Unexpected rule in CreateConstraint: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(CreateConstraint { name, if_not_exists, target: target.unwrap(), properties, entity, kind: kind.unwrap(), options })
+    }
+}
+
+impl ConstraintKind {
+    fn parse(pair: Pair<'_, Rule>) -> Result<ConstraintKind, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::ConstraintKind));
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::UNIQUE => return Ok(ConstraintKind::Unique),
+                Rule::KEY => return Ok(ConstraintKind::Key),
+                Rule::NULL => return Ok(ConstraintKind::NotNull),
+                Rule::CypherType => return Ok(ConstraintKind::Type(CypherType::parse(pair)?)),
+                Rule::SP | Rule::IS | Rule::NOT | Rule::TYPED | Rule::ConstraintEntity => (),
+                _ => unreachable!("Unexpected rule in ConstraintKind: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in ConstraintKind")
+    }
+}
+
+impl ConstraintEntity {
+    fn parse(pair: Pair<'_, Rule>) -> Result<ConstraintEntity, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::ConstraintEntity));
+
+        match pair.into_inner().next() {
+            Some(pair) => match pair.as_rule() {
+                Rule::NODE => Ok(ConstraintEntity::Node),
+                Rule::RELATIONSHIP | Rule::REL => Ok(ConstraintEntity::Relationship),
+                _ => unreachable!("Unexpected rule in ConstraintEntity: {:?}", pair.as_rule()),
+            },
+            None => unreachable!("Unexpected missing rule in ConstraintEntity"),
+        }
+    }
+}
+
+impl DropConstraint {
+    fn parse(pair: Pair<'_, Rule>) -> Result<DropConstraint, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::DropConstraint));
+
+        let mut name = None;
+        let mut if_exists = false;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::SchemaObjectName => name = Some(pair.as_str().to_string()),
+                Rule::IfExists => if_exists = true,
+                Rule::SP | Rule::DROP_ | Rule::CONSTRAINT => (),
+                _ => unreachable!("Unexpected rule in DropConstraint: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(DropConstraint { name: name.unwrap(), if_exists })
+    }
+}
+
+impl SchemaTarget {
+    fn parse(pair: Pair<'_, Rule>) -> Result<SchemaTarget, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::SchemaTarget));
+
+        let pair = pair.into_inner().next().unwrap();
+        let is_node = matches!(pair.as_rule(), Rule::SchemaNodeTarget);
+        let mut variable = None;
+        let mut names = Vec::new();
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Variable => variable = Some(parse_variable(pair)?),
+                Rule::LabelName | Rule::RelTypeName => names.push(pair.as_str().to_string()),
+                Rule::SP | Rule::LeftArrowHead | Rule::RightArrowHead | Rule::Dash => (),
+                _ => unreachable!("Unexpected rule in SchemaTarget: {:?}", pair.as_rule()),
+            }
+        }
+
+        if is_node {
+            Ok(SchemaTarget::Node { variable: variable.unwrap(), labels: names })
+        }
+        else {
+            Ok(SchemaTarget::Relationship { variable: variable.unwrap(), relationship_types: names })
+        }
+    }
+}
+
+fn parse_schema_properties(pair: Pair<'_, Rule>) -> Result<Vec<SchemaProperty>, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::IndexProperties) || matches!(pair.as_rule(), Rule::ConstraintProperties));
+
+    let mut properties = Vec::new();
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::SchemaProperty => properties.push(SchemaProperty::parse(pair)?),
+            Rule::SchemaPropertyList => for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::SchemaProperty => properties.push(SchemaProperty::parse(pair)?),
+                    Rule::SP => (),
+                    _ => unreachable!("Unexpected rule in SchemaPropertyList: {:?}", pair.as_rule()),
+                }
+            },
+            Rule::SP | Rule::EACH => (),
+            _ => unreachable!("Unexpected rule in schema properties: {:?}", pair.as_rule()),
+        }
+    }
+
+    Ok(properties)
+}
+
+impl SchemaProperty {
+    fn parse(pair: Pair<'_, Rule>) -> Result<SchemaProperty, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::SchemaProperty));
+
+        let mut variable = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Variable => variable = Some(parse_variable(pair)?),
+                Rule::PropertyLookup => return Ok(SchemaProperty { variable: variable.unwrap(), property_key: parse_property_lookup(pair)?.pop().unwrap() }),
+                Rule::SP => (),
+                _ => unreachable!("Unexpected rule in SchemaProperty: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in SchemaProperty")
+    }
+}
+
+fn parse_schema_options(pair: Pair<'_, Rule>) -> Result<HashMap<String, Expression>, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::SchemaOptions));
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::MapLiteral => return parse_map_literal(pair),
+            Rule::SP | Rule::OPTIONS => (),
+            _ => unreachable!("Unexpected rule in SchemaOptions: {:?}", pair.as_rule()),
+        }
+    }
+
+    unreachable!("Unexpected missing rule in SchemaOptions")
+}
 
 impl RegularQuery {
     fn parse(pair: Pair<'_, Rule>) -> Result<RegularQuery, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::RegularQuery));
 
+        let mut use_ = None;
         let mut query = None;
         let mut union = Vec::new();
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::SingleQuery => query = Some(SingleQuery::parse(pair)?),
+                Rule::SingleQuery => {
+                    let (single_query_use, single_query) = parse_single_query(pair)?;
+                    use_ = single_query_use;
+                    query = Some(single_query);
+                },
                 Rule::Union => union.push(Union::parse(pair)?),
                 Rule::SP => (),
                 _ => unreachable!("Unexpected rule in RegularQuery: {:?}", pair.as_rule()),
             }
         }
 
-        Ok(RegularQuery { query: query.unwrap(), union })
+        Ok(RegularQuery { use_, query: query.unwrap(), union })
     }
 }
 
 
-impl SingleQuery {
-    fn parse(pair: Pair<'_, Rule>) -> Result<SingleQuery, Error<Rule>> {
-        assert!(matches!(pair.as_rule(), Rule::SingleQuery));
+fn parse_single_query(pair: Pair<'_, Rule>) -> Result<(Option<Use>, SingleQuery), Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::SingleQuery));
+
+    let mut use_ = None;
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::Use => use_ = Some(Use::parse(pair)?),
+            Rule::SinglePartQuery => return Ok((use_, SingleQuery::SinglePartQuery(SinglePartQuery::parse(pair)?))),
+            Rule::MultiPartQuery => return Ok((use_, SingleQuery::MultiPartQuery(MultiPartQuery::parse(pair)?))),
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in SingleQuery: {:?}", pair.as_rule()),
+        }
+    }
+
+    unreachable!("Unexpected missing rule in SingleQuery")
+}
+
+impl Use {
+    fn parse(pair: Pair<'_, Rule>) -> Result<Use, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::Use));
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::SinglePartQuery => return Ok(SingleQuery::SinglePartQuery(SinglePartQuery::parse(pair)?)),
-                Rule::MultiPartQuery => return Ok(SingleQuery::MultiPartQuery(MultiPartQuery::parse(pair)?)),
-                _ => unreachable!("Unexpected rule in SingleQuery: {:?}", pair.as_rule()),
+                Rule::GraphReference => return Ok(Use { graph: GraphReference::parse(pair)? }),
+                Rule::SP | Rule::USE => (),
+                _ => unreachable!("Unexpected rule in Use: {:?}", pair.as_rule()),
             }
         }
 
-        unreachable!("Unexpected missing rule in SingleQuery")
+        unreachable!("Unexpected missing rule in Use")
+    }
+}
+
+impl GraphReference {
+    fn parse(pair: Pair<'_, Rule>) -> Result<GraphReference, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::GraphReference));
+
+        let pair = pair.into_inner().next().unwrap();
+        match pair.as_rule() {
+            Rule::GraphName => {
+                let mut parts = Vec::new();
+                for pair in pair.into_inner() {
+                    match pair.as_rule() {
+                        Rule::SymbolicName => parts.push(parse_symbolic_name(pair)?),
+                        Rule::SP => (),
+                        _ => unreachable!("Unexpected rule in GraphName: {:?}", pair.as_rule()),
+                    }
+                }
+                Ok(GraphReference::Name(parts))
+            },
+            Rule::GraphFunction => {
+                let mut by_element_id = false;
+                for pair in pair.into_inner() {
+                    match pair.as_rule() {
+                        Rule::BYELEMENTID => by_element_id = true,
+                        Rule::Expression if by_element_id => return Ok(GraphReference::ByElementId(Expression::parse(pair)?)),
+                        Rule::Expression => return Ok(GraphReference::ByName(Expression::parse(pair)?)),
+                        Rule::SP | Rule::GRAPH | Rule::BYNAME => (),
+                        _ => unreachable!("Unexpected rule in GraphFunction: {:?}", pair.as_rule()),
+                    }
+                }
+                unreachable!("Unexpected missing rule in GraphFunction")
+            },
+            _ => unreachable!("Unexpected rule in GraphReference: {:?}", pair.as_rule()),
+        }
+    }
+}
+
+fn parse_span(pair: &Pair<'_, Rule>) -> Span {
+    Span { start: pair.as_span().start(), end: pair.as_span().end() }
+}
+
+/// Returns the name with the backticks of escaped symbolic names removed.
+fn parse_symbolic_name(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::SymbolicName));
+
+    let text = pair.as_str();
+    if text.starts_with('`') {
+        Ok(text[1..text.len() - 1].replace("``", "`"))
+    }
+    else {
+        Ok(text.to_string())
     }
 }
 
@@ -128,7 +526,11 @@ impl ReadingClause {
             Some(pair) => match pair.as_rule() {
                 Rule::Match => Ok(ReadingClause::Match(Match::parse(pair)?)),
                 Rule::Unwind => Ok(ReadingClause::Unwind(Unwind::parse(pair)?)),
+                Rule::LoadCsv => Ok(ReadingClause::LoadCsv(LoadCsv::parse(pair)?)),
+                Rule::CallSubquery => Ok(ReadingClause::CallSubquery(CallSubquery::parse(pair)?)),
                 Rule::InQueryCall => Ok(ReadingClause::InQueryCall(InQueryCall::parse(pair)?)),
+                Rule::Filter => Ok(ReadingClause::Filter(Filter::parse(pair)?)),
+                Rule::OrderSkipLimit => Ok(ReadingClause::OrderSkipLimit(OrderSkipLimit::parse(pair)?)),
                 _ => unreachable!(),
             },
             None => unreachable!(),
@@ -163,7 +565,7 @@ fn parse_where(pair: Pair<'_, Rule>) -> Result<Expression, Error<Rule>> {
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::Expression => return Ok(Expression::parse(pair)?),
+            Rule::Expression => return Expression::parse(pair),
             Rule::WHERE | Rule::SP => (),
             _ => unreachable!(),
         }
@@ -216,25 +618,105 @@ impl PatternPart {
     }
 }
 
-fn parse_variable(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
+fn parse_variable(pair: Pair<'_, Rule>) -> Result<Variable, Error<Rule>> {
     assert!(matches!(pair.as_rule(), Rule::Variable));
-    Ok(pair.as_str().to_string())
+
+    let span = parse_span(&pair);
+    match pair.into_inner().next() {
+        Some(pair) => Ok(Variable { name: parse_symbolic_name(pair)?, span }),
+        None => unreachable!("Expected rule in Variable not found"),
+    }
 }
 
-fn parse_relationship_types(pair: Pair<'_, Rule>) -> Result<Vec<String>, Error<Rule>> {
-    assert!(matches!(pair.as_rule(), Rule::RelationshipTypes));
+fn parse_integer_literal(pair: Pair<'_, Rule>) -> Result<i64, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::IntegerLiteral));
 
-    let mut relationship_types = Vec::new();
+    let text = pair.as_str();
+    parse_integer_magnitude(text).and_then(|value| i64::try_from(value).ok()).ok_or_else(|| Error::new_from_span(
+        ErrorVariant::CustomError { message: format!("integer literal {} is out of range", text) },
+        pair.as_span(),
+    ))
+}
 
-    for pair in pair.into_inner() {
-        match pair.as_rule() {
-            Rule::RelTypeName => relationship_types.push(pair.to_string()),
-            Rule::SP => (),
-            _ => unreachable!(),
+/// Parses the digits of an integer literal in its radix, or returns `None` if
+/// they do not fit into a `u64`.
+fn parse_integer_magnitude(text: &str) -> Option<u64> {
+    let result = if let Some(digits) = text.strip_prefix("0x") {
+        u64::from_str_radix(digits, 16)
+    }
+    else if text.len() > 1 && text.starts_with('0') {
+        u64::from_str_radix(&text[1..], 8)
+    }
+    else {
+        text.parse()
+    };
+
+    result.ok()
+}
+
+/// Parses the operand of a unary minus that is nothing but the integer
+/// literal 9223372036854775808, which only fits into an `i64` when negated,
+/// as the literal `i64::MIN`. Returns `None` for any other operand.
+fn parse_negated_integer_minimum(pair: &Pair<'_, Rule>) -> Option<StringListNullOperatorExpression> {
+    assert!(matches!(pair.as_rule(), Rule::StringListNullOperatorExpression));
+
+    let mut span = None;
+    let mut pair = pair.clone();
+    while pair.as_rule() != Rule::IntegerLiteral {
+        if pair.as_rule() == Rule::PropertyOrLabelsExpression {
+            span = Some(parse_span(&pair));
+        }
+        let mut inner = pair.into_inner();
+        pair = inner.next()?;
+        if inner.next().is_some() {
+            return None;
         }
     }
 
-    Ok(relationship_types)
+    if parse_integer_magnitude(pair.as_str()) != Some(i64::MIN.unsigned_abs()) {
+        return None;
+    }
+
+    let expression = PropertyOrLabelsExpression {
+        atom: Atom::Literal(Literal::Number(NumberLiteral::Integer(i64::MIN))),
+        property_lookup: Vec::new(),
+        label_expression: None,
+        span: span?,
+    };
+    Some(StringListNullOperatorExpression { expression, operations: Vec::new() })
+}
+
+impl RangeLiteral {
+    fn parse(pair: Pair<'_, Rule>) -> Result<RangeLiteral, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::RangeLiteral));
+
+        let mut min = None;
+        let mut max = None;
+        let mut dot_dot = false;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::IntegerLiteral => {
+                    let span = pair.as_span();
+                    let bound = u64::try_from(parse_integer_literal(pair)?).map_err(|_| Error::new_from_span(
+                        ErrorVariant::CustomError { message: "relationship length must not be negative".to_string() },
+                        span,
+                    ))?;
+                    if dot_dot { max = Some(bound) } else { min = Some(bound) }
+                }
+                Rule::DOT_DOT => dot_dot = true,
+                Rule::SP => (),
+                _ => unreachable!("Unexpected rule in RangeLiteral: {:?}", pair.as_rule()),
+            }
+        }
+
+        // `*2` is a fixed length, whereas `*2..` and `*..2` leave one side open.
+        if !dot_dot {
+            max = min;
+        }
+
+        Ok(RangeLiteral { min, max })
+    }
 }
 
 impl PatternElement {
@@ -246,7 +728,7 @@ impl PatternElement {
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::PatternElement => return Ok(PatternElement::parse(pair)?),
+                Rule::PatternElement => return PatternElement::parse(pair),
                 Rule::NodePattern => node_pattern = Some(NodePattern::parse(pair)?),
                 Rule::PatternElementChain => relationship_patterns.push(parse_pattern_element_chain(pair)?),
                 Rule::SP => (),
@@ -308,82 +790,202 @@ impl RelationshipDetail {
         assert!(matches!(pair.as_rule(), Rule::RelationshipDetail));
 
         let mut variable = None;
-        let mut relationship_types = Vec::new();
+        let mut label_expression = None;
+        let mut range = None;
         let mut properties = None;
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::Variable => variable = Some(parse_variable(pair)?),
-                Rule::RelationshipTypes => relationship_types = parse_relationship_types(pair)?,
+                Rule::LabelExpression => label_expression = Some(LabelExpression::parse(pair)?),
+                Rule::RangeLiteral => range = Some(RangeLiteral::parse(pair)?),
                 Rule::Properties => properties = Some(Properties::parse(pair)?),
                 Rule::SP => (),
                 _ => unreachable!(),
             }
         }
 
-        Ok(RelationshipDetail { variable, relationship_types, properties })
+        Ok(RelationshipDetail { variable, label_expression, range, properties })
+    }
+}
+
+impl NodePattern {
+    fn parse(pair: Pair<'_, Rule>) -> Result<NodePattern, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::NodePattern));
+
+        let mut variable = None;
+        let mut label_expression = None;
+        let mut properties = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Variable => variable = Some(parse_variable(pair)?),
+                Rule::LabelExpression => label_expression = Some(LabelExpression::parse(pair)?),
+                Rule::Properties => properties = Some(Properties::parse(pair)?),
+                Rule::SP => (),
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(NodePattern { variable, label_expression, properties })
+    }
+}
+
+fn parse_node_labels(pair: Pair<'_, Rule>) -> Result<Vec<LabelOrType>, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::NodeLabels));
+
+    let mut node_labels = Vec::new();
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::NodeLabel => node_labels.push(parse_node_label(pair)?),
+            Rule::SP => (),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(node_labels)
+}
+
+fn parse_node_label(pair: Pair<'_, Rule>) -> Result<LabelOrType, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::NodeLabel));
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::LabelName => return Ok(LabelOrType::Static(pair.as_str().to_string())),
+            Rule::DynamicLabel => return parse_dynamic_label(pair),
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in NodeLabel: {:?}", pair.as_rule()),
+        }
+    }
+
+    unreachable!("Unexpected missing rule in NodeLabel")
+}
+
+fn parse_dynamic_label(pair: Pair<'_, Rule>) -> Result<LabelOrType, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::DynamicLabel));
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::Expression => return Ok(LabelOrType::Dynamic(Expression::parse(pair)?)),
+            Rule::SP | Rule::ALL => (),
+            _ => unreachable!("Unexpected rule in DynamicLabel: {:?}", pair.as_rule()),
+        }
+    }
+
+    unreachable!("Unexpected missing rule in DynamicLabel")
+}
+
+impl LabelExpression {
+    fn parse(pair: Pair<'_, Rule>) -> Result<LabelExpression, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::LabelExpression) || matches!(pair.as_rule(), Rule::IsLabelExpression));
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::LabelDisjunction => return parse_label_disjunction(pair),
+                Rule::SP | Rule::IS => (),
+                _ => unreachable!("Unexpected rule in LabelExpression: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in LabelExpression")
+    }
+}
+
+fn parse_label_disjunction(pair: Pair<'_, Rule>) -> Result<LabelExpression, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::LabelDisjunction));
+
+    let mut expressions = Vec::new();
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::LabelConjunction => expressions.push(parse_label_conjunction(pair)?),
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in LabelDisjunction: {:?}", pair.as_rule()),
+        }
+    }
+
+    if expressions.len() == 1 {
+        Ok(expressions.pop().unwrap())
+    }
+    else {
+        Ok(LabelExpression::Or(expressions))
     }
 }
 
-impl NodePattern {
-    fn parse(pair: Pair<'_, Rule>) -> Result<NodePattern, Error<Rule>> {
-        assert!(matches!(pair.as_rule(), Rule::NodePattern));
+fn parse_label_conjunction(pair: Pair<'_, Rule>) -> Result<LabelExpression, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::LabelConjunction));
 
-        let mut variable = None;
-        let mut node_labels = Vec::new();
-        let mut properties = None;
+    let mut expressions = Vec::new();
 
-        for pair in pair.into_inner() {
-            match pair.as_rule() {
-                Rule::Variable => variable = Some(parse_variable(pair)?),
-                Rule::NodeLabels => node_labels = parse_node_labels(pair)?,
-                Rule::Properties => properties = Some(Properties::parse(pair)?),
-                Rule::SP => (),
-                _ => unreachable!(),
-            }
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::LabelNegation => expressions.push(parse_label_negation(pair)?),
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in LabelConjunction: {:?}", pair.as_rule()),
         }
+    }
 
-        Ok(NodePattern { variable, node_labels, properties })
+    if expressions.len() == 1 {
+        Ok(expressions.pop().unwrap())
+    }
+    else {
+        Ok(LabelExpression::And(expressions))
     }
 }
 
-fn parse_node_labels(pair: Pair<'_, Rule>) -> Result<Vec<String>, Error<Rule>> {
-    assert!(matches!(pair.as_rule(), Rule::NodeLabels));
+fn parse_label_negation(pair: Pair<'_, Rule>) -> Result<LabelExpression, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::LabelNegation));
 
-    let mut node_labels = Vec::new();
+    let mut not_count = 0;
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::NodeLabel => node_labels.push(parse_node_label(pair)?),
+            Rule::LabelNot => not_count += 1,
+            Rule::LabelPrimary => {
+                let mut expression = parse_label_primary(pair)?;
+                for _ in 0..not_count {
+                    expression = LabelExpression::Not(Box::new(expression));
+                }
+                return Ok(expression);
+            },
             Rule::SP => (),
-            _ => unreachable!(),
+            _ => unreachable!("Unexpected rule in LabelNegation: {:?}", pair.as_rule()),
         }
     }
 
-    Ok(node_labels)
+    unreachable!("Unexpected missing rule in LabelNegation")
 }
 
-fn parse_node_label(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
-    assert!(matches!(pair.as_rule(), Rule::NodeLabel));
+fn parse_label_primary(pair: Pair<'_, Rule>) -> Result<LabelExpression, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::LabelPrimary));
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::LabelName => return Ok(pair.to_string()),
+            Rule::LabelDisjunction => return parse_label_disjunction(pair),
+            Rule::LabelWildcard => return Ok(LabelExpression::Wildcard),
+            Rule::DynamicLabelDisjunction => {
+                let expression = pair.into_inner().find(|pair| matches!(pair.as_rule(), Rule::Expression)).unwrap();
+                return Ok(LabelExpression::AnyOf(Expression::parse(expression)?));
+            }
+            Rule::DynamicLabel => return Ok(LabelExpression::Label(parse_dynamic_label(pair)?)),
+            Rule::LabelName => return Ok(LabelExpression::Label(LabelOrType::Static(pair.as_str().to_string()))),
             Rule::SP => (),
-            _ => unreachable!("Unexpected rule in NodeLabel: {:?}", pair.as_rule()),
+            _ => unreachable!("Unexpected rule in LabelPrimary: {:?}", pair.as_rule()),
         }
     }
 
-    unreachable!("Unexpected missing rule in NodeLabel")
+    unreachable!("Unexpected missing rule in LabelPrimary")
 }
 
 impl Properties {
+    #[allow(clippy::never_loop)]
     fn parse(pair: Pair<'_, Rule>) -> Result<Properties, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::Properties));
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::Parameter => return Ok(Properties::Parameter(pair.to_string())),
+                Rule::Parameter => return Ok(Properties::Parameter(parse_parameter(pair)?)),
                 Rule::MapLiteral => return Ok(Properties::MapLiteral(parse_map_literal(pair)?)),
                 _ => unreachable!("Unexpected rule in Properties: {:?}", pair.as_rule()),
             }
@@ -423,13 +1025,12 @@ impl XorExpression {
 
         let mut and_expressions = Vec::new();
 
-        match pair.into_inner().next() {
-            Some(pair) => match pair.as_rule() {
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
                 Rule::AndExpression => and_expressions.push(AndExpression::parse(pair)?),
-                Rule::SP | Rule::AND => (),
-                _ => unreachable!(),
-            },
-            None => unreachable!(),
+                Rule::SP | Rule::XOR => (),
+                _ => unreachable!("Unexpected rule in XorExpression: {:?}", pair.as_rule()),
+            }
         }
 
         Ok(XorExpression(and_expressions))
@@ -478,13 +1079,10 @@ impl ComparisonExpression {
     fn parse(pair: Pair<'_, Rule>) -> Result<ComparisonExpression, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::ComparisonExpression));
 
-        println!("Parsing comparison expression: {:?}", pair);
-
         let mut add_or_subtract_expression = None;
         let mut partial_comparisons = Vec::new();
 
         for pair in pair.into_inner() {
-            println!("{:?}", pair.as_rule());
             match pair.as_rule() {
                 Rule::SP => (),
                 Rule::AddOrSubtractExpression => add_or_subtract_expression = Some(AddOrSubtractExpression::parse(pair)?),
@@ -492,7 +1090,6 @@ impl ComparisonExpression {
                     let mut operator = None;
                     let mut expression = None;
                     for pair in pair.into_inner() {
-                        println!("{:?}", pair.as_rule());
                         match pair.as_rule() {
                             Rule::GE => operator = Some(ComparisonOperator::GreaterEqual),
                             Rule::GT => operator = Some(ComparisonOperator::GreaterThan),
@@ -538,6 +1135,7 @@ impl AddOrSubtractExpression {
                 },
                 Rule::PLUS => operator = Some(PlusMinusOperator::Plus),
                 Rule::MINUS => operator = Some(PlusMinusOperator::Minus),
+                Rule::CONCAT => operator = Some(PlusMinusOperator::Concatenate),
                 _ => unreachable!(),
             }
         }
@@ -605,7 +1203,15 @@ impl UnaryAddOrSubtractExpression {
                 Rule::SP => (),
                 Rule::PLUS => (), // FIXME: I don't really understand what the unary plus is supposed to do.
                 Rule::MINUS => subtract_count += 1,
-                Rule::StringListNullOperatorExpression => return Ok(UnaryAddOrSubtractExpression {negate: subtract_count % 2 == 1, expression: StringListNullOperatorExpression::parse(pair)? }),
+                Rule::StringListNullOperatorExpression => {
+                    let negate = subtract_count % 2 == 1;
+                    if negate {
+                        if let Some(expression) = parse_negated_integer_minimum(&pair) {
+                            return Ok(UnaryAddOrSubtractExpression { negate: false, expression });
+                        }
+                    }
+                    return Ok(UnaryAddOrSubtractExpression { negate, expression: StringListNullOperatorExpression::parse(pair)? });
+                }
                 _ => unreachable!(),
             }
         }
@@ -624,9 +1230,11 @@ impl StringListNullOperatorExpression {
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::PropertyOrLabelsExpression => property_or_labels_expression = Some(PropertyOrLabelsExpression::parse(pair)?),
-                Rule::StringOperatorExpression => operations.push(StringListNullOperatorExpressionInner::StringOperator(StringOperatorExpression::parse(pair)?)),
+                Rule::StringOperatorExpression => operations.push(StringListNullOperatorExpressionInner::StringOperator(Box::new(StringOperatorExpression::parse(pair)?))),
                 Rule::ListOperatorExpression => operations.push(StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::parse(pair)?)),
                 Rule::NullOperatorExpression => operations.push(StringListNullOperatorExpressionInner::NullOperator(NullOperatorExpression::parse(pair)?)),
+                Rule::NormalizedPredicateExpression => operations.push(StringListNullOperatorExpressionInner::NormalizedPredicate(NormalizedPredicateExpression::parse(pair)?)),
+                Rule::TypePredicateExpression => operations.push(StringListNullOperatorExpressionInner::TypePredicate(TypePredicateExpression::parse(pair)?)),
                 _ => unreachable!(),
             }
         }
@@ -635,23 +1243,181 @@ impl StringListNullOperatorExpression {
     }
 }
 
+impl NormalizedPredicateExpression {
+    fn parse(pair: Pair<'_, Rule>) -> Result<NormalizedPredicateExpression, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::NormalizedPredicateExpression));
+
+        let mut not = false;
+        let mut normal_form = NormalForm::Nfc;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::NOT => not = true,
+                Rule::NormalForm => normal_form = NormalForm::parse(pair)?,
+                Rule::SP | Rule::IS | Rule::NORMALIZED => (),
+                _ => unreachable!("Unexpected rule in NormalizedPredicateExpression: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(NormalizedPredicateExpression { not, normal_form })
+    }
+}
+
+impl NormalForm {
+    fn parse(pair: Pair<'_, Rule>) -> Result<NormalForm, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::NormalForm));
+
+        match pair.into_inner().next() {
+            Some(pair) => match pair.as_rule() {
+                Rule::NFC => Ok(NormalForm::Nfc),
+                Rule::NFD => Ok(NormalForm::Nfd),
+                Rule::NFKC => Ok(NormalForm::Nfkc),
+                Rule::NFKD => Ok(NormalForm::Nfkd),
+                _ => unreachable!("Unexpected rule in NormalForm: {:?}", pair.as_rule()),
+            },
+            None => unreachable!("Unexpected missing rule in NormalForm"),
+        }
+    }
+}
+
+impl TypePredicateExpression {
+    fn parse(pair: Pair<'_, Rule>) -> Result<TypePredicateExpression, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::TypePredicateExpression));
+
+        let mut not = false;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::NOT => not = true,
+                Rule::CypherType => return Ok(TypePredicateExpression { not, type_: CypherType::parse(pair)? }),
+                Rule::SP | Rule::IS | Rule::TYPED => (),
+                _ => unreachable!("Unexpected rule in TypePredicateExpression: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in TypePredicateExpression")
+    }
+}
+
+impl CypherType {
+    fn parse(pair: Pair<'_, Rule>) -> Result<CypherType, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::CypherType));
+
+        let mut alternatives = Vec::new();
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::CypherTypePart => alternatives.push(parse_cypher_type_part(pair)?),
+                Rule::SP => (),
+                _ => unreachable!("Unexpected rule in CypherType: {:?}", pair.as_rule()),
+            }
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives.pop().unwrap())
+        }
+        else {
+            Ok(CypherType::Union(alternatives))
+        }
+    }
+}
+
+fn parse_cypher_type_part(pair: Pair<'_, Rule>) -> Result<CypherType, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::CypherTypePart));
+
+    let mut type_ = None;
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::CypherTypeName => type_ = Some(parse_cypher_type_name(pair)?),
+            Rule::TypeNullability => type_ = Some(CypherType::NotNull(Box::new(type_.unwrap()))),
+            Rule::ListTypeSuffix => {
+                let list = CypherType::List(Box::new(type_.unwrap()));
+                if pair.into_inner().any(|pair| matches!(pair.as_rule(), Rule::TypeNullability)) {
+                    type_ = Some(CypherType::NotNull(Box::new(list)));
+                }
+                else {
+                    type_ = Some(list);
+                }
+            },
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in CypherTypePart: {:?}", pair.as_rule()),
+        }
+    }
+
+    Ok(type_.unwrap())
+}
+
+fn parse_cypher_type_name(pair: Pair<'_, Rule>) -> Result<CypherType, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::CypherTypeName));
+
+    let span = pair.as_span();
+    let mut local = false;
+    let mut zoned = false;
+    let mut list = false;
+    let mut any = false;
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::NOTHING => return Ok(CypherType::Nothing),
+            Rule::NULL => return Ok(CypherType::Null),
+            Rule::BOOLEAN | Rule::BOOL => return Ok(CypherType::Boolean),
+            Rule::STRING | Rule::VARCHAR => return Ok(CypherType::String),
+            Rule::INTEGER | Rule::INT => return Ok(CypherType::Integer),
+            Rule::FLOAT => return Ok(CypherType::Float),
+            Rule::DATE => return Ok(CypherType::Date),
+            Rule::DURATION => return Ok(CypherType::Duration),
+            Rule::POINT => return Ok(CypherType::Point),
+            Rule::NODE => return Ok(CypherType::Node),
+            Rule::RELATIONSHIP => return Ok(CypherType::Relationship),
+            Rule::MAP => return Ok(CypherType::Map),
+            Rule::PATH => return Ok(CypherType::Path),
+            Rule::PROPERTY => return Ok(CypherType::PropertyValue),
+            Rule::LOCAL => local = true,
+            Rule::ZONED => zoned = true,
+            Rule::TIME if local => return Ok(CypherType::LocalTime),
+            Rule::TIME if zoned => return Ok(CypherType::ZonedTime),
+            Rule::DATETIME if local => return Ok(CypherType::LocalDateTime),
+            Rule::DATETIME if zoned => return Ok(CypherType::ZonedDateTime),
+            Rule::LIST | Rule::ARRAY => list = true,
+            Rule::ANY_ => any = true,
+            Rule::CypherType if list => return Ok(CypherType::List(Box::new(CypherType::parse(pair)?))),
+            // `ANY<T1 | T2>` is the same type as the union `T1 | T2`.
+            Rule::CypherType if any => return CypherType::parse(pair),
+            Rule::SP | Rule::SIGNED | Rule::VALUE => (),
+            _ => unreachable!("Unexpected rule in CypherTypeName: {:?}", pair.as_rule()),
+        }
+    }
+
+    if any {
+        Ok(CypherType::Any)
+    }
+    else {
+        Err(Error::new_from_span(
+            ErrorVariant::CustomError { message: format!("unknown type {}", span.as_str()) },
+            span,
+        ))
+    }
+}
+
 impl PropertyExpression {
     fn parse(pair: Pair<'_, Rule>) -> Result<PropertyExpression, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::PropertyExpression));
 
         let mut atom = None;
-        let mut property_lookup = None;
+        let mut property_path = Vec::new();
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::Atom => atom = Some(Atom::parse(pair)?),
-                Rule::PropertyLookup => property_lookup = Some(parse_property_lookup(pair)?),
+                Rule::PropertyLookup => property_path.extend(parse_property_lookup(pair)?.into_iter().map(PropertyKey::Static)),
+                Rule::DynamicPropertyLookup => property_path.push(PropertyKey::Dynamic(parse_dynamic_property_lookup(pair)?)),
                 Rule::SP => (),
                 _ => unreachable!("Unexpected rule in PropertyExpression: {:?}", pair.as_rule()),
             }
         }
 
-        Ok(PropertyExpression { atom: atom.unwrap(), property_path: property_lookup.unwrap() })
+        Ok(PropertyExpression { atom: atom.unwrap(), property_path })
     }
 }
 
@@ -659,21 +1425,22 @@ impl PropertyOrLabelsExpression {
     fn parse(pair: Pair<'_, Rule>) -> Result<PropertyOrLabelsExpression, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::PropertyOrLabelsExpression));
 
+        let span = parse_span(&pair);
         let mut atom = None;
         let mut property_lookup = Vec::new();
-        let mut node_labels = Vec::new();
+        let mut label_expression = None;
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::Atom => atom = Some(Atom::parse(pair)?),
-                Rule::PropertyLookup => property_lookup = parse_property_lookup(pair)?,
-                Rule::NodeLabels => node_labels = parse_node_labels(pair)?,
+                Rule::PropertyLookup => property_lookup.extend(parse_property_lookup(pair)?),
+                Rule::LabelExpression | Rule::IsLabelExpression => label_expression = Some(LabelExpression::parse(pair)?),
                 Rule::SP => (),
                 _ => unreachable!(),
             }
         }
 
-        Ok(PropertyOrLabelsExpression { atom: atom.unwrap(), property_lookup, node_labels })
+        Ok(PropertyOrLabelsExpression { atom: atom.unwrap(), property_lookup, label_expression, span })
     }
 }
 
@@ -693,6 +1460,20 @@ fn parse_property_lookup(pair: Pair<'_, Rule>) -> Result<Vec<String>, Error<Rule
     Ok(property_lookup)
 }
 
+fn parse_dynamic_property_lookup(pair: Pair<'_, Rule>) -> Result<Expression, Error<Rule>> {
+    assert!(matches!(pair.as_rule(), Rule::DynamicPropertyLookup));
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::Expression => return Expression::parse(pair),
+            Rule::SP => (),
+            _ => unreachable!("Unexpected rule in DynamicPropertyLookup: {:?}", pair.as_rule()),
+        }
+    }
+
+    unreachable!("Unexpected missing rule in DynamicPropertyLookup")
+}
+
 impl StringOperatorExpression {
     fn parse(pair: Pair<'_, Rule>) -> Result<StringOperatorExpression, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::StringOperatorExpression));
@@ -705,6 +1486,7 @@ impl StringOperatorExpression {
                 Rule::STARTS => operator = Some(StringOperator::StartsWith),
                 Rule::ENDS => operator = Some(StringOperator::EndsWith),
                 Rule::CONTAINS => operator = Some(StringOperator::Contains),
+                Rule::REGEX_MATCH => operator = Some(StringOperator::RegularExpression),
                 Rule::PropertyOrLabelsExpression => return Ok(StringOperatorExpression { operator: operator.unwrap(), expression: PropertyOrLabelsExpression::parse(pair)? }),
                 _ => unreachable!("Unexpected rule in StringOperatorExpression: {:?}", pair.as_rule()),
             }
@@ -725,7 +1507,7 @@ impl ListOperatorExpression {
             match pair.as_rule() {
                 Rule::SP | Rule::IN => (),
                 Rule::DOT_DOT => is_range = true,
-                Rule::PropertyOrLabelsExpression => return Ok(ListOperatorExpression::In(PropertyOrLabelsExpression::parse(pair)? )),
+                Rule::PropertyOrLabelsExpression => return Ok(ListOperatorExpression::In(Box::new(PropertyOrLabelsExpression::parse(pair)?))),
                 Rule::Expression => if !is_range {
                     range_start = Some(Expression::parse(pair)?);
                 }
@@ -754,16 +1536,19 @@ impl Atom {
                 Rule::Literal => return Ok(Atom::Literal(Literal::parse(pair)?)),
                 Rule::Parameter => return Ok(Atom::Parameter(parse_parameter(pair)?)),
                 Rule::CaseExpression => return Ok(Atom::CaseExpression(CaseExpression::parse(pair)?)),
-                Rule::CountStarExpression => return Ok(Atom::CountStar),
+                Rule::CountStarExpression => return Ok(Atom::CountStar(CountStar { span: parse_span(&pair) })),
                 Rule::ListComprehension => return Ok(Atom::ListComprehension(ListComprehension::parse(pair)?)),
                 Rule::PatternComprehension => return Ok(Atom::PatternComprehension(PatternComprehension::parse(pair)?)),
                 Rule::AllFilterExpression => return Ok(Atom::All(parse_all_any_none_single_filter_expression(pair)?)),
                 Rule::AnyFilterExpression => return Ok(Atom::Any(parse_all_any_none_single_filter_expression(pair)?)),
                 Rule::NoneFilterExpression => return Ok(Atom::None(parse_all_any_none_single_filter_expression(pair)?)),
                 Rule::SingleFilterExpression => return Ok(Atom::Single(parse_all_any_none_single_filter_expression(pair)?)),
+                Rule::ReduceExpression => return Ok(Atom::Reduce(ReduceExpression::parse(pair)?)),
                 Rule::RelationshipsPattern => return Ok(Atom::RelationshipsPattern(RelationshipsPattern::parse(pair)?)),
                 Rule::ParenthesizedExpression => return Ok(Atom::ParenthesizedExpression(parse_parenthesized_expression(pair)?)),
                 Rule::FunctionInvocation => return Ok(Atom::FunctionInvocation(FunctionInvocation::parse(pair)?)),
+                Rule::MapProjection => return Ok(Atom::MapProjection(MapProjection::parse(pair)?)),
+                Rule::ExistentialSubquery => return Ok(Atom::ExistentialSubquery(ExistentialSubquery::parse(pair)?)),
                 Rule::Variable => return Ok(Atom::Variable(parse_variable(pair)?)),
                 Rule::SP => (),
                 _ => unreachable!(),
@@ -788,7 +1573,7 @@ fn parse_all_any_none_single_filter_expression(pair: Pair<'_, Rule>) -> Result<F
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::FilterExpression => return Ok(FilterExpression::parse(pair)?),
+            Rule::FilterExpression => return FilterExpression::parse(pair),
             Rule::SP | Rule::ANY_ | Rule::ALL | Rule::NONE | Rule::SINGLE => (),
             _ => unreachable!("Unexpected rule in parse_all_any_none_single_filter_expression: {:?}", pair.as_rule()),
         }
@@ -797,6 +1582,36 @@ fn parse_all_any_none_single_filter_expression(pair: Pair<'_, Rule>) -> Result<F
     unreachable!()
 }
 
+impl ReduceExpression {
+    fn parse(pair: Pair<'_, Rule>) -> Result<ReduceExpression, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::ReduceExpression));
+
+        let mut accumulator = None;
+        let mut initial_value = None;
+        let mut id_in_coll = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Variable => accumulator = Some(parse_variable(pair)?),
+                Rule::IdInColl => id_in_coll = Some(IdInColl::parse(pair)?),
+                Rule::Expression => match id_in_coll.take() {
+                    None => initial_value = Some(Expression::parse(pair)?),
+                    Some(id_in_coll) => return Ok(ReduceExpression {
+                        accumulator: accumulator.unwrap(),
+                        initial_value: initial_value.unwrap(),
+                        id_in_coll,
+                        expression: Expression::parse(pair)?,
+                    }),
+                },
+                Rule::SP | Rule::REDUCE => (),
+                _ => unreachable!("Unexpected rule in ReduceExpression: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in ReduceExpression")
+    }
+}
+
 impl FilterExpression {
     fn parse(pair: Pair<'_, Rule>) -> Result<FilterExpression, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::FilterExpression));
@@ -888,7 +1703,7 @@ fn parse_parenthesized_expression(pair: Pair<'_, Rule>) -> Result<Expression, Er
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::Expression => return Ok(Expression::parse(pair)?),
+            Rule::Expression => return Expression::parse(pair),
             Rule::SP => (),
             _ => unreachable!(),
         }
@@ -901,13 +1716,14 @@ impl FunctionInvocation {
     fn parse(pair: Pair<'_, Rule>) -> Result<FunctionInvocation, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::FunctionInvocation));
 
+        let span = parse_span(&pair);
         let mut function_name = None;
         let mut distinct = false;
         let mut arguments = Vec::new();
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::FunctionName => function_name = Some(pair.as_str().to_string()),
+                Rule::FunctionName => function_name = Some(QualifiedName::parse(pair)?),
                 Rule::DISTINCT => distinct = true,
                 Rule::Expression => arguments.push(Expression::parse(pair)?),
                 Rule::SP => (),
@@ -915,7 +1731,94 @@ impl FunctionInvocation {
             }
         }
 
-        Ok(FunctionInvocation { function_name: function_name.unwrap(), distinct, arguments })
+        Ok(FunctionInvocation { function_name: function_name.unwrap(), distinct, arguments, span })
+    }
+}
+
+impl ExistentialSubquery {
+    fn parse(pair: Pair<'_, Rule>) -> Result<ExistentialSubquery, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::ExistentialSubquery));
+
+        let mut pattern = None;
+        let mut where_ = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::RegularQuery => return Ok(ExistentialSubquery::RegularQuery(RegularQuery::parse(pair)?)),
+                Rule::Pattern => pattern = Some(Pattern::parse(pair)?),
+                Rule::Where => where_ = Some(parse_where(pair)?),
+                Rule::SP | Rule::EXISTS => (),
+                _ => unreachable!("Unexpected rule in ExistentialSubquery: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(ExistentialSubquery::PatternWhere { pattern: pattern.unwrap(), where_ })
+    }
+}
+
+impl QualifiedName {
+    fn parse(pair: Pair<'_, Rule>) -> Result<QualifiedName, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::FunctionName | Rule::ProcedureName));
+
+        let mut namespace = Vec::new();
+        let mut name = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Namespace => for pair in pair.into_inner() {
+                    match pair.as_rule() {
+                        Rule::SymbolicName => namespace.push(parse_symbolic_name(pair)?),
+                        _ => unreachable!("Unexpected rule in Namespace: {:?}", pair.as_rule()),
+                    }
+                },
+                Rule::SymbolicName => name = Some(parse_symbolic_name(pair)?),
+                _ => unreachable!("Unexpected rule in QualifiedName: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(QualifiedName { namespace, name: name.unwrap() })
+    }
+}
+
+impl MapProjection {
+    fn parse(pair: Pair<'_, Rule>) -> Result<MapProjection, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::MapProjection));
+
+        let mut variable = None;
+        let mut items = Vec::new();
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Variable => variable = Some(parse_variable(pair)?),
+                Rule::MapProjectionItem => items.push(MapProjectionItem::parse(pair)?),
+                Rule::SP => (),
+                _ => unreachable!("Unexpected rule in MapProjection: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(MapProjection { variable: variable.unwrap(), items })
+    }
+}
+
+impl MapProjectionItem {
+    fn parse(pair: Pair<'_, Rule>) -> Result<MapProjectionItem, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::MapProjectionItem));
+
+        let mut key = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::PropertyKeyName => key = Some(pair.as_str().to_string()),
+                Rule::Expression => return Ok(MapProjectionItem::Literal { key: key.unwrap(), expression: Expression::parse(pair)? }),
+                Rule::AllPropertiesSelector => return Ok(MapProjectionItem::AllProperties),
+                Rule::PropertyLookup => return Ok(MapProjectionItem::Property(parse_property_lookup(pair)?.pop().unwrap())),
+                Rule::Variable => return Ok(MapProjectionItem::Variable(parse_variable(pair)?)),
+                Rule::SP => (),
+                _ => unreachable!("Unexpected rule in MapProjectionItem: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Unexpected missing rule in MapProjectionItem")
     }
 }
 
@@ -977,6 +1880,7 @@ fn parse_case_alternative(pair: Pair<'_, Rule>) -> Result<(Expression, Expressio
 }
 
 impl Literal {
+    #[allow(clippy::never_loop)]
     fn parse(pair: Pair<'_, Rule>) -> Result<Literal, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::Literal));
 
@@ -996,13 +1900,14 @@ impl Literal {
     }
 }
 
+#[allow(clippy::never_loop)]
 fn parse_string_literal(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
     assert!(matches!(pair.as_rule(), Rule::StringLiteral));
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::StringDoubleText => return Ok(parse_string_text(pair)?),
-            Rule::StringSingleText => return Ok(parse_string_text(pair)?),
+            Rule::StringDoubleText => return parse_string_text(pair),
+            Rule::StringSingleText => return parse_string_text(pair),
             _ => unreachable!(),
         }
     }
@@ -1027,6 +1932,7 @@ fn parse_string_text(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
     Ok(chars.join(""))
 }
 
+#[allow(clippy::never_loop)]
 fn parse_string_text_char(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
     assert!(matches!(pair.as_rule(), Rule::StringDoubleTextChar) || matches!(pair.as_rule(), Rule::StringSingleTextChar));
 
@@ -1034,7 +1940,7 @@ fn parse_string_text_char(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::EscapedChar => return Ok(parse_escaped_char(pair)?),
+            Rule::EscapedChar => return parse_escaped_char(pair),
             _ => unreachable!("Unexpected rule in StringDoubleTextChar or StringSingleTextChar: {:?}", pair.as_rule()),
         }
     }
@@ -1046,6 +1952,7 @@ fn parse_escaped_char(pair: Pair<'_, Rule>) -> Result<String, Error<Rule>> {
     assert!(matches!(pair.as_rule(), Rule::EscapedChar));
 
     match pair.as_str() {
+        "\\\\" => Ok("\\".to_string()),
         "\\'" => Ok("'".to_string()),
         "\\\"" => Ok("\"".to_string()),
         "\\b" | "\\B" => Ok("\u{0008}".to_string()),
@@ -1067,7 +1974,7 @@ impl NumberLiteral {
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::IntegerLiteral => return Ok(NumberLiteral::Integer(pair.as_str().parse().unwrap())),
+                Rule::IntegerLiteral => return Ok(NumberLiteral::Integer(parse_integer_literal(pair)?)),
                 Rule::DoubleLiteral => return Ok(NumberLiteral::Double(pair.as_str().parse().unwrap())),
                 Rule::SP => (),
                 _ => unreachable!(),
@@ -1086,7 +1993,7 @@ fn parse_map_literal(pair: Pair<'_, Rule>) -> Result<HashMap<String, Expression>
 
     for pair in pair.into_inner() {
         match pair.as_rule() {
-            Rule::PropertyKeyName => key = Some(pair.to_string()),
+            Rule::PropertyKeyName => key = Some(pair.as_str().to_string()),
             Rule::Expression => {
                 items.insert(key.take().unwrap(), Expression::parse(pair)?);
             },
@@ -1154,6 +2061,46 @@ impl Unwind {
     }
 }
 
+impl LoadCsv {
+    fn parse(pair: Pair<'_, Rule>) -> Result<LoadCsv, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::LoadCsv));
+
+        let mut with_headers = false;
+        let mut source = None;
+        let mut variable = None;
+        let mut field_terminator = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::HEADERS => with_headers = true,
+                Rule::Expression => source = Some(Expression::parse(pair)?),
+                Rule::Variable => variable = Some(parse_variable(pair)?),
+                Rule::StringLiteral => field_terminator = Some(parse_string_literal(pair)?),
+                Rule::SP | Rule::LOAD | Rule::CSV | Rule::WITH | Rule::FROM | Rule::AS | Rule::FIELDTERMINATOR => (),
+                _ => unreachable!("Unexpected rule in LoadCsv: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(LoadCsv { with_headers, source: source.unwrap(), variable: variable.unwrap(), field_terminator })
+    }
+}
+
+impl CallSubquery {
+    fn parse(pair: Pair<'_, Rule>) -> Result<CallSubquery, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::CallSubquery));
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::RegularQuery => return Ok(CallSubquery { query: RegularQuery::parse(pair)? }),
+                Rule::SP | Rule::CALL => (),
+                _ => unreachable!(),
+            }
+        }
+
+        unreachable!()
+    }
+}
+
 impl InQueryCall {
     fn parse(pair: Pair<'_, Rule>) -> Result<InQueryCall, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::InQueryCall));
@@ -1199,25 +2146,27 @@ impl ExplicitProcedureInvocation {
     fn parse(pair: Pair<'_, Rule>) -> Result<ExplicitProcedureInvocation, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::ExplicitProcedureInvocation));
 
+        let span = parse_span(&pair);
         let mut name = None;
         let mut arguments = Vec::new();
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::ProcedureName => name = Some(pair.as_str().to_string()),
+                Rule::ProcedureName => name = Some(QualifiedName::parse(pair)?),
                 Rule::Expression => arguments.push(Expression::parse(pair)?),
                 Rule::SP => (),
                 _ => unreachable!(),
             }
         }
         
-        Ok(ExplicitProcedureInvocation { name: name.unwrap(), arguments })
+        Ok(ExplicitProcedureInvocation { name: name.unwrap(), arguments, span })
     }
 }
 
 impl UpdatingClause {
+    #[allow(clippy::never_loop)]
     fn parse(pair: Pair<'_, Rule>) -> Result<UpdatingClause, Error<Rule>> {
-        assert!(matches!(pair.as_rule(), Rule::UpdatingClause));
+        assert!(matches!(pair.as_rule(), Rule::UpdatingClause | Rule::ForeachClause));
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
@@ -1226,6 +2175,9 @@ impl UpdatingClause {
                 Rule::Delete => return Ok(UpdatingClause::Delete(Delete::parse(pair)?)),
                 Rule::Set => return Ok(UpdatingClause::Set(Set::parse(pair)?)),
                 Rule::Remove => return Ok(UpdatingClause::Remove(Remove::parse(pair)?)),
+                Rule::Foreach => return Ok(UpdatingClause::Foreach(Foreach::parse(pair)?)),
+                Rule::Filter => return Ok(UpdatingClause::Filter(Filter::parse(pair)?)),
+                Rule::OrderSkipLimit => return Ok(UpdatingClause::OrderSkipLimit(OrderSkipLimit::parse(pair)?)),
                 _ => unreachable!("Unexpected rule in UpdatingClause: {:?}", pair.as_rule()),
             }
         }
@@ -1234,6 +2186,28 @@ impl UpdatingClause {
     }
 }
 
+impl Foreach {
+    fn parse(pair: Pair<'_, Rule>) -> Result<Foreach, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::Foreach));
+
+        let mut variable = None;
+        let mut expression = None;
+        let mut clauses = Vec::new();
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Variable => variable = Some(parse_variable(pair)?),
+                Rule::Expression => expression = Some(Expression::parse(pair)?),
+                Rule::ForeachClause => clauses.push(UpdatingClause::parse(pair)?),
+                Rule::SP | Rule::FOREACH | Rule::IN => (),
+                _ => unreachable!("Unexpected rule in Foreach: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(Foreach { variable: variable.unwrap(), expression: expression.unwrap(), clauses })
+    }
+}
+
 impl Create {
     fn parse(pair: Pair<'_, Rule>) -> Result<Create, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::Create));
@@ -1321,7 +2295,7 @@ impl SetItem {
                 Rule::Variable => variable = Some(parse_variable(pair)?),
                 Rule::PropertyExpression => property_expression = Some(PropertyExpression::parse(pair)?),
                 Rule::Expression => if let Some(property_expression) = property_expression.take() {
-                    return Ok(SetItem::AssignPropertyExpression { property_expression, expression: Expression::parse(pair)? });
+                    return Ok(SetItem::AssignPropertyExpression { property_expression: Box::new(property_expression), expression: Expression::parse(pair)? });
                 }
                 else if eq {
                     return Ok(SetItem::AssignVariable { variable: variable.unwrap(), expression: Expression::parse(pair)? });
@@ -1367,7 +2341,7 @@ impl RemoveItem {
             match pair.as_rule() {
                 Rule::Variable => variable = Some(parse_variable(pair)?),
                 Rule::NodeLabels => return Ok(RemoveItem::VariableNodeLabels { variable: variable.unwrap(), node_labels: parse_node_labels(pair)? }),
-                Rule::PropertyExpression => return Ok(RemoveItem::PropertyExpression(PropertyExpression::parse(pair)? )),
+                Rule::PropertyExpression => return Ok(RemoveItem::PropertyExpression(Box::new(PropertyExpression::parse(pair)?))),
                 Rule::SP => (),
                 _ => unreachable!("Unexpected rule in RemoveItem: {:?}", pair.as_rule()),
             }
@@ -1492,25 +2466,22 @@ impl SortItem {
         assert!(matches!(pair.as_rule(), Rule::SortItem));
 
         let mut expression = None;
-        let mut descending = false;
+        let mut direction = SortDirection::Ascending;
+        let mut nulls = None;
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::Expression => expression = Some(Expression::parse(pair)?),
-                Rule::DESCENDING | Rule::DESC => descending = true,
-                Rule::ASCENDING| Rule::ASC => descending = false,
-                Rule::SP => (),
+                Rule::DESCENDING | Rule::DESC => direction = SortDirection::Descending,
+                Rule::ASCENDING| Rule::ASC => direction = SortDirection::Ascending,
+                Rule::FIRST => nulls = Some(NullsOrder::First),
+                Rule::LAST => nulls = Some(NullsOrder::Last),
+                Rule::SP | Rule::NULLS => (),
                 _ => unreachable!(),
             }
         }
 
-        if descending {
-            Ok(SortItem::Descending(expression.unwrap()))
-        }
-        else {
-            Ok(SortItem::Ascending(expression.unwrap()))
-        }
-        
+        Ok(SortItem { expression: expression.unwrap(), direction, nulls })
     }
 }
 
@@ -1521,7 +2492,7 @@ impl Skip {
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::Expression => return Ok(Skip {expression: Expression::parse(pair)?}),
-                Rule::SP | Rule::SKIP => (),
+                Rule::SP | Rule::SKIP | Rule::OFFSET => (),
                 _ => unreachable!("Unexpected rule in Skip: {:?}", pair.as_rule()),
             }
         }
@@ -1546,6 +2517,44 @@ impl Limit {
     }
 }
 
+impl Filter {
+    fn parse(pair: Pair<'_, Rule>) -> Result<Filter, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::Filter));
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Expression => return Ok(Filter { expression: Expression::parse(pair)? }),
+                Rule::SP | Rule::FILTER | Rule::WHERE => (),
+                _ => unreachable!("Unexpected rule in Filter: {:?}", pair.as_rule()),
+            }
+        }
+
+        unreachable!("Expected rule in Filter not found")
+    }
+}
+
+impl OrderSkipLimit {
+    fn parse(pair: Pair<'_, Rule>) -> Result<OrderSkipLimit, Error<Rule>> {
+        assert!(matches!(pair.as_rule(), Rule::OrderSkipLimit));
+
+        let mut order = None;
+        let mut skip = None;
+        let mut limit = None;
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::Order => order = Some(Order::parse(pair)?),
+                Rule::Skip => skip = Some(Skip::parse(pair)?),
+                Rule::Limit => limit = Some(Limit::parse(pair)?),
+                Rule::SP => (),
+                _ => unreachable!("Unexpected rule in OrderSkipLimit: {:?}", pair.as_rule()),
+            }
+        }
+
+        Ok(OrderSkipLimit { order, skip, limit })
+    }
+}
+
 impl MultiPartQuery {
     fn parse(pair: Pair<'_, Rule>) -> Result<MultiPartQuery, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::MultiPartQuery));
@@ -1601,7 +2610,10 @@ impl Union {
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::ALL => all = true,
-                Rule::SingleQuery => return Ok(Union { all, query: SingleQuery::parse(pair)? }),
+                Rule::SingleQuery => {
+                    let (use_, query) = parse_single_query(pair)?;
+                    return Ok(Union { all, use_, query });
+                },
                 Rule::SP | Rule::UNION => (),
                 _ => unreachable!("Unexpected rule in Union: {:?}", pair.as_rule()),
             }
@@ -1632,10 +2644,8 @@ impl StandaloneCall {
 
         let yield_ = if star {
             Some(StarOrYieldItems::Star)
-        } else if let Some(items) = yield_items {
-            Some(StarOrYieldItems::YieldItems(items))
         } else {
-            None
+            yield_items.map(StarOrYieldItems::YieldItems)
         };
 
         Ok(StandaloneCall {
@@ -1649,9 +2659,10 @@ impl ImplicitProcedureInvocation {
     fn parse(pair: Pair<'_, Rule>) -> Result<ImplicitProcedureInvocation, Error<Rule>> {
         assert!(matches!(pair.as_rule(), Rule::ImplicitProcedureInvocation));
 
+        let span = parse_span(&pair);
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::ProcedureName => return Ok(ImplicitProcedureInvocation { name: pair.as_str().to_string() }),
+                Rule::ProcedureName => return Ok(ImplicitProcedureInvocation { name: QualifiedName::parse(pair)?, span }),
                 Rule::SP => (),
                 _ => unreachable!(),
             }
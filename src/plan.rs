@@ -0,0 +1,936 @@
+//! Logical query plans.
+//!
+//! [`plan`] turns a query into a tree of [`LogicalPlan`] operators that can
+//! be evaluated bottom up: every operator consumes the rows of its input and
+//! produces new rows, a row being a binding of variables to values. The
+//! plan is purely logical, i.e. it does not pick indexes or join orders; a
+//! `MATCH` pattern is expanded from left to right, starting with a scan of
+//! its first node unless that node is already bound.
+//!
+//! The [`Display`](std::fmt::Display) implementation renders the plan as the
+//! text of an `EXPLAIN`, with one operator per line and the inputs of an
+//! operator below it.
+
+mod explain;
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::analysis::functions::FunctionRegistry;
+use crate::analysis::pushdown::conjunction;
+use crate::ast::cypher::visit::{walk_atom as walk_atom_ref, Visitor};
+use crate::ast::cypher::visit_mut::{walk_atom, walk_expression, walk_property_or_labels_expression, VisitorMut};
+use crate::ast::cypher::{
+    AddOrSubtractExpression, Atom, ComparisonExpression, ComparisonOperator, Expression, FilterExpression, IdInColl, InQueryCall, LabelExpression,
+    ListOperatorExpression, Match, MergeAction, NodePattern, NotExpression, Order, OrderSkipLimit, Pattern, PatternElement, PatternPart,
+    ProcedureInvocation, ProjectionBody, ProjectionItem, Properties, PropertyOrLabelsExpression, QualifiedName, Query,
+    ReadingClause, RegularQuery, RelationshipDetail, RelationshipPattern, RemoveItem, SetItem, SingleQuery,
+    SinglePartQuery, SortItem, Span, StandaloneCall, StarOrYieldItems, StringListNullOperatorExpressionInner,
+    UpdatingClause, Variable, With, YieldItem, YieldItems,
+};
+
+/// The direction in which a relationship is traversed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// `(a)-->(b)`
+    Outgoing,
+    /// `(a)<--(b)`
+    Incoming,
+    /// `(a)--(b)` and `(a)<-->(b)`
+    Both,
+}
+
+/// A logical operator and its inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogicalPlan {
+    /// Produces the row the plan is evaluated for: a single empty row at the
+    /// start of a query, or the current row of the outer plan in the inner
+    /// plan of an [`Apply`](LogicalPlan::Apply) or [`Merge`](LogicalPlan::Merge).
+    Argument {
+        /// The variables bound by that row.
+        variables: Vec<String>,
+    },
+    /// Binds the variable to every node that has the labels.
+    NodeScan {
+        input: Box<LogicalPlan>,
+        variable: String,
+        label_expression: Option<LabelExpression>,
+    },
+    /// Follows the relationships of a bound node. If `into` is set, `to` is
+    /// already bound and only relationships between the two nodes are found.
+    Expand {
+        input: Box<LogicalPlan>,
+        from: String,
+        relationship: String,
+        to: String,
+        direction: Direction,
+        types: Option<LabelExpression>,
+        into: bool,
+    },
+    /// Follows paths of `min` to `max` relationships, binding `relationship`
+    /// to the list of relationships. Every relationship has the types and the
+    /// properties.
+    VarLengthExpand {
+        input: Box<LogicalPlan>,
+        from: String,
+        relationship: String,
+        to: String,
+        direction: Direction,
+        types: Option<LabelExpression>,
+        properties: Option<Properties>,
+        min: u64,
+        max: Option<u64>,
+        into: bool,
+    },
+    /// Keeps the rows for which the predicate is true.
+    Filter {
+        input: Box<LogicalPlan>,
+        predicate: Expression,
+    },
+    /// Replaces every row with the named values of the expressions.
+    Projection {
+        input: Box<LogicalPlan>,
+        items: Vec<(String, Expression)>,
+    },
+    /// Groups the rows by the grouping expressions and computes the aggregate
+    /// function calls per group. Without aggregates, this removes duplicates.
+    Aggregate {
+        input: Box<LogicalPlan>,
+        grouping: Vec<(String, Expression)>,
+        aggregates: Vec<(String, Expression)>,
+    },
+    Sort {
+        input: Box<LogicalPlan>,
+        items: Vec<SortItem>,
+    },
+    Skip {
+        input: Box<LogicalPlan>,
+        count: Expression,
+    },
+    Limit {
+        input: Box<LogicalPlan>,
+        count: Expression,
+    },
+    /// Produces a row for every element of the list.
+    Unwind {
+        input: Box<LogicalPlan>,
+        expression: Expression,
+        variable: String,
+    },
+    /// Produces the rows of its input, or the argument row with the
+    /// variables bound to `null` if there are none.
+    Optional {
+        input: Box<LogicalPlan>,
+        variables: Vec<String>,
+    },
+    Union {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        all: bool,
+    },
+    /// Evaluates `plan` for every row of the input and produces the rows it
+    /// returns.
+    Apply {
+        input: Box<LogicalPlan>,
+        plan: Box<LogicalPlan>,
+    },
+    /// Calls a procedure for every row of the input. Without `arguments`,
+    /// the arguments are taken from the query parameters of the same names,
+    /// and without `yields`, all outputs of the procedure are bound to
+    /// variables of the same names.
+    ProcedureCall {
+        input: Box<LogicalPlan>,
+        name: QualifiedName,
+        arguments: Option<Vec<Expression>>,
+        /// The output fields and the variables they are bound to.
+        yields: Option<Vec<(String, String)>>,
+    },
+    Create {
+        input: Box<LogicalPlan>,
+        pattern: Pattern,
+    },
+    /// Evaluates `plan` for every row of the input, and creates the pattern
+    /// if it finds no rows.
+    Merge {
+        input: Box<LogicalPlan>,
+        plan: Box<LogicalPlan>,
+        pattern: PatternPart,
+        on_match: Vec<SetItem>,
+        on_create: Vec<SetItem>,
+    },
+    Delete {
+        input: Box<LogicalPlan>,
+        expressions: Vec<Expression>,
+        detach: bool,
+    },
+    SetProperty {
+        input: Box<LogicalPlan>,
+        items: Vec<SetItem>,
+    },
+    Remove {
+        input: Box<LogicalPlan>,
+        items: Vec<RemoveItem>,
+    },
+    /// Binds the path variable of a pattern part to the path through its
+    /// bound nodes and relationships.
+    ProjectPath {
+        input: Box<LogicalPlan>,
+        part: PatternPart,
+    },
+    /// Returns the columns of the rows to the client. A query without
+    /// `RETURN` has no columns, and neither has a standalone `CALL` that
+    /// yields all outputs of the procedure.
+    ProduceResults {
+        input: Box<LogicalPlan>,
+        columns: Vec<String>,
+    },
+}
+
+impl LogicalPlan {
+    /// Returns the inputs of the operator, the main input first.
+    pub fn children(&self) -> Vec<&LogicalPlan> {
+        match self {
+            LogicalPlan::Argument { .. } => Vec::new(),
+            LogicalPlan::Union { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+            LogicalPlan::Apply { input, plan } | LogicalPlan::Merge { input, plan, .. } => vec![input.as_ref(), plan.as_ref()],
+            LogicalPlan::NodeScan { input, .. }
+            | LogicalPlan::Expand { input, .. }
+            | LogicalPlan::VarLengthExpand { input, .. }
+            | LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Projection { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Skip { input, .. }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Unwind { input, .. }
+            | LogicalPlan::Optional { input, .. }
+            | LogicalPlan::ProcedureCall { input, .. }
+            | LogicalPlan::Create { input, .. }
+            | LogicalPlan::Delete { input, .. }
+            | LogicalPlan::SetProperty { input, .. }
+            | LogicalPlan::Remove { input, .. }
+            | LogicalPlan::ProjectPath { input, .. }
+            | LogicalPlan::ProduceResults { input, .. } => vec![input.as_ref()],
+        }
+    }
+
+    /// Returns the columns the plan produces if it is the plan of a query.
+    pub fn columns(&self) -> &[String] {
+        match self {
+            LogicalPlan::ProduceResults { columns, .. } => columns,
+            _ => &[],
+        }
+    }
+}
+
+/// A query that cannot be planned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanError {
+    pub message: String,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+fn unsupported<T>(what: &str) -> Result<T, PlanError> {
+    Err(PlanError { message: format!("{} cannot be planned", what) })
+}
+
+/// Plans the query.
+///
+/// `USE`, `LOAD CSV`, `CALL { ... }`, `FOREACH` and node or relationship
+/// properties given as a parameter in a `MATCH` cannot be planned.
+pub fn plan(query: &Query) -> Result<LogicalPlan, PlanError> {
+    let mut planner = Planner::new(query.to_string());
+    match query {
+        Query::RegularQuery(query) => planner.regular_query(query, &[]),
+        Query::StandaloneCall(call) => Ok(planner.standalone_call(call)),
+    }
+}
+
+/// Plans a pattern that is matched for every row of an outer plan binding
+/// the variables of `scope`, as in a pattern predicate or an `EXISTS`
+/// subquery. The plan starts with an [`Argument`](LogicalPlan::Argument)
+/// and produces the outer row extended by the variables of the pattern.
+pub fn plan_pattern(pattern: &Pattern, where_: Option<&Expression>, scope: &[String]) -> Result<LogicalPlan, PlanError> {
+    let mut planner = Planner::new(format!("{} {}", pattern, scope.join(" ")));
+    let mut pipeline = Pipeline::new(scope.to_vec());
+    planner.match_(&mut pipeline, &Match { optional: false, pattern: pattern.clone(), where_: where_.cloned() })?;
+    Ok(pipeline.plan)
+}
+
+/// Plans a subquery that is evaluated for every row of an outer plan
+/// binding the variables of `scope`.
+pub fn plan_subquery(query: &RegularQuery, scope: &[String]) -> Result<LogicalPlan, PlanError> {
+    let mut planner = Planner::new(format!("{} {}", query, scope.join(" ")));
+    planner.regular_query(query, scope)
+}
+
+struct Planner {
+    /// The text of the query, to name hidden variables without clashing
+    /// with the variables of the query.
+    text: String,
+    next_name: usize,
+    registry: FunctionRegistry,
+}
+
+/// A plan under construction and the variables the rows of the plan bind
+/// that the query can refer to.
+struct Pipeline {
+    plan: LogicalPlan,
+    scope: Vec<String>,
+}
+
+impl Pipeline {
+    fn new(scope: Vec<String>) -> Self {
+        Pipeline { plan: LogicalPlan::Argument { variables: scope.clone() }, scope }
+    }
+
+    /// Puts an operator on top of the plan.
+    fn push(&mut self, operator: impl FnOnce(Box<LogicalPlan>) -> LogicalPlan) {
+        let input = std::mem::replace(&mut self.plan, LogicalPlan::Argument { variables: Vec::new() });
+        self.plan = operator(Box::new(input));
+    }
+
+    fn filter(&mut self, predicates: Vec<Expression>) {
+        if let Some(predicate) = conjunction(predicates) {
+            self.push(|input| LogicalPlan::Filter { input, predicate });
+        }
+    }
+
+    fn bind(&mut self, variable: &str) {
+        if !self.scope.iter().any(|bound| bound == variable) {
+            self.scope.push(variable.to_string());
+        }
+    }
+}
+
+impl Planner {
+    fn new(text: String) -> Self {
+        Planner { text, next_name: 0, registry: FunctionRegistry::builtin() }
+    }
+
+    /// Returns a new name for a variable the query does not name.
+    fn hidden_name(&mut self, prefix: &str) -> String {
+        loop {
+            let name = format!("{}_{}", prefix, self.next_name);
+            self.next_name += 1;
+            if !self.text.contains(&name) {
+                return name;
+            }
+        }
+    }
+
+    fn hidden_variable(&mut self, prefix: &str) -> Variable {
+        Variable { name: self.hidden_name(prefix), span: Span::default() }
+    }
+
+    fn regular_query(&mut self, query: &RegularQuery, scope: &[String]) -> Result<LogicalPlan, PlanError> {
+        if query.use_.is_some() || query.union.iter().any(|union| union.use_.is_some()) {
+            return unsupported("USE");
+        }
+        let (mut plan, columns) = self.single_query(&query.query, scope)?;
+        for union in &query.union {
+            let (right, right_columns) = self.single_query(&union.query, scope)?;
+            if right_columns != columns {
+                return Err(PlanError { message: "all parts of a UNION must return the same columns".to_string() });
+            }
+            plan = LogicalPlan::Union { left: Box::new(plan), right: Box::new(right), all: union.all };
+        }
+        Ok(LogicalPlan::ProduceResults { input: Box::new(plan), columns })
+    }
+
+    fn standalone_call(&mut self, call: &StandaloneCall) -> LogicalPlan {
+        let (name, arguments) = match &call.invocation {
+            ProcedureInvocation::Explicit(invocation) => (invocation.name.clone(), Some(invocation.arguments.clone())),
+            ProcedureInvocation::Implicit(invocation) => (invocation.name.clone(), None),
+        };
+        let (yields, where_) = match &call.yield_ {
+            Some(StarOrYieldItems::YieldItems(YieldItems::Items { items, where_ })) => (Some(yield_pairs(items)), where_.clone()),
+            Some(StarOrYieldItems::Star | StarOrYieldItems::YieldItems(YieldItems::Star)) | None => (None, None),
+        };
+        let columns = yields.iter().flatten().map(|(_, variable)| variable.clone()).collect();
+        let mut pipeline = Pipeline::new(Vec::new());
+        pipeline.push(|input| LogicalPlan::ProcedureCall { input, name, arguments, yields });
+        pipeline.filter(where_.into_iter().collect());
+        LogicalPlan::ProduceResults { input: Box::new(pipeline.plan), columns }
+    }
+
+    /// Plans a query without `UNION` and returns its columns.
+    fn single_query(&mut self, query: &SingleQuery, scope: &[String]) -> Result<(LogicalPlan, Vec<String>), PlanError> {
+        let mut pipeline = Pipeline::new(scope.to_vec());
+        let last = match query {
+            SingleQuery::SinglePartQuery(query) => query,
+            SingleQuery::MultiPartQuery(query) => {
+                for part in &query.parts {
+                    self.clauses(&mut pipeline, &part.reading_clauses, &part.updating_clauses)?;
+                    self.with(&mut pipeline, &part.with)?;
+                }
+                &query.query
+            }
+        };
+        self.final_part(pipeline, last)
+    }
+
+    fn final_part(&mut self, mut pipeline: Pipeline, query: &SinglePartQuery) -> Result<(LogicalPlan, Vec<String>), PlanError> {
+        self.clauses(&mut pipeline, &query.reading_clauses, &query.updating_clauses)?;
+        match &query.return_ {
+            Some(body) => {
+                self.projection(&mut pipeline, body)?;
+                Ok((pipeline.plan, pipeline.scope))
+            }
+            None => Ok((pipeline.plan, Vec::new())),
+        }
+    }
+
+    fn clauses(
+        &mut self,
+        pipeline: &mut Pipeline,
+        reading_clauses: &[ReadingClause],
+        updating_clauses: &[UpdatingClause],
+    ) -> Result<(), PlanError> {
+        for clause in reading_clauses {
+            self.reading_clause(pipeline, clause)?;
+        }
+        for clause in updating_clauses {
+            self.updating_clause(pipeline, clause)?;
+        }
+        Ok(())
+    }
+
+    fn reading_clause(&mut self, pipeline: &mut Pipeline, clause: &ReadingClause) -> Result<(), PlanError> {
+        match clause {
+            ReadingClause::Match(match_) if match_.optional => {
+                let mut inner = Pipeline::new(pipeline.scope.clone());
+                self.match_(&mut inner, match_)?;
+                let variables: Vec<String> = inner.scope[pipeline.scope.len()..].to_vec();
+                let optional = LogicalPlan::Optional { input: Box::new(inner.plan), variables };
+                pipeline.push(|input| LogicalPlan::Apply { input, plan: Box::new(optional) });
+                pipeline.scope = inner.scope;
+            }
+            ReadingClause::Match(match_) => self.match_(pipeline, match_)?,
+            ReadingClause::Unwind(unwind) => {
+                let (expression, variable) = (unwind.expression.clone(), unwind.variable.name.clone());
+                pipeline.bind(&variable);
+                pipeline.push(|input| LogicalPlan::Unwind { input, expression, variable });
+            }
+            ReadingClause::LoadCsv(_) => return unsupported("LOAD CSV"),
+            ReadingClause::CallSubquery(_) => return unsupported("CALL { ... }"),
+            ReadingClause::InQueryCall(call) => self.in_query_call(pipeline, call),
+            ReadingClause::Filter(filter) => pipeline.filter(vec![filter.expression.clone()]),
+            ReadingClause::OrderSkipLimit(clause) => self.standalone_order_skip_limit(pipeline, clause),
+        }
+        Ok(())
+    }
+
+    fn standalone_order_skip_limit(&mut self, pipeline: &mut Pipeline, clause: &OrderSkipLimit) {
+        self.order_skip_limit(pipeline, clause.order.as_ref(), &[]);
+        push_skip_limit(pipeline, clause.skip.as_ref().map(|skip| &skip.expression), clause.limit.as_ref().map(|limit| &limit.expression));
+    }
+
+    fn in_query_call(&mut self, pipeline: &mut Pipeline, call: &InQueryCall) {
+        let (yields, where_) = match &call.yield_items {
+            Some(YieldItems::Items { items, where_ }) => (Some(yield_pairs(items)), where_.clone()),
+            Some(YieldItems::Star) => (None, None),
+            None => (Some(Vec::new()), None),
+        };
+        for (_, variable) in yields.iter().flatten() {
+            pipeline.bind(variable);
+        }
+        let (name, arguments) = (call.invocation.name.clone(), Some(call.invocation.arguments.clone()));
+        pipeline.push(|input| LogicalPlan::ProcedureCall { input, name, arguments, yields });
+        pipeline.filter(where_.into_iter().collect());
+    }
+
+    fn match_(&mut self, pipeline: &mut Pipeline, match_: &Match) -> Result<(), PlanError> {
+        let mut bound: BTreeSet<String> = pipeline.scope.iter().cloned().collect();
+        let mut relationships: Vec<(Variable, bool)> = Vec::new();
+
+        for part in &match_.pattern.parts {
+            let mut element = part.pattern_element.clone();
+            self.name_anonymous(&mut element);
+
+            let start = &mut element.node_pattern;
+            let variable = name_of(&start.variable);
+            if !bound.contains(&variable) {
+                let label_expression = start.label_expression.take();
+                pipeline.push(|input| LogicalPlan::NodeScan { input, variable: variable.clone(), label_expression });
+            }
+            self.node_predicates(pipeline, start, &mut bound)?;
+
+            let mut from = variable;
+            for (relationship, node) in &mut element.relationship_patterns {
+                let direction = direction(relationship);
+                let detail = relationship.detail_mut().expect("anonymous relationships are named");
+                let mut variable = detail.variable.clone().expect("anonymous relationships are named");
+                let mut predicates = Vec::new();
+                if bound.contains(&variable.name) {
+                    // A relationship bound before is matched again as a new one that must be the same.
+                    let rebound = self.hidden_variable("anon");
+                    predicates.push(equals(&rebound, &variable));
+                    variable = rebound;
+                }
+                let to = name_of(&node.variable);
+                let (relationship, into, types) = (variable.name.clone(), bound.contains(&to), detail.label_expression.take());
+                match detail.range {
+                    Some(range) => {
+                        let properties = detail.properties.take();
+                        let (from, to) = (from.clone(), to.clone());
+                        pipeline.push(|input| LogicalPlan::VarLengthExpand {
+                            input,
+                            from,
+                            relationship,
+                            to,
+                            direction,
+                            types,
+                            properties,
+                            min: range.min.unwrap_or(1),
+                            max: range.max,
+                            into,
+                        });
+                    }
+                    None => {
+                        predicates.extend(detail.take_inline_predicates());
+                        if let Some(Properties::Parameter(_)) = detail.properties {
+                            return unsupported("relationship properties given as a parameter");
+                        }
+                        let (from, to) = (from.clone(), to.clone());
+                        pipeline.push(|input| LogicalPlan::Expand { input, from, relationship, to, direction, types, into });
+                    }
+                }
+                pipeline.filter(predicates);
+                bound.insert(variable.name.clone());
+                relationships.push((variable, detail.range.is_some()));
+                bound.insert(to.clone());
+                self.node_predicates(pipeline, node, &mut bound)?;
+                from = to;
+            }
+
+            if let Some(path) = &part.variable {
+                let part = PatternPart { variable: Some(path.clone()), pattern_element: strip(element) };
+                pipeline.push(|input| LogicalPlan::ProjectPath { input, part });
+                bound.insert(path.name.clone());
+            }
+        }
+
+        // The relationships of a pattern are all different.
+        let mut predicates = Vec::new();
+        for (index, (a, a_list)) in relationships.iter().enumerate() {
+            for (b, b_list) in &relationships[index + 1..] {
+                predicates.push(match (a_list, b_list) {
+                    (false, false) => not_equals(a, b),
+                    (false, true) => not_in(a, b),
+                    (true, false) => not_in(b, a),
+                    (true, true) => {
+                        let element = self.hidden_variable("element");
+                        disjoint(&element, a, b)
+                    }
+                });
+            }
+        }
+        predicates.extend(match_.where_.clone());
+        pipeline.filter(predicates);
+
+        for variable in pattern_variables(&match_.pattern) {
+            pipeline.bind(&variable);
+        }
+        Ok(())
+    }
+
+    /// Gives every node and relationship of the pattern a variable, from left to right.
+    fn name_anonymous(&mut self, element: &mut PatternElement) {
+        if element.node_pattern.variable.is_none() {
+            element.node_pattern.variable = Some(self.hidden_variable("anon"));
+        }
+        for (relationship, node) in &mut element.relationship_patterns {
+            let (RelationshipPattern::LeftAndRight(detail)
+                | RelationshipPattern::Left(detail)
+                | RelationshipPattern::Right(detail)
+                | RelationshipPattern::Undirected(detail)) = relationship;
+            let detail = detail.get_or_insert_with(|| RelationshipDetail {
+                variable: None,
+                label_expression: None,
+                range: None,
+                properties: None,
+            });
+            if detail.variable.is_none() {
+                detail.variable = Some(self.hidden_variable("anon"));
+            }
+            if node.variable.is_none() {
+                node.variable = Some(self.hidden_variable("anon"));
+            }
+        }
+    }
+
+    /// Filters a node that has just been bound by the labels and properties of its pattern.
+    fn node_predicates(&mut self, pipeline: &mut Pipeline, node: &mut NodePattern, bound: &mut BTreeSet<String>) -> Result<(), PlanError> {
+        let mut predicates = Vec::new();
+        predicates.extend(node.take_inline_predicates());
+        if let Some(Properties::Parameter(_)) = node.properties {
+            return unsupported("node properties given as a parameter");
+        }
+        pipeline.filter(predicates);
+        bound.insert(name_of(&node.variable));
+        Ok(())
+    }
+
+    fn updating_clause(&mut self, pipeline: &mut Pipeline, clause: &UpdatingClause) -> Result<(), PlanError> {
+        match clause {
+            UpdatingClause::Create(create) => {
+                let pattern = create.pattern.clone();
+                for variable in pattern_variables(&pattern) {
+                    pipeline.bind(&variable);
+                }
+                pipeline.push(|input| LogicalPlan::Create { input, pattern });
+            }
+            UpdatingClause::Merge(merge) => {
+                let pattern = Pattern { parts: vec![merge.pattern_part.clone()] };
+                let mut inner = Pipeline::new(pipeline.scope.clone());
+                self.match_(&mut inner, &Match { optional: false, pattern, where_: None })?;
+                let (mut on_match, mut on_create) = (Vec::new(), Vec::new());
+                for action in &merge.actions {
+                    match action {
+                        MergeAction::Match(set) => on_match.extend(set.items.iter().cloned()),
+                        MergeAction::Create(set) => on_create.extend(set.items.iter().cloned()),
+                    }
+                }
+                let (plan, pattern) = (Box::new(inner.plan), merge.pattern_part.clone());
+                pipeline.push(|input| LogicalPlan::Merge { input, plan, pattern, on_match, on_create });
+                pipeline.scope = inner.scope;
+            }
+            UpdatingClause::Delete(delete) => {
+                let (expressions, detach) = (delete.expressions.clone(), delete.detach);
+                pipeline.push(|input| LogicalPlan::Delete { input, expressions, detach });
+            }
+            UpdatingClause::Set(set) => {
+                let items = set.items.clone();
+                pipeline.push(|input| LogicalPlan::SetProperty { input, items });
+            }
+            UpdatingClause::Remove(remove) => {
+                let items = remove.items.clone();
+                pipeline.push(|input| LogicalPlan::Remove { input, items });
+            }
+            UpdatingClause::Foreach(_) => return unsupported("FOREACH"),
+            UpdatingClause::Filter(filter) => pipeline.filter(vec![filter.expression.clone()]),
+            UpdatingClause::OrderSkipLimit(clause) => self.standalone_order_skip_limit(pipeline, clause),
+        }
+        Ok(())
+    }
+
+    fn with(&mut self, pipeline: &mut Pipeline, with: &With) -> Result<(), PlanError> {
+        self.projection(pipeline, &with.projection_body)?;
+        pipeline.filter(with.where_.iter().cloned().collect());
+        Ok(())
+    }
+
+    /// Plans the projection of a `WITH` or `RETURN`, including its `ORDER BY`,
+    /// `SKIP` and `LIMIT`, and replaces the scope with the projected names.
+    fn projection(&mut self, pipeline: &mut Pipeline, body: &ProjectionBody) -> Result<(), PlanError> {
+        let mut items: Vec<(String, Expression)> = Vec::new();
+        if body.items.star {
+            items.extend(pipeline.scope.iter().map(|name| (name.clone(), variable_expression(name))));
+        }
+        for item in &body.items.items {
+            items.push(match item {
+                ProjectionItem::Expression(expression) => (column_name(expression), expression.clone()),
+                ProjectionItem::AsVariable { expression, variable } => (variable.name.clone(), expression.clone()),
+            });
+        }
+        let names: Vec<String> = items.iter().map(|(name, _)| name.clone()).collect();
+        let skip = body.skip.as_ref().map(|skip| &skip.expression);
+        let limit = body.limit.as_ref().map(|limit| &limit.expression);
+
+        if items.iter().any(|(_, expression)| self.contains_aggregate(expression)) {
+            self.aggregation(pipeline, items.clone());
+            if body.distinct {
+                let grouping = names.iter().map(|name| (name.clone(), variable_expression(name))).collect();
+                pipeline.push(|input| LogicalPlan::Aggregate { input, grouping, aggregates: Vec::new() });
+            }
+            self.order_skip_limit(pipeline, body.order.as_ref(), &items);
+            push_skip_limit(pipeline, skip, limit);
+        }
+        else if body.distinct {
+            let grouping = items.clone();
+            pipeline.push(|input| LogicalPlan::Aggregate { input, grouping, aggregates: Vec::new() });
+            self.order_skip_limit(pipeline, body.order.as_ref(), &items);
+            push_skip_limit(pipeline, skip, limit);
+        }
+        else if body.order.is_some() {
+            // ORDER BY can refer to the variables before the projection as
+            // well, so they are kept until the rows are sorted.
+            let mut extended: Vec<(String, Expression)> = pipeline
+                .scope
+                .iter()
+                .filter(|name| !names.contains(name))
+                .map(|name| (name.clone(), variable_expression(name)))
+                .collect();
+            extended.extend(items.iter().cloned());
+            pipeline.push(|input| LogicalPlan::Projection { input, items: extended });
+            self.order_skip_limit(pipeline, body.order.as_ref(), &[]);
+            push_skip_limit(pipeline, skip, limit);
+            let items = names.iter().map(|name| (name.clone(), variable_expression(name))).collect();
+            pipeline.push(|input| LogicalPlan::Projection { input, items });
+        }
+        else {
+            pipeline.push(|input| LogicalPlan::Projection { input, items });
+            push_skip_limit(pipeline, skip, limit);
+        }
+        pipeline.scope = names;
+        Ok(())
+    }
+
+    /// Plans an aggregating projection. Items without aggregates are the
+    /// grouping keys, and the aggregates in the other items are computed
+    /// under hidden names unless they are the whole item.
+    fn aggregation(&mut self, pipeline: &mut Pipeline, items: Vec<(String, Expression)>) {
+        let (mut grouping, mut aggregates, mut projected) = (Vec::new(), Vec::new(), Vec::new());
+        let mut hidden = false;
+        for (name, expression) in items {
+            if !self.contains_aggregate(&expression) {
+                projected.push((name.clone(), variable_expression(&name)));
+                grouping.push((name, expression));
+            }
+            else if self.is_aggregate(&expression) {
+                projected.push((name.clone(), variable_expression(&name)));
+                aggregates.push((name, expression));
+            }
+            else {
+                let mut extractor = AggregateExtractor { planner: self, aggregates: Vec::new() };
+                let mut expression = expression;
+                extractor.visit_expression(&mut expression);
+                aggregates.extend(extractor.aggregates);
+                hidden = true;
+                projected.push((name, expression));
+            }
+        }
+        let keys = grouping.clone();
+        pipeline.push(|input| LogicalPlan::Aggregate { input, grouping, aggregates });
+        if hidden {
+            for (_, expression) in &mut projected {
+                substitute(expression, &keys);
+            }
+            pipeline.push(|input| LogicalPlan::Projection { input, items: projected });
+        }
+    }
+
+    /// Sorts the rows, referring to the projected items by their names.
+    fn order_skip_limit(&mut self, pipeline: &mut Pipeline, order: Option<&Order>, items: &[(String, Expression)]) {
+        let Some(order) = order else { return };
+        let mut sort_items = order.items.clone();
+        for item in &mut sort_items {
+            substitute(&mut item.expression, items);
+        }
+        pipeline.push(|input| LogicalPlan::Sort { input, items: sort_items });
+    }
+
+    fn contains_aggregate(&self, expression: &Expression) -> bool {
+        let mut finder = AggregateFinder { registry: &self.registry, found: false };
+        finder.visit_expression(expression);
+        finder.found
+    }
+
+    fn is_aggregate(&self, expression: &Expression) -> bool {
+        match expression.as_atom() {
+            Some(Atom::CountStar(_)) => true,
+            Some(Atom::FunctionInvocation(function)) => self.registry.is_aggregate(function),
+            _ => false,
+        }
+    }
+}
+
+fn push_skip_limit(pipeline: &mut Pipeline, skip: Option<&Expression>, limit: Option<&Expression>) {
+    if let Some(count) = skip.cloned() {
+        pipeline.push(|input| LogicalPlan::Skip { input, count });
+    }
+    if let Some(count) = limit.cloned() {
+        pipeline.push(|input| LogicalPlan::Limit { input, count });
+    }
+}
+
+fn yield_pairs(items: &[YieldItem]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .map(|item| {
+            let field = item.procedure_result_field.clone().unwrap_or_else(|| item.variable.name.clone());
+            (field, item.variable.name.clone())
+        })
+        .collect()
+}
+
+/// The name of the column of a projection item without an alias: the
+/// variable itself, or the text of the expression.
+fn column_name(expression: &Expression) -> String {
+    match expression.as_atom() {
+        Some(Atom::Variable(variable)) => variable.name.clone(),
+        _ => expression.to_string(),
+    }
+}
+
+fn name_of(variable: &Option<Variable>) -> String {
+    variable.as_ref().map(|variable| variable.name.clone()).expect("anonymous nodes are named")
+}
+
+fn direction(relationship: &RelationshipPattern) -> Direction {
+    match relationship {
+        RelationshipPattern::Right(_) => Direction::Outgoing,
+        RelationshipPattern::Left(_) => Direction::Incoming,
+        RelationshipPattern::LeftAndRight(_) | RelationshipPattern::Undirected(_) => Direction::Both,
+    }
+}
+
+/// Returns the named variables of the pattern in the order they appear.
+fn pattern_variables(pattern: &Pattern) -> Vec<String> {
+    let mut variables = Vec::new();
+    for part in &pattern.parts {
+        let element = &part.pattern_element;
+        variables.extend(element.node_pattern.variable.iter().map(|variable| variable.name.clone()));
+        for (relationship, node) in &element.relationship_patterns {
+            variables.extend(relationship.detail().and_then(|detail| detail.variable.as_ref()).map(|variable| variable.name.clone()));
+            variables.extend(node.variable.iter().map(|variable| variable.name.clone()));
+        }
+        variables.extend(part.variable.iter().map(|variable| variable.name.clone()));
+    }
+    variables
+}
+
+/// Removes everything but the variables and lengths from the pattern of a path.
+fn strip(mut element: PatternElement) -> PatternElement {
+    let strip_node = |node: &mut NodePattern| {
+        node.label_expression = None;
+        node.properties = None;
+    };
+    strip_node(&mut element.node_pattern);
+    for (relationship, node) in &mut element.relationship_patterns {
+        if let Some(detail) = relationship.detail_mut() {
+            detail.label_expression = None;
+            detail.properties = None;
+        }
+        strip_node(node);
+    }
+    element
+}
+
+fn variable_expression(name: &str) -> Expression {
+    Expression::from_add_or_subtract(AddOrSubtractExpression::property_or_labels(&Variable { name: name.to_string(), span: Span::default() }, Vec::new(), None))
+}
+
+fn comparison(a: &Variable, operator: ComparisonOperator, b: &Variable) -> Expression {
+    Expression::from_not(NotExpression {
+        not: false,
+        expression: ComparisonExpression {
+            expression: AddOrSubtractExpression::property_or_labels(a, Vec::new(), None),
+            comparisons: vec![(operator, AddOrSubtractExpression::property_or_labels(b, Vec::new(), None))],
+        },
+    })
+}
+
+fn equals(a: &Variable, b: &Variable) -> Expression {
+    comparison(a, ComparisonOperator::Equal, b)
+}
+
+fn not_equals(a: &Variable, b: &Variable) -> Expression {
+    comparison(a, ComparisonOperator::NotEqual, b)
+}
+
+/// `element IN list`
+fn in_list(element: &Variable, list: &Variable) -> ComparisonExpression {
+    let mut expression = AddOrSubtractExpression::property_or_labels(element, Vec::new(), None);
+    let operand = &mut expression.expression.expression.0[0].expression;
+    operand.operations.push(StringListNullOperatorExpressionInner::ListOperator(ListOperatorExpression::In(Box::new(PropertyOrLabelsExpression::plain(Atom::Variable(
+        list.clone(),
+    ))))));
+    ComparisonExpression { expression, comparisons: Vec::new() }
+}
+
+/// `NOT element IN list`
+fn not_in(element: &Variable, list: &Variable) -> Expression {
+    Expression::from_not(NotExpression { not: true, expression: in_list(element, list) })
+}
+
+/// `none(element IN a WHERE element IN b)`
+fn disjoint(element: &Variable, a: &Variable, b: &Variable) -> Expression {
+    let filter = FilterExpression {
+        id_in_coll: IdInColl { variable: element.clone(), expression: variable_expression(&a.name) },
+        where_: Some(Expression::from_not(NotExpression { not: false, expression: in_list(element, b) })),
+    };
+    Expression::from_atom(Atom::None(filter))
+}
+
+/// Replaces the parts of the expression that are one of the items by the
+/// name of the item.
+fn substitute(expression: &mut Expression, items: &[(String, Expression)]) {
+    let items: Vec<(String, String)> = items.iter().map(|(name, expression)| (expression.to_string(), name.clone())).collect();
+    Substitution { items }.visit_expression(expression);
+}
+
+struct Substitution {
+    /// The texts of the expressions and the names to replace them with.
+    items: Vec<(String, String)>,
+}
+
+impl Substitution {
+    fn name(&self, text: &str) -> Option<&str> {
+        self.items.iter().find(|(expression, _)| expression == text).map(|(_, name)| name.as_str())
+    }
+}
+
+impl VisitorMut for Substitution {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match self.name(&expression.to_string()) {
+            Some(name) => *expression = variable_expression(name),
+            None => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_property_or_labels_expression(&mut self, expression: &mut PropertyOrLabelsExpression) {
+        let text = Expression::from_add_or_subtract(AddOrSubtractExpression::from_property_or_labels(expression.clone())).to_string();
+        match self.name(&text) {
+            Some(name) => *expression = PropertyOrLabelsExpression::plain(Atom::Variable(Variable { name: name.to_string(), span: Span::default() })),
+            None => walk_property_or_labels_expression(self, expression),
+        }
+    }
+}
+
+struct AggregateFinder<'r> {
+    registry: &'r FunctionRegistry,
+    found: bool,
+}
+
+impl<'ast> Visitor<'ast> for AggregateFinder<'_> {
+    fn visit_atom(&mut self, atom: &'ast Atom) {
+        match atom {
+            Atom::CountStar(_) => self.found = true,
+            Atom::FunctionInvocation(function) if self.registry.is_aggregate(function) => self.found = true,
+            _ => walk_atom_ref(self, atom),
+        }
+    }
+}
+
+/// Replaces the aggregates of an expression by hidden variables.
+struct AggregateExtractor<'p> {
+    planner: &'p mut Planner,
+    aggregates: Vec<(String, Expression)>,
+}
+
+impl VisitorMut for AggregateExtractor<'_> {
+    fn visit_atom(&mut self, atom: &mut Atom) {
+        let aggregate = match atom {
+            Atom::CountStar(_) => true,
+            Atom::FunctionInvocation(function) => self.planner.registry.is_aggregate(function),
+            _ => false,
+        };
+        if aggregate {
+            let variable = self.planner.hidden_variable("aggregate");
+            let call = std::mem::replace(atom, Atom::Variable(variable.clone()));
+            self.aggregates.push((variable.name, Expression::from_atom(call)));
+        }
+        else {
+            walk_atom(self, atom);
+        }
+    }
+}
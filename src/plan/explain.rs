@@ -0,0 +1,202 @@
+//! The `EXPLAIN` text of a plan.
+//!
+//! Every operator is written on a line of its own as `+Name details`,
+//! followed by its input. The second input of an `Apply`, a `Merge` and a
+//! `Union` is written first, indented with `| `, so that the main input of
+//! every operator continues at the same indentation.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{Direction, LogicalPlan};
+use crate::ast::cypher::{Expression, LabelExpression, Name, Properties, RangeLiteral, RelationshipDetail, RelationshipPattern, Span, Variable};
+
+impl Display for LogicalPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_plan(f, self, "")
+    }
+}
+
+fn write_plan(f: &mut Formatter<'_>, plan: &LogicalPlan, indent: &str) -> fmt::Result {
+    write!(f, "{}+", indent)?;
+    write_operator(f, plan)?;
+    f.write_str("\n")?;
+    match plan {
+        LogicalPlan::Argument { .. } => Ok(()),
+        LogicalPlan::Union { left, right, .. } => {
+            write_plan(f, right, &format!("{}| ", indent))?;
+            write_plan(f, left, indent)
+        }
+        LogicalPlan::Apply { input, plan } | LogicalPlan::Merge { input, plan, .. } => {
+            write_plan(f, plan, &format!("{}| ", indent))?;
+            write_plan(f, input, indent)
+        }
+        _ => write_plan(f, plan.children()[0], indent),
+    }
+}
+
+/// Writes the name of the operator and its details.
+fn write_operator(f: &mut Formatter<'_>, plan: &LogicalPlan) -> fmt::Result {
+    match plan {
+        LogicalPlan::Argument { variables } => write_named(f, "Argument", &Names(variables)),
+        LogicalPlan::NodeScan { variable, label_expression, .. } => {
+            write!(f, "NodeScan {}", Name(variable))?;
+            match label_expression {
+                Some(label_expression) => write!(f, ":{}", label_expression),
+                None => Ok(()),
+            }
+        }
+        LogicalPlan::Expand { from, relationship, to, direction, types, into, .. } => {
+            let pattern = Hop { from, relationship, to, direction: *direction, types, range: None, properties: &None };
+            write!(f, "Expand({}) {}", if *into { "Into" } else { "All" }, pattern)
+        }
+        LogicalPlan::VarLengthExpand { from, relationship, to, direction, types, properties, min, max, into, .. } => {
+            let range = Some(RangeLiteral { min: Some(*min), max: *max });
+            let pattern = Hop { from, relationship, to, direction: *direction, types, range, properties };
+            write!(f, "VarLengthExpand({}) {}", if *into { "Into" } else { "All" }, pattern)
+        }
+        LogicalPlan::Filter { predicate, .. } => write!(f, "Filter {}", predicate),
+        LogicalPlan::Projection { items, .. } => write_named(f, "Projection", &Items(items)),
+        LogicalPlan::Aggregate { grouping, aggregates, .. } => {
+            f.write_str("Aggregate")?;
+            let items: Vec<_> = grouping.iter().chain(aggregates).cloned().collect();
+            if !items.is_empty() {
+                write!(f, " {}", Items(&items))?;
+            }
+            Ok(())
+        }
+        LogicalPlan::Sort { items, .. } => write!(f, "Sort {}", Separated(items)),
+        LogicalPlan::Skip { count, .. } => write!(f, "Skip {}", count),
+        LogicalPlan::Limit { count, .. } => write!(f, "Limit {}", count),
+        LogicalPlan::Unwind { expression, variable, .. } => write!(f, "Unwind {} AS {}", expression, Name(variable)),
+        LogicalPlan::Optional { variables, .. } => write_named(f, "Optional", &Names(variables)),
+        LogicalPlan::Union { all, .. } => f.write_str(if *all { "Union ALL" } else { "Union" }),
+        LogicalPlan::Apply { .. } => f.write_str("Apply"),
+        LogicalPlan::ProcedureCall { name, arguments, yields, .. } => {
+            write!(f, "ProcedureCall {}", name)?;
+            if let Some(arguments) = arguments {
+                write!(f, "({})", Separated(arguments))?;
+            }
+            match yields {
+                None => f.write_str(" YIELD *"),
+                Some(yields) if yields.is_empty() => Ok(()),
+                Some(yields) => {
+                    f.write_str(" YIELD ")?;
+                    for (index, (field, variable)) in yields.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str(", ")?;
+                        }
+                        if field == variable {
+                            write!(f, "{}", Name(variable))?;
+                        }
+                        else {
+                            write!(f, "{} AS {}", Name(field), Name(variable))?;
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        }
+        LogicalPlan::Create { pattern, .. } => write!(f, "Create {}", pattern),
+        LogicalPlan::Merge { pattern, on_match, on_create, .. } => {
+            write!(f, "Merge {}", pattern)?;
+            if !on_match.is_empty() {
+                write!(f, " ON MATCH SET {}", Separated(on_match))?;
+            }
+            if !on_create.is_empty() {
+                write!(f, " ON CREATE SET {}", Separated(on_create))?;
+            }
+            Ok(())
+        }
+        LogicalPlan::Delete { expressions, detach, .. } => {
+            write!(f, "Delete {}{}", if *detach { "DETACH " } else { "" }, Separated(expressions))
+        }
+        LogicalPlan::SetProperty { items, .. } => write!(f, "SetProperty {}", Separated(items)),
+        LogicalPlan::Remove { items, .. } => write!(f, "Remove {}", Separated(items)),
+        LogicalPlan::ProjectPath { part, .. } => write!(f, "ProjectPath {}", part),
+        LogicalPlan::ProduceResults { columns, .. } => write_named(f, "ProduceResults", &Names(columns)),
+    }
+}
+
+/// Writes the name of an operator and its details unless they are empty.
+fn write_named(f: &mut Formatter<'_>, name: &str, details: &dyn Display) -> fmt::Result {
+    let details = details.to_string();
+    if details.is_empty() {
+        f.write_str(name)
+    }
+    else {
+        write!(f, "{} {}", name, details)
+    }
+}
+
+struct Separated<'a, T>(&'a [T]);
+
+impl<T: Display> Display for Separated<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, item) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+struct Names<'a>(&'a [String]);
+
+impl Display for Names<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.0.iter().map(|name| Name(name)).collect();
+        write!(f, "{}", Separated(&names))
+    }
+}
+
+/// Named expressions, written as `expression AS name` unless the expression
+/// is written as the name.
+struct Items<'a>(&'a [(String, Expression)]);
+
+impl Display for Items<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, (name, expression)) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            let expression = expression.to_string();
+            if expression == Name(name).to_string() {
+                f.write_str(&expression)?;
+            }
+            else {
+                write!(f, "{} AS {}", expression, Name(name))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single relationship between two nodes, written as a pattern.
+struct Hop<'a> {
+    from: &'a str,
+    relationship: &'a str,
+    to: &'a str,
+    direction: Direction,
+    types: &'a Option<LabelExpression>,
+    range: Option<RangeLiteral>,
+    properties: &'a Option<Properties>,
+}
+
+impl Display for Hop<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let detail = Some(RelationshipDetail {
+            variable: Some(Variable { name: self.relationship.to_string(), span: Span::default() }),
+            label_expression: self.types.clone(),
+            range: self.range,
+            properties: self.properties.clone(),
+        });
+        let relationship = match self.direction {
+            Direction::Outgoing => RelationshipPattern::Right(detail),
+            Direction::Incoming => RelationshipPattern::Left(detail),
+            Direction::Both => RelationshipPattern::Undirected(detail),
+        };
+        write!(f, "({}){}({})", Name(self.from), relationship, Name(self.to))
+    }
+}
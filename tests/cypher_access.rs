@@ -0,0 +1,121 @@
+use open_cypher::analysis::access::{summarize_access, AccessMode, AccessSummary};
+use open_cypher::analysis::procedures::{ProcedureMode, ProcedureRegistry, ProcedureSignature};
+use open_cypher::ast::cypher::{CypherType, Query};
+
+fn parse(text: &str) -> Query {
+    Query::parse(text).unwrap()
+}
+
+fn set(names: &[&str]) -> std::collections::BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) RETURN n", AccessMode::Read)]
+#[case("MATCH (n)-[r]->(m) WITH n, count(r) AS c WHERE c > 1 RETURN n", AccessMode::Read)]
+#[case("UNWIND [1, 2] AS x RETURN x", AccessMode::Read)]
+#[case("LOAD CSV FROM 'file:///a.csv' AS row RETURN row", AccessMode::Read)]
+#[case("MATCH (n) RETURN n UNION MATCH (n) RETURN n", AccessMode::Read)]
+#[case("CALL db.labels()", AccessMode::Read)]
+#[case("MATCH (n) CALL db.labels() YIELD label RETURN n, label", AccessMode::Read)]
+#[case("CREATE (n:Person)", AccessMode::Write)]
+#[case("MERGE (n:Person {name: 'A'})", AccessMode::Write)]
+#[case("MATCH (n) SET n.name = 'A'", AccessMode::Write)]
+#[case("MATCH (n) REMOVE n:Person", AccessMode::Write)]
+#[case("MATCH (n) DETACH DELETE n", AccessMode::Write)]
+#[case("MATCH (n) SET n.x = 1 FILTER n.y > 0 LIMIT 1 RETURN n", AccessMode::Write)]
+#[case("MATCH (n) WITH n CREATE (m) WITH m MATCH (o) RETURN o", AccessMode::Write)]
+#[case("MATCH (n) RETURN n UNION MATCH (n) CREATE (m) RETURN m AS n", AccessMode::Write)]
+#[case("CALL db.createLabel('Person')", AccessMode::Write)]
+#[case("MATCH (n) FOREACH (x IN [1] | SET n.x = x)", AccessMode::Write)]
+#[case("MATCH (n) FOREACH (x IN [1] | FOREACH (y IN [x] | CREATE (:A {y: y})))", AccessMode::Write)]
+#[case("MATCH (n) CALL db.createLabel('Person') RETURN n", AccessMode::Write)]
+#[case("CREATE INDEX FOR (n:Person) ON (n.name)", AccessMode::Schema)]
+#[case("DROP INDEX person_name IF EXISTS", AccessMode::Schema)]
+#[case("CALL db.awaitIndexes()", AccessMode::Schema)]
+#[case("CALL dbms.listConfig()", AccessMode::Admin)]
+#[case("CALL my.proc()", AccessMode::UnknownProcedure)]
+#[case("CREATE (n) WITH n CALL my.proc() YIELD x RETURN x", AccessMode::UnknownProcedure)]
+#[case("MATCH (n) WHERE EXISTS { CALL my.proc() YIELD x RETURN x } RETURN n", AccessMode::UnknownProcedure)]
+fn access_test_mode(#[case] text: &str, #[case] mode: AccessMode) {
+    assert_eq!(open_cypher::parse_cypher(text).unwrap().access_mode(), mode, "{}", text);
+}
+
+#[test]
+fn access_test_custom_catalog() {
+    let query = parse("MATCH (n) CALL my.proc(n) YIELD x RETURN x");
+    let mut registry = ProcedureRegistry::new();
+    assert_eq!(summarize_access(&query, &registry).mode, AccessMode::UnknownProcedure);
+
+    registry.register(ProcedureSignature::new("my.proc", ProcedureMode::Read).input("node", CypherType::Node).output("x", CypherType::Any));
+    assert_eq!(summarize_access(&query, &registry).mode, AccessMode::Read);
+
+    registry.register(ProcedureSignature::new("my.proc", ProcedureMode::Write).input("node", CypherType::Node).output("x", CypherType::Any));
+    assert_eq!(summarize_access(&query, &registry).mode, AccessMode::Write);
+}
+
+#[test]
+fn access_test_summary_read() {
+    let summary = parse(
+        "MATCH (a:Person {name: 'A'})-[:KNOWS|LIKES]->(b:Person&!Robot) \
+         WHERE b.age > 18 AND a:Admin AND EXISTS { (b)-[:OWNS]->(:Car) } AND (b)-[:HAS]->({title: 'x'}) \
+         RETURN b {.nickname}",
+    )
+    .access_summary();
+
+    assert_eq!(
+        summary,
+        AccessSummary {
+            mode: AccessMode::Read,
+            labels_read: set(&["Admin", "Car", "Person", "Robot"]),
+            relationship_types_read: set(&["HAS", "KNOWS", "LIKES", "OWNS"]),
+            property_keys_read: set(&["age", "name", "nickname", "title"]),
+            ..AccessSummary::default()
+        }
+    );
+}
+
+#[test]
+fn access_test_summary_write() {
+    let summary = parse(
+        "MATCH (a:Person) \
+         CREATE (a)-[:KNOWS {since: a.born}]->(b:Person:Friend {name: 'B'}) \
+         SET a.visited = true, a:Visited, b += {score: 1} \
+         REMOVE a.temp, b:Friend",
+    )
+    .access_summary();
+
+    assert_eq!(
+        summary,
+        AccessSummary {
+            mode: AccessMode::Write,
+            labels_read: set(&["Person"]),
+            labels_written: set(&["Friend", "Person", "Visited"]),
+            relationship_types_written: set(&["KNOWS"]),
+            property_keys_read: set(&["born"]),
+            property_keys_written: set(&["name", "score", "since", "temp", "visited"]),
+            ..AccessSummary::default()
+        }
+    );
+}
+
+#[test]
+fn access_test_summary_merge() {
+    let summary = parse("MERGE (n:Person {name: $name}) ON CREATE SET n.created = timestamp() ON MATCH SET n.seen = n.seen + 1").access_summary();
+
+    assert_eq!(summary.mode, AccessMode::Write);
+    assert_eq!(summary.labels_read, set(&["Person"]));
+    assert_eq!(summary.labels_written, set(&["Person"]));
+    assert_eq!(summary.property_keys_read, set(&["name", "seen"]));
+    assert_eq!(summary.property_keys_written, set(&["created", "name", "seen"]));
+}
+
+#[test]
+fn access_test_summary_schema() {
+    let summary = open_cypher::parse_cypher("CREATE CONSTRAINT IF NOT EXISTS FOR (p:Person) REQUIRE (p.first, p.last) IS NODE KEY").unwrap().access_summary();
+
+    assert_eq!(summary.mode, AccessMode::Schema);
+    assert_eq!(summary.labels_read, set(&["Person"]));
+    assert_eq!(summary.property_keys_read, set(&["first", "last"]));
+    assert!(summary.labels_written.is_empty() && summary.property_keys_written.is_empty());
+}
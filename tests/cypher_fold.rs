@@ -0,0 +1,130 @@
+use open_cypher::analysis::fold::{fold_constants, fold_expression};
+use open_cypher::ast::cypher::{Query, ReadingClause, SingleQuery};
+
+fn folded(text: &str) -> String {
+    fold_constants(&Query::parse(text).unwrap()).to_string()
+}
+
+#[rstest::rstest]
+#[case("RETURN 1 + 2 * 3 AS x", "RETURN 7 AS x")]
+#[case("RETURN (1 + 2) * 3 AS x", "RETURN 9 AS x")]
+#[case("RETURN 7 / 2 AS x, 7 % 3 AS y, -7 / 2 AS z", "RETURN 3 AS x, 1 AS y, -3 AS z")]
+#[case("RETURN 1 + 2.5 AS x", "RETURN 3.5 AS x")]
+#[case("RETURN 2 ^ 3 ^ 2 AS x", "RETURN 64.0 AS x")]
+#[case("RETURN 1 - 5 AS x", "RETURN -4 AS x")]
+#[case("RETURN -(2 * 3) AS x", "RETURN -6 AS x")]
+#[case("RETURN 1 / 0 AS x", "RETURN 1 / 0 AS x")]
+#[case("RETURN 1.0 / 0 AS x", "RETURN 1.0 / 0 AS x")]
+#[case("RETURN 9223372036854775807 + 1 AS x", "RETURN 9223372036854775807 + 1 AS x")]
+#[case("MATCH (n) RETURN 1 + 2 + n.a AS x", "MATCH (n) RETURN 3 + n.a AS x")]
+#[case("MATCH (n) RETURN n.a + 1 + 2 AS x", "MATCH (n) RETURN n.a + 1 + 2 AS x")]
+#[case("RETURN 'a' + 'b' AS x, 'a' || 'b' AS y, 'n' + 1 AS z", "RETURN 'ab' AS x, 'ab' AS y, 'n1' AS z")]
+#[case("RETURN [1, 2] + [3] AS x, [1] + 2 AS y", "RETURN [1, 2, 3] AS x, [1, 2] AS y")]
+#[case("RETURN 1 + null AS x, -null AS y, null * 2 AS z", "RETURN null AS x, null AS y, null AS z")]
+fn fold_test_arithmetic(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) WHERE n.a AND true RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE n.a AND false RETURN n", "MATCH (n) WHERE false RETURN n")]
+#[case("MATCH (n) WHERE n.a OR true RETURN n", "MATCH (n) WHERE true RETURN n")]
+#[case("MATCH (n) WHERE n.a OR false RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE n.a XOR false RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE n.a XOR true XOR true RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE NOT NOT n.a RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE NOT (NOT n.a) RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE NOT ((NOT (n.a))) RETURN n", "MATCH (n) WHERE n.a RETURN n")]
+#[case("MATCH (n) WHERE NOT (n.a OR n.b) RETURN n", "MATCH (n) WHERE NOT (n.a OR n.b) RETURN n")]
+#[case("MATCH (n) WHERE n.a AND 1 < 2 AND n.b RETURN n", "MATCH (n) WHERE n.a AND n.b RETURN n")]
+#[case("MATCH (n) WHERE (n.a OR 1 = 2) AND n.b RETURN n", "MATCH (n) WHERE n.a AND n.b RETURN n")]
+#[case("RETURN NOT true AS x, true XOR true AS y, 1 = 1 AND 2 > 1 AS z", "RETURN false AS x, false AS y, true AS z")]
+fn fold_test_boolean(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[rstest::rstest]
+#[case("RETURN null AND false AS x", "RETURN false AS x")]
+#[case("RETURN null AND true AS x", "RETURN null AS x")]
+#[case("RETURN null OR true AS x", "RETURN true AS x")]
+#[case("RETURN null OR false AS x", "RETURN null AS x")]
+#[case("RETURN null XOR true AS x", "RETURN null AS x")]
+#[case("RETURN NOT null AS x", "RETURN null AS x")]
+#[case("MATCH (n) WHERE n.a AND null RETURN n", "MATCH (n) WHERE n.a AND null RETURN n")]
+#[case("MATCH (n) WHERE n.a OR null OR false RETURN n", "MATCH (n) WHERE n.a OR null RETURN n")]
+#[case("RETURN null = null AS x, null <> 1 AS y, 1 < null AS z", "RETURN null AS x, null AS y, null AS z")]
+#[case("RETURN null IS NULL AS x, 1 IS NULL AS y, null IS NOT NULL AS z", "RETURN true AS x, false AS y, false AS z")]
+#[case("MATCH (n) RETURN null.name AS x, (1 + null) IS NULL AS y", "MATCH (n) RETURN null AS x, true AS y")]
+#[case("RETURN 'abc' STARTS WITH null AS x", "RETURN null AS x")]
+fn fold_test_null(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[rstest::rstest]
+#[case("RETURN 1 < 2 < 3 AS x, 1 < 3 < 2 AS y", "RETURN true AS x, false AS y")]
+#[case("RETURN 1 = 1.0 AS x, 'a' = 1 AS y, 'a' < 'b' AS z", "RETURN true AS x, false AS y, true AS z")]
+#[case("RETURN 'a' < 1 AS x, false < true AS y", "RETURN null AS x, true AS y")]
+#[case("RETURN [1, null] = [1, 2] AS x, [1, null] = [2, 2] AS y, [1] = [1, 2] AS z", "RETURN null AS x, false AS y, false AS z")]
+#[case("RETURN [1, 2] < [1, 3] AS x", "RETURN [1, 2] < [1, 3] AS x")]
+#[case("RETURN 'abc' STARTS WITH 'a' AS x, 'abc' CONTAINS 'd' AS y, 'abc' =~ 'a.*' AS z", "RETURN true AS x, false AS y, 'abc' =~ 'a.*' AS z")]
+#[case("RETURN [1, 2, 3][1] AS x, [1, 2, 3][-1] AS y, [1, 2, 3][5] AS z", "RETURN 2 AS x, 3 AS y, null AS z")]
+#[case("RETURN [1, 2, 3][1..] AS x, [1, 2, 3][..-1] AS y, [1, 2, 3][2..1] AS z", "RETURN [2, 3] AS x, [1, 2] AS y, [] AS z")]
+fn fold_test_comparison(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[rstest::rstest]
+#[case("RETURN 2 IN [1, 2, 3] AS x", "RETURN true AS x")]
+#[case("RETURN 4 IN [1, 2, 3] AS x", "RETURN false AS x")]
+#[case("RETURN 4 IN [1, null, 3] AS x", "RETURN null AS x")]
+#[case("RETURN 1 IN [1, null, 3] AS x", "RETURN true AS x")]
+#[case("RETURN null IN [1, 2] AS x, null IN [] AS y, 1 IN null AS z", "RETURN null AS x, false AS y, null AS z")]
+#[case("RETURN [1, 2] IN [[1, 2], [3]] AS x", "RETURN true AS x")]
+#[case("RETURN 1 IN [1 + 1, 2 - 1] AS x", "RETURN true AS x")]
+#[case("MATCH (n) RETURN n.a IN [1, 1 + 1] AS x, 1 IN [n.a] AS y", "MATCH (n) RETURN n.a IN [1, 2] AS x, 1 IN [n.a] AS y")]
+fn fold_test_in(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[rstest::rstest]
+#[case("RETURN CASE WHEN true THEN 1 ELSE 2 END AS x", "RETURN 1 AS x")]
+#[case("RETURN CASE WHEN false THEN 1 ELSE 2 END AS x", "RETURN 2 AS x")]
+#[case("RETURN CASE WHEN null THEN 1 END AS x", "RETURN null AS x")]
+#[case("MATCH (n) RETURN CASE WHEN 1 > 2 THEN n.a WHEN n.b THEN 1 ELSE 2 END AS x", "MATCH (n) RETURN CASE WHEN n.b THEN 1 ELSE 2 END AS x")]
+#[case("MATCH (n) RETURN CASE WHEN n.b THEN 1 WHEN true THEN 2 END AS x", "MATCH (n) RETURN CASE WHEN n.b THEN 1 WHEN true THEN 2 END AS x")]
+#[case("MATCH (n) RETURN CASE WHEN 1 < 2 THEN n.a + 1 END AS x", "MATCH (n) RETURN (n.a + 1) AS x")]
+#[case("RETURN CASE 2 WHEN 1 THEN 'one' WHEN 2 THEN 'two' END AS x", "RETURN 'two' AS x")]
+#[case("RETURN CASE null WHEN null THEN 1 ELSE 2 END AS x", "RETURN 2 AS x")]
+#[case("MATCH (n) RETURN CASE n.a WHEN 1 + 1 THEN 'two' END AS x", "MATCH (n) RETURN CASE n.a WHEN 2 THEN 'two' END AS x")]
+fn fold_test_case(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n {a: 1 + 1}) WHERE size([x IN n.list WHERE x > 2 * 3]) > 0 RETURN n", "MATCH (n {a: 2}) WHERE size([x IN n.list WHERE x > 6]) > 0 RETURN n")]
+#[case("MATCH (n) RETURN {a: 1 + 1, b: n.b} AS m", "MATCH (n) RETURN {a: 2, b: n.b} AS m")]
+#[case("MATCH (n) WHERE EXISTS { (n)-->(m) WHERE m.a = 2 * 2 } RETURN n", "MATCH (n) WHERE EXISTS { (n)-->(m) WHERE m.a = 4 } RETURN n")]
+#[case("UNWIND [1, 2 * 2] AS x RETURN x ORDER BY x SKIP 1 + 1 LIMIT 2 * 5", "UNWIND [1, 4] AS x RETURN x ORDER BY x SKIP 2 LIMIT 10")]
+#[case("MATCH (n) RETURN (n.a) AS x, -(-1) AS y", "MATCH (n) RETURN n.a AS x, 1 AS y")]
+fn fold_test_nested(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(folded(text), expected);
+}
+
+#[test]
+fn fold_test_expression() {
+    let query = Query::parse("MATCH (n) WHERE n.a > 1 + 1 AND true RETURN n").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = &query.query else { panic!("expected a single part query") };
+    let ReadingClause::Match(match_) = &query.reading_clauses[0] else { panic!("expected a MATCH") };
+    let where_ = match_.where_.as_ref().unwrap();
+    assert_eq!(fold_expression(where_).to_string(), "n.a > 2");
+    // Folding is idempotent.
+    assert_eq!(fold_expression(&fold_expression(where_)), fold_expression(where_));
+}
+
+#[test]
+fn fold_test_reparse() {
+    let text = folded("RETURN 1 - 5 AS x, 0.5 - 1 AS y, [2 - 3] AS z, (0 - 1) IS NULL AS w");
+    assert_eq!(text, "RETURN -4 AS x, -0.5 AS y, [-1] AS z, false AS w");
+    assert_eq!(folded(&text), text);
+}
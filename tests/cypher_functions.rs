@@ -0,0 +1,145 @@
+use open_cypher::analysis::aggregation::{check_aggregation, check_aggregation_with};
+use open_cypher::analysis::functions::{check_functions, Determinism, FunctionCategory, FunctionRegistry, FunctionSignature, ReturnType};
+use open_cypher::analysis::semantic::SemanticErrorKind;
+use open_cypher::analysis::types::infer_types_with;
+use open_cypher::ast::cypher::{CypherType, QualifiedName, Query};
+
+fn parse(text: &str) -> Query {
+    Query::parse(text).unwrap()
+}
+
+fn name(name: &str) -> QualifiedName {
+    let mut parts: Vec<String> = name.split('.').map(str::to_string).collect();
+    let name = parts.pop().unwrap();
+    QualifiedName { namespace: parts, name }
+}
+
+#[rstest::rstest]
+#[case("RETURN toUpper('a'), TOUPPER('b'), tolower('C')")]
+#[case("RETURN substring('abc', 1), substring('abc', 1, 1)")]
+#[case("RETURN range(1, 10), range(1, 10, 2)")]
+#[case("RETURN coalesce(null), coalesce(null, 1, 2, 3)")]
+#[case("RETURN round(1.5), round(1.55, 1), round(1.55, 1, 'HALF_UP')")]
+#[case("RETURN date(), date('2020-01-01'), date.truncate('month', date())")]
+#[case("RETURN datetime.fromEpoch(0, 0), duration.between(date(), date())")]
+#[case("RETURN point.distance(point({x: 0, y: 0}), point({x: 1, y: 1}))")]
+#[case("MATCH (n) RETURN count(DISTINCT n), collect(n.name), percentileCont(n.age, 0.5)")]
+#[case("RETURN pi(), e(), rand(), randomUUID(), timestamp()")]
+#[case("RETURN [x IN [1, 2] | sqrt(x)]")]
+fn functions_test_valid(#[case] text: &str) {
+    let errors = check_functions(&parse(text), &FunctionRegistry::builtin());
+    assert!(errors.is_empty(), "unexpected errors for {}: {:?}", text, errors);
+}
+
+#[rstest::rstest]
+#[case("RETURN foo(1)", SemanticErrorKind::UnknownFunction, "foo(1)", "unknown function `foo`")]
+#[case("RETURN my.lib.udf()", SemanticErrorKind::UnknownFunction, "my.lib.udf()", "unknown function `my.lib.udf`")]
+#[case("RETURN [x IN [1] | bar(x)]", SemanticErrorKind::UnknownFunction, "bar(x)", "unknown function `bar`")]
+#[case("RETURN toUpper()", SemanticErrorKind::InvalidNumberOfArguments, "toUpper()", "expects 1 arguments but got 0")]
+#[case("RETURN toUpper('a', 'b')", SemanticErrorKind::InvalidNumberOfArguments, "toUpper('a', 'b')", "expects 1 arguments but got 2")]
+#[case("RETURN substring('a')", SemanticErrorKind::InvalidNumberOfArguments, "substring('a')", "expects 2 to 3 arguments but got 1")]
+#[case("RETURN coalesce()", SemanticErrorKind::InvalidNumberOfArguments, "coalesce()", "expects at least 1 arguments but got 0")]
+#[case("RETURN pi(1)", SemanticErrorKind::InvalidNumberOfArguments, "pi(1)", "expects 0 arguments but got 1")]
+#[case("RETURN toUpper(DISTINCT 'a')", SemanticErrorKind::InvalidArgumentPassingMode, "toUpper(DISTINCT 'a')", "DISTINCT cannot be used")]
+fn functions_test_errors(#[case] text: &str, #[case] kind: SemanticErrorKind, #[case] call: &str, #[case] message: &str) {
+    let errors = check_functions(&parse(text), &FunctionRegistry::builtin());
+    assert_eq!(errors.len(), 1, "unexpected errors for {}: {:?}", text, errors);
+    assert_eq!(errors[0].kind, kind);
+    assert_eq!(&text[errors[0].span.start..errors[0].span.end], call);
+    assert!(errors[0].message.contains(message), "unexpected message {}", errors[0].message);
+}
+
+#[test]
+fn functions_test_builtin_metadata() {
+    let registry = FunctionRegistry::builtin();
+
+    let count = registry.lookup(&name("COUNT")).unwrap();
+    assert!(count.aggregate && count.determinism == Determinism::Deterministic);
+    assert_eq!(count.category, FunctionCategory::Aggregate);
+
+    let rand = registry.lookup(&name("rand")).unwrap();
+    assert!(!rand.aggregate && !rand.is_deterministic(0));
+    assert_eq!((rand.min_arguments(), rand.max_arguments()), (0, Some(0)));
+
+    let coalesce = registry.lookup(&name("coalesce")).unwrap();
+    assert_eq!((coalesce.min_arguments(), coalesce.max_arguments()), (1, None));
+    assert_eq!(coalesce.return_type(&[CypherType::Null, CypherType::Integer]), CypherType::Integer);
+
+    let head = registry.lookup(&name("head")).unwrap();
+    assert_eq!(head.return_type(&[CypherType::List(Box::new(CypherType::String))]), CypherType::String);
+
+    let truncate = registry.lookup(&name("datetime.truncate")).unwrap();
+    assert_eq!(truncate.category, FunctionCategory::Temporal);
+    assert_eq!(truncate.return_type(&[]), CypherType::ZonedDateTime);
+
+    let categories = [
+        FunctionCategory::Aggregate,
+        FunctionCategory::Predicate,
+        FunctionCategory::Scalar,
+        FunctionCategory::List,
+        FunctionCategory::Math,
+        FunctionCategory::Trigonometric,
+        FunctionCategory::String,
+        FunctionCategory::Temporal,
+        FunctionCategory::Spatial,
+    ];
+    for category in categories {
+        assert!(registry.iter().any(|function| function.category == category), "no {:?} functions", category);
+    }
+    assert!(registry.lookup(&name("toUpper2")).is_none());
+}
+
+#[rstest::rstest]
+#[case("rand()", false)]
+#[case("toUpper('a')", true)]
+#[case("date()", false)]
+#[case("date('2020-01-01')", true)]
+#[case("datetime({year: 2020})", true)]
+#[case("datetime.realtime('UTC')", false)]
+#[case("nope(1)", false)]
+fn functions_test_determinism(#[case] call: &str, #[case] deterministic: bool) {
+    use open_cypher::ast::cypher::{Atom, ProjectionItem, SingleQuery};
+
+    let Query::RegularQuery(query) = parse(&format!("RETURN {}", call)) else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let Some(Atom::FunctionInvocation(function)) = expression.as_atom() else { panic!("expected a function invocation") };
+    assert_eq!(FunctionRegistry::builtin().is_deterministic(function), deterministic);
+}
+
+#[test]
+fn functions_test_user_defined() {
+    let mut registry = FunctionRegistry::builtin();
+    registry.register(
+        FunctionSignature::new("my.lib.slugify", FunctionCategory::UserDefined, ReturnType::Fixed(CypherType::String))
+            .parameter("text", CypherType::String)
+            .optional_parameter("separator", CypherType::String),
+    );
+    registry.register(
+        FunctionSignature::new("my.lib.median", FunctionCategory::UserDefined, ReturnType::Fixed(CypherType::Float))
+            .parameter("values", CypherType::Any)
+            .aggregate(),
+    );
+
+    let query = parse("MATCH (n) RETURN my.lib.slugify(n.name) AS slug, My.Lib.Median(n.age) AS median");
+    assert!(check_functions(&query, &registry).is_empty());
+    assert!(check_functions(&query, &FunctionRegistry::builtin()).len() == 2);
+
+    let info = infer_types_with(&parse("RETURN my.lib.slugify('A B', '-')"), &registry);
+    assert!(info.errors.is_empty());
+    assert!(info.types.values().any(|type_| *type_ == CypherType::String));
+
+    let errors = infer_types_with(&parse("RETURN my.lib.slugify(1)"), &registry).errors;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, SemanticErrorKind::InvalidArgumentType);
+
+    let errors = check_functions(&parse("RETURN my.lib.slugify()"), &registry);
+    assert_eq!(errors[0].kind, SemanticErrorKind::InvalidNumberOfArguments);
+
+    // A user-defined aggregate makes the projection aggregate.
+    let query = parse("MATCH (n) RETURN n.x + my.lib.median(n.age)");
+    assert!(check_aggregation(&query).is_empty());
+    let errors = check_aggregation_with(&query, &registry);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, SemanticErrorKind::AmbiguousAggregationExpression);
+}
@@ -0,0 +1,141 @@
+use open_cypher::analysis::literals::recognize_literals;
+use open_cypher::ast::cypher::{Atom, CoordinateReferenceSystem, Date, DateTime, Duration, LocalDateTime, LocalTime, PointLiteral, ProjectionItem, Query, SingleQuery, TemporalLiteral, Time, TimeZone};
+
+/// Runs the literal recognition on `RETURN <expression>` and returns the
+/// resulting atom of the projection, or the error messages.
+fn recognize(expression: &str) -> Result<Atom, Vec<String>> {
+    let mut query = Query::parse(&format!("RETURN {}", expression)).unwrap();
+    let errors = recognize_literals(&mut query);
+    if !errors.is_empty() {
+        return Err(errors.iter().map(ToString::to_string).collect());
+    }
+
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(mut expression) = query.return_.unwrap().items.items.remove(0) else { panic!("expected an unaliased projection") };
+    let atom = expression.0.remove(0).0.remove(0).0.remove(0).expression.expression.expression.expression.0.remove(0).expression.expression.atom;
+    Ok(atom)
+}
+
+fn date(year: i64, month: u8, day: u8) -> Date {
+    Date { year, month, day }
+}
+
+fn time(hour: u8, minute: u8, second: u8, nanosecond: u32) -> LocalTime {
+    LocalTime { hour, minute, second, nanosecond }
+}
+
+#[rstest::rstest]
+#[case("date('2015-07-21')", TemporalLiteral::Date(date(2015, 7, 21)))]
+#[case("date('20150721')", TemporalLiteral::Date(date(2015, 7, 21)))]
+#[case("date('2015-07')", TemporalLiteral::Date(date(2015, 7, 1)))]
+#[case("date('2015')", TemporalLiteral::Date(date(2015, 1, 1)))]
+#[case("date('2015-W30-2')", TemporalLiteral::Date(date(2015, 7, 21)))]
+#[case("date('2015W302')", TemporalLiteral::Date(date(2015, 7, 21)))]
+#[case("date('2015-202')", TemporalLiteral::Date(date(2015, 7, 21)))]
+#[case("date('2016-02-29')", TemporalLiteral::Date(date(2016, 2, 29)))]
+#[case("date({year: 1984, month: 10, day: 11})", TemporalLiteral::Date(date(1984, 10, 11)))]
+#[case("date({year: 1984, week: 10, dayOfWeek: 3})", TemporalLiteral::Date(date(1984, 3, 7)))]
+#[case("date({year: 1984, quarter: 3, dayOfQuarter: 45})", TemporalLiteral::Date(date(1984, 8, 14)))]
+#[case("date({year: 1984, ordinalDay: 202})", TemporalLiteral::Date(date(1984, 7, 20)))]
+#[case("localtime('21:40:32.142')", TemporalLiteral::LocalTime(time(21, 40, 32, 142_000_000)))]
+#[case("localtime('2140')", TemporalLiteral::LocalTime(time(21, 40, 0, 0)))]
+#[case("localtime({hour: 12, minute: 31, second: 14, nanosecond: 645876123})", TemporalLiteral::LocalTime(time(12, 31, 14, 645_876_123)))]
+#[case("localtime({hour: 12, minute: 31, second: 14, millisecond: 645, microsecond: 876})", TemporalLiteral::LocalTime(time(12, 31, 14, 645_876_000)))]
+#[case("time('21:40:32+01:00')", TemporalLiteral::Time(Time { time: time(21, 40, 32, 0), timezone: Some(TimeZone::Offset(3600)) }))]
+#[case("time('214032-0130')", TemporalLiteral::Time(Time { time: time(21, 40, 32, 0), timezone: Some(TimeZone::Offset(-5400)) }))]
+#[case("time({hour: 10, minute: 35, timezone: '-08:00'})", TemporalLiteral::Time(Time { time: time(10, 35, 0, 0), timezone: Some(TimeZone::Offset(-28800)) }))]
+#[case("localdatetime('2015-07-21T21:40:32.142')", TemporalLiteral::LocalDateTime(LocalDateTime { date: date(2015, 7, 21), time: time(21, 40, 32, 142_000_000) }))]
+#[case("datetime('2015-07-21T21:40:32.142Z')", TemporalLiteral::DateTime(DateTime { date: date(2015, 7, 21), time: time(21, 40, 32, 142_000_000), timezone: Some(TimeZone::Offset(0)) }))]
+#[case("datetime('2015-07-21T21:40:32+02:00[Europe/Stockholm]')", TemporalLiteral::DateTime(DateTime { date: date(2015, 7, 21), time: time(21, 40, 32, 0), timezone: Some(TimeZone::Named { name: "Europe/Stockholm".to_string(), offset: Some(7200) }) }))]
+#[case("datetime({year: 1984, month: 10, day: 11, hour: 12, timezone: 'Europe/Stockholm'})", TemporalLiteral::DateTime(DateTime { date: date(1984, 10, 11), time: time(12, 0, 0, 0), timezone: Some(TimeZone::Named { name: "Europe/Stockholm".to_string(), offset: None }) }))]
+#[case("datetime({epochSeconds: 1000000000})", TemporalLiteral::DateTime(DateTime { date: date(2001, 9, 9), time: time(1, 46, 40, 0), timezone: None }))]
+#[case("duration('P14DT16H12M')", TemporalLiteral::Duration(Duration { months: 0, days: 14, seconds: 58320, nanoseconds: 0 }))]
+#[case("duration('P1Y2M3W')", TemporalLiteral::Duration(Duration { months: 14, days: 21, seconds: 0, nanoseconds: 0 }))]
+#[case("duration('PT0.75M')", TemporalLiteral::Duration(Duration { months: 0, days: 0, seconds: 45, nanoseconds: 0 }))]
+#[case("duration('P0.75M')", TemporalLiteral::Duration(Duration { months: 0, days: 22, seconds: 71509, nanoseconds: 500_000_000 }))]
+#[case("duration('P2.5W')", TemporalLiteral::Duration(Duration { months: 0, days: 17, seconds: 43200, nanoseconds: 0 }))]
+#[case("duration('P2012-02-02T14:37:21.545')", TemporalLiteral::Duration(Duration { months: 24146, days: 2, seconds: 52641, nanoseconds: 545_000_000 }))]
+#[case("duration({days: 14, hours: 16, minutes: 12})", TemporalLiteral::Duration(Duration { months: 0, days: 14, seconds: 58320, nanoseconds: 0 }))]
+#[case("duration({seconds: -1, milliseconds: 500})", TemporalLiteral::Duration(Duration { months: 0, days: 0, seconds: -1, nanoseconds: 500_000_000 }))]
+fn literals_test_temporal(#[case] expression: &str, #[case] expected: TemporalLiteral) {
+    match recognize(expression) {
+        Ok(Atom::TemporalLiteral(literal)) => assert_eq!(literal, expected),
+        other => panic!("expected a temporal literal, got {:?}", other),
+    }
+}
+
+#[rstest::rstest]
+#[case("point({x: 3, y: 0})", CoordinateReferenceSystem::Cartesian, &[3.0, 0.0])]
+#[case("point({x: 0, y: 4, z: 1})", CoordinateReferenceSystem::Cartesian3D, &[0.0, 4.0, 1.0])]
+#[case("point({longitude: 12.78, latitude: 56.7})", CoordinateReferenceSystem::Wgs84, &[12.78, 56.7])]
+#[case("point({longitude: -12.78, latitude: 56.7, height: 100})", CoordinateReferenceSystem::Wgs843D, &[-12.78, 56.7, 100.0])]
+#[case("point({x: 2.3, y: 4.5, crs: 'WGS-84'})", CoordinateReferenceSystem::Wgs84, &[2.3, 4.5])]
+#[case("point({x: 2.3, y: 4.5, srid: 7203})", CoordinateReferenceSystem::Cartesian, &[2.3, 4.5])]
+fn literals_test_point(#[case] expression: &str, #[case] crs: CoordinateReferenceSystem, #[case] coordinates: &[f64]) {
+    match recognize(expression) {
+        Ok(Atom::PointLiteral(literal)) => assert_eq!(literal, PointLiteral { crs, coordinates: coordinates.to_vec() }),
+        other => panic!("expected a point literal, got {:?}", other),
+    }
+}
+
+#[rstest::rstest]
+#[case("date('2015-02-29')", "day must be between 1 and 28")]
+#[case("date('2015-13-01')", "month must be between 1 and 12")]
+#[case("date('2015-W54')", "week must be between 1 and 53")]
+#[case("date('15-07-21')", "expected 4 digits")]
+#[case("date('+123456789012345678901-01-01')", "out of range")]
+#[case("date('-99999999999999999999')", "out of range")]
+#[case("date('+1000000000-01-01')", "year must be between")]
+#[case("date({year: 2020, day: 3})", "field `day` requires `month`")]
+#[case("date({year: 2020, month: 1, week: 3})", "cannot be combined")]
+#[case("date({year: 2020, hour: 1})", "unknown field `hour`")]
+#[case("localtime('25:00')", "hour must be between 0 and 23")]
+#[case("localtime({minute: 3})", "field `hour` is required")]
+#[case("localtime({hour: 1, minute: 2, millisecond: 3})", "sub-second fields require `second`")]
+#[case("time('12:00+19:00')", "out of range")]
+#[case("datetime('2015-07-21T21:40:32.1234567891')", "not a valid fraction")]
+#[case("duration('P1Q')", "unexpected unit `Q`")]
+#[case("duration('PT1D')", "unexpected unit `D`")]
+#[case("duration('P')", "not a valid duration")]
+#[case("duration({years: 'one'})", "field `years` must be a number")]
+#[case("duration({hours: 1e30})", "out of range")]
+#[case("duration({seconds: 1e300})", "field `seconds` is out of range")]
+#[case("duration({years: 9223372036854775807, months: 9223372036854775807})", "out of range")]
+#[case("duration('P99999999999999999999999999999999999Y')", "out of range")]
+#[case("point({x: 1})", "requires both `x` and `y`")]
+#[case("point({longitude: 1, latitude: 91})", "latitude must be between")]
+#[case("point({x: 1, y: 2, crs: 'cartesian-3d'})", "requires 3 coordinates")]
+#[case("point({x: 1, y: 2, crs: 'mars'})", "unknown coordinate reference system")]
+#[case("point('1,2')", "expected a map")]
+fn literals_test_errors(#[case] expression: &str, #[case] message: &str) {
+    match recognize(expression) {
+        Err(errors) => assert!(errors.len() == 1 && errors[0].contains(message), "unexpected errors {:?}", errors),
+        Ok(atom) => panic!("expected an error, got {:?}", atom),
+    }
+}
+
+#[rstest::rstest]
+#[case("date()")]
+#[case("date($value)")]
+#[case("date({year: $year})")]
+#[case("datetime({epochSeconds: 0, timezone: 'Europe/Stockholm'})")]
+#[case("date.truncate('month', date('2015-07-21'))")]
+#[case("toUpper('2015-07-21')")]
+fn literals_test_untouched(#[case] expression: &str) {
+    assert!(matches!(recognize(expression), Ok(Atom::FunctionInvocation(_))));
+}
+
+#[test]
+fn literals_test_nested_and_spans() {
+    let text = "WITH [date('2015-07-21'), date('2015-02-30')] AS dates RETURN dates";
+    let mut query = Query::parse(text).unwrap();
+    let errors = recognize_literals(&mut query);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(&text[errors[0].span.start..errors[0].span.end], "date('2015-02-30')");
+    assert_eq!(errors[0].function, "date");
+
+    let debug = format!("{:?}", query);
+    assert!(debug.contains("TemporalLiteral(Date(Date { year: 2015, month: 7, day: 21 }))"));
+}
@@ -0,0 +1,87 @@
+use open_cypher::analysis::normalize::{fingerprint, normalize, NormalizedQuery};
+use open_cypher::ast::cypher::{Atom, Literal, NumberLiteral, Query};
+
+fn normalized(text: &str) -> NormalizedQuery {
+    normalize(&Query::parse(text).unwrap())
+}
+
+#[rstest::rstest]
+#[case("match (n) return n", "MATCH (n) RETURN n")]
+#[case("MATCH   (n:Person)\n  WHERE n.age   >  30\n RETURN n.name", "MATCH (n:Person) WHERE n.age > $0 RETURN n.name")]
+#[case("MATCH (n {name: 'Alice', age: 42}) RETURN n", "MATCH (n {age: $0, name: $1}) RETURN n")]
+#[case("RETURN {b: 1, a: 'x'} AS m", "RETURN {a: $0, b: $1} AS m")]
+#[case("UNWIND [1, 2, 3] AS x RETURN x", "UNWIND $0 AS x RETURN x")]
+#[case("RETURN [[1, 'a'], []] AS x", "RETURN $0 AS x")]
+#[case("MATCH (n) RETURN [n.a, 'b'] AS x", "MATCH (n) RETURN [n.a, $0] AS x")]
+#[case("MATCH (n) WHERE n.flag = true AND n.x IS NULL RETURN n", "MATCH (n) WHERE n.flag = true AND n.x IS NULL RETURN n")]
+#[case("MATCH (n) WHERE n.x = null OR n.y = false RETURN n", "MATCH (n) WHERE n.x = null OR n.y = false RETURN n")]
+#[case("MATCH (n) RETURN n ORDER BY n.name desc skip 5 limit 10", "MATCH (n) RETURN n ORDER BY n.name DESC SKIP $0 LIMIT $1")]
+#[case("MATCH (n) WHERE n.name = $name RETURN n LIMIT 1", "MATCH (n) WHERE n.name = $name RETURN n LIMIT $0")]
+#[case("MATCH (n) WHERE n.a = $0 AND n.b = 2 RETURN n", "MATCH (n) WHERE n.a = $0 AND n.b = $1 RETURN n")]
+#[case("MATCH (a)-[r:KNOWS*1..3]->(b) RETURN b", "MATCH (a)-[r:KNOWS*1..3]->(b) RETURN b")]
+#[case("CALL db.index.fulltext.queryNodes('names', 'Alice') YIELD node RETURN node", "CALL db.index.fulltext.queryNodes($0, $1) YIELD node RETURN node")]
+#[case("CREATE (n:Person {name: \"O'Brien\"}) SET n.age = -1", "CREATE (n:Person {name: $0}) SET n.age = -$1")]
+#[case("optional match (n) with n, count(*) as c where c > 1 return distinct n", "OPTIONAL MATCH (n) WITH n, count(*) AS c WHERE c > $0 RETURN DISTINCT n")]
+fn normalize_test_text(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(normalized(text).text, expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n:Person) WHERE n.name = 'Alice' RETURN n", "match (n:Person)\nwhere n.name = \"Bob\"\nreturn n")]
+#[case("MATCH (n {a: 1, b: 2}) RETURN n", "MATCH (n {b: 3, a: 4}) RETURN n")]
+#[case("UNWIND [1, 2] AS x RETURN x", "UNWIND ['a', 'b', 'c'] AS x RETURN x")]
+#[case("MATCH (n) RETURN n LIMIT 10", "MATCH (n) RETURN n LIMIT 0x10")]
+fn normalize_test_same_shape(#[case] a: &str, #[case] b: &str) {
+    let (a, b) = (normalized(a), normalized(b));
+    assert_eq!(a.text, b.text);
+    assert_eq!(a.fingerprint, b.fingerprint);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n:Person) RETURN n", "MATCH (n:Company) RETURN n")]
+#[case("MATCH (n) WHERE n.active = true RETURN n", "MATCH (n) WHERE n.active = false RETURN n")]
+#[case("MATCH (n) RETURN n.a", "MATCH (n) RETURN n.b")]
+fn normalize_test_different_shape(#[case] a: &str, #[case] b: &str) {
+    assert_ne!(normalized(a).fingerprint, normalized(b).fingerprint);
+}
+
+#[test]
+fn normalize_test_parameters() {
+    let query = normalized("MATCH (n {name: 'A'}) WHERE n.age > 18 RETURN n, [1, 2] AS list");
+
+    assert_eq!(query.text, "MATCH (n {name: $0}) WHERE n.age > $1 RETURN n, $2 AS list");
+    let names: Vec<&str> = query.parameters.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["0", "1", "2"]);
+    assert_eq!(query.parameters[0].1, Literal::String("A".to_string()));
+    assert_eq!(query.parameters[1].1, Literal::Number(NumberLiteral::Integer(18)));
+    let Literal::ListLiteral(list) = &query.parameters[2].1 else { panic!("expected a list") };
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[1].as_atom(), Some(&Atom::Literal(Literal::Number(NumberLiteral::Integer(2)))));
+}
+
+#[test]
+fn normalize_test_fingerprint() {
+    // FNV-1a test vectors.
+    assert_eq!(fingerprint(""), 0xcbf29ce484222325);
+    assert_eq!(fingerprint("a"), 0xaf63dc4c8601ec8c);
+    assert_eq!(fingerprint("foobar"), 0x85944171f73967e8);
+
+    let query = normalized("MATCH (n) RETURN n");
+    assert_eq!(query.fingerprint, fingerprint("MATCH (n) RETURN n"));
+    assert!(query.parameters.is_empty());
+}
+
+#[test]
+fn normalize_test_display_round_trip() {
+    let text = "MATCH p = (a:Person&!Robot {name: $name})<-[:KNOWS|LIKES*2..]-(b) \
+                WHERE a.name STARTS WITH 'A' AND b:Admin AND all(x IN b.tags WHERE x IS NOT NULL) \
+                WITH a, collect(DISTINCT b {.name, .*}) AS friends, CASE WHEN a.age > 1 THEN 'x' ELSE 'y' END AS c \
+                UNWIND friends AS f \
+                MERGE (f)-[:SEEN]->(a) ON CREATE SET f.count = 1 ON MATCH SET f += {count: f.count + 1} \
+                RETURN a.`first name`, reduce(s = 0, x IN [1.5, 2.0] | s + x) AS total ORDER BY total DESC";
+    let statement = open_cypher::parse_cypher(text).unwrap();
+    let printed = statement.to_string();
+
+    assert_eq!(open_cypher::parse_cypher(&printed).unwrap(), statement);
+    assert_eq!(open_cypher::parse_cypher(&printed).unwrap().to_string(), printed);
+}
@@ -376,15 +376,541 @@
 #[case("MERGE (b)   ON CREATE SET b.created = 1")]
 #[case("MERGE (a:TheLabel)   ON CREATE SET a.num = 42 RETURN a.num")]
 #[case("CREATE (a), (b) MERGE (a)-[:X]->(b) RETURN count(a)")]
+#[case("MATCH (n:A&B) RETURN n")]
+#[case("MATCH (n:A|B) RETURN n")]
+#[case("MATCH (n:!A) RETURN n")]
+#[case("MATCH (n:%) RETURN n")]
+#[case("MATCH (n:(A|B)&C) RETURN n")]
+#[case("MATCH (n) WHERE n IS A|B RETURN n")]
+#[case("MATCH (n) WHERE n:A&!B AND n.num IS NOT NULL RETURN n")]
+#[case("MATCH ()-[r:R|S]->() RETURN r")]
+#[case("MATCH ()-[r:!R]->() RETURN r")]
+#[case("MATCH (n)-[:KNOWS]->(f) RETURN n { .name, .age, friends: collect(f), .* }")]
+#[case("MATCH (n) WITH n, 42 AS answer RETURN n {answer, .name}")]
+#[case("MATCH (n) RETURN n {}")]
+#[case("CREATE INDEX FOR (n:Person) ON (n.name)")]
+#[case("CREATE INDEX person_name IF NOT EXISTS FOR (n:Person) ON (n.name, n.age) OPTIONS {indexProvider: 'range-1.0'}")]
+#[case("CREATE TEXT INDEX rel_text FOR ()-[r:KNOWS]-() ON (r.since)")]
+#[case("CREATE POINT INDEX FOR (n:Place) ON (n.location)")]
+#[case("CREATE FULLTEXT INDEX titles FOR (n:Movie|Book) ON EACH [n.title, n.description]")]
+#[case("CREATE VECTOR INDEX embeddings FOR (m:Movie) ON m.embedding OPTIONS {indexConfig: {`vector.dimensions`: 1536}}")]
+#[case("DROP INDEX person_name IF EXISTS")]
+#[case("CREATE CONSTRAINT book_isbn FOR (book:Book) REQUIRE book.isbn IS UNIQUE")]
+#[case("CREATE CONSTRAINT IF NOT EXISTS FOR (p:Person) REQUIRE (p.first, p.last) IS NODE KEY")]
+#[case("CREATE CONSTRAINT FOR ()-[r:LIKED]-() REQUIRE r.day IS NOT NULL")]
+#[case("CREATE CONSTRAINT movie_title FOR (m:Movie) REQUIRE m.title IS :: STRING")]
+#[case("CREATE CONSTRAINT FOR (m:Movie) REQUIRE m.tags IS TYPED LIST<STRING NOT NULL> | STRING")]
+#[case("DROP CONSTRAINT book_isbn")]
+#[case("EXPLAIN MATCH (n) RETURN n")]
+#[case("PROFILE MATCH (n) RETURN n")]
+#[case("CYPHER runtime=slotted planner=cost MATCH (n) RETURN n")]
+#[case("CYPHER 5 runtime=parallel PROFILE MATCH (n) RETURN count(*)")]
+#[case("  MATCH (n) RETURN n")]
+#[case("LOAD CSV FROM 'file:///artists.csv' AS line CREATE (:Artist {name: line[1], year: toInteger(line[2])})")]
+#[case("LOAD CSV WITH HEADERS FROM $url AS row FIELDTERMINATOR ';' MERGE (p:Person {id: row.id})")]
+#[case("WITH 'https://example.com/data.csv' AS url LOAD CSV WITH HEADERS FROM url AS row RETURN count(row)")]
+#[case("USE movies MATCH (n) RETURN n")]
+#[case("USE composite.shard0 MATCH (n) RETURN n UNION USE composite.shard1 MATCH (n) RETURN n")]
+#[case("USE graph.byName($graphName) MATCH (n) RETURN n")]
+#[case("USE graph.byElementId('4:c0a65d96-4993-4b0c-b036-e7ebd9174905:0') MATCH (n) RETURN n")]
+#[case("MATCH (p:Person) CALL { MATCH (m:Movie) RETURN count(m) AS movies } RETURN p, movies")]
+#[case("CALL{USE other MATCH (n) RETURN n UNION USE other2 MATCH (n) RETURN n} RETURN n")]
+#[case("UNWIND [1, 2] AS x CALL { CREATE (:Item) } RETURN x")]
+#[case("MATCH (n) SET n:$($label) RETURN n")]
+#[case("MATCH (n) SET n:$($labels):Static REMOVE n:$($old)")]
+#[case("MATCH (n:$any($labels)) RETURN n")]
+#[case("MATCH (n:$all($labels)&Person) RETURN n")]
+#[case("CREATE ()-[:$($t)]->()")]
+#[case("MATCH (n) SET n[$key] = $value RETURN n[$key]")]
+#[case("MATCH (n) REMOVE n[$key]")]
+#[case("UNWIND [1, 'a', null] AS x RETURN x IS :: INTEGER NOT NULL")]
+#[case("UNWIND [1, 'a', null] AS x RETURN x IS NOT :: STRING")]
+#[case("MATCH (n) WHERE n.tags IS TYPED LIST<STRING> RETURN n")]
+#[case("RETURN 1.0 :: FLOAT AS f")]
+#[case("MATCH (n) WHERE n.value :: INTEGER | FLOAT | NULL RETURN n")]
+#[case("MATCH (n) WHERE n.value IS :: ANY<BOOLEAN | STRING> AND n.other IS :: NOTHING RETURN n")]
+#[case("MATCH (n) WHERE n.value IS TYPED ZONED DATETIME OR n.value IS TYPED INTEGER LIST NOT NULL RETURN n")]
+#[case("MATCH (n) RETURN n.first || ' ' || n.last AS name")]
+#[case("RETURN 'a' || 'b' = 'ab'")]
+#[case("MATCH (n) WHERE n.name =~ 'Jo.*' RETURN n")]
+#[case("MATCH (n) WHERE n.name=~'(?i)jo.*' RETURN n")]
+#[case("RETURN 'café' IS NORMALIZED, 'café' IS NOT NFKD NORMALIZED")]
+#[case("MATCH (n) WHERE n.name IS NFC NORMALIZED AND n.name STARTS WITH 'A' RETURN n")]
+#[case("RETURN reduce(acc = 0, x IN [1, 2, 3] | acc + x) AS sum")]
+#[case("MATCH p = (a)-->(b) RETURN reduce(total = 0, n IN nodes(p) | total + n.age) AS total")]
+#[case("RETURN REDUCE ( s = '', w IN ['a', 'b'] | s || w )")]
+#[case("MATCH (n) RETURN n ORDER BY n.name DESC NULLS LAST")]
+#[case("MATCH (n) RETURN n ORDER BY n.name NULLS FIRST, n.age ASC NULLS LAST SKIP 1 LIMIT 2")]
+#[case("MATCH (n) RETURN n ORDER BY n.name OFFSET 5 LIMIT 10")]
+#[case("MATCH (n) ORDER BY n.name LIMIT 10 RETURN n")]
+#[case("MATCH (n) OFFSET 2 RETURN n")]
+#[case("MATCH (n) LIMIT 3 RETURN n")]
+#[case("MATCH (n) FILTER n.age > 30 RETURN n")]
+#[case("MATCH (n) FILTER WHERE n.age > 30 ORDER BY n.age SKIP 1 SET n.seen = true")]
+#[case("UNWIND [3, 1, 2] AS x ORDER BY x DESC WITH collect(x) AS xs RETURN xs")]
+#[case("MATCH p = (a)-->(b) FOREACH (n IN nodes(p) | SET n.marked = true)")]
+#[case("FOREACH(x IN [1, 2]|CREATE (:N {x: x}) FOREACH (y IN [x] | MERGE (:M {y: y})))")]
 fn parser_test_success(#[case] query: &str) {
     let _ = open_cypher::parse_cypher(query).unwrap();
 }
 
 #[rstest::rstest]
 #[case("CALL test.my.proc YIELD out RETURN out")]
+#[case("MATCH (n) SET n:$any($labels)")]
+#[case("RETURN reduce(acc = 0, x IN [1, 2, 3])")]
+#[case("RETURN REDUCE (acc = 0, x IN [1] acc + x)")]
 fn parser_test_parse_error(#[case] query: &str) {
     let err = open_cypher::parse_cypher(query).unwrap_err();
     assert!(matches!(err.variant, pest::error::ErrorVariant::ParsingError { .. }));
 
 }
 
+
+#[test]
+fn parser_test_label_expression() {
+    use open_cypher::ast::cypher::{LabelExpression, LabelOrType, Query, ReadingClause, SingleQuery};
+
+    let query = Query::parse("MATCH (n:(A|B)&!C) RETURN n").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ReadingClause::Match(match_) = &query.reading_clauses[0] else { panic!("expected a MATCH clause") };
+    let label_expression = match_.pattern.parts[0].pattern_element.node_pattern.label_expression.as_ref().unwrap();
+
+    let LabelExpression::And(operands) = label_expression else { panic!("expected a conjunction") };
+    assert!(matches!(&operands[0], LabelExpression::Or(labels) if labels.len() == 2));
+    assert!(matches!(&operands[1], LabelExpression::Not(label) if matches!(label.as_ref(), LabelExpression::Label(LabelOrType::Static(name)) if name == "C")));
+}
+
+#[test]
+fn parser_test_map_projection() {
+    use open_cypher::ast::cypher::{Atom, MapProjectionItem, ProjectionItem, Query, SingleQuery};
+
+    let query = Query::parse("MATCH (n) RETURN n { .name, answer: 42, count, .* }").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let Some(Atom::MapProjection(map_projection)) = expression.as_atom() else { panic!("expected a map projection") };
+
+    assert_eq!(map_projection.variable, "n");
+    assert!(matches!(&map_projection.items[0], MapProjectionItem::Property(key) if key == "name"));
+    assert!(matches!(&map_projection.items[1], MapProjectionItem::Literal { key, .. } if key == "answer"));
+    assert!(matches!(&map_projection.items[2], MapProjectionItem::Variable(variable) if variable == "count"));
+    assert!(matches!(&map_projection.items[3], MapProjectionItem::AllProperties));
+}
+
+#[test]
+fn parser_test_schema_command() {
+    use open_cypher::ast::cypher::{ConstraintEntity, ConstraintKind, CypherType, IndexKind, Query, SchemaCommand, SchemaTarget, StatementBody};
+
+    let body = open_cypher::parse_cypher("CREATE FULLTEXT INDEX titles IF NOT EXISTS FOR (n:Movie|Book) ON EACH [n.title, n.plot]").unwrap().body;
+    let StatementBody::SchemaCommand(SchemaCommand::CreateIndex(index)) = body else { panic!("expected CREATE INDEX") };
+    assert!(matches!(index.kind, IndexKind::Fulltext));
+    assert_eq!(index.name.as_deref(), Some("titles"));
+    assert!(index.if_not_exists);
+    assert!(matches!(&index.target, SchemaTarget::Node { variable, labels } if variable == "n" && labels == &["Movie", "Book"]));
+    assert_eq!(index.properties.iter().map(|property| property.property_key.as_str()).collect::<Vec<_>>(), ["title", "plot"]);
+
+    let body = open_cypher::parse_cypher("CREATE CONSTRAINT FOR ()-[r:RATED]->() REQUIRE r.stars IS :: INTEGER | FLOAT").unwrap().body;
+    let StatementBody::SchemaCommand(SchemaCommand::CreateConstraint(constraint)) = body else { panic!("expected CREATE CONSTRAINT") };
+    assert!(constraint.name.is_none());
+    assert!(constraint.entity.is_none());
+    assert!(matches!(&constraint.target, SchemaTarget::Relationship { relationship_types, .. } if relationship_types == &["RATED"]));
+    assert!(matches!(&constraint.kind, ConstraintKind::Type(CypherType::Union(types)) if types.len() == 2));
+
+    let body = open_cypher::parse_cypher("CREATE CONSTRAINT FOR ()-[r:RATED]-() REQUIRE r.id IS REL UNIQUE").unwrap().body;
+    let StatementBody::SchemaCommand(SchemaCommand::CreateConstraint(constraint)) = body else { panic!("expected CREATE CONSTRAINT") };
+    assert_eq!(constraint.entity, Some(ConstraintEntity::Relationship));
+    assert!(matches!(constraint.kind, ConstraintKind::Unique));
+
+    assert!(Query::parse("DROP INDEX titles").is_err());
+}
+
+#[rstest::rstest]
+#[case("CREATE CONSTRAINT FOR ()-[r:RATED]-() REQUIRE r.id IS REL UNIQUE", "CREATE CONSTRAINT FOR ()-[r:RATED]-() REQUIRE r.id IS RELATIONSHIP UNIQUE")]
+#[case("CREATE CONSTRAINT FOR (p:Person) REQUIRE p.id IS UNIQUE", "CREATE CONSTRAINT FOR (p:Person) REQUIRE p.id IS UNIQUE")]
+#[case("CREATE CONSTRAINT FOR (p:Person) REQUIRE (p.a, p.b) IS NODE KEY", "CREATE CONSTRAINT FOR (p:Person) REQUIRE (p.a, p.b) IS NODE KEY")]
+#[case("CREATE CONSTRAINT FOR (p:Person) REQUIRE p.id IS KEY", "CREATE CONSTRAINT FOR (p:Person) REQUIRE p.id IS KEY")]
+fn parser_test_constraint_display(#[case] query: &str, #[case] expected: &str) {
+    let statement = open_cypher::parse_cypher(query).unwrap();
+    assert_eq!(statement.to_string(), expected);
+    assert_eq!(open_cypher::parse_cypher(expected).unwrap(), statement);
+}
+
+#[test]
+fn parser_test_statement_prefixes() {
+    use open_cypher::ast::cypher::{ExecutionMode, Query, StatementBody};
+
+    let statement = open_cypher::parse_cypher("CYPHER 5 runtime=slotted planner=cost EXPLAIN MATCH (n) RETURN n").unwrap();
+    assert!(matches!(statement.mode, ExecutionMode::Explain));
+    assert_eq!(statement.options[0].version.as_deref(), Some("5"));
+    assert_eq!(statement.options[0].options, [("runtime".to_string(), "slotted".to_string()), ("planner".to_string(), "cost".to_string())]);
+    assert!(matches!(statement.body, StatementBody::Query(Query::RegularQuery(_))));
+
+    let statement = open_cypher::parse_cypher("MATCH (n) RETURN n").unwrap();
+    assert!(matches!(statement.mode, ExecutionMode::Normal));
+    assert!(statement.options.is_empty());
+}
+
+#[test]
+fn parser_test_load_csv() {
+    use open_cypher::ast::cypher::{Query, ReadingClause, SingleQuery};
+
+    let query = Query::parse("LOAD CSV WITH HEADERS FROM 'file:///people.csv' AS row FIELDTERMINATOR ';' RETURN row").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ReadingClause::LoadCsv(load_csv) = &query.reading_clauses[0] else { panic!("expected a LOAD CSV clause") };
+    assert!(load_csv.with_headers);
+    assert_eq!(load_csv.variable, "row");
+    assert_eq!(load_csv.field_terminator.as_deref(), Some(";"));
+}
+
+#[rstest::rstest]
+#[case("1.0e10", 1e10)]
+#[case("1.5E-3", 1.5e-3)]
+#[case(".5e2", 50.0)]
+#[case("0.1", 0.1)]
+#[case("123456789012345680000.0", 1.2345678901234568e20)]
+#[case("1.7976931348623157e308", f64::MAX)]
+#[case("5e-324", 5e-324)]
+#[case("1e-7", 1e-7)]
+fn parser_test_double_round_trip(#[case] literal: &str, #[case] expected: f64) {
+    use open_cypher::ast::cypher::{Atom, Literal, NumberLiteral, ProjectionItem, Query, SingleQuery};
+
+    let double = |text: &str| {
+        let Query::RegularQuery(query) = Query::parse(text).unwrap() else { panic!("expected a regular query") };
+        let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+        let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+        let Some(Atom::Literal(Literal::Number(NumberLiteral::Double(value)))) = expression.as_atom() else { panic!("expected a double") };
+        *value
+    };
+
+    let query = format!("RETURN {}", literal);
+    assert_eq!(double(&query), expected);
+    let printed = Query::parse(&query).unwrap().to_string();
+    assert_eq!(double(&printed), expected, "{}", printed);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) RETURN n", &[])]
+#[case("USE movies MATCH (n) RETURN n", &["movies"])]
+#[case("USE composite.`shard-0` MATCH (n) RETURN n UNION ALL USE composite.`shard-1` MATCH (n) RETURN n", &["composite.`shard-0`", "composite.`shard-1`"])]
+#[case("USE `composite.shard0` MATCH (n) RETURN n UNION USE composite.shard0 MATCH (n) RETURN n", &["`composite.shard0`", "composite.shard0"])]
+#[case("USE graph.byName('people') MATCH (n) RETURN n UNION USE graph.byName($name) MATCH (n) RETURN n", &["people"])]
+#[case("MATCH (n) CALL { USE other MATCH (m) RETURN m } RETURN n, m", &["other"])]
+#[case("USE movies MATCH (n) CALL { USE people MATCH (m) RETURN m UNION USE movies MATCH (m) RETURN m } RETURN n, m", &["movies", "people"])]
+#[case("CALL { CALL { USE inner MATCH (m) RETURN m } RETURN m } RETURN m", &["inner"])]
+fn parser_test_target_graphs(#[case] query: &str, #[case] expected: &[&str]) {
+    let query = open_cypher::ast::cypher::Query::parse(query).unwrap();
+    assert_eq!(query.target_graphs(), expected);
+}
+
+#[test]
+fn parser_test_dynamic_labels_and_properties() {
+    use open_cypher::ast::cypher::{LabelOrType, PropertyKey, Query, SetItem, SingleQuery, UpdatingClause};
+
+    let query = Query::parse("MATCH (n) SET n:$($label):Person, n[$key] = 1").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let UpdatingClause::Set(set) = &query.updating_clauses[0] else { panic!("expected a SET clause") };
+
+    let SetItem::VariableNodeLabels { node_labels, .. } = &set.items[0] else { panic!("expected labels to be set") };
+    assert!(matches!(&node_labels[0], LabelOrType::Dynamic(_)));
+    assert!(matches!(&node_labels[1], LabelOrType::Static(label) if label == "Person"));
+
+    let SetItem::AssignPropertyExpression { property_expression, .. } = &set.items[1] else { panic!("expected a property to be set") };
+    assert!(matches!(property_expression.property_path.as_slice(), [PropertyKey::Dynamic(_)]));
+}
+
+#[test]
+fn parser_test_any_of_labels() {
+    use open_cypher::ast::cypher::{LabelExpression, Query, ReadingClause, SingleQuery};
+
+    let query = Query::parse("MATCH (n:$any($labels)) RETURN n").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ReadingClause::Match(match_) = &query.reading_clauses[0] else { panic!("expected a MATCH clause") };
+    let label_expression = match_.pattern.parts[0].pattern_element.node_pattern.label_expression.as_ref().unwrap();
+
+    assert!(matches!(label_expression, LabelExpression::AnyOf(_)));
+    assert_eq!(label_expression.to_string(), "$any($labels)");
+}
+
+#[test]
+fn parser_test_type_predicate() {
+    use open_cypher::ast::cypher::{CypherType, ProjectionItem, Query, SingleQuery, StringListNullOperatorExpressionInner};
+
+    let query = Query::parse("UNWIND [1] AS x RETURN x IS NOT TYPED LIST<INTEGER NOT NULL> | STRING").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let operations = &expression.0[0].0[0].0[0].expression.expression.expression.expression.0[0].expression.operations;
+
+    let [StringListNullOperatorExpressionInner::TypePredicate(predicate)] = operations.as_slice() else { panic!("expected a type predicate") };
+    assert!(predicate.not);
+    let CypherType::Union(alternatives) = &predicate.type_ else { panic!("expected a union type") };
+    assert!(matches!(&alternatives[0], CypherType::List(element) if matches!(element.as_ref(), CypherType::NotNull(inner) if matches!(inner.as_ref(), CypherType::Integer))));
+    assert!(matches!(&alternatives[1], CypherType::String));
+}
+
+#[rstest::rstest]
+#[case("RETURN 1 IS :: ANY", "ANY")]
+#[case("RETURN 1 IS :: ANY VALUE", "ANY")]
+#[case("RETURN 1 IS :: ANY NODE", "NODE")]
+#[case("RETURN 1 IS :: ANY<INTEGER>", "INTEGER")]
+#[case("RETURN 1 IS :: ANY VALUE<BOOLEAN | STRING>", "BOOLEAN | STRING")]
+#[case("RETURN 1 IS :: LIST<ANY<INTEGER | FLOAT>>", "LIST<INTEGER | FLOAT>")]
+fn parser_test_any_type(#[case] query: &str, #[case] expected: &str) {
+    use open_cypher::ast::cypher::{ProjectionItem, Query, SingleQuery, StringListNullOperatorExpressionInner};
+
+    let query = Query::parse(query).unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let operations = &expression.0[0].0[0].0[0].expression.expression.expression.expression.0[0].expression.operations;
+
+    let [StringListNullOperatorExpressionInner::TypePredicate(predicate)] = operations.as_slice() else { panic!("expected a type predicate") };
+    assert_eq!(predicate.type_.to_string(), expected);
+}
+
+#[test]
+fn parser_test_string_operators() {
+    use open_cypher::ast::cypher::{NormalForm, PlusMinusOperator, ProjectionItem, Query, SingleQuery, StringListNullOperatorExpressionInner, StringOperator};
+
+    let query = Query::parse("RETURN 'a' || 'b' = 'ab', 'x' =~ 'x+', 'y' IS NOT NFKC NORMALIZED").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let items = &query.return_.as_ref().unwrap().items.items;
+    let comparisons = items.iter().map(|item| match item {
+        ProjectionItem::Expression(expression) => &expression.0[0].0[0].0[0].expression,
+        ProjectionItem::AsVariable { .. } => panic!("expected an unaliased projection"),
+    }).collect::<Vec<_>>();
+
+    // `||` binds tighter than `=`.
+    assert_eq!(comparisons[0].comparisons.len(), 1);
+    assert!(matches!(comparisons[0].expression.operations.as_slice(), [(PlusMinusOperator::Concatenate, _)]));
+
+    let operations = &comparisons[1].expression.expression.expression.0[0].expression.operations;
+    assert!(matches!(operations.as_slice(), [StringListNullOperatorExpressionInner::StringOperator(operator)] if matches!(operator.operator, StringOperator::RegularExpression)));
+
+    let operations = &comparisons[2].expression.expression.expression.0[0].expression.operations;
+    assert!(matches!(operations.as_slice(), [StringListNullOperatorExpressionInner::NormalizedPredicate(predicate)] if predicate.not && matches!(predicate.normal_form, NormalForm::Nfkc)));
+}
+
+#[test]
+fn parser_test_reduce() {
+    use open_cypher::ast::cypher::{Atom, ProjectionItem, Query, SingleQuery};
+
+    let query = Query::parse("RETURN reduce(acc = 0, x IN [1, 2, 3] | acc + x)").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let Some(Atom::Reduce(reduce)) = expression.as_atom() else { panic!("expected a reduce expression") };
+
+    assert_eq!(reduce.accumulator, "acc");
+    assert!(reduce.initial_value.as_atom().is_some());
+    assert_eq!(reduce.id_in_coll.variable, "x");
+    assert!(reduce.expression.as_atom().is_none());
+}
+
+#[test]
+fn parser_test_standalone_order_skip_limit() {
+    use open_cypher::ast::cypher::{NullsOrder, Query, ReadingClause, SingleQuery, SortDirection};
+
+    let query = Query::parse("MATCH (n) FILTER n.age > 30 ORDER BY n.name DESC NULLS FIRST, n.age OFFSET 5 LIMIT 10 RETURN n").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+
+    assert_eq!(query.reading_clauses.len(), 3);
+    assert!(matches!(query.reading_clauses[1], ReadingClause::Filter(_)));
+    let ReadingClause::OrderSkipLimit(clause) = &query.reading_clauses[2] else { panic!("expected an ORDER BY clause") };
+    let order = clause.order.as_ref().unwrap();
+    assert_eq!(order.items[0].direction, SortDirection::Descending);
+    assert_eq!(order.items[0].nulls, Some(NullsOrder::First));
+    assert_eq!(order.items[1].direction, SortDirection::Ascending);
+    assert_eq!(order.items[1].nulls, None);
+    assert!(clause.skip.is_some());
+    assert!(clause.limit.is_some());
+}
+
+#[test]
+fn parser_test_order_skip_limit_after_updates() {
+    use open_cypher::ast::cypher::{Query, SingleQuery, UpdatingClause};
+
+    let query = Query::parse("MATCH (n) SET n.seen = true FILTER n.age > 30 ORDER BY n.age LIMIT 1 DELETE n RETURN n").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+
+    assert_eq!(query.reading_clauses.len(), 1);
+    assert_eq!(query.updating_clauses.len(), 4);
+    assert!(matches!(query.updating_clauses[1], UpdatingClause::Filter(_)));
+    let UpdatingClause::OrderSkipLimit(clause) = &query.updating_clauses[2] else { panic!("expected an ORDER BY clause") };
+    assert!(clause.order.is_some() && clause.skip.is_none() && clause.limit.is_some());
+    assert!(matches!(query.updating_clauses[3], UpdatingClause::Delete(_)));
+}
+
+#[rstest::rstest]
+#[case("CALL db.index.fulltext.queryNodes('titles', 'matrix')", &["db", "index", "fulltext"], "queryNodes", "db.index.fulltext.queryNodes")]
+#[case("CALL db.labels", &["db"], "labels", "db.labels")]
+#[case("CALL `my.ns`.`proc``x`()", &["my.ns"], "proc`x", "`my.ns`.`proc``x`")]
+#[case("CALL `apoc`.coll.sum([1, 2]) YIELD value RETURN value", &["apoc", "coll"], "sum", "apoc.coll.sum")]
+fn parser_test_procedure_names(#[case] query: &str, #[case] namespace: &[&str], #[case] name: &str, #[case] display: &str) {
+    use open_cypher::ast::cypher::{ProcedureInvocation, Query, ReadingClause, SingleQuery};
+
+    let qualified_name = match Query::parse(query).unwrap() {
+        Query::StandaloneCall(call) => match call.invocation {
+            ProcedureInvocation::Explicit(invocation) => invocation.name,
+            ProcedureInvocation::Implicit(invocation) => invocation.name,
+        },
+        Query::RegularQuery(query) => {
+            let SingleQuery::SinglePartQuery(mut query) = query.query else { panic!("expected a single part query") };
+            let ReadingClause::InQueryCall(call) = query.reading_clauses.remove(0) else { panic!("expected a CALL clause") };
+            call.invocation.name
+        }
+    };
+
+    assert_eq!(qualified_name.namespace, namespace);
+    assert_eq!(qualified_name.name, name);
+    assert_eq!(qualified_name.to_string(), display);
+}
+
+#[test]
+fn parser_test_qualified_name_matching() {
+    use open_cypher::ast::cypher::{Atom, ProjectionItem, Query, SingleQuery};
+
+    let query = Query::parse("RETURN apoc.coll.`sum`([1, 2])").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let Some(Atom::FunctionInvocation(function)) = expression.as_atom() else { panic!("expected a function invocation") };
+    let name = &function.function_name;
+
+    assert_eq!(name.to_string(), "apoc.coll.sum");
+    assert!(name.is_in_namespace(&["apoc"]));
+    assert!(name.is_in_namespace(&["apoc", "coll"]));
+    assert!(!name.is_in_namespace(&["apo"]));
+    assert!(name.matches("apoc.*"));
+    assert!(name.matches("apoc.coll.*"));
+    assert!(name.matches("apoc.coll.sum"));
+    assert!(!name.matches("apoc.coll"));
+    assert!(!name.matches("db.*"));
+}
+
+#[rstest::rstest]
+#[case("RETURN 0x1F", Some(31))]
+#[case("RETURN 017", Some(15))]
+#[case("RETURN 0", Some(0))]
+#[case("RETURN 0x8000000000000000", None)]
+#[case("RETURN 99999999999999999999", None)]
+#[case("RETURN 9223372036854775807", Some(i64::MAX))]
+#[case("RETURN -9223372036854775808", Some(i64::MIN))]
+#[case("RETURN -0x8000000000000000", Some(i64::MIN))]
+#[case("RETURN 9223372036854775808", None)]
+fn parser_test_integer_literals(#[case] query: &str, #[case] expected: Option<i64>) {
+    use open_cypher::ast::cypher::{Atom, Literal, NumberLiteral, ProjectionItem, Query, SingleQuery};
+
+    let query = match Query::parse(query) {
+        Ok(query) => query,
+        Err(error) => {
+            assert_eq!(expected, None, "{}", error);
+            return;
+        }
+    };
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    let Some(Atom::Literal(Literal::Number(NumberLiteral::Integer(value)))) = expression.as_atom() else { panic!("expected an integer") };
+    assert_eq!(Some(*value), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (a)-[*]->(b) RETURN a", None, None)]
+#[case("MATCH (a)-[*2]->(b) RETURN a", Some(2), Some(2))]
+#[case("MATCH (a)-[r:KNOWS*1..3]->(b) RETURN a", Some(1), Some(3))]
+#[case("MATCH (a)-[*..3]->(b) RETURN a", None, Some(3))]
+#[case("MATCH (a)-[*2..]->(b) RETURN a", Some(2), None)]
+fn parser_test_variable_length_relationships(#[case] query: &str, #[case] min: Option<u64>, #[case] max: Option<u64>) {
+    use open_cypher::ast::cypher::{Query, RangeLiteral, ReadingClause, RelationshipPattern, SingleQuery};
+
+    let Query::RegularQuery(query) = Query::parse(query).unwrap() else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ReadingClause::Match(match_) = &query.reading_clauses[0] else { panic!("expected a MATCH clause") };
+    let (relationship, _) = &match_.pattern.parts[0].pattern_element.relationship_patterns[0];
+    let detail = match relationship {
+        RelationshipPattern::LeftAndRight(detail) | RelationshipPattern::Left(detail)
+        | RelationshipPattern::Right(detail) | RelationshipPattern::Undirected(detail) => detail,
+    };
+    assert_eq!(detail.as_ref().unwrap().range, Some(RangeLiteral { min, max }));
+}
+
+#[test]
+fn parser_test_foreach() {
+    use open_cypher::ast::cypher::{Query, SingleQuery, UpdatingClause};
+
+    let text = "MATCH (a) FOREACH (x IN a.list | SET a.seen = x FOREACH (y IN [x] | DELETE a)) RETURN a";
+    let Query::RegularQuery(query) = Query::parse(text).unwrap() else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = &query.query else { panic!("expected a single part query") };
+    let UpdatingClause::Foreach(foreach) = &query.updating_clauses[0] else { panic!("expected FOREACH") };
+    assert_eq!(foreach.variable, "x");
+    assert_eq!(foreach.expression.to_string(), "a.list");
+    assert!(matches!(foreach.clauses.as_slice(), [UpdatingClause::Set(_), UpdatingClause::Foreach(_)]));
+
+    let printed = Query::parse(text).unwrap().to_string();
+    assert_eq!(printed, text);
+    assert!(Query::parse("MATCH (a) FOREACH (x IN a.list | FILTER x > 1)").is_err());
+}
+
+#[test]
+fn parser_test_call_subquery() {
+    use open_cypher::ast::cypher::{Query, ReadingClause, SingleQuery};
+
+    let text = "MATCH (n) CALL { USE other MATCH (m) RETURN m } RETURN n, m";
+    let Query::RegularQuery(query) = Query::parse(text).unwrap() else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = &query.query else { panic!("expected a single part query") };
+    let ReadingClause::CallSubquery(subquery) = &query.reading_clauses[1] else { panic!("expected a CALL subquery") };
+    assert_eq!(subquery.query.use_.as_ref().unwrap().graph.static_name().as_deref(), Some("other"));
+    assert!(subquery.query.query.final_part().return_.is_some());
+
+    let printed = Query::parse(text).unwrap().to_string();
+    assert_eq!(printed, text);
+    assert!(Query::parse("MATCH (n) CALL { } RETURN n").is_err());
+    assert!(Query::parse("MATCH (n) CALL { MATCH (m) RETURN m RETURN n").is_err());
+}
+
+#[test]
+fn parser_test_variables() {
+    use open_cypher::ast::cypher::{Query, ReadingClause, SingleQuery};
+
+    let Query::RegularQuery(query) = Query::parse("UNWIND [1] AS `my var` RETURN 1").unwrap() else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ReadingClause::Unwind(unwind) = &query.reading_clauses[0] else { panic!("expected an UNWIND clause") };
+    assert_eq!(unwind.variable.name, "my var");
+    assert_eq!((unwind.variable.span.start, unwind.variable.span.end), (14, 22));
+}
+
+#[rstest::rstest]
+#[case("RETURN true XOR false XOR true", 3)]
+#[case("RETURN true XOR false", 2)]
+#[case("RETURN true", 1)]
+fn parser_test_xor(#[case] query: &str, #[case] operands: usize) {
+    use open_cypher::ast::cypher::{ProjectionItem, Query, SingleQuery};
+
+    let Query::RegularQuery(query) = Query::parse(query).unwrap() else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an unaliased projection") };
+    assert_eq!(expression.0[0].0.len(), operands);
+}
+
+#[test]
+fn parser_test_existential_subquery() {
+    use open_cypher::ast::cypher::{Atom, ExistentialSubquery, Query, ReadingClause, SingleQuery};
+
+    let query = Query::parse("MATCH (a) WHERE EXISTS { (a)-->(b) WHERE b.x = 1 } RETURN a").unwrap();
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ReadingClause::Match(match_) = &query.reading_clauses[0] else { panic!("expected a MATCH clause") };
+    let Some(Atom::ExistentialSubquery(ExistentialSubquery::PatternWhere { pattern, where_ })) = match_.where_.as_ref().unwrap().as_atom() else {
+        panic!("expected an existential subquery")
+    };
+    assert_eq!(pattern.parts.len(), 1);
+    assert!(where_.is_some());
+}
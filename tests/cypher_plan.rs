@@ -0,0 +1,218 @@
+use open_cypher::ast::cypher::Query;
+use open_cypher::plan::{plan, Direction, LogicalPlan};
+
+fn explain(text: &str) -> Vec<String> {
+    let query = Query::parse(text).unwrap();
+    plan(&query).unwrap().to_string().lines().map(str::to_string).collect()
+}
+
+#[rstest::rstest]
+#[case("MATCH (n:Person) WHERE n.age > 30 RETURN n.name", vec![
+    "+ProduceResults `n.name`",
+    "+Projection n.name AS `n.name`",
+    "+Filter n.age > 30",
+    "+NodeScan n:Person",
+    "+Argument",
+])]
+#[case("MATCH (a:A {x: 1})-[r:KNOWS]->(b)<-[:LIKES*1..3]-(c:C) RETURN a", vec![
+    "+ProduceResults a",
+    "+Projection a",
+    "+Filter NOT r IN anon_0",
+    "+Filter c:C",
+    "+VarLengthExpand(All) (b)<-[anon_0:LIKES*1..3]-(c)",
+    "+Expand(All) (a)-[r:KNOWS]->(b)",
+    "+Filter a.x = 1",
+    "+NodeScan a:A",
+    "+Argument",
+])]
+#[case("MATCH (a) MATCH (a:A)-[r {w: 1}]-(b:B), (b)-[s*2]->(a) RETURN b", vec![
+    "+ProduceResults b",
+    "+Projection b",
+    "+Filter NOT r IN s",
+    "+VarLengthExpand(Into) (b)-[s*2]->(a)",
+    "+Filter b:B",
+    "+Filter r.w = 1",
+    "+Expand(All) (a)-[r]-(b)",
+    "+Filter a:A",
+    "+NodeScan a",
+    "+Argument",
+])]
+#[case("MATCH (a)-[x*]->(b)-[y*..2]->(c) RETURN c", vec![
+    "+ProduceResults c",
+    "+Projection c",
+    "+Filter none(element_0 IN x WHERE element_0 IN y)",
+    "+VarLengthExpand(All) (b)-[y*1..2]->(c)",
+    "+VarLengthExpand(All) (a)-[x*1..]->(b)",
+    "+NodeScan a",
+    "+Argument",
+])]
+#[case("MATCH (a)-[r]->(b) MATCH (b)-[r]->(c) RETURN c", vec![
+    "+ProduceResults c",
+    "+Projection c",
+    "+Filter anon_0 = r",
+    "+Expand(All) (b)-[anon_0]->(c)",
+    "+Expand(All) (a)-[r]->(b)",
+    "+NodeScan a",
+    "+Argument",
+])]
+#[case("MATCH p = (a)-[:T*]-(:B) RETURN p", vec![
+    "+ProduceResults p",
+    "+Projection p",
+    "+ProjectPath p = (a)-[anon_0*]-(anon_1)",
+    "+Filter anon_1:B",
+    "+VarLengthExpand(All) (a)-[anon_0:T*1..]-(anon_1)",
+    "+NodeScan a",
+    "+Argument",
+])]
+fn plan_test_match(#[case] text: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(explain(text), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (a) OPTIONAL MATCH (a)-->(b) WHERE b.x = 1 RETURN a, b", vec![
+    "+ProduceResults a, b",
+    "+Projection a, b",
+    "+Apply",
+    "| +Optional b",
+    "| +Filter b.x = 1",
+    "| +Expand(All) (a)-[anon_0]->(b)",
+    "| +Argument a",
+    "+NodeScan a",
+    "+Argument",
+])]
+#[case("UNWIND [1, 2] AS x WITH x WHERE x > 1 RETURN x", vec![
+    "+ProduceResults x",
+    "+Projection x",
+    "+Filter x > 1",
+    "+Projection x",
+    "+Unwind [1, 2] AS x",
+    "+Argument",
+])]
+#[case("MATCH (n) CALL proc(n) YIELD out AS o WHERE o > 1 RETURN o", vec![
+    "+ProduceResults o",
+    "+Projection o",
+    "+Filter o > 1",
+    "+ProcedureCall proc(n) YIELD out AS o",
+    "+NodeScan n",
+    "+Argument",
+])]
+#[case("CALL db.labels", vec![
+    "+ProduceResults",
+    "+ProcedureCall db.labels YIELD *",
+    "+Argument",
+])]
+#[case("RETURN 1 AS x UNION ALL RETURN 2 AS x", vec![
+    "+ProduceResults x",
+    "+Union ALL",
+    "| +Projection 2 AS x",
+    "| +Argument",
+    "+Projection 1 AS x",
+    "+Argument",
+])]
+fn plan_test_clauses(#[case] text: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(explain(text), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) RETURN n.a, count(*) ORDER BY n.a", vec![
+    "+ProduceResults `n.a`, `count(*)`",
+    "+Sort `n.a`",
+    "+Aggregate n.a AS `n.a`, count(*) AS `count(*)`",
+    "+NodeScan n",
+    "+Argument",
+])]
+#[case("MATCH (n) RETURN n.a AS a, count(*) + 1 AS c", vec![
+    "+ProduceResults a, c",
+    "+Projection a, aggregate_0 + 1 AS c",
+    "+Aggregate n.a AS a, count(*) AS aggregate_0",
+    "+NodeScan n",
+    "+Argument",
+])]
+#[case("MATCH (n) RETURN DISTINCT count(n) AS c", vec![
+    "+ProduceResults c",
+    "+Aggregate c",
+    "+Aggregate count(n) AS c",
+    "+NodeScan n",
+    "+Argument",
+])]
+#[case("UNWIND [3, 1, 2] AS x RETURN DISTINCT x ORDER BY x DESC SKIP 1", vec![
+    "+ProduceResults x",
+    "+Skip 1",
+    "+Sort x DESC",
+    "+Aggregate x",
+    "+Unwind [3, 1, 2] AS x",
+    "+Argument",
+])]
+#[case("MATCH (n) RETURN n.name AS name ORDER BY n.age LIMIT 2", vec![
+    "+ProduceResults name",
+    "+Projection name",
+    "+Limit 2",
+    "+Sort n.age",
+    "+Projection n, n.name AS name",
+    "+NodeScan n",
+    "+Argument",
+])]
+#[case("MATCH (n) WITH * RETURN *", vec![
+    "+ProduceResults n",
+    "+Projection n",
+    "+Projection n",
+    "+NodeScan n",
+    "+Argument",
+])]
+fn plan_test_projection(#[case] text: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(explain(text), expected);
+}
+
+#[rstest::rstest]
+#[case("CREATE (a:A {x: 1})-[:R]->(b) SET a.y = 2 REMOVE a:A DETACH DELETE b", vec![
+    "+ProduceResults",
+    "+Delete DETACH b",
+    "+Remove a:A",
+    "+SetProperty a.y = 2",
+    "+Create (a:A {x: 1})-[:R]->(b)",
+    "+Argument",
+])]
+#[case("MATCH (a) MERGE (a)-[:R]->(b:B) ON CREATE SET b.c = 1 ON MATCH SET b.m = 1 RETURN b", vec![
+    "+ProduceResults b",
+    "+Projection b",
+    "+Merge (a)-[:R]->(b:B) ON MATCH SET b.m = 1 ON CREATE SET b.c = 1",
+    "| +Filter b:B",
+    "| +Expand(All) (a)-[anon_0:R]->(b)",
+    "| +Argument a",
+    "+NodeScan a",
+    "+Argument",
+])]
+fn plan_test_update(#[case] text: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(explain(text), expected);
+}
+
+#[rstest::rstest]
+#[case("RETURN 1 AS x UNION RETURN 2 AS y", "all parts of a UNION must return the same columns")]
+#[case("MATCH (n $props) RETURN n", "node properties given as a parameter cannot be planned")]
+#[case("LOAD CSV FROM 'file.csv' AS row RETURN row", "LOAD CSV cannot be planned")]
+#[case("CALL { MATCH (n) RETURN n } RETURN n", "CALL { ... } cannot be planned")]
+#[case("MATCH (n) FOREACH (x IN [1] | SET n.x = x)", "FOREACH cannot be planned")]
+fn plan_test_error(#[case] text: &str, #[case] expected: &str) {
+    let query = Query::parse(text).unwrap();
+    assert_eq!(plan(&query).unwrap_err().to_string(), expected);
+}
+
+#[test]
+fn plan_test_hidden_names() {
+    // Hidden variables do not clash with the variables of the query.
+    assert_eq!(explain("MATCH (anon_0)-->(anon_1) RETURN anon_0")[2], "+Expand(All) (anon_0)-[anon_2]->(anon_1)");
+}
+
+#[test]
+fn plan_test_operators() {
+    let query = Query::parse("MATCH (a)<-[r:T]-(b) RETURN b").unwrap();
+    let plan = plan(&query).unwrap();
+    assert_eq!(plan.columns(), ["b"]);
+    let mut operator = &plan;
+    while !matches!(operator, LogicalPlan::Expand { .. }) {
+        operator = operator.children()[0];
+    }
+    let LogicalPlan::Expand { from, relationship, to, direction, into, .. } = operator else { unreachable!() };
+    assert_eq!((from.as_str(), relationship.as_str(), to.as_str()), ("a", "r", "b"));
+    assert_eq!((*direction, *into), (Direction::Incoming, false));
+}
@@ -0,0 +1,96 @@
+use open_cypher::analysis::procedures::{check_procedures, ProcedureCatalog, ProcedureMode, ProcedureRegistry, ProcedureSignature};
+use open_cypher::analysis::semantic::SemanticErrorKind;
+use open_cypher::ast::cypher::{CypherType, QualifiedName, Query};
+
+fn parse(text: &str) -> Query {
+    Query::parse(text).unwrap()
+}
+
+#[rstest::rstest]
+#[case("CALL db.labels()")]
+#[case("CALL db.labels")]
+#[case("CALL db.labels() YIELD *")]
+#[case("CALL db.labels() YIELD label")]
+#[case("CALL db.labels() YIELD label AS l WHERE l STARTS WITH 'A'")]
+#[case("CALL db.labels() YIELD label RETURN label")]
+#[case("MATCH (n) CALL db.labels() YIELD label AS l RETURN n, l")]
+#[case("CALL db.index.fulltext.queryNodes('names', 'Alice') YIELD node, score RETURN node")]
+#[case("CALL db.index.fulltext.queryNodes('names', 'Alice', {limit: 5}) YIELD node RETURN node")]
+#[case("CALL db.index.fulltext.queryNodes")]
+#[case("CALL db.awaitIndexes()")]
+#[case("CALL db.awaitIndexes(300)")]
+#[case("CALL db.createLabel('Person')")]
+#[case("MATCH (n) WITH n CALL db.awaitIndexes() RETURN n")]
+#[case("MATCH (n) WHERE EXISTS { CALL db.labels() YIELD label RETURN label } RETURN n")]
+fn procedures_test_valid(#[case] text: &str) {
+    let errors = check_procedures(&parse(text), &ProcedureRegistry::builtin());
+    assert!(errors.is_empty(), "unexpected errors for {}: {:?}", text, errors);
+}
+
+#[rstest::rstest]
+#[case("CALL db.nope()", SemanticErrorKind::ProcedureNotFound, "db.nope()", "unknown procedure `db.nope`")]
+#[case("CALL db.nope", SemanticErrorKind::ProcedureNotFound, "db.nope", "unknown procedure `db.nope`")]
+#[case("CALL DB.LABELS()", SemanticErrorKind::ProcedureNotFound, "DB.LABELS()", "unknown procedure `DB.LABELS`")]
+#[case("MATCH (n) CALL my.proc() YIELD x RETURN x", SemanticErrorKind::ProcedureNotFound, "my.proc()", "unknown procedure `my.proc`")]
+#[case("CALL db.labels(1)", SemanticErrorKind::InvalidNumberOfArguments, "db.labels(1)", "expects 0 arguments but got 1")]
+#[case(
+    "CALL db.index.fulltext.queryNodes('names')",
+    SemanticErrorKind::InvalidNumberOfArguments,
+    "db.index.fulltext.queryNodes('names')",
+    "expects 2 to 3 arguments but got 1"
+)]
+#[case("CALL db.labels() YIELD name", SemanticErrorKind::UndefinedVariable, "name", "has no output `name`")]
+#[case("CALL db.labels() YIELD name AS label", SemanticErrorKind::UndefinedVariable, "label", "has no output `name`")]
+#[case("MATCH (n) CALL db.labels() YIELD * RETURN n", SemanticErrorKind::InvalidYield, "db.labels()", "YIELD * can only be used in a standalone CALL")]
+#[case("MATCH (n) CALL db.labels() RETURN n", SemanticErrorKind::InvalidYield, "db.labels()", "must YIELD the outputs it uses")]
+#[case("CALL db.createLabel('A') YIELD *", SemanticErrorKind::InvalidYield, "db.createLabel('A')", "has no outputs to yield")]
+#[case(
+    "MATCH (n) WHERE EXISTS { CALL db.labels() YIELD * RETURN 1 } RETURN n",
+    SemanticErrorKind::InvalidYield,
+    "db.labels()",
+    "YIELD * can only be used"
+)]
+fn procedures_test_errors(#[case] text: &str, #[case] kind: SemanticErrorKind, #[case] span: &str, #[case] message: &str) {
+    let errors = check_procedures(&parse(text), &ProcedureRegistry::builtin());
+    assert_eq!(errors.len(), 1, "unexpected errors for {}: {:?}", text, errors);
+    assert_eq!(errors[0].kind, kind);
+    assert_eq!(&text[errors[0].span.start..errors[0].span.end], span);
+    assert!(errors[0].message.contains(message), "{}", errors[0].message);
+}
+
+#[test]
+fn procedures_test_registry() {
+    let mut registry = ProcedureRegistry::new();
+    let query = parse("CALL my.lib.proc(1) YIELD result RETURN result");
+    assert_eq!(check_procedures(&query, &registry)[0].kind, SemanticErrorKind::ProcedureNotFound);
+
+    let signature = ProcedureSignature::new("my.lib.proc", ProcedureMode::Write)
+        .input("value", CypherType::Integer)
+        .output("result", CypherType::String);
+    assert!(registry.register(signature).is_none());
+    assert!(check_procedures(&query, &registry).is_empty());
+
+    let builtin = ProcedureRegistry::builtin();
+    let name = |name: &str| QualifiedName { namespace: vec!["db".to_string()], name: name.to_string() };
+    assert_eq!(builtin.lookup(&name("labels")).unwrap().mode, ProcedureMode::Read);
+    assert_eq!(builtin.lookup(&name("createLabel")).unwrap().mode, ProcedureMode::Write);
+    assert!(builtin.lookup(&name("awaitIndexes")).unwrap().is_void());
+}
+
+struct SingleProcedure(ProcedureSignature);
+
+impl ProcedureCatalog for SingleProcedure {
+    fn lookup(&self, name: &QualifiedName) -> Option<&ProcedureSignature> {
+        (*name == self.0.name).then_some(&self.0)
+    }
+}
+
+#[test]
+fn procedures_test_custom_catalog() {
+    let catalog = SingleProcedure(ProcedureSignature::new("ping", ProcedureMode::Read).output("ok", CypherType::Boolean));
+
+    assert!(check_procedures(&parse("CALL ping() YIELD ok RETURN ok"), &catalog).is_empty());
+    let errors = check_procedures(&parse("CALL db.labels()"), &catalog);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, SemanticErrorKind::ProcedureNotFound);
+}
@@ -0,0 +1,113 @@
+use open_cypher::analysis::pushdown::{conjunction, pull_up_predicates, push_down_predicates, split_conjuncts};
+use open_cypher::ast::cypher::{Match, Query, ReadingClause, SingleQuery};
+
+fn parse(text: &str) -> Query {
+    Query::parse(text).unwrap()
+}
+
+fn first_match(query: &Query) -> &Match {
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = &query.query else { panic!("expected a single part query") };
+    let ReadingClause::Match(match_) = &query.reading_clauses[0] else { panic!("expected a MATCH") };
+    match_
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) WHERE n.name = 'Alice' RETURN n", "MATCH (n {name: 'Alice'}) RETURN n")]
+#[case("MATCH (n) WHERE 'Alice' = n.name RETURN n", "MATCH (n {name: 'Alice'}) RETURN n")]
+#[case("MATCH (n) WHERE n:Person RETURN n", "MATCH (n:Person) RETURN n")]
+#[case("MATCH (n:Person) WHERE n:Admin:User RETURN n", "MATCH (n:Person&Admin&User) RETURN n")]
+#[case("MATCH (n:Person) WHERE n:Person RETURN n", "MATCH (n:Person) RETURN n")]
+#[case("MATCH (n) WHERE n:Person AND n.name = $name AND n.age > 30 RETURN n", "MATCH (n:Person {name: $name}) WHERE n.age > 30 RETURN n")]
+#[case("MATCH (n {age: 42}) WHERE n.name = 'Alice' RETURN n", "MATCH (n {age: 42, name: 'Alice'}) RETURN n")]
+#[case("MATCH (n {name: 'Alice'}) WHERE n.name = 'Alice' RETURN n", "MATCH (n {name: 'Alice'}) RETURN n")]
+#[case("MATCH (n {name: 'Alice'}) WHERE n.name = 'Bob' RETURN n", "MATCH (n {name: 'Alice'}) WHERE n.name = 'Bob' RETURN n")]
+#[case("MATCH (n $props) WHERE n.name = 'Alice' RETURN n", "MATCH (n $props) WHERE n.name = 'Alice' RETURN n")]
+#[case("MATCH (a)-[r]->(b) WHERE r.since = 2020 AND b.name = 'x' RETURN a", "MATCH (a)-[r {since: 2020}]->(b {name: 'x'}) RETURN a")]
+#[case("MATCH (a)-[r*1..3]->(b) WHERE r.since = 2020 RETURN a", "MATCH (a)-[r*1..3]->(b) WHERE r.since = 2020 RETURN a")]
+#[case("MATCH (a)-[r]->(b) WHERE a.name = b.name RETURN a", "MATCH (a)-[r]->(b) WHERE a.name = b.name RETURN a")]
+#[case("MATCH (a) WHERE a.x = a.y OR a:Person RETURN a", "MATCH (a) WHERE a.x = a.y OR a:Person RETURN a")]
+#[case("MATCH (a) WHERE a.list = [1, 2] + [3] RETURN a", "MATCH (a {list: [1, 2] + [3]}) RETURN a")]
+#[case("MATCH (a) WHERE a.name = 'x' OR a.name = 'y' RETURN a", "MATCH (a) WHERE a.name = 'x' OR a.name = 'y' RETURN a")]
+#[case("MATCH (a) WHERE NOT a.name = 'x' RETURN a", "MATCH (a) WHERE NOT a.name = 'x' RETURN a")]
+#[case("MATCH (a) WHERE (a.x = 1 AND a:A) AND (a.y = 2 OR a.z = 3) RETURN a", "MATCH (a:A {x: 1}) WHERE a.y = 2 OR a.z = 3 RETURN a")]
+#[case("WITH 1 AS x MATCH (a) WHERE a.x = x RETURN a", "WITH 1 AS x MATCH (a) WHERE a.x = x RETURN a")]
+#[case("MATCH (a) MATCH (a)-->(b) WHERE a.x = 1 RETURN b", "MATCH (a) MATCH (a {x: 1})-->(b) RETURN b")]
+#[case("MATCH (a) OPTIONAL MATCH (a)-->(b) WHERE b:B RETURN b", "MATCH (a) OPTIONAL MATCH (a)-->(b:B) RETURN b")]
+#[case("MATCH (a) WHERE EXISTS { MATCH (a)-->(b) WHERE b:B RETURN b } RETURN a", "MATCH (a) WHERE EXISTS { MATCH (a)-->(b:B) RETURN b } RETURN a")]
+fn pushdown_test_push_down(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(push_down_predicates(&parse(text)).to_string(), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n {name: 'Alice'}) RETURN n", "MATCH (n) WHERE n.name = 'Alice' RETURN n")]
+#[case("MATCH (n:Person) RETURN n", "MATCH (n) WHERE n:Person RETURN n")]
+#[case("MATCH (n:Person {name: 'Alice', age: 42}) WHERE n.x > 1 RETURN n", "MATCH (n) WHERE n:Person AND n.age = 42 AND n.name = 'Alice' AND n.x > 1 RETURN n")]
+#[case("MATCH (a)-[r:KNOWS {since: 2020}]->(b:B) RETURN a", "MATCH (a)-[r]->(b) WHERE r:KNOWS AND r.since = 2020 AND b:B RETURN a")]
+#[case("MATCH (a)-[r:KNOWS*1..3]->(:B) RETURN a", "MATCH (a)-[r:KNOWS*1..3]->(:B) RETURN a")]
+#[case("MATCH (n $props) RETURN n", "MATCH (n $props) RETURN n")]
+#[case("MATCH (n:A|B {x: 1 + 1}) WHERE n.y = 1 OR n.z = 2 RETURN n", "MATCH (n) WHERE n:A|B AND n.x = 1 + 1 AND (n.y = 1 OR n.z = 2) RETURN n")]
+#[case("MATCH (n {flag: true OR false}) RETURN n", "MATCH (n) WHERE n.flag = (true OR false) RETURN n")]
+#[case("MATCH (n) RETURN n", "MATCH (n) RETURN n")]
+fn pushdown_test_pull_up(#[case] text: &str, #[case] expected: &str) {
+    assert_eq!(pull_up_predicates(&parse(text)).to_string(), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) WHERE n:Person AND n.name = 'Alice' RETURN n")]
+#[case("MATCH (a)-[r]->(b) WHERE r:KNOWS AND r.since = 2020 AND b:B RETURN a")]
+fn pushdown_test_round_trip(#[case] text: &str) {
+    let query = parse(text);
+    let pushed = push_down_predicates(&query);
+    assert_ne!(pushed.to_string(), text);
+    assert_eq!(pull_up_predicates(&pushed).to_string(), text);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) WHERE n.a = 1 RETURN n", vec![("n.a = 1", vec!["n"], Some("n"))])]
+#[case("MATCH (n)-[r]->(m) WHERE n.a = m.a AND r.since > 1 AND n:A RETURN n", vec![("n.a = m.a", vec!["m", "n"], None), ("r.since > 1", vec!["r"], Some("r")), ("n:A", vec!["n"], Some("n"))])]
+#[case("MATCH (n) WHERE n.a = x AND true RETURN n", vec![("n.a = x", vec!["n", "x"], None), ("true", vec![], None)])]
+#[case("MATCH (n) WHERE all(x IN n.list WHERE x > 0) RETURN n", vec![("all(x IN n.list WHERE x > 0)", vec!["n"], Some("n"))])]
+#[case("MATCH (n) WHERE reduce(s = 0, x IN n.list | s + x) > 0 RETURN n", vec![("reduce(s = 0, x IN n.list | s + x) > 0", vec!["n"], Some("n"))])]
+#[case("MATCH p = (n)-->() WHERE length(p) > 1 AND size(n {.name}) > 0 RETURN n", vec![("length(p) > 1", vec!["p"], Some("p")), ("size(n {.name}) > 0", vec!["n"], Some("n"))])]
+#[case("MATCH (n) WHERE (n)-->(m) RETURN n", vec![("(n)-->(m)", vec!["m", "n"], None)])]
+#[case("MATCH (n) RETURN n", vec![])]
+fn pushdown_test_conjuncts(#[case] text: &str, #[case] expected: Vec<(&str, Vec<&str>, Option<&str>)>) {
+    let query = parse(text);
+    let conjuncts: Vec<_> = first_match(&query)
+        .conjuncts()
+        .into_iter()
+        .map(|conjunct| (conjunct.expression.to_string(), conjunct.variables.into_iter().collect::<Vec<_>>(), conjunct.pattern_variable))
+        .collect();
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(expression, variables, variable)| {
+            (expression.to_string(), variables.into_iter().map(str::to_string).collect::<Vec<_>>(), variable.map(str::to_string))
+        })
+        .collect();
+    assert_eq!(conjuncts, expected);
+}
+
+#[test]
+fn pushdown_test_split_and_join() {
+    let query = parse("MATCH (n) WHERE n.a = 1 AND (n.b = 2 AND (n.c OR n.d)) AND n.e XOR n.f RETURN n");
+    let where_ = first_match(&query).where_.as_ref().unwrap();
+    // XOR binds weaker than AND, so the expression is not a conjunction.
+    assert_eq!(split_conjuncts(where_).len(), 1);
+
+    let query = parse("MATCH (n) WHERE n.a = 1 AND (n.b = 2 AND (n.c OR n.d)) AND (n.e XOR n.f) RETURN n");
+    let conjuncts = split_conjuncts(first_match(&query).where_.as_ref().unwrap());
+    let texts: Vec<_> = conjuncts.iter().map(ToString::to_string).collect();
+    assert_eq!(texts, ["n.a = 1", "n.b = 2", "n.c OR n.d", "n.e XOR n.f"]);
+    assert_eq!(conjunction(conjuncts).unwrap().to_string(), "n.a = 1 AND n.b = 2 AND (n.c OR n.d) AND (n.e XOR n.f)");
+    assert_eq!(conjunction(Vec::new()), None);
+}
+
+#[test]
+fn pushdown_test_match_methods() {
+    let query = parse("MATCH p = (a)-[r]->(b), (c) WHERE a:A RETURN a");
+    let match_ = first_match(&query);
+    assert_eq!(match_.pattern_variables().into_iter().collect::<Vec<_>>(), ["a", "b", "c", "p", "r"]);
+    assert_eq!(match_.push_down_predicates().to_string(), "MATCH p = (a:A)-[r]->(b), (c)");
+    assert_eq!(match_.push_down_predicates().pull_up_predicates().to_string(), match_.to_string());
+}
@@ -0,0 +1,153 @@
+use open_cypher::analysis::aggregation::check_aggregation;
+use open_cypher::analysis::semantic::{check_variables, SemanticError, SemanticErrorKind};
+use open_cypher::ast::cypher::Query;
+
+fn check(text: &str) -> Vec<SemanticError> {
+    check_variables(&Query::parse(text).unwrap())
+}
+
+#[rstest::rstest]
+#[case("MATCH (a) RETURN a")]
+#[case("MATCH (a)-[r]->(b) WHERE a.name = b.name RETURN r")]
+#[case("MATCH (a {name: b.name})-->(b) RETURN a")]
+#[case("MATCH (a) MATCH (a)-[r]->(b) RETURN a, b, r")]
+#[case("MATCH p = (a)-->(b) RETURN p, nodes(p)")]
+#[case("MATCH (a)-[rs*1..3]->(b) RETURN rs")]
+#[case("MATCH (a) WITH a.name AS name, a RETURN name, a")]
+#[case("MATCH (a) WITH * RETURN a")]
+#[case("MATCH (a) WITH *, a.name AS name RETURN a, name")]
+#[case("MATCH (a) WITH a AS b MATCH (b)-->(c) RETURN c")]
+#[case("MATCH (a) WITH a.name AS name WHERE name STARTS WITH 'A' RETURN name")]
+#[case("MATCH (a) RETURN a.name AS name ORDER BY a.age, name")]
+#[case("MATCH (a) RETURN DISTINCT a.name ORDER BY a.name")]
+#[case("MATCH (a) RETURN DISTINCT a ORDER BY a.name")]
+#[case("MATCH (a) RETURN a.name, count(*) ORDER BY a.name, count(*)")]
+#[case("MATCH (a) WITH a.name AS name, count(a) AS c ORDER BY name, c RETURN name")]
+#[case("UNWIND [1, 2, 3] AS x RETURN x")]
+#[case("WITH [1, 2] AS list UNWIND list AS x RETURN x")]
+#[case("RETURN [x IN range(1, 10) WHERE x % 2 = 0 | x * 2] AS evens")]
+#[case("RETURN all(x IN [1, 2] WHERE x > 0), reduce(acc = 0, x IN [1, 2] | acc + x)")]
+#[case("MATCH (a) WHERE (a)-->() RETURN a")]
+#[case("MATCH (a) WHERE EXISTS { MATCH (a)-->(b) WHERE b.name = a.name RETURN b } RETURN a")]
+#[case("MATCH (a) WHERE EXISTS { (a)-[r]->(b) WHERE r.weight > 1 } RETURN a")]
+#[case("MATCH (a) RETURN a {.name, .age}")]
+#[case("MATCH (a) RETURN a AS x UNION MATCH (b) RETURN b AS x")]
+#[case("CREATE (a:Person)-[:KNOWS]->(b:Person) RETURN a, b")]
+#[case("MATCH (a), (b) CREATE (a)-[r:KNOWS]->(b) RETURN r")]
+#[case("MERGE (a:Person {name: 'A'}) ON CREATE SET a.created = timestamp() RETURN a")]
+#[case("MATCH (a) SET a.name = 'x', a:Person REMOVE a.age DETACH DELETE a")]
+#[case("CALL db.labels() YIELD label RETURN label")]
+#[case("LOAD CSV WITH HEADERS FROM 'file:///a.csv' AS row CREATE (:Person {name: row.name})")]
+#[case("MATCH (a)-[r]->(b) WITH r MATCH ()-[r]->() RETURN r")]
+#[case("WITH 1 AS x RETURN [x IN [1, 2] | x] AS xs")]
+#[case("MATCH (a) FOREACH (x IN [1, 2] | CREATE (b {x: x}) SET a.x = x) FOREACH (x IN [3] | SET a.y = x)")]
+#[case("MATCH (a) CALL { MATCH (b) RETURN b } RETURN a, b")]
+#[case("MATCH (a) CALL { WITH a MATCH (a)-->(b) RETURN b.name AS name UNION RETURN 'none' AS name } RETURN a, name")]
+#[case("UNWIND [1, 2] AS x CALL { CREATE (:Item) } RETURN x")]
+fn semantic_test_valid(#[case] text: &str) {
+    let errors = check(text);
+    assert!(errors.is_empty(), "unexpected errors for {}: {:?}", text, errors);
+}
+
+#[rstest::rstest]
+#[case("MATCH (a) RETURN b", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) WITH a RETURN c", SemanticErrorKind::UndefinedVariable, "c")]
+#[case("MATCH (a), (b) WITH a RETURN b", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) WITH a.name AS name RETURN a", SemanticErrorKind::UndefinedVariable, "a")]
+#[case("MATCH (a) WHERE b.name = 'x' RETURN a", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) WITH a.name AS name WHERE a.age > 3 RETURN name", SemanticErrorKind::UndefinedVariable, "a")]
+#[case("RETURN [x IN [1, 2] | x] AS xs, x", SemanticErrorKind::UndefinedVariable, "x")]
+#[case("RETURN reduce(acc = 0, x IN [1, 2] | acc + y)", SemanticErrorKind::UndefinedVariable, "y")]
+#[case("MATCH (a) WHERE (a)-->(b) RETURN a", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) WHERE EXISTS { MATCH (a)-->(b) RETURN b } RETURN b", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) RETURN a AS x UNION RETURN a AS x", SemanticErrorKind::UndefinedVariable, "a")]
+#[case("RETURN b {.name}", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) SET b.name = 'x'", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) DELETE b", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("CALL db.index.fulltext.queryNodes('index', x)", SemanticErrorKind::UndefinedVariable, "x")]
+#[case("MATCH (a) RETURN a ORDER BY b", SemanticErrorKind::UndefinedVariable, "b")]
+#[case("MATCH (a) RETURN DISTINCT a.x AS x ORDER BY a.y", SemanticErrorKind::UndefinedVariable, "a")]
+#[case("MATCH (n) WITH count(n) AS c ORDER BY n.x RETURN c", SemanticErrorKind::UndefinedVariable, "n")]
+#[case("MATCH (a) FOREACH (x IN [1] | CREATE (b)) RETURN x", SemanticErrorKind::UndefinedVariable, "x")]
+#[case("CALL { MATCH (a)-->(b) RETURN b } RETURN a", SemanticErrorKind::UndefinedVariable, "a")]
+#[case("MATCH (a) CALL { MATCH (b) RETURN b AS a } RETURN a", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("UNWIND [1, 2] AS x UNWIND [3, 4] AS x RETURN x", SemanticErrorKind::VariableAlreadyBound, "x")]
+#[case("MATCH (a) FOREACH (a IN [1] | CREATE (b))", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH (a) UNWIND [1, 2] AS a RETURN a", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH (a) CALL db.labels() YIELD label AS a RETURN a", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH p = (a)-->(b) MATCH p = (c)-->(d) RETURN p", SemanticErrorKind::VariableAlreadyBound, "p")]
+#[case("MATCH (a) CREATE (a)", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH (a) CREATE (a:Person)-[:KNOWS]->(b)", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH (a)-[r]->(b) CREATE (a)-[r:KNOWS]->(b)", SemanticErrorKind::VariableAlreadyBound, "r")]
+#[case("MATCH (a) MERGE (a {name: 'x'})", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH (a) RETURN a.name AS x, a.age AS x", SemanticErrorKind::VariableAlreadyBound, "x")]
+#[case("MATCH (a), (n) WITH *, a.name AS n RETURN n", SemanticErrorKind::VariableAlreadyBound, "n")]
+#[case("MATCH (a) RETURN *, 1 AS a", SemanticErrorKind::VariableAlreadyBound, "a")]
+#[case("MATCH (a)-[a]->(b) RETURN a", SemanticErrorKind::VariableTypeConflict, "a")]
+#[case("MATCH (a)-[r]->(b) MATCH (r) RETURN r", SemanticErrorKind::VariableTypeConflict, "r")]
+#[case("MATCH (a)-[r]->(b) WITH r AS n MATCH (n) RETURN n", SemanticErrorKind::VariableTypeConflict, "n")]
+#[case("WITH 1 AS n MATCH (n) RETURN n", SemanticErrorKind::VariableTypeConflict, "n")]
+#[case("MATCH p = ()-->() MATCH (p) RETURN p", SemanticErrorKind::VariableTypeConflict, "p")]
+#[case("MATCH ()-[r*]->() MATCH ()-[r]->() RETURN r", SemanticErrorKind::VariableTypeConflict, "r")]
+fn semantic_test_errors(#[case] text: &str, #[case] kind: SemanticErrorKind, #[case] variable: &str) {
+    let errors = check(text);
+    assert_eq!(errors.len(), 1, "unexpected errors for {}: {:?}", text, errors);
+    assert_eq!(errors[0].kind, kind);
+    assert_eq!(&text[errors[0].span.start..errors[0].span.end], variable);
+}
+
+#[test]
+fn semantic_test_spans() {
+    let text = "MATCH (a) WITH a AS b RETURN a, c";
+    let errors = check(text);
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|error| error.kind == SemanticErrorKind::UndefinedVariable));
+    assert_eq!(errors[0].span.start, text.rfind("a,").unwrap());
+    assert_eq!(errors[1].span.start, text.len() - 1);
+    assert_eq!(errors[1].to_string(), format!("UndefinedVariable at {}..{}: variable `c` is not defined", text.len() - 1, text.len()));
+}
+
+fn check_aggregates(text: &str) -> Vec<SemanticError> {
+    check_aggregation(&Query::parse(text).unwrap())
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) RETURN count(*)")]
+#[case("MATCH (n) RETURN n.name, count(*) AS c")]
+#[case("MATCH (n) RETURN n, collect(n.name)")]
+#[case("MATCH (n) RETURN n.name, n.name + count(*)")]
+#[case("MATCH (n) RETURN n, n.age + sum(n.age)")]
+#[case("MATCH (n) RETURN n.x AS x, max(n.y) AS m ORDER BY x, m")]
+#[case("MATCH (n) RETURN n.x, count(*) ORDER BY n.x")]
+#[case("MATCH (n) RETURN n.x, count(*) ORDER BY count(*) DESC")]
+#[case("MATCH (n) WITH n.x AS x, count(*) AS c WHERE c > 1 RETURN x")]
+#[case("MATCH (n) RETURN [x IN collect(n) | x.name] AS names")]
+#[case("MATCH (n) RETURN reduce(acc = 0, x IN collect(n.age) | acc + x)")]
+#[case("MATCH (n) RETURN percentileDisc(n.age, 0.5), stDev(n.age), AVG(n.age)")]
+#[case("MATCH (n) WHERE EXISTS { MATCH (n)-->(m) RETURN count(m) } RETURN n")]
+#[case("MATCH (n) RETURN n ORDER BY n.name")]
+#[case("RETURN toUpper('a')")]
+fn aggregation_test_valid(#[case] text: &str) {
+    let errors = check_aggregates(text);
+    assert!(errors.is_empty(), "unexpected errors for {}: {:?}", text, errors);
+}
+
+#[rstest::rstest]
+#[case("MATCH (n) RETURN n.x + count(*)", SemanticErrorKind::AmbiguousAggregationExpression, "n")]
+#[case("MATCH (n) RETURN n.x, n.y + count(*)", SemanticErrorKind::AmbiguousAggregationExpression, "n")]
+#[case("MATCH (n) RETURN n.x AS x, count(*) ORDER BY n.y", SemanticErrorKind::AmbiguousAggregationExpression, "n")]
+#[case("MATCH (n) RETURN count(count(*))", SemanticErrorKind::NestedAggregation, "count(*)")]
+#[case("MATCH (n) RETURN sum(max(n.age))", SemanticErrorKind::NestedAggregation, "max(n.age)")]
+#[case("MATCH (n) WHERE count(n) > 1 RETURN n", SemanticErrorKind::InvalidAggregation, "count(n)")]
+#[case("MATCH (n) WITH n WHERE collect(n) = [] RETURN n", SemanticErrorKind::InvalidAggregation, "collect(n)")]
+#[case("MATCH (n) RETURN n ORDER BY max(n.age)", SemanticErrorKind::InvalidAggregation, "max(n.age)")]
+#[case("MATCH (n) SET n.total = sum(n.x)", SemanticErrorKind::InvalidAggregation, "sum(n.x)")]
+#[case("UNWIND [1, 2] AS x RETURN [y IN [1, 2] | count(y)]", SemanticErrorKind::InvalidAggregation, "count(y)")]
+#[case("MATCH (n) RETURN count(*) LIMIT count(*)", SemanticErrorKind::InvalidAggregation, "count(*)")]
+fn aggregation_test_errors(#[case] text: &str, #[case] kind: SemanticErrorKind, #[case] expression: &str) {
+    let errors = check_aggregates(text);
+    assert_eq!(errors.len(), 1, "unexpected errors for {}: {:?}", text, errors);
+    assert_eq!(errors[0].kind, kind);
+    assert_eq!(&text[errors[0].span.start..errors[0].span.end], expression);
+}
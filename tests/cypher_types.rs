@@ -0,0 +1,123 @@
+use open_cypher::analysis::pushdown::pull_up_predicates;
+use open_cypher::analysis::types::infer_types;
+use open_cypher::ast::cypher::{CypherType, ProjectionItem, Query, SingleQuery, Span};
+
+fn list(element: CypherType) -> CypherType {
+    CypherType::List(Box::new(element))
+}
+
+/// Infers the types of `text` and returns the type of its first returned
+/// expression together with the error messages.
+fn infer(text: &str) -> (Option<CypherType>, Vec<String>) {
+    let query = Query::parse(text).unwrap();
+    let info = infer_types(&query);
+    let errors = info.errors.iter().map(ToString::to_string).collect();
+
+    let Query::RegularQuery(regular_query) = &query else { panic!("expected a regular query") };
+    let return_ = match &regular_query.query {
+        SingleQuery::SinglePartQuery(query) => &query.return_,
+        SingleQuery::MultiPartQuery(query) => &query.query.return_,
+    };
+    let type_ = return_.as_ref().map(|return_| {
+        let (ProjectionItem::Expression(expression) | ProjectionItem::AsVariable { expression, .. }) = &return_.items.items[0];
+        info.type_of(expression).cloned().expect("every expression is typed")
+    });
+
+    (type_, errors)
+}
+
+#[rstest::rstest]
+#[case("RETURN 1", CypherType::Integer)]
+#[case("RETURN 1 + 2 * 3", CypherType::Integer)]
+#[case("RETURN 1 + 2.5", CypherType::Float)]
+#[case("RETURN 2 ^ 3", CypherType::Float)]
+#[case("RETURN 'a' + 1", CypherType::String)]
+#[case("RETURN 'a' || 'b'", CypherType::String)]
+#[case("RETURN [1, 2] + 3", list(CypherType::Integer))]
+#[case("RETURN [1, 'a']", list(CypherType::Any))]
+#[case("RETURN [1, 2.0]", list(CypherType::Union(vec![CypherType::Integer, CypherType::Float])))]
+#[case("RETURN [1, null]", list(CypherType::Integer))]
+#[case("RETURN 1 < 2 AND NOT false", CypherType::Boolean)]
+#[case("RETURN {a: 1}", CypherType::Map)]
+#[case("MATCH (n) RETURN n", CypherType::Node)]
+#[case("MATCH ()-[r]->() RETURN r", CypherType::Relationship)]
+#[case("MATCH ()-[r*]->() RETURN r", list(CypherType::Relationship))]
+#[case("MATCH p = ()-->() RETURN p", CypherType::Path)]
+#[case("MATCH (n) RETURN n.name", CypherType::Any)]
+#[case("MATCH (n) RETURN n:Person", CypherType::Boolean)]
+#[case("MATCH (n) WITH n AS m RETURN m", CypherType::Node)]
+#[case("UNWIND [1, 2] AS x RETURN x", CypherType::Integer)]
+#[case("UNWIND $list AS x RETURN x", CypherType::Any)]
+#[case("CALL { MATCH (n) RETURN n } RETURN n", CypherType::Node)]
+#[case("RETURN [x IN [1, 2] | toString(x)]", list(CypherType::String))]
+#[case("RETURN [x IN ['a'] WHERE x STARTS WITH 'a']", list(CypherType::String))]
+#[case("RETURN reduce(acc = 0, x IN [1, 2] | acc + x)", CypherType::Integer)]
+#[case("RETURN CASE WHEN true THEN 1 ELSE 2 END", CypherType::Integer)]
+#[case("RETURN CASE WHEN true THEN 1 END", CypherType::Integer)]
+#[case("RETURN [1, 2][0]", CypherType::Integer)]
+#[case("MATCH (n) RETURN count(*)", CypherType::Integer)]
+#[case("MATCH (n) RETURN collect(n)", list(CypherType::Node))]
+#[case("MATCH p = ()-->() RETURN nodes(p)", list(CypherType::Node))]
+#[case("RETURN toUpper('a')", CypherType::String)]
+#[case("RETURN date('2020-01-01') + duration('P1D')", CypherType::Date)]
+#[case("RETURN my.udf(1)", CypherType::Any)]
+#[case("MATCH (n) RETURN (n)-->()", list(CypherType::Path))]
+#[case("MATCH (n) RETURN size((n)-[:KNOWS]->())", CypherType::Integer)]
+fn types_test_inference(#[case] text: &str, #[case] expected: CypherType) {
+    let (type_, errors) = infer(text);
+    assert!(errors.is_empty(), "unexpected errors for {}: {:?}", text, errors);
+    assert_eq!(type_, Some(expected));
+}
+
+#[rstest::rstest]
+#[case("RETURN 1 + true", "1 + true", "operator `+` cannot be applied to INTEGER and BOOLEAN")]
+#[case("RETURN 'a' - 1", "'a' - 1", "operator `-` cannot be applied to STRING and INTEGER")]
+#[case("RETURN [1] * 2", "[1] * 2", "operator `*` cannot be applied")]
+#[case("RETURN -'a'", "'a'", "unary `-` cannot be applied to STRING")]
+#[case("MATCH (n) WHERE n.name STARTS WITH 5 RETURN n", "5", "a string operator expects STRING but got INTEGER")]
+#[case("UNWIND 3 AS x RETURN x", "3", "UNWIND expects LIST<ANY> but got INTEGER")]
+#[case("MATCH (n) WHERE 1 RETURN n", "1", "WHERE expects BOOLEAN but got INTEGER")]
+#[case("RETURN 1 AND true", "1", "AND expects BOOLEAN but got INTEGER")]
+#[case("RETURN 1 IN 2", "2", "IN expects LIST<ANY> but got INTEGER")]
+#[case("RETURN 'abc'.length", "'abc'.length", "cannot access property `length` of STRING")]
+#[case("RETURN 1:Label", "1:Label", "a label predicate expects NODE | RELATIONSHIP but got INTEGER")]
+#[case("MATCH (n) RETURN n LIMIT 'ten'", "'ten'", "LIMIT expects INTEGER but got STRING")]
+#[case("RETURN toUpper(1)", "1", "toUpper() expects STRING but got INTEGER")]
+#[case("MATCH (n) RETURN length(n)", "n", "length() expects PATH but got NODE")]
+#[case("MATCH (n) CREATE (m {friend: n})", "n", "a property expects PROPERTY VALUE but got NODE")]
+#[case("RETURN [x IN 5 | x]", "5", "IN expects LIST<ANY> but got INTEGER")]
+#[case("MATCH (n) FOREACH (x IN 'a' | SET n.x = x)", "'a'", "FOREACH expects LIST<ANY> but got STRING")]
+#[case("MATCH (n) FOREACH (x IN [true] | SET n.x = x + 1)", "x + 1", "operator `+` cannot be applied to BOOLEAN and INTEGER")]
+fn types_test_errors(#[case] text: &str, #[case] operand: &str, #[case] message: &str) {
+    let query = Query::parse(text).unwrap();
+    let errors = infer_types(&query).errors;
+
+    assert_eq!(errors.len(), 1, "unexpected errors for {}: {:?}", text, errors);
+    assert_eq!(&text[errors[0].span.start..errors[0].span.end], operand);
+    assert!(errors[0].message.contains(message), "unexpected message {}", errors[0].message);
+}
+
+#[rstest::rstest]
+#[case("RETURN $p + true")]
+#[case("RETURN null + 1")]
+#[case("MATCH (n) RETURN n.name STARTS WITH n.prefix")]
+#[case("MATCH (n) RETURN n.age + 1, n.name + 'x'")]
+#[case("RETURN 1 = 'a', 1 < 'a'")]
+#[case("RETURN [1] + true")]
+#[case("WITH date('2020-01-01') AS d RETURN d.year")]
+#[case("MATCH (n) WHERE n:Person AND exists(n.name) RETURN n")]
+#[case("MATCH (n) WHERE (n)-->() AND NOT (n)<--() RETURN n")]
+#[case("MATCH (n) RETURN [m IN [n] WHERE (m)-->()]")]
+fn types_test_unknown_types_pass(#[case] text: &str) {
+    let (_, errors) = infer(text);
+    assert!(errors.is_empty(), "unexpected errors for {}: {:?}", text, errors);
+}
+
+#[test]
+fn types_test_rewritten_expressions() {
+    let query = pull_up_predicates(&Query::parse("MATCH (n:Person) RETURN n").unwrap());
+    let info = infer_types(&query);
+
+    assert!(info.errors.is_empty(), "{:?}", info.errors);
+    assert!(!info.types.contains_key(&Span::default()));
+}
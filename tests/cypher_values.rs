@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+
+use open_cypher::analysis::literals::recognize_literals;
+use open_cypher::ast::cypher::value::Value;
+use open_cypher::ast::cypher::{Atom, Expression, ProjectionItem, Query, SingleQuery};
+
+/// Parses `RETURN <text>` and returns the expression.
+fn expression(text: &str) -> Expression {
+    let mut query = Query::parse(&format!("RETURN {}", text)).unwrap();
+    assert_eq!(recognize_literals(&mut query), vec![]);
+    let Query::RegularQuery(query) = query else { panic!("expected a regular query") };
+    let SingleQuery::SinglePartQuery(query) = query.query else { panic!("expected a single part query") };
+    let ProjectionItem::Expression(expression) = &query.return_.unwrap().items.items[0] else { panic!("expected an expression") };
+    expression.clone()
+}
+
+fn value(text: &str) -> Value {
+    match expression(text).as_atom() {
+        Some(Atom::Literal(literal)) => Value::from_literal(literal).expect("a constant literal"),
+        Some(Atom::TemporalLiteral(literal)) => Value::from(literal.clone()),
+        Some(Atom::PointLiteral(point)) => Value::from(point.clone()),
+        _ => panic!("expected a literal"),
+    }
+}
+
+/// Sorts the elements of a list literal the way `ORDER BY` does.
+fn sorted(list: &str, descending: bool) -> Vec<String> {
+    let Value::List(mut values) = value(list) else { panic!("expected a list") };
+    values.sort_by(|a, b| if descending { b.order(a) } else { a.order(b) });
+    values.iter().map(Value::to_string).collect()
+}
+
+#[rstest::rstest]
+#[case("[true, false]", vec!["false", "true"])]
+#[case("['.*', '', ' ', 'one']", vec!["''", "' '", "'.*'", "'one'"])]
+#[case("[1, 3, 2]", vec!["1", "2", "3"])]
+#[case("[1.5, 1.3, 999.99]", vec!["1.3", "1.5", "999.99"])]
+#[case("[[], ['a'], ['a', 1], [1], [1, 'a'], [1, null], [null, 1], [null, 2]]",
+    vec!["[]", "['a']", "['a', 1]", "[1]", "[1, 'a']", "[1, null]", "[null, 1]", "[null, 2]"])]
+#[case("[date({year: 1910, month: 5, day: 6}), date({year: 1980, month: 12, day: 24}), date({year: 1984, month: 10, day: 12}), \
+    date({year: 1985, month: 5, day: 6}), date({year: 1980, month: 10, day: 24}), date({year: 1984, month: 10, day: 11})]",
+    vec!["date('1910-05-06')", "date('1980-10-24')", "date('1980-12-24')", "date('1984-10-11')", "date('1984-10-12')", "date('1985-05-06')"])]
+#[case("[localtime({hour: 10, minute: 35}), localtime({hour: 12, minute: 31, second: 14, nanosecond: 645876123}), \
+    localtime({hour: 12, minute: 31, second: 14, nanosecond: 645876124}), localtime({hour: 12, minute: 35, second: 13}), \
+    localtime({hour: 12, minute: 30, second: 14, nanosecond: 645876123})]",
+    vec!["localtime('10:35:00')", "localtime('12:30:14.645876123')", "localtime('12:31:14.645876123')", "localtime('12:31:14.645876124')",
+        "localtime('12:35:13')"])]
+#[case("[time({hour: 10, minute: 35, timezone: '-08:00'}), time({hour: 12, minute: 31, second: 14, nanosecond: 645876123, timezone: '+01:00'}), \
+    time({hour: 12, minute: 31, second: 14, nanosecond: 645876124, timezone: '+01:00'}), time({hour: 12, minute: 35, second: 15, timezone: '+05:00'}), \
+    time({hour: 12, minute: 30, second: 14, nanosecond: 645876123, timezone: '+01:01'})]",
+    vec!["time('12:35:15+05:00')", "time('12:30:14.645876123+01:01')", "time('12:31:14.645876123+01:00')", "time('12:31:14.645876124+01:00')",
+        "time('10:35:00-08:00')"])]
+#[case("[datetime({year: 1984, month: 10, day: 11, hour: 12, minute: 30, second: 14, nanosecond: 12, timezone: '+00:15'}), \
+    datetime({year: 1984, month: 10, day: 11, hour: 12, minute: 31, second: 14, nanosecond: 645876123, timezone: '+00:17'}), \
+    datetime({year: 1, month: 1, day: 1, hour: 1, minute: 1, second: 1, nanosecond: 1, timezone: '-11:59'}), \
+    datetime({year: 1980, month: 12, day: 11, hour: 12, minute: 31, second: 14, timezone: '-11:59'})]",
+    vec!["datetime('0001-01-01T01:01:01.000000001-11:59')", "datetime('1980-12-11T12:31:14-11:59')",
+        "datetime('1984-10-11T12:31:14.645876123+00:17')", "datetime('1984-10-11T12:30:14.000000012+00:15')"])]
+fn test_order_of_values_of_one_type(#[case] list: &str, #[case] ascending: Vec<&str>) {
+    assert_eq!(sorted(list, false), ascending);
+    let mut descending = ascending;
+    descending.reverse();
+    assert_eq!(sorted(list, true), descending);
+}
+
+#[test]
+fn test_order_of_values_of_different_types() {
+    let list = "[null, 1, 1.5, true, 'a', point({x: 1, y: 2}), duration('P1D'), localtime('12:00'), time('12:00Z'), date('2020-01-01'), \
+        localdatetime('2020-01-01T12:00'), datetime('2020-01-01T12:00Z'), [1], {a: 1}]";
+    assert_eq!(sorted(list, false), vec![
+        "{a: 1}",
+        "[1]",
+        "datetime('2020-01-01T12:00:00Z')",
+        "localdatetime('2020-01-01T12:00:00')",
+        "date('2020-01-01')",
+        "time('12:00:00Z')",
+        "localtime('12:00:00')",
+        "duration('P1D')",
+        "point({x: 1.0, y: 2.0, crs: 'cartesian'})",
+        "'a'",
+        "true",
+        "1",
+        "1.5",
+        "null",
+    ]);
+}
+
+#[rstest::rstest]
+#[case("1", "1", Some(true))]
+#[case("1", "1.0", Some(true))]
+#[case("1", "'1'", Some(false))]
+#[case("null", "null", None)]
+#[case("1", "null", None)]
+#[case("[1, 2]", "[1, 2.0]", Some(true))]
+#[case("[1, null]", "[1, 2]", None)]
+#[case("[1, null]", "[2, null]", Some(false))]
+#[case("[1]", "[1, null]", Some(false))]
+#[case("{a: 1, b: null}", "{a: 1, b: 2}", None)]
+#[case("{a: 1}", "{b: 1}", Some(false))]
+#[case("{`a`: 1}", "{a: 1}", Some(true))]
+#[case("date('2020-01-01')", "date('2020-01-01')", Some(true))]
+#[case("date('2020-01-01')", "localdatetime('2020-01-01T00:00')", Some(false))]
+#[case("time('12:00+01:00')", "time('11:00Z')", Some(true))]
+#[case("datetime('2020-01-01T00:30+01:00')", "datetime('2019-12-31T23:30Z')", Some(true))]
+#[case("duration('P1D')", "duration('PT24H')", Some(false))]
+#[case("point({x: 1, y: 2})", "point({x: 1.0, y: 2.0})", Some(true))]
+#[case("point({x: 1, y: 2})", "point({longitude: 1, latitude: 2})", Some(false))]
+fn test_equality(#[case] a: &str, #[case] b: &str, #[case] expected: Option<bool>) {
+    assert_eq!(value(a).equals(&value(b)), expected);
+    assert_eq!(value(b).equals(&value(a)), expected);
+}
+
+#[rstest::rstest]
+#[case("1", "2", Some(Ordering::Less))]
+#[case("2.5", "2", Some(Ordering::Greater))]
+#[case("'b'", "'a'", Some(Ordering::Greater))]
+#[case("false", "true", Some(Ordering::Less))]
+#[case("1", "'1'", None)]
+#[case("1", "null", None)]
+#[case("true", "1", None)]
+#[case("[1, 2]", "[1, 3]", Some(Ordering::Less))]
+#[case("[1, 2]", "[1]", Some(Ordering::Greater))]
+#[case("[1, 'a']", "[2, 1]", Some(Ordering::Less))]
+#[case("[1, 'a']", "[1, 1]", None)]
+#[case("[null, 1]", "[null, 2]", None)]
+#[case("{a: 1}", "{a: 2}", None)]
+#[case("date('2020-01-01')", "date('2019-12-31')", Some(Ordering::Greater))]
+#[case("time('12:00+01:00')", "time('11:30Z')", Some(Ordering::Less))]
+#[case("date('2020-01-01')", "localdatetime('2020-01-01T00:00')", None)]
+#[case("duration('P1D')", "duration('P2D')", None)]
+#[case("point({x: 1, y: 2})", "point({x: 2, y: 2})", None)]
+fn test_comparability(#[case] a: &str, #[case] b: &str, #[case] expected: Option<Ordering>) {
+    assert_eq!(value(a).compare(&value(b)), expected);
+    assert_eq!(value(b).compare(&value(a)), expected.map(Ordering::reverse));
+}
+
+#[rstest::rstest]
+#[case("null", "null", true)]
+#[case("1", "1.0", true)]
+#[case("[1, null]", "[1.0, null]", true)]
+#[case("{a: null}", "{a: null}", true)]
+#[case("[1, null]", "[1, 2]", false)]
+#[case("'1'", "1", false)]
+#[case("time('12:00+01:00')", "time('11:00Z')", true)]
+fn test_equivalence(#[case] a: &str, #[case] b: &str, #[case] expected: bool) {
+    assert_eq!(value(a).is_equivalent(&value(b)), expected);
+}
+
+#[test]
+fn test_nan() {
+    let nan = Value::Float(f64::NAN);
+    assert_eq!(nan.equals(&nan), Some(false));
+    assert_eq!(nan.compare(&Value::Integer(1)), None);
+    assert!(nan.is_equivalent(&Value::Float(f64::NAN)));
+    assert_eq!(nan.order(&Value::Float(f64::INFINITY)), Ordering::Greater);
+    assert_eq!(nan.order(&Value::Null), Ordering::Less);
+}
+
+#[rstest::rstest]
+#[case("[1, -2.5, 'a', null]", Some(Value::from(vec![Value::Integer(1), Value::Float(-2.5), Value::from("a"), Value::Null])))]
+#[case("{`my key`: [true], b: date('2020-01-01')}", Some(Value::Map([
+    ("my key".to_string(), Value::from(vec![true])),
+    ("b".to_string(), value("date('2020-01-01')")),
+].into())))]
+#[case("[1, 1 + 1]", None)]
+#[case("{a: $parameter}", None)]
+fn test_from_literal(#[case] text: &str, #[case] expected: Option<Value>) {
+    let Some(Atom::Literal(literal)) = expression(text).as_atom().cloned() else { panic!("expected a literal") };
+    assert_eq!(Value::from_literal(&literal), expected);
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "engine")]
+
+use std::collections::HashMap;
+
+use open_cypher::ast::cypher::Query;
+use open_cypher::engine::{Graph, Value};
+
+const SOCIAL: &str = "CREATE (a:Person {name: 'Alice', age: 30})-[:KNOWS {since: 2001}]->(b:Person {name: 'Bob', age: 25}), \
+    (b)-[:KNOWS {since: 2010}]->(c:Person {name: 'Carol'}), (a)-[:LIKES]->(c), (a)-[:WORKS_AT]->(:Company {name: 'Acme'})";
+
+fn social() -> Graph {
+    let mut graph = Graph::new();
+    graph.run(SOCIAL).unwrap();
+    graph
+}
+
+/// Runs the query and renders every row as its values separated by ` | `.
+fn rows(graph: &mut Graph, text: &str) -> Vec<String> {
+    let result = graph.run(text).unwrap_or_else(|error| panic!("{}: {}", text, error));
+    result.rows.iter().map(|row| row.iter().map(Value::to_string).collect::<Vec<_>>().join(" | ")).collect()
+}
+
+#[rstest::rstest]
+#[case("MATCH (a)-[:KNOWS]->(b) RETURN a.name, b.name ORDER BY a.name", vec!["'Alice' | 'Bob'", "'Bob' | 'Carol'"])]
+#[case("MATCH (a:Person)<-[:KNOWS]-(b) RETURN a.name", vec!["'Bob'", "'Carol'"])]
+#[case("MATCH (a {name: 'Bob'})--(b) RETURN b.name ORDER BY b.name", vec!["'Alice'", "'Carol'"])]
+#[case("MATCH (a:Person) WHERE a.age >= 25 AND a.name STARTS WITH 'A' RETURN a.name", vec!["'Alice'"])]
+#[case("MATCH (a:Person|Company) WHERE NOT a:Person RETURN a.name", vec!["'Acme'"])]
+#[case("MATCH (a)-[r:KNOWS|LIKES]->(b) WHERE r.since IS NULL RETURN type(r), b.name", vec!["'LIKES' | 'Carol'"])]
+#[case("MATCH (a {name: 'Alice'})-[:KNOWS*]->(b) RETURN b.name ORDER BY b.name", vec!["'Bob'", "'Carol'"])]
+#[case("MATCH (a {name: 'Alice'})-[:KNOWS*0..1]->(b) RETURN b.name ORDER BY b.name", vec!["'Alice'", "'Bob'"])]
+#[case("MATCH (a {name: 'Alice'})-[:KNOWS*2 {since: 2010}]->(b) RETURN b.name", Vec::<&str>::new())]
+#[case("MATCH p = (:Person {name: 'Alice'})-[:KNOWS*]->(:Person {name: 'Carol'}) RETURN length(p), [n IN nodes(p) | n.name]",
+    vec!["2 | ['Alice', 'Bob', 'Carol']"])]
+#[case("MATCH (a)-[:KNOWS]->(b)-[:KNOWS]->(c), (a)-[:LIKES]->(c) RETURN a.name, c.name", vec!["'Alice' | 'Carol'"])]
+#[case("MATCH (a:Person) OPTIONAL MATCH (a)-[:LIKES]->(b) RETURN a.name, b.name ORDER BY a.name",
+    vec!["'Alice' | 'Carol'", "'Bob' | null", "'Carol' | null"])]
+#[case("MATCH (a:Person) WHERE (a)-[:LIKES]->() RETURN a.name", vec!["'Alice'"])]
+#[case("MATCH (a:Person) WHERE EXISTS { (a)-[:KNOWS]->(b) WHERE b.age < 30 } RETURN a.name", vec!["'Alice'"])]
+#[case("MATCH (a:Person) WITH a.name AS name, a.age AS age WHERE age > 26 RETURN name", vec!["'Alice'"])]
+#[case("MATCH (a:Person) RETURN a.name ORDER BY a.age DESC, a.name", vec!["'Carol'", "'Alice'", "'Bob'"])]
+#[case("MATCH (a:Person) RETURN a.name ORDER BY a.age NULLS FIRST", vec!["'Carol'", "'Bob'", "'Alice'"])]
+#[case("MATCH (a:Person) RETURN a.name ORDER BY a.name SKIP 1 LIMIT 1", vec!["'Bob'"])]
+#[case("MATCH (a:Person) RETURN a {.name, .age} ORDER BY a.name LIMIT 1", vec!["{age: 30, name: 'Alice'}"])]
+fn test_match(#[case] query: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(rows(&mut social(), query), expected);
+}
+
+#[rstest::rstest]
+#[case("MATCH (a:Person) RETURN count(*), count(a.age), sum(a.age), avg(a.age), min(a.age), max(a.name)",
+    vec!["3 | 2 | 55 | 27.5 | 25 | 'Carol'"])]
+#[case("MATCH (a)-[r]->() RETURN a.name, count(r) AS n ORDER BY n DESC", vec!["'Alice' | 3", "'Bob' | 1"])]
+#[case("MATCH (a)-->(b) RETURN a.name, collect(b.name) ORDER BY a.name", vec!["'Alice' | ['Bob', 'Carol', 'Acme']", "'Bob' | ['Carol']"])]
+#[case("MATCH (a)-->(b) RETURN count(DISTINCT a) + 1", vec!["3"])]
+#[case("MATCH (a:Nobody) RETURN count(a), collect(a), sum(a.x), avg(a.x)", vec!["0 | [] | 0 | null"])]
+#[case("MATCH (a:Nobody) RETURN a.name, count(a)", Vec::<&str>::new())]
+#[case("UNWIND [1, 2, 2, 3, 4] AS x RETURN percentileDisc(x, 0.5), percentileCont(x, 0.25), stDevP(x)",
+    vec!["2 | 2.0 | 1.019803902718557"])]
+#[case("UNWIND [] AS x RETURN percentileCont(x, 0.5), percentileDisc(x, 2)", vec!["null | null"])]
+#[case("UNWIND [1, 1.0, '1', [1], null] AS x RETURN DISTINCT x", vec!["1", "'1'", "[1]", "null"])]
+fn test_aggregation(#[case] query: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(rows(&mut social(), query), expected);
+}
+
+#[rstest::rstest]
+#[case("RETURN 1 + 2 * 3, 7 / 2, 7 % 2, 2 ^ 3, -(1)", vec!["7 | 3 | 1 | 8.0 | -1"])]
+#[case("RETURN 'a' + 'b', 'a' + 1, [1] + 2, 0 + [1], 'abc' =~ 'a.*', 'abc' CONTAINS 'd'", vec!["'ab' | 'a1' | [1, 2] | [0, 1] | true | false"])]
+#[case("RETURN null = null, null <> 1, 1 = 1.0, [1, null] = [1, 2], [1, null] = [2, null]", vec!["null | null | true | null | false"])]
+#[case("RETURN true OR null, false AND null, true XOR null, NOT null", vec!["true | false | null | null"])]
+#[case("RETURN 1 < 2 < 3, 1 < 'a', 'a' < 'b', [1, 2] < [1, 3]", vec!["true | null | true | true"])]
+#[case("RETURN 2 IN [1, 2], 3 IN [1, null], [1, 2, 3][1], [1, 2, 3][-1], [1, 2, 3][1..]", vec!["true | null | 2 | 3 | [2, 3]"])]
+#[case("RETURN CASE 2 WHEN 1 THEN 'one' WHEN 2 THEN 'two' END, CASE WHEN false THEN 1 ELSE 0 END", vec!["'two' | 0"])]
+#[case("RETURN [x IN range(1, 5) WHERE x % 2 = 1 | x * 10], reduce(s = 0, x IN [1, 2, 3] | s + x)", vec!["[10, 30, 50] | 6"])]
+#[case("RETURN all(x IN [1, 2] WHERE x > 0), any(x IN [1, null] WHERE x > 1), none(x IN [] WHERE true), single(x IN [1, 2] WHERE x > 1)",
+    vec!["true | null | true | true"])]
+#[case("RETURN toInteger('42'), toInteger(3.9), toFloat('1.5'), toString(1.0), toBoolean('TRUE'), toInteger('x')",
+    vec!["42 | 3 | 1.5 | '1.0' | true | null"])]
+#[case("RETURN size('abc'), toUpper('abc'), substring('hello', 1, 3), split('a,b', ','), replace('aa', 'a', 'b'), reverse([1, 2])",
+    vec!["3 | 'ABC' | 'ell' | ['a', 'b'] | 'bb' | [2, 1]"])]
+#[case("RETURN coalesce(null, 1), head([1, 2]), last([]), tail([1, 2]), range(0, 6, 3), abs(-2), sqrt(4)", vec!["1 | 1 | null | [2] | [0, 3, 6] | 2 | 2.0"])]
+#[case("RETURN {a: 1, b: [true]}.b, 1 IS :: INTEGER, 1 IS NOT NULL", vec!["[true] | true | true"])]
+#[case("RETURN round(2.5), round(2.345, 2), round(-1234.5, -2), round(2.5, 9223372036854775807), round(1e300, 400) = 1e300, round(1.5, -400)",
+    vec!["3.0 | 2.35 | -1200.0 | 2.5 | true | 0.0"])]
+#[case("RETURN ltrim('  a '), rtrim('xxaxx', 'x'), pi() > 3", vec!["'a ' | 'xxa' | true"])]
+#[case("RETURN -9223372036854775808, 9223372036854775807, -9223372036854775808 < 0", vec!["-9223372036854775808 | 9223372036854775807 | true"])]
+fn test_expressions(#[case] query: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(rows(&mut Graph::new(), query), expected);
+}
+
+#[rstest::rstest]
+#[case("UNWIND [3, 1, 2] AS x RETURN x", vec!["3", "1", "2"])]
+#[case("UNWIND [] AS x RETURN x", Vec::<&str>::new())]
+#[case("UNWIND null AS x RETURN x", Vec::<&str>::new())]
+#[case("UNWIND [[1, 2], [3]] AS xs UNWIND xs AS x RETURN x", vec!["1", "2", "3"])]
+#[case("WITH [1, 2] AS xs UNWIND xs AS x WITH x WHERE x > 1 RETURN x", vec!["2"])]
+#[case("UNWIND [2, null, 1] AS x RETURN x ORDER BY x DESC", vec!["null", "2", "1"])]
+#[case("UNWIND [true, 'a', 1, [1], {a: 1}, null, 0.5] AS x RETURN x ORDER BY x", vec!["{a: 1}", "[1]", "'a'", "true", "0.5", "1", "null"])]
+#[case("UNWIND [time('12:00+01:00'), time('11:30Z'), date('2020-01-01')] AS t RETURN t ORDER BY t",
+    vec!["date('2020-01-01')", "time('12:00:00+01:00')", "time('11:30:00Z')"])]
+#[case("RETURN 1 AS x UNION RETURN 1 AS x", vec!["1"])]
+#[case("RETURN 1 AS x UNION ALL RETURN 1 AS x", vec!["1", "1"])]
+fn test_unwind_and_union(#[case] query: &str, #[case] expected: Vec<&str>) {
+    assert_eq!(rows(&mut Graph::new(), query), expected);
+}
+
+#[rstest::rstest]
+#[case(&["CREATE (:A {x: 1}), (:A:B)"], "MATCH (n:A) RETURN n ORDER BY n.x", vec!["(:A {x: 1})", "(:A:B)"])]
+#[case(&["CREATE p = (:A)-[:R {w: 2}]->(:B) RETURN p"], "MATCH p = ()-->() RETURN p", vec!["(:A)-[:R {w: 2}]->(:B)"])]
+#[case(&["CREATE (a:A)<-[:R]-(b:B)"], "MATCH (a)-[r]->(b) RETURN labels(a), labels(b)", vec!["['B'] | ['A']"])]
+#[case(&["CREATE (a:A), (a)-[:R]->(a)"], "MATCH (a)-[r]-(b) RETURN count(r)", vec!["1"])]
+#[case(&["UNWIND [1, 2] AS i CREATE (:N {i: i})"], "MATCH (n:N) RETURN n.i", vec!["1", "2"])]
+#[case(&["MERGE (:A {x: 1})", "MERGE (:A {x: 1})", "MERGE (:A {x: 2})"], "MATCH (n:A) RETURN n.x", vec!["1", "2"])]
+#[case(&["CREATE (:A {x: 1})", "MATCH (a:A) MERGE (a)-[:R]->(b:B)", "MATCH (a:A) MERGE (a)-[:R]->(b:B)"],
+    "MATCH (a)-[r]->(b) RETURN count(*)", vec!["1"])]
+#[case(&["MERGE (n:A) ON CREATE SET n.created = true", "MERGE (n:A) ON MATCH SET n.matched = true"],
+    "MATCH (n) RETURN n", vec!["(:A {created: true, matched: true})"])]
+#[case(&["CREATE (:A {x: 1, y: 2})", "MATCH (n) SET n.x = n.x + 1, n.y = null, n:B"], "MATCH (n) RETURN n", vec!["(:A:B {x: 2})"])]
+#[case(&["CREATE (:A {x: 1, y: 2})", "MATCH (n) SET n = {z: 3}"], "MATCH (n) RETURN n", vec!["(:A {z: 3})"])]
+#[case(&["CREATE (:A {x: 1, y: 2})", "MATCH (n) SET n += {y: null, z: 3}"], "MATCH (n) RETURN n", vec!["(:A {x: 1, z: 3})"])]
+#[case(&["CREATE (:A:B {x: 1, y: 2})", "MATCH (n) REMOVE n:B, n.x"], "MATCH (n) RETURN n", vec!["(:A {y: 2})"])]
+#[case(&["CREATE (:A {since: date('2020-01-01')}), (:A {since: date('2019-12-31')})"],
+    "MATCH (n:A) WHERE n.since < date('2020-01-01') RETURN n.since", vec!["date('2019-12-31')"])]
+#[case(&["UNWIND [1, 2, 3] AS i CREATE (n:N {i: i}) FILTER i > 1 ORDER BY i DESC LIMIT 1 SET n.last = true"],
+    "MATCH (n:N) RETURN n.i, n.last", vec!["1 | null", "2 | null", "3 | true"])]
+#[case(&["CREATE (:A)-[:R]->(:B)", "MATCH ()-[r]->() DELETE r", "MATCH (n:A) DELETE n"], "MATCH (n) RETURN n", vec!["(:B)"])]
+#[case(&["CREATE (:A)-[:R]->(:B)", "MATCH (n:A) DETACH DELETE n"], "MATCH (n) OPTIONAL MATCH (n)--(m) RETURN n, m", vec!["(:B) | null"])]
+#[case(&["CREATE (:A), (:B)-[:R]->(:C {p: 1})"], "CALL db.labels()", vec!["'A'", "'B'", "'C'"])]
+#[case(&["CREATE (:A), (:B)-[:R]->(:C {p: 1})"], "CALL db.propertyKeys() YIELD propertyKey AS key RETURN key", vec!["'p'"])]
+fn test_updates(#[case] updates: &[&str], #[case] query: &str, #[case] expected: Vec<&str>) {
+    let mut graph = Graph::new();
+    for update in updates {
+        graph.run(update).unwrap_or_else(|error| panic!("{}: {}", update, error));
+    }
+    assert_eq!(rows(&mut graph, query), expected);
+}
+
+#[test]
+fn test_created_values_are_returned() {
+    let mut graph = Graph::new();
+    let result = graph.run("CREATE (n:A {x: 1}) SET n.y = 2 RETURN n, n.x AS x").unwrap();
+    assert_eq!(result.columns, ["n", "x"]);
+    assert_eq!(result.column("x"), Some(vec![&Value::Integer(1)]));
+    let Value::Node(node) = &result.rows[0][0] else { panic!("expected a node") };
+    assert_eq!(node.labels, ["A"]);
+    assert_eq!(node.properties.get("y"), Some(&Value::Integer(2)));
+    assert_eq!((graph.node_count(), graph.relationship_count()), (1, 0));
+}
+
+#[test]
+fn test_parameters() {
+    let mut graph = social();
+    let query = Query::parse("MATCH (a:Person) WHERE a.name IN $names RETURN a.age ORDER BY a.age LIMIT $limit").unwrap();
+    let parameters = HashMap::from([
+        ("names".to_string(), Value::from(vec!["Alice", "Bob"])),
+        ("limit".to_string(), Value::Integer(1)),
+    ]);
+    let result = graph.execute(&query, &parameters).unwrap();
+    assert_eq!(result.rows, vec![vec![Value::Integer(25)]]);
+}
+
+#[rstest::rstest]
+#[case("CREATE (:A)-[:R]->(:B)", "MATCH (n:A) DELETE n", "DETACH DELETE")]
+#[case("CREATE (:A)", "MATCH (n:A) SET n.x = 1 RETURN n.x / 0", "division by zero")]
+#[case("CREATE (:A)", "MATCH (n:A) SET n.x = {a: 1}", "cannot be set to MAP")]
+#[case("CREATE (:A)", "CREATE (n:A)-[:R]-(:B)", "direction")]
+#[case("CREATE (:A)", "RETURN $missing", "missing parameter $missing")]
+#[case("CREATE (:A)", "RETURN count(count(*))", "can only be used in RETURN and WITH")]
+#[case("CREATE (:A)", "RETURN nope(1)", "unsupported function nope()")]
+#[case("CREATE (:A)", "RETURN timestamp()", "unsupported function timestamp()")]
+#[case("CREATE (:A)", "RETURN size()", "size() takes 1 arguments but got 0")]
+#[case("CREATE (:A)", "RETURN substring('a')", "substring() takes 2 to 3 arguments but got 1")]
+#[case("CREATE (:A)", "UNWIND [1] AS x RETURN percentileCont(x, 2)", "between 0.0 and 1.0")]
+#[case("CREATE (:A)", "UNWIND range(1, 100000000) AS x RETURN count(x)", "range() would create 100000000 elements, more than 1000000")]
+#[case("CREATE (:A)", "RETURN size(range(-9223372036854775808, 9223372036854775807))", "more than 1000000")]
+fn test_errors_leave_the_graph_unchanged(#[case] setup: &str, #[case] query: &str, #[case] message: &str) {
+    let mut graph = Graph::new();
+    graph.run(setup).unwrap();
+    let (nodes, relationships) = (graph.node_count(), graph.relationship_count());
+    let error = graph.run(query).unwrap_err();
+    assert!(error.message.contains(message), "{}", error);
+    assert_eq!((graph.node_count(), graph.relationship_count()), (nodes, relationships));
+    assert!(graph.nodes().all(|node| node.properties.is_empty()));
+}
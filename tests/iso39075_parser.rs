@@ -29,6 +29,7 @@ NEXT CREATE SCHEMA /fee")]
 #[case("INSERT (:Person { firstname: 'Firstname', lastname: 'Lastname', joined: DATE '2023-01-01' })-[:MEMBER_SINCE { since: \"2023-03-20\" }]->(:Team { name: 'Teamname' })")]
 #[case("MATCH (a { firstname: 'Robert' }), (b { lastname: 'Kowalski' }) INSERT (a)-[:GRADUATED]->(b)")]
 #[case("MATCH (p:Person)-[r:IS_FRIENDS_WITH]->(friend:Person) WHERE EXISTS (MATCH (p)-[:WORKS_FOR]->(:Company {name: \"GQL, Inc.\"})) RETURN p, r, friend")]
+#[case("MATCH (p:Person) WHERE p.age > 30 IS NOT FALSE RETURN p")]
 // #[case("MATCH (p:Person)-[r:IS_FRIENDS_WITH]->(friend:Person) WHERE EXISTS { MATCH (p)-[:WORKS_FOR]->(:Company { name: \"GQL, Inc.\" }) RETURN p } RETURN p, r, friend")]
 #[case("SESSION SET GRAPH CURRENT_GRAPH")]
 #[case("SESSION SET GRAPH CURRENT_PROPERTY_GRAPH")]